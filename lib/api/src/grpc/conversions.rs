@@ -368,6 +368,8 @@ impl From<SearchParams> for segment::types::SearchParams {
             exact: params.exact.unwrap_or(false),
             quantization: params.quantization.map(|q| q.into()),
             indexed_only: params.indexed_only.unwrap_or(false),
+            rescore_with_exact: false,
+            rescore_oversampling: None,
         }
     }
 }
@@ -432,6 +434,7 @@ impl From<segment::types::ScoredPoint> for ScoredPoint {
             score: point.score,
             version: point.version,
             vectors: point.vector.map(|v| v.into()),
+            score_breakdown: point.score_breakdown.unwrap_or_default(),
         }
     }
 }
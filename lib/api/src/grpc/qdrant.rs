@@ -86,7 +86,7 @@ pub mod vectors_config {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VectorsConfigDiff {
-    #[prost(oneof = "vectors_config_diff::Config", tags = "1, 2")]
+    #[prost(oneof = "vectors_config_diff::Config", tags = "1, 2, 3")]
     #[validate]
     pub config: ::core::option::Option<vectors_config_diff::Config>,
 }
@@ -100,6 +100,9 @@ pub mod vectors_config_diff {
         Params(super::VectorParamsDiff),
         #[prost(message, tag = "2")]
         ParamsMap(super::VectorParamsDiffMap),
+        /// New named vectors to add, existing points get zero vectors
+        #[prost(message, tag = "3")]
+        Add(super::VectorParamsMap),
     }
 }
 #[derive(validator::Validate)]
@@ -489,6 +492,9 @@ pub struct CollectionParams {
     /// How many replicas should apply the operation for us to consider it successful
     #[prost(uint32, optional, tag = "7")]
     pub write_consistency_factor: ::core::option::Option<u32>,
+    /// If true - updates from clients are rejected. Reads are not affected
+    #[prost(bool, optional, tag = "8")]
+    pub read_only: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -504,6 +510,9 @@ pub struct CollectionParamsDiff {
     /// If true - point's payload will not be stored in memory
     #[prost(bool, optional, tag = "3")]
     pub on_disk_payload: ::core::option::Option<bool>,
+    /// If true - updates from clients are rejected. Reads are not affected
+    #[prost(bool, optional, tag = "4")]
+    pub read_only: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -609,6 +618,18 @@ pub struct CollectionInfo {
     /// number of indexed vectors in the collection.
     #[prost(uint64, optional, tag = "10")]
     pub indexed_vectors_count: ::core::option::Option<u64>,
+    /// size of the collection data on disk in bytes, excluding snapshots
+    #[prost(uint64, tag = "11")]
+    pub disk_data_size_bytes: u64,
+    /// shards which could not be queried for the information
+    #[prost(uint32, repeated, tag = "12")]
+    pub degraded_shards: ::prost::alloc::vec::Vec<u32>,
+    /// if true - information of some shards is missing
+    #[prost(bool, tag = "13")]
+    pub partial_result: bool,
+    /// shards which did not respond in time, e.g. still initializing
+    #[prost(uint32, repeated, tag = "14")]
+    pub unavailable_shards: ::prost::alloc::vec::Vec<u32>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -1069,6 +1090,8 @@ pub enum ReplicaState {
     Initializing = 3,
     /// A shard which receives data, but is not used for search; Useful for backup shards
     Listener = 4,
+    /// The shard is being recovered from a snapshot, reads and updates are routed to other replicas
+    Recovering = 5,
 }
 impl ReplicaState {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -1082,6 +1105,7 @@ impl ReplicaState {
             ReplicaState::Partial => "Partial",
             ReplicaState::Initializing => "Initializing",
             ReplicaState::Listener => "Listener",
+            ReplicaState::Recovering => "Recovering",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -1092,6 +1116,7 @@ impl ReplicaState {
             "Partial" => Some(Self::Partial),
             "Initializing" => Some(Self::Initializing),
             "Listener" => Some(Self::Listener),
+            "Recovering" => Some(Self::Recovering),
             _ => None,
         }
     }
@@ -2156,6 +2181,55 @@ pub struct InitiateShardTransferRequest {
     #[prost(uint32, tag = "2")]
     pub shard_id: u32,
 }
+#[derive(validator::Validate)]
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckDiskSpaceRequest {
+    /// Name of the collection
+    #[prost(string, tag = "1")]
+    #[validate(length(min = 1, max = 255))]
+    pub collection_name: ::prost::alloc::string::String,
+    /// If set, estimate the size of the local replica of this shard
+    #[prost(uint32, optional, tag = "2")]
+    pub shard_id: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckDiskSpaceResponse {
+    /// Available disk space in bytes
+    #[prost(uint64, tag = "1")]
+    pub available_bytes: u64,
+    /// Time spent to process
+    #[prost(double, tag = "2")]
+    pub time: f64,
+    /// Estimated size of the requested local shard, if present on the peer
+    #[prost(uint64, optional, tag = "3")]
+    pub shard_size_bytes: ::core::option::Option<u64>,
+}
+#[derive(validator::Validate)]
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UploadShardSnapshotRequest {
+    /// Name of the collection
+    #[prost(string, tag = "1")]
+    #[validate(length(min = 1, max = 255))]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Id of the shard to restore
+    #[prost(uint32, tag = "2")]
+    pub shard_id: u32,
+    /// Next chunk of the snapshot archive
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// Id of the peer which pushes the snapshot in a shard transfer
+    #[prost(uint64, tag = "4")]
+    pub from_peer_id: u64,
+    /// Total size of the snapshot archive in bytes
+    #[prost(uint64, tag = "5")]
+    pub snapshot_size: u64,
+}
 /// Generated client implementations.
 pub mod collections_internal_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -2295,6 +2369,64 @@ pub mod collections_internal_client {
                 .insert(GrpcMethod::new("qdrant.CollectionsInternal", "Initiate"));
             self.inner.unary(req, path, codec).await
         }
+        ///
+        /// Get available disk space for the collection storage
+        pub async fn check_disk_space(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckDiskSpaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckDiskSpaceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.CollectionsInternal/CheckDiskSpace",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("qdrant.CollectionsInternal", "CheckDiskSpace"));
+            self.inner.unary(req, path, codec).await
+        }
+        ///
+        /// Upload a shard snapshot in chunks and restore the local shard from it
+        pub async fn upload_shard_snapshot(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<
+                Message = super::UploadShardSnapshotRequest,
+            >,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionOperationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.CollectionsInternal/UploadShardSnapshot",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("qdrant.CollectionsInternal", "UploadShardSnapshot"),
+                );
+            self.inner.client_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2322,6 +2454,24 @@ pub mod collections_internal_server {
             tonic::Response<super::CollectionOperationResponse>,
             tonic::Status,
         >;
+        ///
+        /// Get available disk space for the collection storage
+        async fn check_disk_space(
+            &self,
+            request: tonic::Request<super::CheckDiskSpaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckDiskSpaceResponse>,
+            tonic::Status,
+        >;
+        ///
+        /// Upload a shard snapshot in chunks and restore the local shard from it
+        async fn upload_shard_snapshot(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::UploadShardSnapshotRequest>>,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionOperationResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct CollectionsInternalServer<T: CollectionsInternal> {
@@ -2497,6 +2647,106 @@ pub mod collections_internal_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.CollectionsInternal/CheckDiskSpace" => {
+                    #[allow(non_camel_case_types)]
+                    struct CheckDiskSpaceSvc<T: CollectionsInternal>(pub Arc<T>);
+                    impl<
+                        T: CollectionsInternal,
+                    > tonic::server::UnaryService<super::CheckDiskSpaceRequest>
+                    for CheckDiskSpaceSvc<T> {
+                        type Response = super::CheckDiskSpaceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CheckDiskSpaceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CollectionsInternal>::check_disk_space(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CheckDiskSpaceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.CollectionsInternal/UploadShardSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct UploadShardSnapshotSvc<T: CollectionsInternal>(pub Arc<T>);
+                    impl<
+                        T: CollectionsInternal,
+                    > tonic::server::ClientStreamingService<
+                        super::UploadShardSnapshotRequest,
+                    > for UploadShardSnapshotSvc<T> {
+                        type Response = super::CollectionOperationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::UploadShardSnapshotRequest>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CollectionsInternal>::upload_shard_snapshot(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UploadShardSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -2713,6 +2963,9 @@ pub struct UpsertPoints {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "4")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "5")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -2732,6 +2985,9 @@ pub struct DeletePoints {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "4")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "5")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -2773,6 +3029,9 @@ pub struct UpdatePointVectors {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "4")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "5")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2806,6 +3065,9 @@ pub struct DeletePointVectors {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "5")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "6")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -2828,6 +3090,9 @@ pub struct SetPayloadPoints {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "6")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "7")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -2850,6 +3115,9 @@ pub struct DeletePayloadPoints {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "6")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "7")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -2869,6 +3137,9 @@ pub struct ClearPayloadPoints {
     /// Write ordering guarantees
     #[prost(message, optional, tag = "4")]
     pub ordering: ::core::option::Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    #[prost(string, optional, tag = "5")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -3102,6 +3373,9 @@ pub struct SearchPoints {
     /// Options for specifying read consistency guarantees
     #[prost(message, optional, tag = "12")]
     pub read_consistency: ::core::option::Option<ReadConsistency>,
+    /// Defines how to order points with equal scores
+    #[prost(enumeration = "TiebreakerMode", optional, tag = "13")]
+    pub tiebreaker: ::core::option::Option<i32>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -3118,6 +3392,15 @@ pub struct SearchBatchPoints {
     /// Options for specifying read consistency guarantees
     #[prost(message, optional, tag = "3")]
     pub read_consistency: ::core::option::Option<ReadConsistency>,
+    /// Return components of the score of each point
+    #[prost(bool, optional, tag = "4")]
+    pub explain_score: ::core::option::Option<bool>,
+    /// If set, the search is aborted after this many milliseconds. Results without payloads are returned if the timeout is hit while retrieving payloads
+    #[prost(uint64, optional, tag = "5")]
+    pub timeout_ms: ::core::option::Option<u64>,
+    /// Do not execute the search, only estimate its cost. Results are empty
+    #[prost(bool, optional, tag = "6")]
+    pub dry_run: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3474,11 +3757,14 @@ pub struct PointsOperationResponse {
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResult {
     /// Number of operation
-    #[prost(uint64, tag = "1")]
-    pub operation_id: u64,
+    #[prost(uint64, optional, tag = "1")]
+    pub operation_id: ::core::option::Option<u64>,
     /// Operation status
     #[prost(enumeration = "UpdateStatus", tag = "2")]
     pub status: i32,
+    /// Number of shard replicas which acknowledged the operation
+    #[prost(uint32, optional, tag = "3")]
+    pub shards_written: ::core::option::Option<u32>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3499,6 +3785,9 @@ pub struct ScoredPoint {
     /// Vectors to search
     #[prost(message, optional, tag = "6")]
     pub vectors: ::core::option::Option<Vectors>,
+    /// Components of the score, only returned if explanation of the score is requested
+    #[prost(map = "string, float", tag = "7")]
+    pub score_breakdown: ::std::collections::HashMap<::prost::alloc::string::String, f32>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3562,6 +3851,9 @@ pub struct SearchResponse {
 pub struct BatchResult {
     #[prost(message, repeated, tag = "1")]
     pub result: ::prost::alloc::vec::Vec<ScoredPoint>,
+    /// Lowest HNSW ef used by the shards for this search, not reported if the search was exact
+    #[prost(uint64, optional, tag = "2")]
+    pub hnsw_ef_used: ::core::option::Option<u64>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3572,6 +3864,26 @@ pub struct SearchBatchResponse {
     /// Time spent to process
     #[prost(double, tag = "2")]
     pub time: f64,
+    /// Set if the search timed out while retrieving payloads, results are then returned without payloads and vectors
+    #[prost(string, optional, tag = "3")]
+    pub timeout: ::core::option::Option<::prost::alloc::string::String>,
+    /// Estimated cost of the batch, set instead of the results if dry run is requested
+    #[prost(message, optional, tag = "4")]
+    pub dry_run: ::core::option::Option<SearchDryRunResult>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchDryRunResult {
+    /// Number of shards the batch would be sent to
+    #[prost(uint64, tag = "1")]
+    pub shards_touched: u64,
+    /// Estimated number of points matching the filters of the batch, per shard
+    #[prost(uint64, repeated, tag = "2")]
+    pub estimated_candidates_per_shard: ::prost::alloc::vec::Vec<u64>,
+    /// Estimated size of payloads returned by the batch
+    #[prost(uint64, tag = "3")]
+    pub estimated_payload_bytes: u64,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -3999,6 +4311,35 @@ impl ReadConsistencyType {
 #[derive(serde::Serialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum TiebreakerMode {
+    /// Points with equal scores are ordered by ascending point ID, default
+    IdAscending = 0,
+    /// Points with equal scores are ordered by descending point ID
+    IdDescending = 1,
+}
+impl TiebreakerMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TiebreakerMode::IdAscending => "IdAscending",
+            TiebreakerMode::IdDescending => "IdDescending",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "IdAscending" => Some(Self::IdAscending),
+            "IdDescending" => Some(Self::IdDescending),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum FieldType {
     Keyword = 0,
     Integer = 1,
@@ -6009,6 +6350,8 @@ pub struct SearchBatchPointsInternal {
     pub search_points: ::prost::alloc::vec::Vec<SearchPoints>,
     #[prost(uint32, optional, tag = "3")]
     pub shard_id: ::core::option::Option<u32>,
+    #[prost(bool, optional, tag = "4")]
+    pub explain_score: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[derive(validator::Validate)]
@@ -89,6 +89,7 @@ impl Validate for crate::grpc::qdrant::vectors_config_diff::Config {
         match self {
             Config::Params(params) => params.validate(),
             Config::ParamsMap(params_map) => params_map.validate(),
+            Config::Add(params_map) => params_map.validate(),
         }
     }
 }
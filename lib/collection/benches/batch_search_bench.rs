@@ -69,6 +69,8 @@ fn batch_search_bench(c: &mut Criterion) {
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
     let collection_config = CollectionConfig {
@@ -86,6 +88,7 @@ fn batch_search_bench(c: &mut Criterion) {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        use_cross_device_snapshot_copy: None,
     };
 
     let shared_config = Arc::new(RwLock::new(collection_config));
@@ -143,11 +146,19 @@ fn batch_search_bench(c: &mut Criterion) {
                             with_payload: None,
                             with_vector: None,
                             score_threshold: None,
+                            ..Default::default()
                         };
                         let result = shard
                             .search(
                                 Arc::new(SearchRequestBatch {
                                     searches: vec![search_query],
+                                    debug: false,
+                                    dry_run: false,
+                                    explain_score: false,
+                                    min_shards: None,
+                                    wait_for_shards: None,
+                                    timeout_ms: None,
+                                    score_normalizer: None,
                                 }),
                                 search_runtime_handle,
                             )
@@ -175,11 +186,21 @@ fn batch_search_bench(c: &mut Criterion) {
                             with_payload: None,
                             with_vector: None,
                             score_threshold: None,
+                            ..Default::default()
                         };
                         searches.push(search_query);
                     }
 
-                    let search_query = SearchRequestBatch { searches };
+                    let search_query = SearchRequestBatch {
+                        searches,
+                        debug: false,
+                        dry_run: false,
+                        explain_score: false,
+                        min_shards: None,
+                        wait_for_shards: None,
+                        timeout_ms: None,
+                        score_normalizer: None,
+                    };
                     let result = shard
                         .search(Arc::new(search_query), search_runtime_handle)
                         .await
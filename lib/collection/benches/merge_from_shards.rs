@@ -0,0 +1,94 @@
+#[cfg(not(target_os = "windows"))]
+mod prof;
+
+use std::num::{NonZeroU32, NonZeroU64};
+
+use collection::collection::Collection;
+use collection::config::CollectionParams;
+use collection::operations::types::{SearchRequest, VectorParams};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::{thread_rng, Rng};
+use segment::types::{Distance, ScoredPoint};
+
+const BATCH_SIZE: usize = 128;
+const SHARDS: usize = 8;
+const LIMIT: usize = 100;
+
+fn merge_from_shards_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge-from-shards-bench");
+
+    let collection_params = CollectionParams {
+        vectors: VectorParams {
+            size: NonZeroU64::new(100).unwrap(),
+            distance: Distance::Dot,
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: None,
+        }
+        .into(),
+        shard_number: NonZeroU32::new(SHARDS as u32).unwrap(),
+        replication_factor: NonZeroU32::new(1).unwrap(),
+        write_consistency_factor: NonZeroU32::new(1).unwrap(),
+        on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
+    };
+    let distances = collection_params.vector_distances();
+
+    let search: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": vec![0.0; 100],
+        "limit": LIMIT,
+    }))
+    .unwrap();
+    let searches = vec![search; BATCH_SIZE];
+
+    let mut rng = thread_rng();
+    let shard_results: Vec<Vec<Vec<ScoredPoint>>> = (0..SHARDS)
+        .map(|shard| {
+            (0..BATCH_SIZE)
+                .map(|_| {
+                    (0..LIMIT)
+                        .map(|i| ScoredPoint {
+                            id: ((shard * LIMIT + i) as u64).into(),
+                            version: 0,
+                            score: rng.gen(),
+                            payload: None,
+                            vector: None,
+                            score_breakdown: None,
+                            search_params_echo: None,
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    group.bench_function("sequential-merge", |b| {
+        b.iter_batched(
+            || shard_results.clone(),
+            |shard_results| {
+                Collection::merge_searches(shard_results, &searches, &distances, None, usize::MAX)
+                    .unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("parallel-merge", |b| {
+        b.iter_batched(
+            || shard_results.clone(),
+            |shard_results| {
+                Collection::merge_searches(shard_results, &searches, &distances, None, 0).unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = merge_from_shards_bench,
+}
+
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+#[cfg(not(target_os = "windows"))]
+mod prof;
+
+use std::collections::BTreeMap;
+use std::num::{NonZeroU32, NonZeroU64};
+
+use collection::config::CollectionParams;
+use collection::operations::types::{VectorParams, VectorsConfig};
+use criterion::{criterion_group, criterion_main, Criterion};
+use segment::types::Distance;
+
+const BATCH_SIZE: usize = 1000;
+
+fn vector_distances_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vector-distances-bench");
+
+    let vectors = (0..10)
+        .map(|i| {
+            let params = VectorParams {
+                size: NonZeroU64::new(100).unwrap(),
+                distance: Distance::Cosine,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+            };
+            (format!("vector-{i}"), params)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let collection_params = CollectionParams {
+        vectors: VectorsConfig::Multi(vectors),
+        shard_number: NonZeroU32::new(1).unwrap(),
+        replication_factor: NonZeroU32::new(1).unwrap(),
+        write_consistency_factor: NonZeroU32::new(1).unwrap(),
+        on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
+    };
+    let names: Vec<_> = (0..BATCH_SIZE)
+        .map(|i| format!("vector-{}", i % 10))
+        .collect();
+
+    // Previous behavior: look up vector params of each search of the batch
+    group.bench_function("vector-params-per-search", |b| {
+        b.iter(|| {
+            for name in &names {
+                let _distance = collection_params.get_vector_params(name).unwrap().distance;
+            }
+        })
+    });
+
+    // Distances are collected once per batch
+    group.bench_function("vector-distances-per-batch", |b| {
+        b.iter(|| {
+            let distances = collection_params.vector_distances();
+            for name in &names {
+                let _distance = distances.get(name).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = vector_distances_bench,
+}
+
+criterion_main!(benches);
@@ -1,48 +1,74 @@
-use std::cmp::max;
+use std::cmp::{max, Ordering};
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::Future;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures::future::{join_all, try_join_all};
+use futures::future::{join_all, try_join, try_join_all, BoxFuture};
+use futures::stream::{self, FuturesUnordered};
+use futures::{FutureExt as _, Stream, StreamExt as _};
 use itertools::Itertools;
+use parking_lot::{Mutex as ParkingMutex, RwLock as ParkingRwLock};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use segment::common::version::StorageVersion;
+use segment::data_types::vectors::{
+    NamedVector, NamedVectorStruct, VectorStruct, DEFAULT_VECTOR_NAME,
+};
 use segment::spaces::tools::{peek_top_largest_iterable, peek_top_smallest_iterable};
 use segment::types::{
-    ExtendedPointId, Order, QuantizationConfig, ScoredPoint, WithPayload, WithPayloadInterface,
-    WithVector,
+    ExtendedPointId, Filter, Order, QuantizationConfig, ScoredPoint, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use semver::Version;
-use tar::Builder as TarBuilder;
 use tokio::fs::{copy, create_dir_all, rename};
 use tokio::runtime::Handle;
-use tokio::sync::{Mutex, RwLock, RwLockWriteGuard};
+use tokio::sync::{Mutex, RwLock, RwLockWriteGuard, Semaphore};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 use validator::Validate;
 
-use crate::collection_state::{ShardInfo, State};
-use crate::common::file_utils::move_file;
+use crate::collection_manager::fusion::{normalize_scores, rrf_scoring, DEFAULT_RRF_K};
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
+use crate::collection_manager::rescoring::ExactRescoring;
+use crate::collection_state::{state_checksum, ShardInfo, State};
+use crate::common::file_lock::FileLock;
+use crate::common::file_utils::{is_cross_device_error, move_file};
+use crate::common::idempotency_cache::{IdempotencyCache, Reservation};
 use crate::common::is_ready::IsReady;
-use crate::config::CollectionConfig;
+use crate::common::points_count_cache::PointsCountCache;
+use crate::common::process_memory::ProcessMemory;
+use crate::config::{
+    check_hnsw_config, check_optimizers_config, CollectionConfig, VectorDistances,
+};
 use crate::hash_ring::HashRing;
 use crate::operations::config_diff::{
     CollectionParamsDiff, DiffConfig, HnswConfigDiff, OptimizersConfigDiff, QuantizationConfigDiff,
 };
 use crate::operations::consistency_params::ReadConsistency;
+use crate::operations::operation_effect::{EstimateOperationEffectArea, OperationEffectArea};
 use crate::operations::point_ops::WriteOrdering;
 use crate::operations::shared_storage_config::SharedStorageConfig;
 use crate::operations::snapshot_ops::{
-    get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
+    archive_snapshot_dir, get_snapshot_description, list_snapshots_in_directory,
+    update_latest_snapshot_link, ArchivingProgressWriter, ShardSnapshotManifest,
+    SnapshotDescription, SnapshotHashes, SnapshotManifest, SnapshotPhase, SnapshotProgress,
+    SnapshotProgressCallback,
 };
 use crate::operations::types::{
-    CollectionClusterInfo, CollectionError, CollectionInfo, CollectionResult, CountRequest,
-    CountResult, LocalShardInfo, NodeType, PointRequest, Record, RemoteShardInfo, ScrollRequest,
-    ScrollResult, SearchRequest, SearchRequestBatch, UpdateResult, VectorsConfigDiff,
+    CacheStats, CollectionClusterInfo, CollectionError, CollectionInfo, CollectionResult,
+    ContextExample, CountRequest, CountResult, Fusion, LocalShardInfo, MultiVectorSearchRequest,
+    NodeType, PointRequest, Record, RemoteShardInfo, ScoreNormalizerType, ScrollKey, ScrollRequest,
+    ScrollResult, SearchBatchDebugResult, SearchBatchResult, SearchDryRunResult, SearchMeta,
+    SearchQualityHint, SearchRequest, SearchRequestBatch, SegmentReadEstimation, ShardFilter,
+    TiebreakerMode, UpdateConfigResult, UpdateResult, VectorName, VectorQuery, VectorsConfigDiff,
+    WeightedVector,
 };
 use crate::operations::CollectionUpdateOperations;
-use crate::optimizers_builder::OptimizersConfig;
+use crate::optimizers_builder::{OptimizerRestartReason, OptimizersConfig};
+use crate::recommendations::context_query_vector;
 use crate::shards::channel_service::ChannelService;
 use crate::shards::collection_shard_distribution::CollectionShardDistribution;
 use crate::shards::local_shard::LocalShard;
@@ -51,16 +77,20 @@ use crate::shards::replica_set::ReplicaState::{Active, Dead, Initializing, Liste
 use crate::shards::replica_set::{
     Change, ChangePeerState, ReplicaState, ShardReplicaSet as ReplicaSetShard,
 }; // TODO rename ReplicaShard to ReplicaSetShard
-use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::shard::{PeerId, Shard, ShardId};
 use crate::shards::shard_config::{self, ShardConfig};
 use crate::shards::shard_holder::{LockedShardHolder, ShardHolder};
+use crate::shards::shard_trait::ShardSearchResult;
 use crate::shards::shard_versioning::versioned_shard_path;
+use crate::shards::telemetry::OptimizerQueueStats;
 use crate::shards::transfer::shard_transfer::{
     change_remote_shard_route, check_transfer_conflicts_strict, finalize_partial_shard,
     handle_transferred_shard_proxy, revert_proxy_shard_to_local, spawn_transfer_task,
-    ShardTransfer, ShardTransferKey,
+    ShardTransfer, ShardTransferKey, TransferCompensation, TransferProgress,
+};
+use crate::shards::transfer::transfer_tasks_pool::{
+    TaskResult, TransferTaskStatus, TransferTasksPool,
 };
-use crate::shards::transfer::transfer_tasks_pool::{TaskResult, TransferTasksPool};
 use crate::shards::{replica_set, CollectionId, HASH_RING_SHARD_SCALE};
 use crate::telemetry::CollectionTelemetry;
 
@@ -68,6 +98,64 @@ pub type VectorLookupFuture<'a> = Box<dyn Future<Output = CollectionResult<Vec<R
 pub type OnTransferFailure = Arc<dyn Fn(ShardTransfer, CollectionId, &str) + Send + Sync>;
 pub type OnTransferSuccess = Arc<dyn Fn(ShardTransfer, CollectionId) + Send + Sync>;
 pub type RequestShardTransfer = Arc<dyn Fn(ShardTransfer) + Send + Sync>;
+/// Retrieves a point with its vectors from another collection, see `SearchRequest::lookup_from`
+pub type VectorLookup = Arc<
+    dyn Fn(CollectionId, ExtendedPointId) -> BoxFuture<'static, CollectionResult<Record>>
+        + Send
+        + Sync,
+>;
+/// Called before the search, may modify the request. Returned error aborts the search.
+pub type PreSearchHook = Arc<dyn Fn(&mut SearchRequest) -> CollectionResult<()> + Send + Sync>;
+/// Called with the results of the search. Returned error is reported instead of the results.
+pub type PostSearchHook = Arc<dyn Fn(&[ScoredPoint]) -> CollectionResult<()> + Send + Sync>;
+
+/// Hooks for middleware-style processing of client searches, e.g. access control or logging.
+/// Called for each search of a batch.
+#[derive(Clone, Default)]
+pub struct SearchHooks {
+    pub pre_search_hook: Option<PreSearchHook>,
+    pub post_search_hook: Option<PostSearchHook>,
+}
+
+/// Called with the part of a client update applied to a shard, after the shard accepted it
+pub type OnUpdateHook = Arc<dyn Fn(ShardId, &CollectionUpdateOperations) + Send + Sync>;
+/// Async version of [`OnUpdateHook`], awaited before the result of the update is returned
+pub type OnUpdateHookAsync =
+    Arc<dyn Fn(ShardId, CollectionUpdateOperations) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Hooks notified about client updates of the collection, e.g. for change data capture.
+/// Hooks can't fail and don't affect the result of the update.
+#[derive(Clone, Default)]
+pub struct UpdateHooks {
+    pub on_update_hook: Option<OnUpdateHook>,
+    pub on_update_hook_async: Option<OnUpdateHookAsync>,
+}
+
+impl UpdateHooks {
+    fn is_empty(&self) -> bool {
+        self.on_update_hook.is_none() && self.on_update_hook_async.is_none()
+    }
+
+    async fn notify(&self, shard_id: ShardId, operation: CollectionUpdateOperations) {
+        if let Some(on_update_hook) = &self.on_update_hook {
+            on_update_hook(shard_id, &operation);
+        }
+        if let Some(on_update_hook_async) = &self.on_update_hook_async {
+            on_update_hook_async(shard_id, operation).await;
+        }
+    }
+}
+
+const COLLECTION_LOCK_FILE: &str = "collection.lock";
+
+/// How long to wait for other snapshots of the collection to finish before giving up
+const SNAPSHOT_PERMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a requested shard transfer to finish
+const WAIT_TRANSFER_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// How often to check if a requested shard transfer is finished
+const WAIT_TRANSFER_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 struct CollectionVersion;
 
@@ -88,19 +176,41 @@ pub struct Collection {
     snapshots_path: PathBuf,
     channel_service: ChannelService,
     transfer_tasks: Mutex<TransferTasksPool>,
+    /// Outgoing transfers which push a shard snapshot instead of streaming points,
+    /// with the directory to create the snapshot in
+    snapshot_transfers: ParkingMutex<HashMap<ShardTransferKey, PathBuf>>,
     request_shard_transfer_cb: RequestShardTransfer,
-    #[allow(dead_code)] //Might be useful in case of repartition implementation
+    vector_lookup_cb: Option<VectorLookup>,
+    search_hooks: SearchHooks,
+    update_hooks: UpdateHooks,
     notify_peer_failure_cb: ChangePeerState,
     init_time: Duration,
     // One-way boolean flag that is set to true when the collection is fully initialized
     // i.e. all shards are activated for the first time.
     is_initialized: Arc<IsReady>,
+    points_count_cache: PointsCountCache,
+    idempotency_cache: Arc<IdempotencyCache>,
     // Lock to temporary block collection update operations while the collection is being migrated.
     // Lock is acquired for read on update operation and can be acquired for write externally,
     // which will block all update operations until the lock is released.
     updates_lock: RwLock<()>,
     // Update runtime handle.
     update_runtime: Handle,
+    // Search runtime handle.
+    search_runtime: Handle,
+    // Copy of the shard transfers, used when the shard holder is locked by another operation.
+    // Refreshed on every change of the transfers and on every successful read.
+    transfers_cache: Arc<ParkingRwLock<BTreeMap<ShardTransferKey, ShardTransfer>>>,
+    // Limits the number of snapshots created at the same time.
+    snapshots_semaphore: Semaphore,
+    // Content hashes of the collection snapshots, used to deduplicate them.
+    snapshot_hashes: SnapshotHashes,
+    // Quality hints of all searches since the start, which requested it, merged together.
+    // Reported in the telemetry.
+    search_quality_hint: ParkingMutex<Option<SearchQualityHint>>,
+    // Exclusive lock on the collection directory, prevents other processes from opening it.
+    // Released when the collection is dropped.
+    _lock: FileLock,
 }
 
 impl Collection {
@@ -108,6 +218,26 @@ impl Collection {
         self.id.clone()
     }
 
+    pub fn this_peer_id(&self) -> PeerId {
+        self.this_peer_id
+    }
+
+    /// Directory where the collection data is stored
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Directory where the collection snapshots are stored
+    pub fn snapshots_path(&self) -> &Path {
+        &self.snapshots_path
+    }
+
+    /// Assign tenant to a shard, so that searches for this tenant skip other shards.
+    /// Returns the assigned shard.
+    pub async fn assign_tenant_shard(&self, tenant_id: String) -> Option<ShardId> {
+        self.shards_holder.write().await.assign_tenant(tenant_id)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
         name: CollectionId,
@@ -120,11 +250,16 @@ impl Collection {
         channel_service: ChannelService,
         on_replica_failure: ChangePeerState,
         request_shard_transfer: RequestShardTransfer,
+        vector_lookup: Option<VectorLookup>,
+        search_hooks: SearchHooks,
+        update_hooks: UpdateHooks,
         search_runtime: Option<Handle>,
         update_runtime: Option<Handle>,
     ) -> Result<Self, CollectionError> {
         let start_time = std::time::Instant::now();
 
+        let lock = Self::acquire_lock(path)?;
+
         let mut shard_holder = ShardHolder::new(path, HashRing::fair(HASH_RING_SHARD_SCALE))?;
 
         let shared_collection_config = Arc::new(RwLock::new(collection_config.clone()));
@@ -156,6 +291,14 @@ impl Collection {
         CollectionVersion::save(path)?;
         collection_config.save(path)?;
 
+        let idempotency_cache =
+            Arc::new(IdempotencyCache::new(shared_storage_config.idempotency_ttl));
+        IdempotencyCache::spawn_eviction_task(&idempotency_cache);
+
+        let max_concurrent_snapshots = shared_storage_config.max_concurrent_snapshots.max(1);
+        let points_count_cache =
+            PointsCountCache::new(shared_storage_config.points_count_cache_ttl);
+
         Ok(Self {
             id: name.clone(),
             shards_holder: locked_shard_holder,
@@ -166,12 +309,36 @@ impl Collection {
             snapshots_path: snapshots_path.to_owned(),
             channel_service,
             transfer_tasks: Mutex::new(TransferTasksPool::new(name.clone())),
+            snapshot_transfers: Default::default(),
             request_shard_transfer_cb: request_shard_transfer.clone(),
+            vector_lookup_cb: vector_lookup,
+            search_hooks,
+            update_hooks,
             notify_peer_failure_cb: on_replica_failure.clone(),
             init_time: start_time.elapsed(),
             is_initialized: Arc::new(Default::default()),
+            points_count_cache,
+            idempotency_cache,
             updates_lock: RwLock::new(()),
             update_runtime: update_runtime.unwrap_or_else(Handle::current),
+            search_runtime: search_runtime.unwrap_or_else(Handle::current),
+            transfers_cache: Default::default(),
+            snapshots_semaphore: Semaphore::new(max_concurrent_snapshots),
+            snapshot_hashes: SnapshotHashes::new(snapshots_path),
+            search_quality_hint: Default::default(),
+            _lock: lock,
+        })
+    }
+
+    /// Acquire an exclusive lock on the collection directory.
+    /// Fails with `CollectionError::Unavailable` if the collection is already opened by another process.
+    fn acquire_lock(path: &Path) -> CollectionResult<FileLock> {
+        FileLock::try_acquire(&path.join(COLLECTION_LOCK_FILE)).map_err(|err| match err {
+            CollectionError::Unavailable { .. } => CollectionError::unavailable(format!(
+                "Collection at {} is used by another process",
+                path.display(),
+            )),
+            err => err,
         })
     }
 
@@ -208,10 +375,16 @@ impl Collection {
         channel_service: ChannelService,
         on_replica_failure: replica_set::ChangePeerState,
         request_shard_transfer: RequestShardTransfer,
+        vector_lookup: Option<VectorLookup>,
+        search_hooks: SearchHooks,
+        update_hooks: UpdateHooks,
         search_runtime: Option<Handle>,
         update_runtime: Option<Handle>,
-    ) -> Self {
+    ) -> CollectionResult<Self> {
         let start_time = std::time::Instant::now();
+
+        let lock = Self::acquire_lock(path)?;
+
         let stored_version = CollectionVersion::load(path)
             .expect("Can't read collection version")
             .parse()
@@ -266,7 +439,15 @@ impl Collection {
 
         let locked_shard_holder = Arc::new(LockedShardHolder::new(shard_holder));
 
-        Self {
+        let idempotency_cache =
+            Arc::new(IdempotencyCache::new(shared_storage_config.idempotency_ttl));
+        IdempotencyCache::spawn_eviction_task(&idempotency_cache);
+
+        let max_concurrent_snapshots = shared_storage_config.max_concurrent_snapshots.max(1);
+        let points_count_cache =
+            PointsCountCache::new(shared_storage_config.points_count_cache_ttl);
+
+        Ok(Self {
             id: collection_id.clone(),
             shards_holder: locked_shard_holder,
             collection_config: shared_collection_config,
@@ -276,39 +457,98 @@ impl Collection {
             snapshots_path: snapshots_path.to_owned(),
             channel_service,
             transfer_tasks: Mutex::new(TransferTasksPool::new(collection_id.clone())),
+            snapshot_transfers: Default::default(),
             request_shard_transfer_cb: request_shard_transfer.clone(),
+            vector_lookup_cb: vector_lookup,
+            search_hooks,
+            update_hooks,
             notify_peer_failure_cb: on_replica_failure,
             init_time: start_time.elapsed(),
             is_initialized: Arc::new(Default::default()),
+            points_count_cache,
+            idempotency_cache,
             updates_lock: RwLock::new(()),
             update_runtime: update_runtime.unwrap_or_else(Handle::current),
-        }
+            search_runtime: search_runtime.unwrap_or_else(Handle::current),
+            transfers_cache: Default::default(),
+            snapshots_semaphore: Semaphore::new(max_concurrent_snapshots),
+            snapshot_hashes: SnapshotHashes::new(snapshots_path),
+            search_quality_hint: Default::default(),
+            _lock: lock,
+        })
+    }
+
+    /// Create a new replica set for the shard, with replicas on the given peers
+    pub(crate) async fn create_replica_set(
+        &self,
+        shard_id: ShardId,
+        replicas: &[PeerId],
+    ) -> CollectionResult<ReplicaSetShard> {
+        let is_local = replicas.contains(&self.this_peer_id);
+        let peers = replicas
+            .iter()
+            .copied()
+            .filter(|peer_id| *peer_id != self.this_peer_id)
+            .collect();
+
+        ReplicaSetShard::build(
+            shard_id,
+            self.name(),
+            self.this_peer_id,
+            is_local,
+            peers,
+            self.notify_peer_failure_cb.clone(),
+            &self.path,
+            self.collection_config.clone(),
+            self.shared_storage_config.clone(),
+            self.channel_service.clone(),
+            self.update_runtime.clone(),
+            self.search_runtime.clone(),
+        )
+        .await
     }
 
     /// Return a list of local shards, present on this peer
     pub async fn get_local_shards(&self) -> Vec<ShardId> {
+        self.get_local_shards_with_state()
+            .await
+            .into_iter()
+            .map(|(shard_id, _)| shard_id)
+            .collect()
+    }
+
+    /// Return a list of local shards, present on this peer, along with the state of their replica
+    pub async fn get_local_shards_with_state(&self) -> Vec<(ShardId, ReplicaState)> {
         let shards_holder = self.shards_holder.read().await;
         let mut res = vec![];
         for (shard_id, replica_set) in shards_holder.get_shards() {
             if replica_set.has_local_shard().await {
-                res.push(*shard_id);
+                let state = replica_set
+                    .peer_state(&replica_set.this_peer_id())
+                    .unwrap_or(ReplicaState::Dead);
+                res.push((*shard_id, state));
             }
         }
         res
     }
 
+    #[deprecated = "use `is_fully_stable`, which also accounts for ongoing transfers"]
     pub async fn is_all_active(&self) -> bool {
+        self.is_fully_stable().await
+    }
+
+    /// Whether all replicas of all shards are active and no shard transfers are in progress
+    pub async fn is_fully_stable(&self) -> bool {
         let shards_holder = self.shards_holder.read().await;
-        for (_, replica_set) in shards_holder.get_shards() {
-            if !replica_set
+        if !shards_holder.shard_transfers.read().is_empty() {
+            return false;
+        }
+        shards_holder.get_shards().all(|(_, replica_set)| {
+            replica_set
                 .peers()
                 .into_iter()
                 .all(|(_, state)| state == ReplicaState::Active)
-            {
-                return false;
-            }
-        }
-        true
+        })
     }
 
     pub async fn set_shard_replica_state(
@@ -316,7 +556,7 @@ impl Collection {
         shard_id: ShardId,
         peer_id: PeerId,
         state: ReplicaState,
-        from_state: Option<ReplicaState>,
+        from_states: Option<HashSet<ReplicaState>>,
     ) -> CollectionResult<()> {
         let shard_holder = self.shards_holder.read().await;
         let replica_set = shard_holder
@@ -330,13 +570,13 @@ impl Collection {
         );
 
         // Validation:
-        // 0. Check that `from_state` matches current state
+        // 0. Check that current state is one of `from_states`
 
-        if from_state.is_some() {
+        if let Some(from_states) = &from_states {
             let current_state = replica_set.peer_state(&peer_id);
-            if current_state != from_state {
+            if !current_state.map_or(false, |current| from_states.contains(&current)) {
                 return Err(CollectionError::bad_input(format!(
-                    "Replica {peer_id} of shard {shard_id} has state {current_state:?}, but expected {from_state:?}"
+                    "Replica {peer_id} of shard {shard_id} has state {current_state:?}, but expected one of {from_states:?}"
                 )));
             }
         }
@@ -370,14 +610,18 @@ impl Collection {
             // Terminate transfer if source or target replicas are now dead
             let related_transfers = shard_holder.get_related_transfers(&shard_id, &peer_id);
             for transfer in related_transfers {
-                self._abort_shard_transfer(transfer.key(), &shard_holder)
-                    .await?;
+                self._abort_shard_transfer(
+                    transfer.key(),
+                    Some(&format!("replica {peer_id} is marked as dead")),
+                    &shard_holder,
+                )
+                .await?;
             }
         }
 
         if !self.is_initialized.check_ready() {
             // If not initialized yet, we need to check if it was initialized by this call
-            let state = self.state().await;
+            let state = self.state().await?;
             let mut is_fully_active = true;
             for (_shard_id, shard_info) in state.shards {
                 if shard_info
@@ -432,24 +676,37 @@ impl Collection {
     }
 
     pub async fn check_transfer_exists(&self, transfer_key: &ShardTransferKey) -> bool {
-        let shard_holder_read = self.shards_holder.read().await;
-        let matched = shard_holder_read
-            .shard_transfers
-            .read()
-            .iter()
-            .any(|transfer| transfer_key.check(transfer));
-        matched
+        self.get_transfer(transfer_key).await.is_some()
     }
 
+    /// Find transfer by its key.
+    ///
+    /// Doesn't wait for the shard holder lock: if it is held for writing,
+    /// the transfer is looked up in the cached copy of the transfers.
     pub async fn get_transfer(&self, transfer_key: &ShardTransferKey) -> Option<ShardTransfer> {
-        let shard_holder_read = self.shards_holder.read().await;
-        let transfer = shard_holder_read
+        match self.shards_holder.try_read() {
+            Ok(shard_holder_read) => {
+                self.refresh_transfers_cache(&shard_holder_read);
+                let transfer = shard_holder_read
+                    .shard_transfers
+                    .read()
+                    .get(transfer_key)
+                    .cloned();
+                transfer
+            }
+            Err(_) => self.transfers_cache.read().get(transfer_key).cloned(),
+        }
+    }
+
+    /// Replace the cached copy of the transfers with the current ones of the shard holder.
+    pub(crate) fn refresh_transfers_cache(&self, shard_holder: &ShardHolder) {
+        let transfers = shard_holder
             .shard_transfers
             .read()
             .iter()
-            .find(|transfer| transfer_key.check(transfer))
-            .cloned();
-        transfer
+            .map(|(key, transfer)| (key.clone(), transfer.clone()))
+            .collect();
+        *self.transfers_cache.write() = transfers;
     }
 
     pub async fn get_outgoing_transfers(&self, current_peer_id: &PeerId) -> Vec<ShardTransfer> {
@@ -465,7 +722,7 @@ impl Collection {
         let transfers = shard_holder
             .shard_transfers
             .read()
-            .iter()
+            .values()
             .filter(|&transfer| predicate(transfer))
             .cloned()
             .collect();
@@ -485,17 +742,21 @@ impl Collection {
         let shard_holder = self.shards_holder.clone();
         let collection_id = self.id.clone();
         let channel_service = self.channel_service.clone();
+        let progress = Arc::new(ParkingMutex::new(TransferProgress::default()));
+        let snapshot_temp_dir = self.snapshot_transfers.lock().get(&transfer.key()).cloned();
 
         let transfer_task = spawn_transfer_task(
             shard_holder,
             transfer.clone(),
             collection_id,
             channel_service,
+            progress.clone(),
+            snapshot_temp_dir,
             on_finish,
             on_error,
         );
 
-        active_transfer_tasks.add_task(&transfer, transfer_task);
+        active_transfer_tasks.add_task(&transfer, transfer_task, progress);
     }
 
     pub async fn start_shard_transfer<T, F>(
@@ -513,6 +774,7 @@ impl Collection {
             let shards_holder = self.shards_holder.read().await;
             let _was_not_transferred =
                 shards_holder.register_start_shard_transfer(shard_transfer.clone())?;
+            self.refresh_transfers_cache(&shards_holder);
             let replica_set_opt = shards_holder.get_shard(&shard_id);
 
             // Check if current node owns the shard which should be transferred
@@ -534,6 +796,11 @@ impl Collection {
             // (on all peers, regardless if shard is local or remote on that peer).
             //
             // This should disable queries to receiver replica even if it was active before.
+            // Updates buffered during a previous transfer are outdated by the transferred data
+            if is_receiver {
+                replica_set.clear_pending_operations().await?;
+            }
+
             if !is_local && is_receiver {
                 let shard = LocalShard::build(
                     shard_id,
@@ -560,9 +827,134 @@ impl Collection {
         Ok(do_transfer)
     }
 
+    /// Make sure the receiving peer has enough disk space to accept the transferred shard.
+    ///
+    /// Should be checked before the transfer is proposed to consensus, as the result depends on
+    /// the state of other peers and must not affect applying of consensus operations.
+    /// The size of the shard is estimated by the sending peer, the check is skipped if
+    /// the sending peer has no local replica of the shard.
+    pub async fn check_transfer_disk_space(
+        &self,
+        shard_transfer: &ShardTransfer,
+    ) -> CollectionResult<()> {
+        /// Required ratio of available disk space to the estimated shard size
+        const DISK_SPACE_RESERVE_FACTOR: f64 = 1.5;
+
+        let estimated_size = if shard_transfer.from == self.this_peer_id {
+            self.estimate_local_shard_size(shard_transfer.shard_id)
+                .await
+        } else {
+            self.channel_service
+                .check_disk_space(shard_transfer.from, &self.id, Some(shard_transfer.shard_id))
+                .await?
+                .shard_size_bytes
+        };
+        let Some(estimated_size) = estimated_size else {
+            return Ok(());
+        };
+
+        let available_space = if shard_transfer.to == self.this_peer_id {
+            self.available_disk_space().await?
+        } else {
+            self.channel_service
+                .check_disk_space(shard_transfer.to, &self.id, None)
+                .await?
+                .available_bytes
+        };
+
+        let required_space = (estimated_size as f64 * DISK_SPACE_RESERVE_FACTOR) as u64;
+        if available_space < required_space {
+            return Err(CollectionError::bad_request(format!(
+                "Not enough disk space on peer {} to transfer shard {}: \
+                 {available_space} bytes available, {required_space} bytes required",
+                shard_transfer.to, shard_transfer.shard_id,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check if a snapshot of the shard pushed by `from_peer_id` can be accepted.
+    ///
+    /// Snapshots are only accepted from the sending peer of a running transfer of the shard
+    /// into the `Partial` local replica, see [`Collection::snapshot_shard_to_peer`],
+    /// and only if there is enough disk space to store and unpack them.
+    pub async fn check_pushed_shard_snapshot(
+        &self,
+        shard_id: ShardId,
+        from_peer_id: PeerId,
+        snapshot_size: u64,
+    ) -> CollectionResult<()> {
+        let transfer_key = ShardTransferKey {
+            shard_id,
+            from: from_peer_id,
+            to: self.this_peer_id,
+        };
+        if !self.check_transfer_exists(&transfer_key).await {
+            return Err(CollectionError::bad_request(format!(
+                "Snapshot of shard {shard_id} is not expected: \
+                 no transfer of the shard from peer {from_peer_id} is running",
+            )));
+        }
+
+        let local_state = self
+            .shards_holder
+            .read()
+            .await
+            .get_shard(&shard_id)
+            .and_then(|replica_set| replica_set.peer_state(&self.this_peer_id));
+        if local_state != Some(ReplicaState::Partial) {
+            return Err(CollectionError::bad_request(format!(
+                "Snapshot of shard {shard_id} is not expected: \
+                 local replica is {local_state:?}, not Partial",
+            )));
+        }
+
+        // Archive is stored and unpacked before the shard is restored from it
+        let available_space = self.available_disk_space().await?;
+        let required_space = snapshot_size.saturating_mul(2);
+        if available_space < required_space {
+            return Err(CollectionError::bad_request(format!(
+                "Not enough disk space to accept snapshot of shard {shard_id}: \
+                 {available_space} bytes available, {required_space} bytes required",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Estimated size of the local replica of the shard, if present on this peer
+    pub async fn estimate_local_shard_size(&self, shard_id: ShardId) -> Option<u64> {
+        let shards_holder = self.shards_holder.read().await;
+        shards_holder
+            .get_shard(&shard_id)?
+            .estimate_local_shard_size()
+            .await
+    }
+
+    /// Available disk space on the drive, which stores the collection
+    pub async fn available_disk_space(&self) -> CollectionResult<u64> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            fs4::available_space(&path).map_err(|err| {
+                CollectionError::service_error(format!(
+                    "Can't get available disk space for {}: {err}",
+                    path.display(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    /// Status of all transfer tasks started on this peer
+    pub async fn get_transfer_tasks_status(&self) -> CollectionResult<Vec<TransferTaskStatus>> {
+        Ok(self.transfer_tasks.lock().await.snapshot())
+    }
+
     /// Handles finishing of the shard transfer.
     ///
-    /// Returns true if state was changed, false otherwise.
+    /// Each step of the finalization records its compensating action before it is applied.
+    /// If any step fails, recorded compensations are applied in reverse order, so the shard is
+    /// left in the state it had before the finalization, and the transfer is unregistered as
+    /// aborted.
     pub async fn finish_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<()> {
         let transfer_finished = self
             .transfer_tasks
@@ -575,15 +967,135 @@ impl Collection {
 
         let shards_holder_guard = self.shards_holder.read().await;
 
+        let mut compensations = Vec::new();
+        let mut detached_local = None;
+        if let Err(err) = self
+            ._finish_shard_transfer(
+                &transfer,
+                &shards_holder_guard,
+                &mut compensations,
+                &mut detached_local,
+            )
+            .await
+        {
+            log::error!(
+                "Failed to finalize transfer of shard {} from {} to {}: {err}",
+                transfer.shard_id,
+                transfer.from,
+                transfer.to,
+            );
+
+            let mut compensation_errors = Vec::new();
+            for compensation in compensations.iter().rev() {
+                if let Err(err) = compensation
+                    .apply(&shards_holder_guard, transfer.shard_id, &mut detached_local)
+                    .await
+                {
+                    log::error!(
+                        "Failed to apply {compensation:?} to shard {} during rollback: {err}",
+                        transfer.shard_id,
+                    );
+                    compensation_errors.push(err);
+                }
+            }
+
+            // Rolled back transfer can't be finished anymore, it has to be started again
+            if let Err(abort_err) =
+                shards_holder_guard.register_abort_transfer(&transfer.key(), Some(&err.to_string()))
+            {
+                log::error!(
+                    "Failed to unregister transfer of shard {} after rollback: {abort_err}",
+                    transfer.shard_id,
+                );
+            }
+            self.refresh_transfers_cache(&shards_holder_guard);
+
+            return Err(CollectionError::transfer_finalization_failed(
+                transfer.shard_id,
+                err,
+                compensation_errors,
+            ));
+        }
+
+        // Transfer can't be rolled back anymore, data of the moved shard is not needed
+        if let Some(local) = detached_local {
+            if let Some(replica_set) = shards_holder_guard.get_shard(&transfer.shard_id) {
+                if let Err(err) = replica_set.clear_detached_local(local).await {
+                    log::error!(
+                        "Failed to clear data of shard {} moved to peer {}: {err}",
+                        transfer.shard_id,
+                        transfer.to,
+                    );
+                }
+            }
+        }
+
+        let finish_was_registered =
+            shards_holder_guard.register_finish_transfer(&transfer.key())?;
+        self.refresh_transfers_cache(&shards_holder_guard);
+        log::debug!("finish_was_registered: {}", finish_was_registered);
+
+        if self.this_peer_id == transfer.to {
+            self.spawn_apply_pending_operations(transfer.shard_id);
+        }
+        Ok(())
+    }
+
+    /// Apply updates buffered during a transfer into the local replica in a background task,
+    /// so they are not applied as a part of a consensus operation.
+    ///
+    /// If the operations can't be applied, they are dropped and the local replica is reported
+    /// as failed, so it is recovered by another transfer.
+    fn spawn_apply_pending_operations(&self, shard_id: ShardId) {
+        let shards_holder = self.shards_holder.clone();
+        self.update_runtime.spawn(async move {
+            let shards_holder = shards_holder.read().await;
+            let Some(replica_set) = shards_holder.get_shard(&shard_id) else {
+                return;
+            };
+            if let Err(err) = replica_set.apply_pending_operations().await {
+                log::error!(
+                    "Failed to apply updates buffered during transfer of shard {shard_id}: {err}"
+                );
+                if let Err(err) = replica_set.clear_pending_operations().await {
+                    log::error!("Failed to drop updates buffered for shard {shard_id}: {err}");
+                }
+                replica_set.notify_peer_failure(replica_set.this_peer_id());
+            }
+        });
+    }
+
+    async fn _finish_shard_transfer(
+        &self,
+        transfer: &ShardTransfer,
+        shards_holder_guard: &ShardHolder,
+        compensations: &mut Vec<TransferCompensation>,
+        detached_local: &mut Option<Shard>,
+    ) -> CollectionResult<()> {
+        let replica_set = shards_holder_guard.get_shard(&transfer.shard_id);
+        let peer_state =
+            |peer_id| replica_set.and_then(|replica_set| replica_set.peer_state(&peer_id));
+
         // Should happen on transfer side
         // Unwrap forward proxy into local shard, or replace it with remote shard
         // depending on the `sync` flag.
         if self.this_peer_id == transfer.from {
+            // Local replica is removed from the replica set, if the shard is moved
+            compensations.push(TransferCompensation::RestorePeerState {
+                peer_id: self.this_peer_id,
+                state: peer_state(self.this_peer_id),
+            });
+            compensations.push(TransferCompensation::RestorePeerState {
+                peer_id: transfer.to,
+                state: peer_state(transfer.to),
+            });
+            compensations.push(TransferCompensation::RevertProxy);
             let proxy_promoted = handle_transferred_shard_proxy(
-                &shards_holder_guard,
+                shards_holder_guard,
                 transfer.shard_id,
                 transfer.to,
                 transfer.sync,
+                detached_local,
             )
             .await?;
             log::debug!("proxy_promoted: {}", proxy_promoted);
@@ -592,8 +1104,11 @@ impl Collection {
         // Should happen on receiving side
         // Promote partial shard to active shard
         if self.this_peer_id == transfer.to {
+            if peer_state(self.this_peer_id) == Some(ReplicaState::Partial) {
+                compensations.push(TransferCompensation::DeactivateLocalReplica);
+            }
             let shard_promoted =
-                finalize_partial_shard(&shards_holder_guard, transfer.shard_id).await?;
+                finalize_partial_shard(shards_holder_guard, transfer.shard_id).await?;
             log::debug!(
                 "shard_promoted: {}, shard_id: {}, peer_id: {}",
                 shard_promoted,
@@ -605,8 +1120,20 @@ impl Collection {
         // Should happen on a third-party side
         // Change direction of the remote shards or add a new remote shard
         if self.this_peer_id != transfer.from {
+            if self.this_peer_id != transfer.to {
+                compensations.push(TransferCompensation::RestorePeerState {
+                    peer_id: transfer.to,
+                    state: peer_state(transfer.to),
+                });
+            }
+            if !transfer.sync {
+                compensations.push(TransferCompensation::RestorePeerState {
+                    peer_id: transfer.from,
+                    state: peer_state(transfer.from),
+                });
+            }
             let remote_shard_rerouted = change_remote_shard_route(
-                &shards_holder_guard,
+                shards_holder_guard,
                 transfer.shard_id,
                 transfer.from,
                 transfer.to,
@@ -615,15 +1142,13 @@ impl Collection {
             .await?;
             log::debug!("remote_shard_rerouted: {}", remote_shard_rerouted);
         }
-        let finish_was_registered =
-            shards_holder_guard.register_finish_transfer(&transfer.key())?;
-        log::debug!("finish_was_registered: {}", finish_was_registered);
         Ok(())
     }
 
     async fn _abort_shard_transfer(
         &self,
         transfer_key: ShardTransferKey,
+        reason: Option<&str>,
         shard_holder_guard: &ShardHolder,
     ) -> CollectionResult<()> {
         let _transfer_finished = self
@@ -646,6 +1171,10 @@ impl Collection {
 
         let transfer = self.get_transfer(&transfer_key).await;
 
+        if self.this_peer_id == transfer_key.to {
+            replica_set.clear_pending_operations().await?;
+        }
+
         if transfer.map(|x| x.sync).unwrap_or(false) {
             replica_set.set_replica_state(&transfer_key.to, ReplicaState::Dead)?;
         } else {
@@ -656,7 +1185,9 @@ impl Collection {
             revert_proxy_shard_to_local(shard_holder_guard, transfer_key.shard_id).await?;
         }
 
-        let _finish_was_registered = shard_holder_guard.register_finish_transfer(&transfer_key)?;
+        let _abort_was_registered =
+            shard_holder_guard.register_abort_transfer(&transfer_key, reason)?;
+        self.refresh_transfers_cache(shard_holder_guard);
 
         Ok(())
     }
@@ -667,22 +1198,35 @@ impl Collection {
     /// 2. Stop transfer task
     /// 3. Unwrap the proxy
     /// 4. Remove temp shard, or mark it as dead
+    /// 5. Record the transfer with its abort reason in the transfer history
     pub async fn abort_shard_transfer(
         &self,
         transfer_key: ShardTransferKey,
+        reason: Option<&str>,
     ) -> CollectionResult<()> {
         let shard_holder_guard = self.shards_holder.read().await;
         // Internal implementation, used to prevents double-read deadlock
-        self._abort_shard_transfer(transfer_key, &shard_holder_guard)
+        self._abort_shard_transfer(transfer_key, reason, &shard_holder_guard)
             .await
     }
 
+    /// Reason of the last aborted transfer of the given shard, if it is known
+    pub async fn get_last_transfer_abort_reason(&self, shard_id: ShardId) -> Option<String> {
+        self.shards_holder
+            .read()
+            .await
+            .get_last_aborted_transfer(shard_id)
+            .and_then(|aborted| aborted.abort_reason)
+    }
+
     /// Initiate local partial shard
     pub fn initiate_shard_transfer(
         &self,
         shard_id: ShardId,
     ) -> impl Future<Output = CollectionResult<()>> + 'static {
         let shards_holder = self.shards_holder.clone();
+        let init_timeout =
+            Duration::from_secs(self.shared_storage_config.shard_transfer_init_timeout_secs);
 
         async move {
             let shards_holder = shards_holder.read_owned().await;
@@ -708,11 +1252,11 @@ impl Collection {
             let shard_transfer_requested = tokio::task::spawn_blocking(move || {
                 shards_holder.shard_transfers.wait_for(
                     |shard_transfers| {
-                        shard_transfers.iter().any(|shard_transfer| {
+                        shard_transfers.values().any(|shard_transfer| {
                             shard_transfer.shard_id == shard_id && shard_transfer.to == this_peer_id
                         })
                     },
-                    Duration::from_secs(60),
+                    init_timeout,
                 )
             });
 
@@ -720,13 +1264,13 @@ impl Collection {
                 Ok(true) => Ok(()),
 
                 Ok(false) => {
-                    let description = "\
-                        Failed to initiate shard transfer: \
-                        Didn't receive shard transfer notification from consensus in 60 seconds";
+                    let description = format!(
+                        "Failed to initiate shard transfer: \
+                         Didn't receive shard transfer notification from consensus in {} seconds",
+                        init_timeout.as_secs(),
+                    );
 
-                    Err(CollectionError::Timeout {
-                        description: description.into(),
-                    })
+                    Err(CollectionError::Timeout { description })
                 }
 
                 Err(err) => Err(CollectionError::service_error(format!(
@@ -740,7 +1284,8 @@ impl Collection {
 
     /// Handle collection updates from peers.
     ///
-    /// Shard transfer aware.
+    /// Shard transfer aware: if the local replica of the shard is `Partial`, client updates are
+    /// buffered and applied when the transfer into the replica is finished.
     pub async fn update_from_peer(
         &self,
         operation: CollectionUpdateOperations,
@@ -750,11 +1295,24 @@ impl Collection {
         let _update_lock = self.updates_lock.read().await;
         let shard_holder_guard = self.shards_holder.read().await;
 
-        let res = match shard_holder_guard.get_shard(&shard_selection) {
-            None => None,
-            Some(target_shard) => target_shard.update_local(operation.clone(), wait).await?,
+        let Some(target_shard) = shard_holder_guard.get_shard(&shard_selection) else {
+            let known_shards = shard_holder_guard
+                .get_shards()
+                .map(|(shard_id, _)| *shard_id)
+                .sorted()
+                .collect_vec();
+            return Err(shard_not_found_error_with_known(
+                shard_selection,
+                &known_shards,
+            ));
         };
 
+        let res = target_shard
+            .update_local_or_buffer(operation.clone(), wait, true)
+            .await;
+        self.points_count_cache.invalidate();
+        let res = res?;
+
         if let Some(res) = res {
             Ok(res)
         } else {
@@ -764,17 +1322,102 @@ impl Collection {
         }
     }
 
+    /// Same as `update_from_client`, but protected from applying the same operation twice.
+    ///
+    /// If an operation with the same `idempotency_key` was recently applied, its result is
+    /// returned without applying the operation again. If it is being applied by a concurrent
+    /// request, its result is awaited. Only successful results are remembered.
+    /// Reusing the key for another operation is an error.
+    pub async fn update_from_client_idempotent(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        ordering: WriteOrdering,
+        idempotency_key: Option<Uuid>,
+    ) -> CollectionResult<UpdateResult> {
+        let Some(idempotency_key) = idempotency_key.filter(|_| self.idempotency_cache.is_enabled())
+        else {
+            return self.update_from_client(operation, wait, ordering).await;
+        };
+
+        let operation_hash = IdempotencyCache::operation_hash(&operation)?;
+        loop {
+            match self
+                .idempotency_cache
+                .reserve(idempotency_key, operation_hash)?
+            {
+                Reservation::Reserved(reservation) => {
+                    let result = self.update_from_client(operation, wait, ordering).await?;
+                    reservation.complete(result.clone());
+                    return Ok(result);
+                }
+                Reservation::Applied(result) => {
+                    log::debug!(
+                        "Update with idempotency key {idempotency_key} was already applied to collection {}",
+                        self.id,
+                    );
+                    return Ok(result);
+                }
+                Reservation::InProgress(mut receiver) => {
+                    // If the concurrent request fails, the key is released and reserved again
+                    let result = receiver
+                        .wait_for(Option::is_some)
+                        .await
+                        .ok()
+                        .and_then(|result| result.clone());
+                    if let Some(result) = result {
+                        return Ok(result);
+                    }
+                }
+            }
+        }
+    }
+
     pub async fn update_from_client(
         &self,
         operation: CollectionUpdateOperations,
         wait: bool,
         ordering: WriteOrdering,
     ) -> CollectionResult<UpdateResult> {
+        self.update_from_client_with_result(operation, wait, ordering, true)
+            .await
+    }
+
+    /// Same as `update_from_client`, but allows to skip the update result.
+    ///
+    /// If `with_result` is false, shards don't wait for the operation to be applied and the
+    /// update returns right after it is written into the WAL of each shard.
+    /// The returned result carries no operation id and is always `Acknowledged`.
+    pub async fn update_from_client_with_result(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        ordering: WriteOrdering,
+        with_result: bool,
+    ) -> CollectionResult<UpdateResult> {
+        if self.collection_config.read().await.params.read_only {
+            return Err(CollectionError::forbidden(format!(
+                "Collection {} is read-only",
+                self.id,
+            )));
+        }
         operation.validate()?;
+        self.check_payload_size(&operation).await?;
         let _update_lock = self.updates_lock.read().await;
 
-        let mut results = {
+        let results = {
             let shards_holder = self.shards_holder.read().await;
+
+            // Only operations on the same points contend, others proceed in parallel
+            let _point_guards = match operation.estimate_effect_area() {
+                OperationEffectArea::Points(point_ids)
+                    if self.shared_storage_config.serialized_point_updates =>
+                {
+                    shards_holder.lock_points(point_ids).await
+                }
+                _ => vec![],
+            };
+
             let shard_to_op = shards_holder.split_by_shard(operation);
 
             if shard_to_op.is_empty() {
@@ -783,14 +1426,29 @@ impl Collection {
                 ));
             }
 
+            // Keep the operation of each shard for the update hooks, only if there are any
+            let with_hooks = !self.update_hooks.is_empty();
             let shard_requests = shard_to_op
                 .into_iter()
                 .map(move |(replica_set, operation)| {
-                    replica_set.update_with_consistency(operation, wait, ordering)
+                    let hook_operation =
+                        with_hooks.then(|| (replica_set.shard_id, operation.clone()));
+                    replica_set
+                        .update_with_consistency(operation, wait, ordering, with_result)
+                        .map(|result| (hook_operation, result))
                 });
             join_all(shard_requests).await
         };
 
+        self.points_count_cache.invalidate();
+
+        let (hook_operations, mut results): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+        for (hook_operation, result) in hook_operations.into_iter().zip(&results) {
+            if let (Some((shard_id, operation)), Ok(_)) = (hook_operation, result) {
+                self.update_hooks.notify(shard_id, operation).await;
+            }
+        }
+
         let with_error = results.iter().filter(|result| result.is_err()).count();
 
         // one request per shard
@@ -814,27 +1472,329 @@ impl Collection {
                 first_err
             }
         } else {
+            let shards_written = results
+                .iter()
+                .flatten()
+                .map(|result| result.shards_written)
+                .sum();
             // At least one result is always present.
-            results.pop().unwrap()
+            results.pop().unwrap().map(|result| UpdateResult {
+                shards_written,
+                ..result
+            })
         }
     }
 
-    pub async fn search_batch(
+    /// Reject the operation if it assigns a payload larger than the configured limit to any point
+    async fn check_payload_size(
         &self,
-        request: SearchRequestBatch,
-        read_consistency: Option<ReadConsistency>,
-        shard_selection: Option<ShardId>,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
-        // shortcuts batch if all requests with limit=0
-        if request.searches.iter().all(|s| s.limit == 0) {
-            return Ok(vec![]);
-        }
-        // A factor which determines if we need to use the 2-step search or not
-        // Should be adjusted based on usage statistics.
-        const PAYLOAD_TRANSFERS_FACTOR_THRESHOLD: usize = 10;
-
-        let is_payload_required = request.searches.iter().all(|s| {
-            s.with_payload
+        operation: &CollectionUpdateOperations,
+    ) -> CollectionResult<()> {
+        let Some(max_payload_size) = self
+            .collection_config
+            .read()
+            .await
+            .params
+            .max_payload_size_bytes
+        else {
+            return Ok(());
+        };
+
+        let oversized = operation
+            .payload_sizes()
+            .into_iter()
+            .filter(|(_, size)| *size > max_payload_size)
+            .map(|(id, size)| format!("{id} ({size} bytes)"))
+            .collect_vec();
+
+        if oversized.is_empty() {
+            return Ok(());
+        }
+
+        Err(CollectionError::bad_input(format!(
+            "Payload size exceeds the limit of {max_payload_size} bytes for points: {}",
+            oversized.join(", "),
+        )))
+    }
+
+    pub async fn search_batch(
+        &self,
+        request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        let result = self
+            .search_batch_with_timeout(request, read_consistency, shard_selection)
+            .await?;
+        match result.timeout {
+            Some(err) => Err(err),
+            None => Ok(result.results),
+        }
+    }
+
+    /// Search the batch, aborting it after `request.timeout_ms`, if specified.
+    ///
+    /// If the timeout is hit while retrieving payloads of the results in the two-step search,
+    /// results of the first step are returned without payloads, with `timeout` set in the result.
+    /// If `request.dry_run` is set, no search is executed. Results are empty and the estimated
+    /// cost of the batch is set instead, see [`Collection::estimate_search_cost`].
+    pub async fn search_batch_with_timeout(
+        &self,
+        request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<SearchBatchResult> {
+        if request.dry_run {
+            let batch_size = request.searches.len();
+            let estimation = self
+                .estimate_search_cost(request, read_consistency, shard_selection, None)
+                .await?;
+            return Ok(SearchBatchResult {
+                results: vec![vec![]; batch_size],
+                dry_run: Some(estimation),
+                ..Default::default()
+            });
+        }
+
+        let Some(timeout_ms) = request.timeout_ms else {
+            return self
+                .search_batch_until(request, read_consistency, shard_selection, None)
+                .await;
+        };
+
+        let timeout = Duration::from_millis(timeout_ms);
+        let deadline = tokio::time::Instant::now() + timeout;
+        tokio::time::timeout(
+            timeout,
+            self.search_batch_until(request, read_consistency, shard_selection, Some(deadline)),
+        )
+        .await
+        .map_err(|_| search_batch_timeout(timeout_ms))?
+    }
+
+    async fn search_batch_until(
+        &self,
+        mut request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+        deadline: Option<tokio::time::Instant>,
+    ) -> CollectionResult<SearchBatchResult> {
+        self.run_pre_search_hook(&mut request, shard_selection)?;
+        let rescorings = self
+            .preprocess_search_batch(&mut request, read_consistency)
+            .await?;
+        let normalization = self
+            .prepare_score_normalization(&request, shard_selection)
+            .await?;
+
+        let mut result = self
+            .search_batch_grouped(request, read_consistency, shard_selection, deadline)
+            .await?;
+
+        apply_exact_rescorings(&mut result.results, &rescorings);
+        apply_score_normalization(&mut result.results, normalization);
+        self.run_post_search_hook(&result.results, shard_selection)?;
+        Ok(result)
+    }
+
+    /// Run the pre search hook on each search of the batch.
+    ///
+    /// Only client searches are intercepted, not the internal searches of other peers.
+    fn run_pre_search_hook(
+        &self,
+        request: &mut SearchRequestBatch,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<()> {
+        if let (Some(pre_search_hook), None) = (&self.search_hooks.pre_search_hook, shard_selection)
+        {
+            for search in &mut request.searches {
+                pre_search_hook(search)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the post search hook on the result of each search of the batch.
+    ///
+    /// Only client searches are intercepted, not the internal searches of other peers.
+    fn run_post_search_hook(
+        &self,
+        results: &[Vec<ScoredPoint>],
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<()> {
+        if let (Some(post_search_hook), None) =
+            (&self.search_hooks.post_search_hook, shard_selection)
+        {
+            for points in results {
+                post_search_hook(points)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the searches of the batch and resolve them into requests, which can be sent to
+    /// shards.
+    ///
+    /// Returns exact rescoring of the results of each search, see [`apply_exact_rescorings`].
+    async fn preprocess_search_batch(
+        &self,
+        request: &mut SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+    ) -> CollectionResult<Vec<Option<ExactRescoring>>> {
+        self.collection_config
+            .read()
+            .await
+            .params
+            .check_vector_names_exist(
+                request
+                    .searches
+                    .iter()
+                    .flat_map(|search| search.vector.vector_names()),
+            )?;
+        for search in &mut request.searches {
+            self.resolve_lookup_from(search).await?;
+            self.resolve_context_pairs(search, read_consistency).await?;
+            search.apply_negative_examples()?;
+            search.apply_vector_normalization();
+            search.apply_payload_fields();
+        }
+        self.prepare_exact_rescoring(&mut request.searches).await
+    }
+
+    /// Prepare the searches with `rescore_with_exact` enabled for exact rescoring of their results
+    async fn prepare_exact_rescoring(
+        &self,
+        searches: &mut [SearchRequest],
+    ) -> CollectionResult<Vec<Option<ExactRescoring>>> {
+        let collection_config = self.collection_config.read().await;
+        searches
+            .iter_mut()
+            .map(|search| {
+                ExactRescoring::prepare(search, |vector_name| {
+                    Ok(collection_config
+                        .params
+                        .get_vector_params(vector_name)?
+                        .distance)
+                })
+            })
+            .collect()
+    }
+
+    /// Execute the searches, grouped by their effective read consistency and shard filter
+    async fn search_batch_grouped(
+        &self,
+        request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+        deadline: Option<tokio::time::Instant>,
+    ) -> CollectionResult<SearchBatchResult> {
+        if request.searches.iter().all(|search| {
+            search.read_consistency.is_none()
+                && search.shard_filter.is_none()
+                && search.tenant_id.is_none()
+        }) {
+            return self
+                .search_batch_with_consistency(
+                    request,
+                    read_consistency,
+                    shard_selection,
+                    None,
+                    deadline,
+                )
+                .await;
+        }
+
+        // Group searches by their effective read consistency and shard filter,
+        // preserving original positions
+        type SearchGroupKey = (Option<ReadConsistency>, Option<ShardFilter>);
+        let mut groups: Vec<(SearchGroupKey, Vec<usize>, Vec<SearchRequest>)> = vec![];
+        let shard_holder = self.shards_holder.read().await;
+        for (index, search) in request.searches.into_iter().enumerate() {
+            let shard_filter = shard_holder
+                .resolve_shard_filter(search.shard_filter.as_ref(), search.tenant_id.as_deref());
+            let key = (search.read_consistency.or(read_consistency), shard_filter);
+            match groups.iter_mut().find(|(group, ..)| *group == key) {
+                Some((_, indices, searches)) => {
+                    indices.push(index);
+                    searches.push(search);
+                }
+                None => groups.push((key, vec![index], vec![search])),
+            }
+        }
+        drop(shard_holder);
+
+        let explain_score = request.explain_score;
+        let min_shards = request.min_shards;
+        let wait_for_shards = request.wait_for_shards;
+        let timeout_ms = request.timeout_ms;
+        let score_normalizer = request.score_normalizer;
+        let batch_size = groups.iter().map(|(_, indices, _)| indices.len()).sum();
+        let groups_results = try_join_all(groups.into_iter().map(
+            |((consistency, shard_filter), indices, searches)| async move {
+                let results = self
+                    .search_batch_with_consistency(
+                        SearchRequestBatch {
+                            searches,
+                            debug: false,
+                            dry_run: false,
+                            explain_score,
+                            min_shards,
+                            wait_for_shards,
+                            timeout_ms,
+                            score_normalizer,
+                        },
+                        consistency,
+                        shard_selection,
+                        shard_filter.as_ref(),
+                        deadline,
+                    )
+                    .await?;
+                CollectionResult::Ok((indices, results))
+            },
+        ))
+        .await?;
+
+        // Reassemble results in the original order
+        let mut result = SearchBatchResult {
+            results: vec![vec![]; batch_size],
+            shards_successful: usize::MAX,
+            hnsw_ef_used: vec![None; batch_size],
+            ..Default::default()
+        };
+        for (indices, group_result) in groups_results {
+            for (&index, ef) in indices.iter().zip(group_result.hnsw_ef_used) {
+                result.hnsw_ef_used[index] = ef;
+            }
+            for (index, points) in indices.into_iter().zip(group_result.results) {
+                result.results[index] = points;
+            }
+            result.cache_stats.hits += group_result.cache_stats.hits;
+            result.cache_stats.misses += group_result.cache_stats.misses;
+            result.shards_successful = result.shards_successful.min(group_result.shards_successful);
+            result.timeout = result.timeout.or(group_result.timeout);
+            result.meta.merge(group_result.meta);
+        }
+        Ok(result)
+    }
+
+    async fn search_batch_with_consistency(
+        &self,
+        request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+        shard_filter: Option<&ShardFilter>,
+        deadline: Option<tokio::time::Instant>,
+    ) -> CollectionResult<SearchBatchResult> {
+        // shortcuts batch if all requests with limit=0
+        if request.searches.iter().all(|s| s.limit == 0) {
+            return Ok(SearchBatchResult::default());
+        }
+        // A factor which determines if we need to use the 2-step search or not
+        // Should be adjusted based on usage statistics.
+        const PAYLOAD_TRANSFERS_FACTOR_THRESHOLD: usize = 10;
+
+        let is_payload_required = request.searches.iter().all(|s| {
+            s.with_payload
                 .clone()
                 .map(|p| p.is_required())
                 .unwrap_or_default()
@@ -852,7 +1812,14 @@ impl Collection {
         let sum_offsets: usize = request.searches.iter().map(|s| s.offset).sum();
 
         // Number of records we need to retrieve to fill the search result.
-        let require_transfers = self.shards_holder.read().await.len() * (sum_limits + sum_offsets);
+        let target_shards_count = self
+            .shards_holder
+            .read()
+            .await
+            .search_target_shards(shard_selection, shard_filter)?
+            .0
+            .len();
+        let require_transfers = target_shards_count * (sum_limits + sum_offsets);
         // Actually used number of records.
         let used_transfers = sum_limits;
 
@@ -873,60 +1840,498 @@ impl Collection {
             }
             let without_payload_batch = SearchRequestBatch {
                 searches: without_payload_requests,
+                debug: false,
+                dry_run: false,
+                explain_score: request.explain_score,
+                min_shards: request.min_shards,
+                wait_for_shards: request.wait_for_shards,
+                timeout_ms: request.timeout_ms,
+                score_normalizer: request.score_normalizer,
             };
-            let without_payload_results = self
-                ._search_batch(without_payload_batch, read_consistency, shard_selection)
+            let mut result = self
+                ._search_batch(
+                    without_payload_batch,
+                    read_consistency,
+                    shard_selection,
+                    shard_filter,
+                )
                 .await?;
+            // Results without payload are returned on timeout, so they are only kept with a deadline
+            let without_payload_results = match deadline {
+                Some(_) => result.results.clone(),
+                None => std::mem::take(&mut result.results),
+            };
             let filled_results = without_payload_results
                 .into_iter()
-                .zip(request.clone().searches.into_iter())
+                .zip(&request.searches)
                 .map(|(without_payload_result, req)| {
                     self.fill_search_result_with_payload(
                         without_payload_result,
                         req.with_payload.clone(),
-                        req.with_vector.unwrap_or_default(),
+                        req.with_vector.clone().unwrap_or_default(),
                         read_consistency,
                         shard_selection,
                     )
                 });
-            try_join_all(filled_results).await
-        } else {
-            let result = self
-                ._search_batch(request, read_consistency, shard_selection)
-                .await?;
+            let filled_results = try_join_all(filled_results);
+            match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, filled_results).await {
+                    Ok(filled_results) => result.results = filled_results?,
+                    // Out of time, return the found points without payload
+                    Err(_) => {
+                        result.timeout =
+                            Some(search_batch_timeout(request.timeout_ms.unwrap_or_default()))
+                    }
+                },
+                None => result.results = filled_results.await?,
+            }
             Ok(result)
+        } else {
+            self._search_batch(request, read_consistency, shard_selection, shard_filter)
+                .await
         }
     }
 
+    /// Search the batch on target shards and merge the results.
+    ///
+    /// If `shard_filter` is specified, only shards passing it are queried.
     pub async fn _search_batch(
         &self,
         request: SearchRequestBatch,
         read_consistency: Option<ReadConsistency>,
         shard_selection: Option<ShardId>,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        shard_filter: Option<&ShardFilter>,
+    ) -> CollectionResult<SearchBatchResult> {
         let request = Arc::new(request);
+        // Shards only execute single vector searches, ensembles are combined on merge
+        let shard_request = request
+            .split_ensembles()
+            .map(Arc::new)
+            .unwrap_or_else(|| request.clone());
+
+        // query all shards concurrently, wait for all of them, unless requested otherwise
+        let mut cache_stats = CacheStats::default();
+        let mut meta = SearchMeta::default();
+        let all_searches_res = {
+            let shard_holder = self.shards_holder.read().await;
+            let (target_shards, skipped_shards) =
+                shard_holder.search_target_shards(shard_selection, shard_filter)?;
+            meta.shards_skipped = skipped_shards;
+            let required_shards = request
+                .wait_for_shards
+                .map_or(target_shards.len(), |wait_for_shards| {
+                    wait_for_shards.min(target_shards.len())
+                });
+            let mut pending_searches: FuturesUnordered<_> = target_shards
+                .iter()
+                .map(|shard| {
+                    let search = shard.search_with_info(shard_request.clone(), read_consistency);
+                    async move {
+                        let start = std::time::Instant::now();
+                        let search_res = search.await;
+                        (shard.shard_id, start.elapsed(), search_res)
+                    }
+                })
+                .collect();
+            let mut all_searches_res = Vec::with_capacity(required_shards);
+            while all_searches_res.len() < required_shards {
+                match pending_searches.next().await {
+                    Some((shard_id, latency, search_res)) => {
+                        let shard_result = match search_res {
+                            Ok(res) => res,
+                            // Tolerate failed shards, if partial results are allowed
+                            Err(err) if request.min_shards.is_some() => {
+                                log::warn!("Search on shard of {} failed: {err}", self.id);
+                                continue;
+                            }
+                            Err(err) => return Err(err),
+                        };
+                        if shard_result.cache_hit {
+                            cache_stats.hits += 1;
+                        } else {
+                            cache_stats.misses += 1;
+                        }
+                        meta.shards_queried.push(shard_id);
+                        meta.latency_ms_per_shard
+                            .insert(shard_id, latency.as_millis() as u64);
+                        all_searches_res.push(shard_result);
+                    }
+                    None => break,
+                }
+            }
+            // Dropping the remaining futures cancels requests to slow shards, if not awaited
+            drop(pending_searches);
+            meta.shards_queried.sort_unstable();
+
+            all_searches_res
+        };
+
+        // Quality hint is computed from the `ef` reported by the merged shards
+        let mut hnsw_ef_used = Vec::new();
+        let mut shards_ef = Vec::with_capacity(all_searches_res.len());
+        for shard_result in &all_searches_res {
+            let shard_ef = shard_request
+                .searches
+                .iter()
+                .zip(&shard_result.hnsw_ef_used)
+                .filter(|(search, _)| search.return_quality_hint)
+                .filter_map(|(_, ef)| *ef)
+                .min();
+            shards_ef.extend(shard_ef);
+            ShardSearchResult::merge_hnsw_ef_used(&mut hnsw_ef_used, &shard_result.hnsw_ef_used);
+        }
+        meta.quality_hint = SearchQualityHint::from_shards_ef(&shards_ef);
+        if let Some(hint) = meta.quality_hint {
+            let mut search_quality_hint = self.search_quality_hint.lock();
+            *search_quality_hint =
+                Some(search_quality_hint.map_or(hint, |aggregated| aggregated.merge(&hint)));
+        }
+        let all_searches_res: Vec<_> = all_searches_res
+            .into_iter()
+            .map(|shard_result| shard_result.results)
+            .collect();
+
+        let shards_successful = all_searches_res.len();
+        if let Some(min_shards) = request.min_shards {
+            if shards_successful < min_shards {
+                return Err(CollectionError::service_error(format!(
+                    "Only {shards_successful} shards responded successfully, but at least {min_shards} required",
+                )));
+            }
+        }
+
+        let hnsw_ef_used = unsplit_hnsw_ef_used(&request, hnsw_ef_used);
+        let results = self
+            .merge_from_shards(all_searches_res, request, shard_selection)
+            .await?;
+        Ok(SearchBatchResult {
+            results,
+            cache_stats,
+            shards_successful,
+            timeout: None,
+            meta,
+            dry_run: None,
+            hnsw_ef_used,
+        })
+    }
+
+    /// Estimate the cost of the search batch without executing it.
+    ///
+    /// Candidates are estimated by approximate counting of points matching the filter of each
+    /// search, payload size is extrapolated from a small sample of points of each shard.
+    /// Only shards, which would be queried by `_search_batch`, are taken into account.
+    pub async fn estimate_search_cost(
+        &self,
+        request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+        shard_filter: Option<&ShardFilter>,
+    ) -> CollectionResult<SearchDryRunResult> {
+        let request = Arc::new(request);
+
+        let shard_estimations = {
+            let shard_holder = self.shards_holder.read().await;
+            let (target_shards, _) =
+                shard_holder.search_target_shards(shard_selection, shard_filter)?;
+            let all_estimations = target_shards
+                .iter()
+                .map(|shard| shard.estimate_search_cost(request.clone(), read_consistency));
+            try_join_all(all_estimations).await?
+        };
+
+        Ok(SearchDryRunResult {
+            shards_touched: shard_estimations.len(),
+            estimated_candidates_per_shard: shard_estimations
+                .iter()
+                .map(|(candidates, _)| *candidates)
+                .collect(),
+            estimated_payload_bytes: shard_estimations
+                .iter()
+                .map(|(_, payload_bytes)| *payload_bytes)
+                .sum(),
+        })
+    }
+
+    /// Same as `_search_batch`, but if `request.debug` is set, also returns raw results of each
+    /// shard before merging.
+    pub async fn search_batch_debug(
+        &self,
+        mut request: SearchRequestBatch,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<SearchBatchDebugResult> {
+        self.run_pre_search_hook(&mut request, shard_selection)?;
+        let rescorings = self
+            .preprocess_search_batch(&mut request, read_consistency)
+            .await?;
+        let normalization = self
+            .prepare_score_normalization(&request, shard_selection)
+            .await?;
+
+        let request = Arc::new(request);
+        let shard_request = request
+            .split_ensembles()
+            .map(Arc::new)
+            .unwrap_or_else(|| request.clone());
 
         // query all shards concurrently
-        let all_searches_res = {
+        let (shard_ids, all_searches_res, segment_read_estimations) = {
             let shard_holder = self.shards_holder.read().await;
-            let target_shards = shard_holder.target_shard(shard_selection)?;
+            let (target_shards, _) = shard_holder.search_target_shards(shard_selection, None)?;
+            let shard_ids: Vec<_> = target_shards.iter().map(|shard| shard.shard_id).collect();
             let all_searches = target_shards
                 .iter()
-                .map(|shard| shard.search(request.clone(), read_consistency));
-            try_join_all(all_searches).await?
+                .map(|shard| shard.search(shard_request.clone(), read_consistency));
+            let all_searches_res = try_join_all(all_searches).await?;
+
+            let mut segment_read_estimations = Vec::with_capacity(request.searches.len());
+            for search in &request.searches {
+                if !search.estimate_segment_reads {
+                    segment_read_estimations.push(None);
+                    continue;
+                }
+                let mut search_estimation = SegmentReadEstimation::default();
+                for shard in &target_shards {
+                    if let Some(shard_estimation) = shard
+                        .segment_read_estimation_local(search.filter.as_ref())
+                        .await
+                    {
+                        search_estimation.merge(&shard_estimation);
+                    }
+                }
+                segment_read_estimations.push(Some(search_estimation));
+            }
+
+            (shard_ids, all_searches_res, segment_read_estimations)
         };
 
-        self.merge_from_shards(all_searches_res, request, shard_selection)
-            .await
+        let per_shard = if request.debug {
+            shard_ids
+                .into_iter()
+                .zip(all_searches_res.iter().cloned())
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let mut merged = self
+            .merge_from_shards(all_searches_res, request, shard_selection)
+            .await?;
+        apply_exact_rescorings(&mut merged, &rescorings);
+        apply_score_normalization(&mut merged, normalization);
+        self.run_post_search_hook(&merged, shard_selection)?;
+
+        Ok(SearchBatchDebugResult {
+            merged,
+            per_shard,
+            segment_read_estimations,
+        })
     }
 
+    /// Merge results of the searches from all shards.
     async fn merge_from_shards(
         &self,
-        mut all_searches_res: Vec<Vec<Vec<ScoredPoint>>>,
+        all_searches_res: Vec<Vec<Vec<ScoredPoint>>>,
         request: Arc<SearchRequestBatch>,
         shard_selection: Option<u32>,
     ) -> Result<Vec<Vec<ScoredPoint>>, CollectionError> {
-        let batch_size = request.searches.len();
+        // Look up all distances at once, instead of locking the config for each search
+        let distances = self
+            .collection_config
+            .read()
+            .await
+            .params
+            .vector_distances();
+
+        self.merge_shard_results(all_searches_res, &request, &distances, shard_selection)
+    }
+
+    /// Resolve the order of the scores of each search, if the results of the batch are normalized
+    /// with `request.score_normalizer`, see [`apply_score_normalization`].
+    ///
+    /// Only results of client requests are normalized, after exact rescoring, so that the final
+    /// result of each search is normalized at once.
+    async fn prepare_score_normalization(
+        &self,
+        request: &SearchRequestBatch,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<Option<(ScoreNormalizerType, Vec<Order>)>> {
+        let normalizer = request.score_normalizer.unwrap_or_default();
+        if shard_selection.is_some() || normalizer == ScoreNormalizerType::None {
+            return Ok(None);
+        }
+
+        let distances = self
+            .collection_config
+            .read()
+            .await
+            .params
+            .vector_distances();
+        let orders = request
+            .searches
+            .iter()
+            .map(|search| match &search.vector {
+                VectorQuery::Single(vector) => {
+                    Ok(distances.get(vector.get_name())?.distance_order())
+                }
+                VectorQuery::Ensemble(_) => Ok(Order::LargeBetter),
+            })
+            .collect::<CollectionResult<_>>()?;
+        Ok(Some((normalizer, orders)))
+    }
+
+    /// Merge results of the searches from all shards, keeping original scores.
+    ///
+    /// Shards are expected to execute `request` with ensembles split, see
+    /// [`SearchRequestBatch::split_ensembles`]. Results of the parts of each ensemble are then
+    /// combined into a single result, as selected by [`SearchRequest::fusion`].
+    fn merge_shard_results(
+        &self,
+        all_searches_res: Vec<Vec<Vec<ScoredPoint>>>,
+        request: &SearchRequestBatch,
+        distances: &VectorDistances,
+        shard_selection: Option<u32>,
+    ) -> Result<Vec<Vec<ScoredPoint>>, CollectionError> {
+        let Some(split_request) = request.split_ensembles() else {
+            return Self::merge_searches(
+                all_searches_res,
+                &request.searches,
+                distances,
+                shard_selection,
+                self.shared_storage_config.parallel_merge_threshold,
+            );
+        };
+
+        let mut split_results = Self::merge_searches(
+            all_searches_res,
+            &split_request.searches,
+            distances,
+            shard_selection,
+            self.shared_storage_config.parallel_merge_threshold,
+        )?
+        .into_iter();
+
+        let mut results = Vec::with_capacity(request.searches.len());
+        for search in &request.searches {
+            let VectorQuery::Ensemble(vectors) = &search.vector else {
+                results.push(split_results.next().unwrap_or_default());
+                continue;
+            };
+
+            let mut top_res = match search.fusion.unwrap_or_default() {
+                Fusion::WeightedSum => Self::fuse_weighted_sum(
+                    vectors,
+                    split_results.by_ref(),
+                    search,
+                    distances,
+                    request.explain_score,
+                )?,
+                Fusion::Rrf => {
+                    let parts: Vec<_> = split_results.by_ref().take(vectors.len()).collect();
+                    let mut score_breakdowns: HashMap<_, HashMap<_, _>> = HashMap::new();
+                    if request.explain_score {
+                        for (weighted, part_results) in vectors.iter().zip(&parts) {
+                            for point in part_results {
+                                score_breakdowns
+                                    .entry(point.id)
+                                    .or_default()
+                                    .insert(weighted.name.clone(), point.score);
+                            }
+                        }
+                    }
+                    let mut fused = rrf_scoring(
+                        parts,
+                        search.rrf_k.unwrap_or(DEFAULT_RRF_K),
+                        search.limit + search.offset,
+                    );
+                    for point in &mut fused {
+                        point.score_breakdown = score_breakdowns.remove(&point.id);
+                    }
+                    fused
+                }
+            };
+            if shard_selection.is_none() {
+                drain_offset(&mut top_res, search.offset);
+            }
+            if let Some(threshold) = search.score_threshold {
+                top_res.retain(|point| point.score > threshold);
+            }
+            results.push(top_res);
+        }
+
+        Ok(results)
+    }
+
+    /// Combine results of the parts of an ensemble search with the weighted sum of their scores,
+    /// see [`Fusion::WeightedSum`]. Returns the best `limit + offset` points.
+    fn fuse_weighted_sum(
+        vectors: &[WeightedVector],
+        parts_results: impl Iterator<Item = Vec<ScoredPoint>>,
+        search: &SearchRequest,
+        distances: &VectorDistances,
+        explain_score: bool,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let mut combined: HashMap<ExtendedPointId, ScoredPoint> = HashMap::new();
+        for (weighted, part_results) in vectors.iter().zip(parts_results) {
+            let distance = distances.get(&weighted.name)?;
+            if !matches!(distance.distance_order(), Order::LargeBetter) {
+                return Err(CollectionError::bad_input(format!(
+                    "Ensemble search requires distances where larger score is better, \
+                     vector {} uses {distance:?}",
+                    weighted.name,
+                )));
+            }
+
+            for point in part_results {
+                let part_score = point.score;
+                let weighted_score = weighted.weight * part_score;
+                let combined_point = match combined.entry(point.id) {
+                    Entry::Occupied(entry) => {
+                        let combined_point = entry.into_mut();
+                        combined_point.score += weighted_score;
+                        combined_point
+                    }
+                    Entry::Vacant(entry) => entry.insert(ScoredPoint {
+                        score: weighted_score,
+                        score_breakdown: None,
+                        ..point
+                    }),
+                };
+                if explain_score {
+                    combined_point
+                        .score_breakdown
+                        .get_or_insert_with(HashMap::new)
+                        .insert(weighted.name.clone(), part_score);
+                }
+            }
+        }
+
+        let tiebreaker = search.tiebreaker.unwrap_or_default();
+        let ranked_res = combined.into_values().map(|point| RankedPoint {
+            point,
+            order: Order::LargeBetter,
+            tiebreaker,
+        });
+        Ok(
+            peek_top_largest_iterable(ranked_res, search.limit + search.offset)
+                .into_iter()
+                .map(|ranked| ranked.point)
+                .collect(),
+        )
+    }
+
+    /// Merge results of single vector searches from all shards
+    ///
+    /// Batches with more than `parallel_merge_threshold` searches are merged on the rayon
+    /// thread pool. Results are in the order of `searches` either way.
+    pub fn merge_searches(
+        mut all_searches_res: Vec<Vec<Vec<ScoredPoint>>>,
+        searches: &[SearchRequest],
+        distances: &VectorDistances,
+        shard_selection: Option<u32>,
+        parallel_merge_threshold: usize,
+    ) -> Result<Vec<Vec<ScoredPoint>>, CollectionError> {
+        let batch_size = searches.len();
 
         // merge results from shards in order
         let mut merged_results: Vec<Vec<ScoredPoint>> = vec![vec![]; batch_size];
@@ -935,35 +2340,57 @@ impl Collection {
                 merged_results[index].append(shard_searches_result)
             }
         }
-        let collection_params = self.collection_config.read().await.params.clone();
-        let top_results: Vec<_> = merged_results
-            .into_iter()
-            .zip(request.searches.iter())
-            .map(|(res, request)| {
-                let distance = collection_params
-                    .get_vector_params(request.vector.get_name())?
-                    .distance;
-                let mut top_res = match distance.distance_order() {
+        let merge_search =
+            |(res, request): (Vec<ScoredPoint>, &SearchRequest)| -> CollectionResult<_> {
+                let vector_name = request
+                    .vector
+                    .as_single()
+                    .map_or(DEFAULT_VECTOR_NAME, |vector| vector.get_name());
+                let distance = distances.get(vector_name)?;
+                let order = distance.distance_order();
+                let tiebreaker = request.tiebreaker.unwrap_or_default();
+                let ranked_res = res.into_iter().map(|point| RankedPoint {
+                    point,
+                    order,
+                    tiebreaker,
+                });
+                let mut top_res: Vec<_> = match order {
                     Order::LargeBetter => {
-                        peek_top_largest_iterable(res, request.limit + request.offset)
+                        peek_top_largest_iterable(ranked_res, request.limit + request.offset)
                     }
                     Order::SmallBetter => {
-                        peek_top_smallest_iterable(res, request.limit + request.offset)
+                        peek_top_smallest_iterable(ranked_res, request.limit + request.offset)
                     }
-                };
+                }
+                .into_iter()
+                .map(|ranked| ranked.point)
+                .collect();
                 // Remove `offset` from top result only for client requests
                 // to avoid applying `offset` twice in distributed mode.
-                if shard_selection.is_none() && request.offset > 0 {
-                    if top_res.len() >= request.offset {
-                        // Panics if the end point > length of the vector.
-                        top_res.drain(..request.offset);
-                    } else {
-                        top_res.clear()
-                    }
+                if shard_selection.is_none() {
+                    drain_offset(&mut top_res, request.offset);
+                }
+                // Shards prune results by threshold on their own, but enforce it here as well,
+                // so that results from every shard are guaranteed to satisfy it.
+                if let Some(threshold) = request.score_threshold {
+                    top_res.retain(|point| distance.check_threshold(point.score, threshold));
                 }
                 Ok(top_res)
-            })
-            .collect::<CollectionResult<Vec<_>>>()?;
+            };
+
+        let top_results = if batch_size > parallel_merge_threshold {
+            merged_results
+                .into_par_iter()
+                .zip(searches)
+                .map(&merge_search)
+                .collect::<CollectionResult<Vec<_>>>()?
+        } else {
+            merged_results
+                .into_iter()
+                .zip(searches)
+                .map(&merge_search)
+                .collect::<CollectionResult<Vec<_>>>()?
+        };
 
         Ok(top_results)
     }
@@ -990,10 +2417,22 @@ impl Collection {
                 .collect());
         };
 
+        if !with_payload.as_ref().map_or(false, |p| p.is_required()) {
+            return self
+                .fill_search_result_with_vectors(
+                    search_result,
+                    with_vector,
+                    read_consistency,
+                    shard_selection,
+                )
+                .await;
+        }
+
         let retrieve_request = PointRequest {
             ids: search_result.iter().map(|x| x.id).collect(),
             with_payload,
             with_vector,
+            strict: false,
         };
         let retrieved_records = self
             .retrieve(retrieve_request, read_consistency, shard_selection)
@@ -1018,23 +2457,250 @@ impl Collection {
         Ok(enriched_result)
     }
 
+    /// Vector-only version of [`Collection::fill_search_result_with_payload`].
+    /// Payload is not read from the segments and not sent between peers at all.
+    async fn fill_search_result_with_vectors(
+        &self,
+        search_result: Vec<ScoredPoint>,
+        with_vector: WithVector,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let retrieve_request = PointRequest {
+            ids: search_result.iter().map(|x| x.id).collect(),
+            with_payload: Some(WithPayloadInterface::Bool(false)),
+            with_vector,
+            strict: false,
+        };
+        let mut vectors_map: HashMap<ExtendedPointId, Option<VectorStruct>> = self
+            .retrieve(retrieve_request, read_consistency, shard_selection)
+            .await?
+            .into_iter()
+            .map(|rec| (rec.id, rec.vector))
+            .collect();
+        let enriched_result = search_result
+            .into_iter()
+            .filter_map(|scored_point| {
+                // Same as for payload, points deleted between search and retrieve are dropped
+                vectors_map
+                    .remove(&scored_point.id)
+                    .map(|vector| ScoredPoint {
+                        payload: None,
+                        vector,
+                        ..scored_point
+                    })
+            })
+            .collect();
+        Ok(enriched_result)
+    }
+
+    /// Search by several named vectors with a single request to each shard.
+    ///
+    /// The query vector of each search is searched in the vector it is keyed by,
+    /// results are returned by vector name.
+    pub async fn multi_vector_search_batch(
+        &self,
+        request: MultiVectorSearchRequest,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<HashMap<VectorName, Vec<ScoredPoint>>> {
+        let mut vector_names = Vec::with_capacity(request.searches.len());
+        let mut searches = Vec::with_capacity(request.searches.len());
+        for (vector_name, mut search) in request.searches {
+            if vector_names.contains(&vector_name) {
+                return Err(CollectionError::bad_input(format!(
+                    "Vector {vector_name} is searched more than once",
+                )));
+            }
+            let VectorQuery::Single(vector) = search.vector else {
+                return Err(CollectionError::bad_input(format!(
+                    "Search by vector {vector_name} must have a single query vector",
+                )));
+            };
+            search.vector = NamedVectorStruct::from(NamedVector {
+                name: vector_name.clone(),
+                vector: vector.to_vector(),
+            })
+            .into();
+            vector_names.push(vector_name);
+            searches.push(search);
+        }
+
+        let request = SearchRequestBatch {
+            searches,
+            debug: false,
+            dry_run: false,
+            explain_score: false,
+            min_shards: None,
+            wait_for_shards: None,
+            timeout_ms: None,
+            score_normalizer: None,
+        };
+        let mut results = self
+            .search_batch(request, read_consistency, shard_selection)
+            .await?
+            .into_iter();
+
+        Ok(vector_names
+            .into_iter()
+            .map(|vector_name| (vector_name, results.next().unwrap_or_default()))
+            .collect())
+    }
+
     pub async fn search(
         &self,
         request: SearchRequest,
         read_consistency: Option<ReadConsistency>,
         shard_selection: Option<ShardId>,
     ) -> CollectionResult<Vec<ScoredPoint>> {
-        if request.limit == 0 {
-            return Ok(vec![]);
-        }
+        let (result, _meta) = self
+            .search_with_meta(request, read_consistency, shard_selection)
+            .await?;
+        Ok(result)
+    }
+
+    /// Same as `search`, but also returns which shards were queried and how long they took
+    pub async fn search_with_meta(
+        &self,
+        request: SearchRequest,
+        read_consistency: Option<ReadConsistency>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<(Vec<ScoredPoint>, SearchMeta)> {
         // search is a special case of search_batch with a single batch
         let request_batch = SearchRequestBatch {
             searches: vec![request],
+            debug: false,
+            dry_run: false,
+            explain_score: false,
+            min_shards: None,
+            wait_for_shards: None,
+            timeout_ms: None,
+            score_normalizer: None,
         };
-        let results = self
-            ._search_batch(request_batch, read_consistency, shard_selection)
+        let SearchBatchResult { results, meta, .. } = self
+            .search_batch_until(request_batch, read_consistency, shard_selection, None)
             .await?;
-        Ok(results.into_iter().next().unwrap())
+        let result = results.into_iter().next().unwrap_or_default();
+        Ok((result, meta))
+    }
+
+    /// Replace the query vector of the request with the vector of the point from `lookup_from`
+    async fn resolve_lookup_from(&self, request: &mut SearchRequest) -> CollectionResult<()> {
+        let Some((collection_name, point_id)) = request.lookup_from.take() else {
+            return Ok(());
+        };
+
+        let Some(vector_name) = request.vector.as_single().map(|vector| vector.get_name()) else {
+            return Err(CollectionError::bad_input(
+                "Vector lookup is not supported by ensemble search".to_string(),
+            ));
+        };
+        let Some(vector_lookup) = &self.vector_lookup_cb else {
+            return Err(CollectionError::service_error(
+                "Vector lookup from other collections is not available".to_string(),
+            ));
+        };
+
+        let record = vector_lookup(collection_name.clone(), point_id).await?;
+        let vector = record.get_vector_by_name(vector_name).ok_or_else(|| {
+            CollectionError::bad_input(format!(
+                "Point {point_id} of collection {collection_name} has no vector {vector_name}"
+            ))
+        })?;
+
+        let vector = vector.clone();
+        request.vector = if vector_name == DEFAULT_VECTOR_NAME {
+            NamedVectorStruct::Default(vector)
+        } else {
+            NamedVectorStruct::Named(NamedVector {
+                name: vector_name.to_string(),
+                vector,
+            })
+        }
+        .into();
+        Ok(())
+    }
+
+    /// Replace the query vector with the one derived from `context_pairs` of the request,
+    /// see `SearchRequest::context_pairs`
+    async fn resolve_context_pairs(
+        &self,
+        request: &mut SearchRequest,
+        read_consistency: Option<ReadConsistency>,
+    ) -> CollectionResult<()> {
+        if request.context_pairs.is_empty() {
+            return Ok(());
+        }
+        let context_pairs = std::mem::take(&mut request.context_pairs);
+
+        let Some(vector_name) = request
+            .vector
+            .as_single()
+            .map(|vector| vector.get_name().to_string())
+        else {
+            return Err(CollectionError::bad_input(
+                "Context search is not supported by ensemble search".to_string(),
+            ));
+        };
+
+        let point_ids = context_pairs
+            .iter()
+            .flat_map(|(positive, negative)| [positive, negative])
+            .filter_map(|example| match example {
+                ContextExample::PointId(point_id) => Some(*point_id),
+                ContextExample::Vector(_) => None,
+            })
+            .unique()
+            .collect_vec();
+
+        let point_vectors: HashMap<_, _> = if point_ids.is_empty() {
+            HashMap::new()
+        } else {
+            let point_request = PointRequest {
+                ids: point_ids,
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_vector: WithVector::Selector(vec![vector_name.clone()]),
+                strict: true,
+            };
+            self.retrieve(point_request, read_consistency, None)
+                .await?
+                .into_iter()
+                .filter_map(|record| {
+                    let vector = record.get_vector_by_name(&vector_name)?.clone();
+                    Some((record.id, vector))
+                })
+                .collect()
+        };
+
+        let example_vector = |example: &ContextExample| match example {
+            ContextExample::Vector(vector) => Ok(vector.clone()),
+            ContextExample::PointId(point_id) => {
+                point_vectors.get(point_id).cloned().ok_or_else(|| {
+                    CollectionError::bad_input(format!(
+                        "Point {point_id} has no vector {vector_name}"
+                    ))
+                })
+            }
+        };
+
+        let mut positives = Vec::with_capacity(context_pairs.len());
+        let mut negatives = Vec::with_capacity(context_pairs.len());
+        for (positive, negative) in &context_pairs {
+            positives.push(example_vector(positive)?);
+            negatives.push(example_vector(negative)?);
+        }
+
+        let vector = context_query_vector(&positives, &negatives)?;
+        request.vector = if vector_name == DEFAULT_VECTOR_NAME {
+            NamedVectorStruct::Default(vector)
+        } else {
+            NamedVectorStruct::Named(NamedVector {
+                name: vector_name,
+                vector,
+            })
+        }
+        .into();
+        Ok(())
     }
 
     pub async fn scroll_by(
@@ -1045,7 +2711,24 @@ impl Collection {
     ) -> CollectionResult<ScrollResult> {
         let default_request = ScrollRequest::default();
 
-        let offset = request.offset;
+        // Point with the ID of the composite key was returned on the previous page, skip it
+        let (offset, filter, skip_id) = match request.offset {
+            None => (None, request.filter, None),
+            Some(ScrollKey::PointId(id)) => (Some(id), request.filter, None),
+            Some(ScrollKey::Composite { last_id, filter }) => {
+                if request
+                    .filter
+                    .as_ref()
+                    .is_some_and(|request_filter| *request_filter != filter)
+                {
+                    return Err(CollectionError::bad_input(
+                        "Filter of the request doesn't match the filter of the scroll key"
+                            .to_string(),
+                    ));
+                }
+                (Some(last_id), Some(filter), Some(last_id))
+            }
+        };
         let limit = request
             .limit
             .unwrap_or_else(|| default_request.limit.unwrap());
@@ -1054,6 +2737,7 @@ impl Collection {
             .clone()
             .unwrap_or_else(|| default_request.with_payload.clone().unwrap());
         let with_vector = request.with_vector;
+        let shard_selection = shard_selection.or(request.shard_key);
 
         if limit == 0 {
             return Err(CollectionError::BadRequest {
@@ -1063,29 +2747,60 @@ impl Collection {
 
         // Needed to return next page offset.
         let limit = limit + 1;
-        let retrieved_points: Vec<_> = {
+        let shard_limit = limit + usize::from(skip_id.is_some());
+
+        // Counted concurrently with the scroll, so the count may include points
+        // updated between the shard reads of both
+        let total_count = async {
+            if !request.with_total_count {
+                return Ok(None);
+            }
+            let count_request = CountRequest {
+                filter: filter.clone(),
+                exact: true,
+                shard_key: None,
+            };
+            let result = self.count(count_request, shard_selection, None).await?;
+            CollectionResult::Ok(Some(result.count))
+        };
+
+        let retrieved_points = async {
             let shards_holder = self.shards_holder.read().await;
             let target_shards = shards_holder.target_shard(shard_selection)?;
-            let scroll_futures = target_shards.into_iter().map(|shard| {
-                shard.scroll_by(
-                    offset,
-                    limit,
-                    &with_payload_interface,
-                    &with_vector,
-                    request.filter.as_ref(),
-                    read_consistency,
-                )
+            let scroll_futures = target_shards.into_iter().map(|shard| async {
+                let mut records = shard
+                    .scroll_by(
+                        offset,
+                        shard_limit,
+                        &with_payload_interface,
+                        &with_vector,
+                        filter.as_ref(),
+                        read_consistency,
+                    )
+                    .await?;
+                if request.with_shard_key {
+                    for record in &mut records {
+                        record.shard_id = Some(shard.shard_id);
+                    }
+                }
+                CollectionResult::Ok(records)
             });
 
-            try_join_all(scroll_futures).await?
+            merge_sorted_by_id(scroll_futures.collect::<FuturesUnordered<_>>(), shard_limit).await
         };
-        let mut points: Vec<_> = retrieved_points
-            .into_iter()
-            .flatten()
-            .sorted_by_key(|point| point.id)
-            .take(limit)
-            .collect();
 
+        let (mut points, total_count) = try_join(retrieved_points, total_count).await?;
+        if let Some(skip_id) = skip_id {
+            if points.first().is_some_and(|point| point.id == skip_id) {
+                points.remove(0);
+            }
+        }
+        points.truncate(limit);
+
+        // Each shard returns its first matching points starting from `offset`. A shard which
+        // returned fewer points than requested, or none at all, has no more matching points,
+        // so the merged points are complete up to the next page offset. Lowering the offset
+        // to account for unfiltered points of empty shards would repeat already returned points.
         let next_page_offset = if points.len() < limit {
             // This was the last page
             None
@@ -1093,29 +2808,108 @@ impl Collection {
             // remove extra point, it would be a first point of the next page
             Some(points.pop().unwrap().id)
         };
+        let next_page_key = next_page_offset.and_then(|_| {
+            Some(ScrollKey::Composite {
+                last_id: points.last()?.id,
+                filter: filter.unwrap_or_default(),
+            })
+        });
         Ok(ScrollResult {
             points,
             next_page_offset,
+            next_page_key,
+            total_count,
+        })
+    }
+
+    /// Stream all points matching the filter, ordered by ID.
+    ///
+    /// Points are read with `scroll_by` in batches of `batch_size`, the next batch is only read
+    /// once the previous one is consumed. The stream ends after the first error.
+    pub fn points_stream(
+        &self,
+        filter: Option<Filter>,
+        batch_size: usize,
+        with_payload: WithPayloadInterface,
+        with_vector: WithVector,
+        read_consistency: Option<ReadConsistency>,
+    ) -> impl Stream<Item = CollectionResult<Record>> + '_ {
+        // Offset of the next batch, `None` once the last batch is read
+        let first_offset: Option<Option<ExtendedPointId>> = Some(None);
+        stream::unfold(first_offset, move |offset| {
+            let request = offset.map(|offset| ScrollRequest {
+                offset: offset.map(ScrollKey::from),
+                limit: Some(batch_size),
+                filter: filter.clone(),
+                with_payload: Some(with_payload.clone()),
+                with_vector: with_vector.clone(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
+            });
+            async move {
+                let result = self.scroll_by(request?, read_consistency, None).await;
+                let (batch, next_offset) = match result {
+                    Ok(result) => (
+                        result.points.into_iter().map(Ok).collect(),
+                        result.next_page_offset.map(Some),
+                    ),
+                    Err(err) => (vec![Err(err)], None),
+                };
+                Some((stream::iter(batch), next_offset))
+            }
         })
+        .flatten()
     }
 
+    /// Count points matching the request
+    ///
+    /// Approximate count without filter is cached between writes, so it might not reflect
+    /// the latest updates. Exact count always queries the shards.
+    ///
+    /// If `cancel` is given, the shards stop counting once it is cancelled
+    /// and `CollectionError::Cancelled` is returned instead of a partial result.
+    ///
+    /// `shard_selection` takes precedence over `shard_key` of the request.
     pub async fn count(
         &self,
         request: CountRequest,
         shard_selection: Option<ShardId>,
+        cancel: Option<CancellationToken>,
     ) -> CollectionResult<CountResult> {
+        let shard_selection = shard_selection.or(request.shard_key);
+
+        // Fast path for approximate count of all points, served from cache between writes
+        let is_cacheable = request.filter.is_none() && !request.exact && shard_selection.is_none();
+        if is_cacheable {
+            if let Some(count) = self.points_count_cache.get() {
+                return Ok(CountResult { count });
+            }
+        }
+        let cache_generation = self.points_count_cache.generation();
+
         let request = Arc::new(request);
 
         let counts: Vec<_> = {
             let shards_holder = self.shards_holder.read().await;
             let target_shards = shards_holder.target_shard(shard_selection)?;
-            let count_futures = target_shards
-                .into_iter()
-                .map(|shard| shard.count(request.clone()));
+            let count_futures = target_shards.into_iter().map(|shard| {
+                let request = request.clone();
+                let cancel = cancel.as_ref();
+                async move {
+                    match cancel {
+                        Some(cancel) => shard.count_cancellable(request, cancel).await,
+                        None => shard.count(request).await,
+                    }
+                }
+            });
             try_join_all(count_futures).await?.into_iter().collect()
         };
 
         let total_count = counts.iter().map(|x| x.count).sum::<usize>();
+        if is_cacheable {
+            self.points_count_cache.set(cache_generation, total_count);
+        }
         let aggregated_count = CountResult { count: total_count };
         Ok(aggregated_count)
     }
@@ -1145,7 +2939,23 @@ impl Collection {
             });
             try_join_all(retrieve_futures).await?
         };
-        let points = all_shard_collection_results.into_iter().flatten().collect();
+        let points: Vec<_> = all_shard_collection_results.into_iter().flatten().collect();
+
+        if request.strict {
+            let found: HashSet<_> = points.iter().map(|point| point.id).collect();
+            let missing_ids: Vec<_> = request
+                .ids
+                .iter()
+                .filter(|id| !found.contains(id))
+                .unique()
+                .collect();
+            if !missing_ids.is_empty() {
+                return Err(CollectionError::NotFound {
+                    what: format!("point ids {missing_ids:?}"),
+                });
+            }
+        }
+
         Ok(points)
     }
 
@@ -1177,7 +2987,9 @@ impl Collection {
     ) -> CollectionResult<()> {
         {
             let mut config = self.collection_config.write().await;
-            config.hnsw_config = hnsw_config_diff.update(&config.hnsw_config)?;
+            let hnsw_config = hnsw_config_diff.update(&config.hnsw_config)?;
+            check_hnsw_config(&hnsw_config)?;
+            config.hnsw_config = hnsw_config;
         }
         self.collection_config.read().await.save(&self.path)?;
         Ok(())
@@ -1191,9 +3003,11 @@ impl Collection {
     pub async fn update_quantization_config_from_diff(
         &self,
         quantization_config_diff: QuantizationConfigDiff,
-    ) -> CollectionResult<()> {
-        {
+    ) -> CollectionResult<UpdateConfigResult> {
+        let mut warning = None;
+        let requires_optimizer_restart = {
             let mut config = self.collection_config.write().await;
+            let old_quantization_config = config.quantization_config.clone();
             match quantization_config_diff {
                 QuantizationConfigDiff::Scalar(scalar) => {
                     config
@@ -1211,19 +3025,33 @@ impl Collection {
                         .replace(QuantizationConfig::Binary(binary));
                 }
                 QuantizationConfigDiff::Disabled(_) => {
-                    config.quantization_config = None;
+                    if config.quantization_config.take().is_some() {
+                        warning = Some(
+                            "Quantization removed; call recreate_optimizers_blocking to rebuild \
+                             unquantized indexes"
+                                .to_string(),
+                        );
+                    }
                 }
             }
-        }
+            config.quantization_config != old_quantization_config
+        };
         self.collection_config.read().await.save(&self.path)?;
-        Ok(())
+        Ok(UpdateConfigResult {
+            requires_optimizer_restart,
+            warning,
+        })
     }
 
     /// Updates vectors config:
     /// Saves new params on disk
     ///
+    /// Either updates params of existing vectors, or adds new named vectors, see
+    /// [`VectorsConfigDiff`].
+    ///
     /// After this, `recreate_optimizers_blocking` must be called to create new optimizers using
-    /// the updated configuration.
+    /// the updated configuration. Added vectors are created in the segments of local shards
+    /// meanwhile, with zero vectors for existing points, and indexed by the new optimizers.
     pub async fn update_vectors_from_diff(
         &self,
         update_vectors_diff: &VectorsConfigDiff,
@@ -1244,6 +3072,7 @@ impl Collection {
     /// Handle replica changes
     ///
     /// add and remove replicas from replica set
+    /// New replicas are created in `Partial` state and populated with a shard transfer
     pub async fn handle_replica_changes(
         &self,
         replica_changes: Vec<Change>,
@@ -1255,6 +3084,51 @@ impl Collection {
 
         for change in replica_changes {
             match change {
+                Change::Add(shard_id, peer_id) => {
+                    let Some(replica_set) = read_shard_holder.get_shard(&shard_id) else {
+                        return Err(CollectionError::BadRequest {
+                            description: format!("Shard {} of {} not found", shard_id, self.name()),
+                        });
+                    };
+
+                    let peers = replica_set.peers();
+
+                    if peers.contains_key(&peer_id) {
+                        return Err(CollectionError::BadRequest {
+                            description: format!(
+                                "Peer {peer_id} already has a replica of shard {shard_id}"
+                            ),
+                        });
+                    }
+
+                    // Pick source deterministically, so all peers agree on it
+                    let Some(transfer_from) = peers
+                        .into_iter()
+                        .filter(|(_, state)| *state == ReplicaState::Active)
+                        .map(|(peer_id, _)| peer_id)
+                        .min()
+                    else {
+                        return Err(CollectionError::BadRequest {
+                            description: format!(
+                                "Shard {shard_id} has no active replicas to transfer from"
+                            ),
+                        });
+                    };
+
+                    replica_set
+                        .ensure_replica_with_state(&peer_id, ReplicaState::Partial)
+                        .await?;
+
+                    // Only the source peer requests the transfer to avoid duplicated proposals
+                    if transfer_from == self.this_peer_id {
+                        self.request_shard_transfer(ShardTransfer {
+                            shard_id,
+                            from: transfer_from,
+                            to: peer_id,
+                            sync: true,
+                        });
+                    }
+                }
                 Change::Remove(shard_id, peer_id) => {
                     let replica_set_opt = read_shard_holder.get_shard(&shard_id);
                     let replica_set = if let Some(replica_set) = replica_set_opt {
@@ -1296,14 +3170,28 @@ impl Collection {
     pub async fn update_optimizer_params_from_diff(
         &self,
         optimizer_config_diff: OptimizersConfigDiff,
-    ) -> CollectionResult<()> {
-        {
+    ) -> CollectionResult<Vec<OptimizerRestartReason>> {
+        // Only check the threshold if it is updated, not to block updates of other parameters
+        let check_indexing_threshold = optimizer_config_diff.indexing_threshold.is_some();
+        let restart_reasons = {
             let mut config = self.collection_config.write().await;
-            config.optimizer_config =
+            let new_optimizer_config =
                 DiffConfig::update(optimizer_config_diff, &config.optimizer_config)?;
-        }
+            if check_indexing_threshold {
+                check_optimizers_config(
+                    &new_optimizer_config,
+                    self.shared_storage_config.min_indexing_threshold,
+                )?;
+            }
+            let restart_reasons = OptimizerRestartReason::from_configs(
+                &config.optimizer_config,
+                &new_optimizer_config,
+            );
+            config.optimizer_config = new_optimizer_config;
+            restart_reasons
+        };
         self.collection_config.read().await.save(&self.path)?;
-        Ok(())
+        Ok(restart_reasons)
     }
 
     /// Updates shard optimization params: Saves new params on disk
@@ -1330,54 +3218,102 @@ impl Collection {
     ///
     /// Partially blocking. Stopping existing optimizers is blocking. Starting new optimizers is
     /// not blocking.
-    pub async fn recreate_optimizers_blocking(&self) -> CollectionResult<()> {
+    ///
+    /// If `restart_mask` is specified, only optimizers of the listed kinds are recreated.
+    pub async fn recreate_optimizers_blocking(
+        &self,
+        restart_mask: Option<HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
         let shard_holder = self.shards_holder.read().await;
         let updates = shard_holder
             .all_shards()
-            .map(|replica_set| replica_set.on_optimizer_config_update());
+            .map(|replica_set| replica_set.on_optimizer_config_update(restart_mask.as_ref()));
         try_join_all(updates).await?;
         Ok(())
     }
 
     pub async fn info(&self, shard_selection: Option<ShardId>) -> CollectionResult<CollectionInfo> {
-        let (all_shard_collection_results, mut info) = {
+        let all_shard_collection_results = {
             let shards_holder = self.shards_holder.read().await;
 
             let target_shards = shards_holder.target_shard(shard_selection)?;
 
-            let first_shard = *target_shards.first().ok_or_else(|| {
-                CollectionError::service_error(
+            if target_shards.is_empty() {
+                return Err(CollectionError::service_error(
                     "There are no shards for selected collection".to_string(),
-                )
-            })?;
+                ));
+            }
 
-            let info = first_shard.info().await?;
-            let info_futures = target_shards.into_iter().skip(1).map(|shard| shard.info());
+            // Initializing shards might not be able to respond for a long time,
+            // don't let them block the whole request
+            let timeout = self.shared_storage_config.shard_info_timeout;
+            let info_futures = target_shards.into_iter().map(|shard| async move {
+                let info = tokio::time::timeout(timeout, shard.info()).await.ok();
+                (shard.shard_id, info)
+            });
 
-            (try_join_all(info_futures).await?, info)
+            join_all(info_futures).await
         };
 
-        all_shard_collection_results
-            .into_iter()
-            .for_each(|shard_info| {
-                info.status = max(info.status, shard_info.status);
-                info.optimizer_status =
-                    max(info.optimizer_status.clone(), shard_info.optimizer_status);
-                info.vectors_count += shard_info.vectors_count;
-                info.indexed_vectors_count += shard_info.indexed_vectors_count;
-                info.points_count += shard_info.points_count;
-                info.segments_count += shard_info.segments_count;
-                for (key, schema) in shard_info.payload_schema {
-                    match info.payload_schema.entry(key) {
-                        Entry::Occupied(o) => {
-                            o.into_mut().points += schema.points;
-                        }
-                        Entry::Vacant(v) => {
-                            v.insert(schema);
-                        }
-                    };
+        // Skip shards which failed to respond, but report them
+        let mut degraded_shards = vec![];
+        let mut unavailable_shards = vec![];
+        let mut first_err = None;
+        let mut shard_infos = vec![];
+        for (shard_id, shard_info_res) in all_shard_collection_results {
+            match shard_info_res {
+                Some(Ok(shard_info)) => shard_infos.push(shard_info),
+                None => {
+                    log::debug!(
+                        "Timeout while getting info of shard {shard_id} of collection {}",
+                        self.id,
+                    );
+                    unavailable_shards.push(shard_id);
                 }
-            });
+                Some(Err(err)) => {
+                    log::warn!(
+                        "Failed to get info of shard {shard_id} of collection {}: {err}",
+                        self.id,
+                    );
+                    degraded_shards.push(shard_id);
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        let mut shard_infos = shard_infos.into_iter();
+        let Some(mut info) = shard_infos.next() else {
+            // All shards failed, there is nothing to report
+            return Err(first_err.unwrap_or_else(|| CollectionError::Timeout {
+                description: format!(
+                    "Operation 'Collection info' timed out after {} ms",
+                    self.shared_storage_config.shard_info_timeout.as_millis(),
+                ),
+            }));
+        };
+        info.partial_result = !degraded_shards.is_empty() || !unavailable_shards.is_empty();
+        info.degraded_shards = degraded_shards;
+        info.unavailable_shards = unavailable_shards;
+
+        shard_infos.for_each(|shard_info| {
+            info.status = max(info.status, shard_info.status);
+            info.optimizer_status = max(info.optimizer_status.clone(), shard_info.optimizer_status);
+            info.vectors_count += shard_info.vectors_count;
+            info.indexed_vectors_count += shard_info.indexed_vectors_count;
+            info.points_count += shard_info.points_count;
+            info.segments_count += shard_info.segments_count;
+            info.disk_data_size_bytes += shard_info.disk_data_size_bytes;
+            for (key, schema) in shard_info.payload_schema {
+                match info.payload_schema.entry(key) {
+                    Entry::Occupied(o) => {
+                        o.into_mut().points += schema.points;
+                    }
+                    Entry::Vacant(v) => {
+                        v.insert(schema);
+                    }
+                };
+            }
+        });
         Ok(info)
     }
 
@@ -1389,6 +3325,7 @@ impl Collection {
         let count_request = Arc::new(CountRequest {
             filter: None,
             exact: false, // Don't need exact count of unique ids here, only size estimation
+            shard_key: None,
         });
         // extract shards info
         for (shard_id, replica_set) in shards_holder.get_shards() {
@@ -1423,6 +3360,7 @@ impl Collection {
             }
         }
         let shard_transfers = shards_holder.get_shard_transfer_info();
+        let shard_key_ranges = shards_holder.shard_key_ranges();
 
         // sort by shard_id
         local_shards.sort_by_key(|k| k.shard_id);
@@ -1434,26 +3372,61 @@ impl Collection {
             local_shards,
             remote_shards,
             shard_transfers,
+            shard_key_ranges,
         };
         Ok(info)
     }
 
-    pub async fn state(&self) -> State {
+    pub async fn state(&self) -> CollectionResult<State> {
         let shards_holder = self.shards_holder.read().await;
-        let transfers = shards_holder.shard_transfers.read().clone();
-        State {
-            config: self.collection_config.read().await.clone(),
-            shards: shards_holder
-                .get_shards()
-                .map(|(shard_id, replicas)| {
-                    let shard_info = ShardInfo {
-                        replicas: replicas.peers(),
-                    };
-                    (*shard_id, shard_info)
-                })
-                .collect(),
+        let transfers: HashSet<_> = shards_holder
+            .shard_transfers
+            .read()
+            .values()
+            .cloned()
+            .collect();
+        let config = self.collection_config.read().await.clone();
+        let shards: HashMap<_, _> = shards_holder
+            .get_shards()
+            .map(|(shard_id, replicas)| {
+                let shard_info = ShardInfo {
+                    replicas: replicas.peers(),
+                };
+                (*shard_id, shard_info)
+            })
+            .collect();
+        let state_checksum = state_checksum(
+            &config,
+            shards
+                .iter()
+                .map(|(shard_id, shard_info)| (*shard_id, &shard_info.replicas)),
+            &transfers,
+        )?;
+        Ok(State {
+            config,
+            shards,
             transfers,
-        }
+            state_checksum,
+        })
+    }
+
+    /// Checksum of the collection state, same as `state().state_checksum`,
+    /// but without cloning the whole state.
+    pub async fn state_checksum(&self) -> CollectionResult<String> {
+        let shards_holder = self.shards_holder.read().await;
+        let config = self.collection_config.read().await;
+        let shard_replicas: Vec<_> = shards_holder
+            .get_shards()
+            .map(|(shard_id, replicas)| (*shard_id, replicas.peers()))
+            .collect();
+        let transfers = shards_holder.shard_transfers.read();
+        state_checksum(
+            &config,
+            shard_replicas
+                .iter()
+                .map(|(shard_id, replicas)| (*shard_id, replicas)),
+            transfers.values(),
+        )
     }
 
     pub async fn apply_state(
@@ -1475,17 +3448,61 @@ impl Collection {
             (shards_telemetry, shards_holder.get_shard_transfer_info())
         };
 
+        let mut optimizer_queue = OptimizerQueueStats::default();
+        for local_telemetry in shards_telemetry
+            .iter()
+            .filter_map(|shard| shard.local.as_ref())
+        {
+            optimizer_queue += &local_telemetry.optimizer_queue;
+        }
+
         CollectionTelemetry {
             id: self.name(),
             init_time_ms: self.init_time.as_millis() as u64,
             config: self.collection_config.read().await.clone(),
             shards: shards_telemetry,
             transfers,
+            optimizer_queue,
+            search_quality_hint: *self.search_quality_hint.lock(),
         }
     }
 
     pub async fn list_snapshots(&self) -> CollectionResult<Vec<SnapshotDescription>> {
-        list_snapshots_in_directory(&self.snapshots_path).await
+        let mut snapshots = list_snapshots_in_directory(&self.snapshots_path).await?;
+        let snapshot_hashes = self.snapshot_hashes.load()?;
+        let hashes_by_name: HashMap<_, _> = snapshot_hashes
+            .into_iter()
+            .map(|(hash, name)| (name, hash))
+            .collect();
+        for snapshot in &mut snapshots {
+            snapshot.content_hash = hashes_by_name.get(&snapshot.name).cloned();
+        }
+        Ok(snapshots)
+    }
+
+    /// List snapshots sorted by creation time, returning only `limit` snapshots after `offset`
+    pub async fn list_snapshots_paginated(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> CollectionResult<SnapshotListPage> {
+        let mut snapshots = self.list_snapshots().await?;
+        snapshots.sort_by(|a, b| {
+            a.creation_time
+                .cmp(&b.creation_time)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let total = snapshots.len();
+        let page_end = offset.saturating_add(limit).min(total);
+        let next_offset = (page_end < total).then_some(page_end);
+        let snapshots = snapshots.into_iter().skip(offset).take(limit).collect();
+
+        Ok(SnapshotListPage {
+            snapshots,
+            total,
+            next_offset,
+        })
     }
 
     pub async fn get_snapshot_path(&self, snapshot_name: &str) -> CollectionResult<PathBuf> {
@@ -1530,13 +3547,24 @@ impl Collection {
     ///
     /// * `global_temp_dir`: directory used to host snapshots while they are being created
     /// * `this_peer_id`: current peer id
+    /// * `progress_cb`: optional callback, called after each captured shard and periodically during archiving
     ///
     /// returns: Result<SnapshotDescription, CollectionError>
     pub async fn create_snapshot(
         &self,
         global_temp_dir: &Path,
         this_peer_id: PeerId,
+        progress_cb: Option<SnapshotProgressCallback>,
     ) -> CollectionResult<SnapshotDescription> {
+        // Released when the snapshot is created or failed
+        let _snapshot_permit =
+            tokio::time::timeout(SNAPSHOT_PERMIT_TIMEOUT, self.snapshots_semaphore.acquire())
+                .await
+                .map_err(|_| CollectionError::unavailable("too many concurrent snapshots"))?
+                .map_err(|err| {
+                    CollectionError::service_error(format!("Can't acquire snapshot permit: {err}"))
+                })?;
+
         let snapshot_name = format!(
             "{}-{}-{}.snapshot",
             self.name(),
@@ -1568,8 +3596,20 @@ impl Collection {
                 // If node is listener, we can save whatever currently is in the storage
                 let save_wal = self.shared_storage_config.node_type != NodeType::Listener;
                 replica_set
-                    .create_snapshot(&snapshot_temp_dir_path, &shard_snapshot_path, save_wal)
+                    .create_snapshot(
+                        &snapshot_temp_dir_path,
+                        &shard_snapshot_path,
+                        save_wal,
+                        None,
+                    )
                     .await?;
+                if let Some(progress_cb) = &progress_cb {
+                    progress_cb(SnapshotProgress {
+                        phase: SnapshotPhase::CapturingShards,
+                        bytes_archived: 0,
+                        total_bytes_estimate: 0,
+                    });
+                }
             }
         }
 
@@ -1579,6 +3619,10 @@ impl Collection {
             .read()
             .await
             .save(&snapshot_temp_dir_path)?;
+        let manifest = SnapshotManifest {
+            collection_id: self.id.clone(),
+            content_hash: None,
+        };
 
         // Dedicated temporary file for archiving this snapshot (deleted on drop)
         let mut snapshot_temp_arc_file = tempfile::Builder::new()
@@ -1589,30 +3633,161 @@ impl Collection {
         let snapshot_temp_dir_path_clone = snapshot_temp_dir_path.clone();
         log::debug!("Archiving snapshot {:?}", &snapshot_temp_dir_path);
         let archiving = tokio::task::spawn_blocking(move || {
-            let mut builder = TarBuilder::new(snapshot_temp_arc_file.as_file_mut());
-            // archive recursively collection directory `snapshot_path_with_arc_extension` into `snapshot_path`
-            builder.append_dir_all(".", &snapshot_temp_dir_path_clone)?;
-            builder.finish()?;
-            drop(builder);
-            // return ownership of the file
-            Ok::<_, CollectionError>(snapshot_temp_arc_file)
+            let memory_before = ProcessMemory::current();
+            // Resident memory before archiving and its peak during archiving
+            let memory_usage = move || {
+                let memory_after = ProcessMemory::current()?;
+                let memory_before = memory_before?;
+                let peak = ProcessMemory::peak_between(memory_before, memory_after);
+                Some((memory_before.rss_bytes, peak))
+            };
+
+            let Some(progress_cb) = progress_cb else {
+                // archive recursively collection directory `snapshot_path_with_arc_extension` into `snapshot_path`
+                let (content_hash, _) = archive_snapshot_dir(
+                    snapshot_temp_arc_file.as_file_mut(),
+                    &snapshot_temp_dir_path_clone,
+                    manifest,
+                )?;
+                // return ownership of the file
+                return Ok::<_, CollectionError>((
+                    snapshot_temp_arc_file,
+                    content_hash,
+                    None,
+                    0,
+                    memory_usage(),
+                ));
+            };
+
+            let total_bytes_estimate = fs_extra::dir::get_size(&snapshot_temp_dir_path_clone)
+                .map_err(|err| {
+                    CollectionError::service_error(format!(
+                        "Can't get size of snapshot directory {}: {err}",
+                        snapshot_temp_dir_path_clone.display(),
+                    ))
+                })?;
+            let writer = ArchivingProgressWriter::new(
+                snapshot_temp_arc_file.as_file_mut(),
+                &progress_cb,
+                total_bytes_estimate,
+            );
+            let (content_hash, writer) =
+                archive_snapshot_dir(writer, &snapshot_temp_dir_path_clone, manifest)?;
+            let bytes_archived = writer.finish();
+            // return ownership of the file and the callback
+            Ok((
+                snapshot_temp_arc_file,
+                content_hash,
+                Some(progress_cb),
+                bytes_archived,
+                memory_usage(),
+            ))
         });
-        snapshot_temp_arc_file = archiving.await??;
+        let (archived_file, content_hash, progress_cb, bytes_archived, memory_usage) =
+            archiving.await??;
+        snapshot_temp_arc_file = archived_file;
+
+        let peak_memory_bytes_during_archiving = match memory_usage {
+            Some((memory_before, peak_memory)) => {
+                let memory_delta = peak_memory.saturating_sub(memory_before);
+                let warning_threshold = self
+                    .shared_storage_config
+                    .snapshot_memory_warning_threshold_bytes;
+                if warning_threshold > 0 && memory_delta > warning_threshold {
+                    log::warn!(
+                        "Archiving snapshot {snapshot_name} increased memory usage by {memory_delta} bytes, \
+                         peak memory usage is {peak_memory} bytes",
+                    );
+                }
+                peak_memory
+            }
+            None => 0,
+        };
+
+        let report_progress = |phase| {
+            if let Some(progress_cb) = &progress_cb {
+                progress_cb(SnapshotProgress {
+                    phase,
+                    bytes_archived,
+                    total_bytes_estimate: bytes_archived,
+                });
+            }
+        };
+
+        // Do not store the same content twice
+        let snapshot_hashes = self.snapshot_hashes.load()?;
+        if let Some(existing_name) = snapshot_hashes.get(&content_hash) {
+            let existing_path = self.snapshots_path.join(existing_name);
+            if existing_path.exists() {
+                log::info!(
+                    "Collection snapshot {} has the same content as {:?}, skipping",
+                    snapshot_name,
+                    existing_path
+                );
+                let snapshots_path = self.snapshots_path.clone();
+                let existing_name = existing_name.clone();
+                tokio::task::spawn_blocking(move || {
+                    update_latest_snapshot_link(&snapshots_path, &existing_name)
+                })
+                .await??;
+                report_progress(SnapshotPhase::Complete);
+                let mut description = get_snapshot_description(&existing_path).await?;
+                description.content_hash = Some(content_hash);
+                description.peak_memory_bytes_during_archiving = peak_memory_bytes_during_archiving;
+                return Ok(description);
+            }
+        }
+
+        report_progress(SnapshotPhase::Moving);
 
         // Move snapshot to permanent location.
-        // We can't move right away, because snapshot folder can be on another mounting point.
-        // We can't copy to the target location directly, because copy is not atomic.
-        // So we copy to the final location with a temporary name and then rename atomically.
-        let snapshot_path_tmp_move = snapshot_path.with_extension("tmp");
-        copy(&snapshot_temp_arc_file.path(), &snapshot_path_tmp_move).await?;
-        rename(&snapshot_path_tmp_move, &snapshot_path).await?;
+        // Try to rename first, it is atomic and cheap if the temporary directory is on the same
+        // file system as the snapshots directory.
+        let allow_cross_device_copy = self
+            .collection_config
+            .read()
+            .await
+            .use_cross_device_snapshot_copy
+            .unwrap_or(true);
+        if let Err(err) = snapshot_temp_arc_file.persist(&snapshot_path) {
+            if !is_cross_device_error(&err.error) {
+                return Err(err.error.into());
+            }
+            if !allow_cross_device_copy {
+                return Err(CollectionError::service_error(format!(
+                    "Can't move snapshot {snapshot_name} into {}: \
+                     temporary directory is on another file system and copying is disabled",
+                    self.snapshots_path.display(),
+                )));
+            }
+            // Snapshot folder is on another mounting point.
+            // We can't copy to the target location directly, because copy is not atomic.
+            // So we copy to the final location with a temporary name and then rename atomically.
+            let snapshot_temp_arc_file = err.file;
+            let snapshot_path_tmp_move = snapshot_path.with_extension("tmp");
+            copy(&snapshot_temp_arc_file.path(), &snapshot_path_tmp_move).await?;
+            rename(&snapshot_path_tmp_move, &snapshot_path).await?;
+        }
+
+        self.snapshot_hashes
+            .insert(content_hash.clone(), snapshot_name.clone())?;
+        let snapshots_path = self.snapshots_path.clone();
+        let latest_name = snapshot_name.clone();
+        tokio::task::spawn_blocking(move || {
+            update_latest_snapshot_link(&snapshots_path, &latest_name)
+        })
+        .await??;
+        report_progress(SnapshotPhase::Complete);
 
         log::info!(
             "Collection snapshot {} completed into {:?}",
             snapshot_name,
             snapshot_path
         );
-        get_snapshot_description(&snapshot_path).await
+        let mut description = get_snapshot_description(&snapshot_path).await?;
+        description.content_hash = Some(content_hash);
+        description.peak_memory_bytes_during_archiving = peak_memory_bytes_during_archiving;
+        Ok(description)
     }
 
     pub async fn list_shard_snapshots(
@@ -1627,14 +3802,50 @@ impl Collection {
             return Ok(Vec::new());
         }
 
-        list_snapshots_in_directory(&snapshots_path).await
+        let mut snapshots = list_snapshots_in_directory(&snapshots_path).await?;
+
+        for snapshot in &mut snapshots {
+            let snapshot_path = snapshots_path.join(&snapshot.name);
+            let manifest = tokio::task::spawn_blocking(move || {
+                ShardSnapshotManifest::read_from_archive(&snapshot_path)
+            })
+            .await?;
+
+            match manifest {
+                Ok(manifest) => {
+                    snapshot.qdrant_version = manifest.map(|manifest| manifest.qdrant_version);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Failed to read manifest of shard snapshot {}: {err}",
+                        snapshot.name
+                    );
+                }
+            }
+        }
+
+        Ok(snapshots)
     }
 
+    /// Create snapshot of a local shard
+    ///
+    /// If `filter` is specified, only points matching it are included into the snapshot.
+    /// If `include_wal` is set, the WAL of the shard is flushed and archived along with the
+    /// segments, and the snapshot name gets a `_with_wal` suffix.
+    /// WAL can't be included into filtered snapshots.
     pub async fn create_shard_snapshot(
         &self,
         shard_id: ShardId,
         temp_dir: &Path,
+        filter: Option<&Filter>,
+        include_wal: bool,
     ) -> CollectionResult<SnapshotDescription> {
+        if include_wal && filter.is_some() {
+            return Err(CollectionError::bad_input(
+                "Filtered shard snapshot can't include WAL".to_string(),
+            ));
+        }
+
         let shards_holder = self.shards_holder.read().await;
         let shard = shards_holder
             .get_shard(&shard_id)
@@ -1647,52 +3858,16 @@ impl Collection {
         }
 
         let snapshot_file_name = format!(
-            "{}-shard-{shard_id}-{}.snapshot",
+            "{}-shard-{shard_id}-{}{}.snapshot",
             self.name(),
             chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S"),
+            if include_wal { "_with_wal" } else { "" },
         );
 
-        let snapshot_temp_dir = tempfile::Builder::new()
-            .prefix(&format!("{snapshot_file_name}-temp-"))
-            .tempdir_in(temp_dir)?;
-
-        let snapshot_target_dir = tempfile::Builder::new()
-            .prefix(&format!("{snapshot_file_name}-target-"))
-            .tempdir_in(temp_dir)?;
-
-        shard
-            .create_snapshot(snapshot_temp_dir.path(), snapshot_target_dir.path(), false)
+        let temp_file = shard
+            .create_snapshot_archive(&self.id, temp_dir, &snapshot_file_name, include_wal, filter)
             .await?;
 
-        if let Err(err) = snapshot_temp_dir.close() {
-            log::error!("Failed to remove temporary directory: {err}");
-        }
-
-        let mut temp_file = tempfile::Builder::new()
-            .prefix(&format!("{snapshot_file_name}-"))
-            .tempfile_in(temp_dir)?;
-
-        let task = {
-            let snapshot_target_dir = snapshot_target_dir.path().to_path_buf();
-
-            tokio::task::spawn_blocking(move || -> CollectionResult<_> {
-                let mut tar = TarBuilder::new(temp_file.as_file_mut());
-                tar.append_dir_all(".", &snapshot_target_dir)?;
-                tar.finish()?;
-                drop(tar);
-
-                Ok(temp_file)
-            })
-        };
-
-        let task_result = task.await;
-
-        if let Err(err) = snapshot_target_dir.close() {
-            log::error!("Failed to remove temporary directory: {err}");
-        }
-
-        let temp_file = task_result??;
-
         let snapshot_path = self.shard_snapshot_path_unchecked(shard_id, snapshot_file_name)?;
 
         if let Some(snapshot_dir) = snapshot_path.parent() {
@@ -1713,12 +3888,17 @@ impl Collection {
         }
     }
 
+    /// Path to the snapshots directory of the local shard, the directory is created if it doesn't exist
     pub async fn get_snapshots_path_for_shard(
         &self,
         shard_id: ShardId,
     ) -> CollectionResult<PathBuf> {
         self.assert_shard_is_local(shard_id).await?;
-        Ok(self.snapshots_path_for_shard_unchecked(shard_id))
+        let snapshots_path = self.snapshots_path_for_shard_unchecked(shard_id);
+        if !snapshots_path.exists() {
+            tokio::fs::create_dir_all(&snapshots_path).await?;
+        }
+        Ok(snapshots_path)
     }
 
     pub async fn get_shard_snapshot_path(
@@ -1841,20 +4021,222 @@ impl Collection {
             .get_shard(&shard_id)
             .ok_or_else(|| shard_not_found_error(shard_id))?;
 
+        // Validate snapshot data before touching existing shard, so it stays intact on failure
+        if LocalShard::check_data(snapshot_shard_path) {
+            let collection_config = self.collection_config.read().await;
+            LocalShard::check_data_compatibility(snapshot_shard_path, &collection_config)?;
+        }
+
         replica_set
             .restore_local_replica_from(snapshot_shard_path)
             .await
     }
 
+    /// Seed the replica on `target_peer_id` with a snapshot of the local shard
+    ///
+    /// Requests a sync transfer to the target peer, which pushes a snapshot of the local shard
+    /// instead of streaming the points. The target replica is `Partial` during the transfer and
+    /// only accepts the snapshot while the transfer is running. Updates received meanwhile are
+    /// forwarded to the target and applied on top of the snapshot once the transfer is finished.
+    ///
+    /// Waits until the transfer is finished.
+    pub async fn snapshot_shard_to_peer(
+        &self,
+        shard_id: ShardId,
+        target_peer_id: PeerId,
+        temp_dir: &Path,
+    ) -> CollectionResult<()> {
+        if target_peer_id == self.this_peer_id {
+            return Err(CollectionError::bad_input(format!(
+                "Can't push snapshot of shard {shard_id} to the same peer {target_peer_id}"
+            )));
+        }
+
+        {
+            let shard_holder = self.shards_holder.read().await;
+            let replica_set = shard_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| shard_not_found_error(shard_id))?;
+
+            if replica_set.peer_state(&self.this_peer_id) != Some(ReplicaState::Active) {
+                return Err(CollectionError::bad_input(format!(
+                    "Replica of shard {shard_id} on peer {} is not active",
+                    self.this_peer_id,
+                )));
+            }
+
+            if !replica_set.peers().contains_key(&target_peer_id) {
+                return Err(CollectionError::bad_input(format!(
+                    "Peer {target_peer_id} has no replica of shard {shard_id}"
+                )));
+            }
+        }
+
+        let transfer = ShardTransfer {
+            shard_id,
+            from: self.this_peer_id,
+            to: target_peer_id,
+            sync: true,
+        };
+        let transfer_key = transfer.key();
+        self.snapshot_transfers
+            .lock()
+            .insert(transfer_key.clone(), temp_dir.to_path_buf());
+        self.request_shard_transfer(transfer);
+
+        let result = self
+            .wait_shard_transfer(
+                &transfer_key,
+                &format!("push snapshot of shard {shard_id} to peer {target_peer_id}"),
+            )
+            .await;
+        self.snapshot_transfers.lock().remove(&transfer_key);
+        result
+    }
+
+    /// Recover local replica of the shard from the running replica on `source_peer_id`
+    ///
+    /// Requests a sync transfer from the source peer, waits for consensus to start it,
+    /// same as [`Collection::initiate_shard_transfer`], and then until it is finished.
+    /// The local replica is deactivated by the transfer, so it doesn't serve reads until recovered.
+    /// The local replica is not marked `Dead` explicitly, because that would trigger an automatic
+    /// recovery from an arbitrary active replica.
+    pub async fn recover_local_shard_from_peer(
+        &self,
+        shard_id: ShardId,
+        source_peer_id: PeerId,
+    ) -> CollectionResult<()> {
+        if source_peer_id == self.this_peer_id {
+            return Err(CollectionError::bad_input(format!(
+                "Can't recover shard {shard_id} from the same peer {source_peer_id}"
+            )));
+        }
+
+        {
+            let shard_holder = self.shards_holder.read().await;
+            let replica_set = shard_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| shard_not_found_error(shard_id))?;
+
+            if !replica_set.has_local_shard().await {
+                return Err(CollectionError::bad_input(format!(
+                    "Shard {shard_id} has no local replica on peer {}",
+                    self.this_peer_id,
+                )));
+            }
+
+            if replica_set.peer_state(&source_peer_id) != Some(ReplicaState::Active) {
+                return Err(CollectionError::bad_input(format!(
+                    "Replica of shard {shard_id} on peer {source_peer_id} is not active"
+                )));
+            }
+        }
+
+        let transfer = ShardTransfer {
+            shard_id,
+            from: source_peer_id,
+            to: self.this_peer_id,
+            sync: true,
+        };
+        let transfer_key = transfer.key();
+        self.request_shard_transfer(transfer);
+
+        self.wait_shard_transfer(
+            &transfer_key,
+            &format!("recover shard {shard_id} from peer {source_peer_id}"),
+        )
+        .await
+    }
+
+    /// Wait for consensus to start the requested transfer, and then until it is finished.
+    ///
+    /// Fails if the receiving replica is not `Active` after the transfer, e.g. because it was aborted.
+    async fn wait_shard_transfer(
+        &self,
+        transfer_key: &ShardTransferKey,
+        action: &str,
+    ) -> CollectionResult<()> {
+        let shard_id = transfer_key.shard_id;
+
+        let init_timeout =
+            Duration::from_secs(self.shared_storage_config.shard_transfer_init_timeout_secs);
+        let shards_holder = self.shards_holder.clone().read_owned().await;
+        let started_key = transfer_key.clone();
+        let transfer_started = tokio::task::spawn_blocking(move || {
+            shards_holder.shard_transfers.wait_for(
+                |shard_transfers| shard_transfers.contains_key(&started_key),
+                init_timeout,
+            )
+        })
+        .await?;
+        if !transfer_started {
+            return Err(CollectionError::Timeout {
+                description: format!(
+                    "Failed to {action}: \
+                     Didn't receive shard transfer notification from consensus in {} seconds",
+                    init_timeout.as_secs(),
+                ),
+            });
+        }
+
+        let wait_transfer = async {
+            while self.check_transfer_exists(transfer_key).await {
+                tokio::time::sleep(WAIT_TRANSFER_POLL_INTERVAL).await;
+            }
+        };
+        tokio::time::timeout(WAIT_TRANSFER_TIMEOUT, wait_transfer)
+            .await
+            .map_err(|_| {
+                CollectionError::timeout(WAIT_TRANSFER_TIMEOUT.as_secs() as usize, action)
+            })?;
+
+        let shard_holder = self.shards_holder.read().await;
+        let replica_set = shard_holder
+            .get_shard(&shard_id)
+            .ok_or_else(|| shard_not_found_error(shard_id))?;
+
+        if replica_set.peer_state(&transfer_key.to) != Some(ReplicaState::Active) {
+            let abort_reason = shard_holder
+                .get_last_aborted_transfer(shard_id)
+                .and_then(|aborted| aborted.abort_reason)
+                .unwrap_or_else(|| "unknown reason".to_string());
+            return Err(CollectionError::service_error(format!(
+                "Failed to {action}: {abort_reason}"
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Restore collection from snapshot
     ///
+    /// If `expected_collection_id` is set, the snapshot must be created from this collection,
+    /// which is checked before unpacking. Snapshots created without a manifest are not checked.
+    ///
     /// This method performs blocking IO.
     pub fn restore_snapshot(
         snapshot_path: &Path,
         target_dir: &Path,
         this_peer_id: PeerId,
         is_distributed: bool,
+        expected_collection_id: Option<&str>,
     ) -> CollectionResult<()> {
+        if let Some(expected_collection_id) = expected_collection_id {
+            match SnapshotManifest::read_from_archive(snapshot_path)? {
+                Some(manifest) if manifest.collection_id != expected_collection_id => {
+                    return Err(CollectionError::bad_input(format!(
+                        "Snapshot was created from collection {}, but is restored into collection {expected_collection_id}",
+                        manifest.collection_id,
+                    )));
+                }
+                Some(_) => {}
+                None => log::debug!(
+                    "Snapshot {} has no manifest, skipping collection check",
+                    snapshot_path.display(),
+                ),
+            }
+        }
+
         // decompress archive
         let archive_file = std::fs::File::open(snapshot_path)?;
         let mut ar = tar::Archive::new(archive_file);
@@ -1893,9 +4275,26 @@ impl Collection {
         Ok(())
     }
 
+    /// Remove all replicas of the peer, aborting transfers from and to it first
     pub async fn remove_shards_at_peer(&self, peer_id: PeerId) -> CollectionResult<()> {
+        // Block updates until the removal is complete, so that no writes race with it
+        let _updates_guard = self.updates_lock.write().await;
+
+        let related_transfers = self
+            .get_transfers(|transfer| transfer.from == peer_id || transfer.to == peer_id)
+            .await;
+
         let shard_holder = self.shards_holder.read().await;
 
+        for transfer in related_transfers {
+            self._abort_shard_transfer(
+                transfer.key(),
+                Some(&format!("peer {peer_id} is removed")),
+                &shard_holder,
+            )
+            .await?;
+        }
+
         for (_shard_id, replica_set) in shard_holder.get_shards() {
             replica_set.remove_peer(peer_id).await?;
         }
@@ -1956,6 +4355,11 @@ impl Collection {
             let this_peer_state = peers.get(this_peer_id).copied();
             let is_last_active = peers.values().filter(|state| **state == Active).count() == 1;
 
+            if this_peer_state == Some(Active) && replica_set.has_pending_operations().await {
+                // Updates buffered during a transfer were not applied, e.g. because of a restart
+                self.spawn_apply_pending_operations(shard_id);
+            }
+
             if this_peer_state == Some(Initializing) {
                 // It is possible, that collection creation didn't report
                 // Try to activate shard, as the collection clearly exists
@@ -1975,6 +4379,29 @@ impl Collection {
                 continue;
             }
 
+            let stale_replica_timeout = self.shared_storage_config.stale_replica_timeout;
+            if this_peer_state == Some(Active)
+                && !stale_replica_timeout.is_zero()
+                && replica_set.is_local_stale(stale_replica_timeout).await
+            {
+                if is_last_active {
+                    log::warn!(
+                        "Local shard {}:{shard_id} didn't apply updates for more than {} seconds, but it is the last active replica",
+                        self.id,
+                        stale_replica_timeout.as_secs(),
+                    );
+                } else {
+                    log::warn!(
+                        "Local shard {}:{shard_id} didn't apply updates for more than {} seconds, marking it as dead",
+                        self.id,
+                        stale_replica_timeout.as_secs(),
+                    );
+                    // Dead replica is recovered from other replicas on the next sync
+                    (self.notify_peer_failure_cb)(*this_peer_id, shard_id);
+                }
+                continue;
+            }
+
             if this_peer_state != Some(Dead) || replica_set.is_dummy().await {
                 continue; // All good
             }
@@ -2008,17 +4435,214 @@ impl Collection {
         Ok(())
     }
 
+    /// Blocks the current thread until the collection is initialized, use `spawn_blocking` in
+    /// async context or prefer `wait_collection_ready`.
     pub fn wait_collection_initiated(&self, timeout: Duration) -> bool {
         self.is_initialized.await_ready_for_timeout(timeout)
     }
 
+    /// Wait until the collection is initialized without blocking the executor
+    ///
+    /// Return `true` if ready, `false` if timed out.
+    pub async fn wait_collection_ready(&self, timeout: Duration) -> bool {
+        self.is_initialized
+            .await_ready_for_timeout_async(timeout)
+            .await
+    }
+
     pub async fn lock_updates(&self) -> RwLockWriteGuard<()> {
         self.updates_lock.write().await
     }
 }
 
+/// `ef` of each search of the batch from the `ef` of the searches sent to the shards,
+/// see [`SearchRequestBatch::split_ensembles`]. Ensembles report the lowest `ef` of their parts.
+fn unsplit_hnsw_ef_used(
+    request: &SearchRequestBatch,
+    hnsw_ef_used: Vec<Option<usize>>,
+) -> Vec<Option<usize>> {
+    if hnsw_ef_used.is_empty() {
+        return hnsw_ef_used;
+    }
+    let mut hnsw_ef_used = hnsw_ef_used.into_iter();
+    request
+        .searches
+        .iter()
+        .map(|search| {
+            let parts = match &search.vector {
+                VectorQuery::Single(_) => 1,
+                VectorQuery::Ensemble(vectors) => vectors.len(),
+            };
+            hnsw_ef_used.by_ref().take(parts).flatten().min()
+        })
+        .collect()
+}
+
+/// Remove first `offset` points of the result
+fn drain_offset(points: &mut Vec<ScoredPoint>, offset: usize) {
+    if offset == 0 {
+        return;
+    }
+    if points.len() >= offset {
+        // Panics if the end point > length of the vector.
+        points.drain(..offset);
+    } else {
+        points.clear()
+    }
+}
+
+/// Scored point ordered by score first and by point ID for equal scores,
+/// so that merging results from multiple shards is deterministic.
+struct RankedPoint {
+    point: ScoredPoint,
+    order: Order,
+    tiebreaker: TiebreakerMode,
+}
+
+impl Ord for RankedPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let id_order = match self.tiebreaker {
+            TiebreakerMode::IdAscending => other.point.id.cmp(&self.point.id),
+            TiebreakerMode::IdDescending => self.point.id.cmp(&other.point.id),
+        };
+        // Points which are better by ID should be "larger" for `LargeBetter`
+        // and "smaller" for `SmallBetter`.
+        let id_order = match self.order {
+            Order::LargeBetter => id_order,
+            Order::SmallBetter => id_order.reverse(),
+        };
+        self.point.cmp(&other.point).then(id_order)
+    }
+}
+
+impl PartialOrd for RankedPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RankedPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RankedPoint {}
+
 fn shard_not_found_error(shard_id: ShardId) -> CollectionError {
     CollectionError::NotFound {
         what: format!("shard {shard_id}"),
     }
 }
+
+fn search_batch_timeout(timeout_ms: u64) -> CollectionError {
+    CollectionError::Timeout {
+        description: format!("Search batch timed out after {timeout_ms} ms"),
+    }
+}
+
+fn shard_not_found_error_with_known(
+    shard_id: ShardId,
+    known_shards: &[ShardId],
+) -> CollectionError {
+    CollectionError::NotFound {
+        what: format!("shard {shard_id} (known shards: {known_shards:?})"),
+    }
+}
+
+/// Replace results of the searches with exact rescoring by their rescored results
+fn apply_exact_rescorings(results: &mut [Vec<ScoredPoint>], rescorings: &[Option<ExactRescoring>]) {
+    for (points, rescoring) in results.iter_mut().zip(rescorings) {
+        if let Some(rescoring) = rescoring {
+            *points = rescoring.apply(std::mem::take(points));
+        }
+    }
+}
+
+/// Normalize scores of the results of each search, see [`Collection::prepare_score_normalization`]
+fn apply_score_normalization(
+    results: &mut [Vec<ScoredPoint>],
+    normalization: Option<(ScoreNormalizerType, Vec<Order>)>,
+) {
+    if let Some((normalizer, orders)) = normalization {
+        for (points, order) in results.iter_mut().zip(orders) {
+            normalize_scores(points, normalizer, order);
+        }
+    }
+}
+
+/// Merge of per-shard scroll results, each of which is sorted by point ID.
+///
+/// Shard results are merged in order of arrival and only first `limit` points are kept,
+/// so results of all shards are never held in memory at once.
+async fn merge_sorted_by_id(
+    shard_results: impl Stream<Item = CollectionResult<Vec<Record>>>,
+    limit: usize,
+) -> CollectionResult<Vec<Record>> {
+    futures::pin_mut!(shard_results);
+
+    let mut merged = Vec::new();
+    while let Some(records) = shard_results.next().await {
+        merged = merged
+            .into_iter()
+            .merge_by(records?, |left: &Record, right: &Record| {
+                left.id <= right.id
+            })
+            .take(limit)
+            .collect();
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(shard_id: ShardId, ids: &[u64]) -> Vec<Record> {
+        ids.iter()
+            .map(|&id| Record {
+                id: id.into(),
+                payload: None,
+                vector: None,
+                shard_id: Some(shard_id),
+            })
+            .collect()
+    }
+
+    fn ids(records: &[Record]) -> Vec<ExtendedPointId> {
+        records.iter().map(|record| record.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_merge_sorted_by_id() {
+        let shard_results = vec![
+            Ok(records(0, &[2, 5, 8])),
+            Ok(records(1, &[1, 3, 9])),
+            Ok(records(2, &[])),
+            Ok(records(3, &[4, 6, 7])),
+        ];
+
+        let merged = merge_sorted_by_id(stream::iter(shard_results.clone()), 5)
+            .await
+            .unwrap();
+        assert_eq!(ids(&merged), [1u64, 2, 3, 4, 5].map(ExtendedPointId::from));
+
+        // Results are merged in any order of arrival
+        let merged = merge_sorted_by_id(stream::iter(shard_results.into_iter().rev()), 100)
+            .await
+            .unwrap();
+        assert_eq!(
+            ids(&merged),
+            [1u64, 2, 3, 4, 5, 6, 7, 8, 9].map(ExtendedPointId::from),
+        );
+
+        // Error of any shard fails the merge
+        let shard_results = vec![
+            Ok(records(0, &[1, 2])),
+            Err(CollectionError::service_error("shard failed".to_string())),
+        ];
+        assert!(merge_sorted_by_id(stream::iter(shard_results), 5)
+            .await
+            .is_err());
+    }
+}
@@ -223,6 +223,8 @@ pub(crate) fn get_merge_optimizer(
             on_disk_payload: false,
             replication_factor: NonZeroU32::new(1).unwrap(),
             write_consistency_factor: NonZeroU32::new(1).unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         },
         Default::default(),
         Default::default(),
@@ -254,6 +256,8 @@ pub(crate) fn get_indexing_optimizer(
             on_disk_payload: false,
             replication_factor: NonZeroU32::new(1).unwrap(),
             write_consistency_factor: NonZeroU32::new(1).unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         },
         Default::default(),
         Default::default(),
@@ -0,0 +1,150 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use segment::types::{Order, ScoreType, ScoredPoint};
+
+use crate::operations::types::ScoreNormalizerType;
+
+/// Default value of the `k` constant of Reciprocal Rank Fusion.
+/// Dampens the influence of the top ranked points of each result list.
+pub const DEFAULT_RRF_K: u32 = 60;
+
+/// Combine several ranked result lists into a single one with Reciprocal Rank Fusion.
+///
+/// Each point gets a score of `sum(1 / (k + rank))` over all lists it appears in,
+/// where `rank` starts from 1. Original scores of the points are discarded.
+/// Points with equal fused score are ordered by ID, so the result is deterministic.
+pub fn rrf_scoring(
+    responses: impl IntoIterator<Item = Vec<ScoredPoint>>,
+    k: u32,
+    limit: usize,
+) -> Vec<ScoredPoint> {
+    let mut fused: HashMap<_, ScoredPoint> = HashMap::new();
+
+    for response in responses {
+        for (rank, point) in response.into_iter().enumerate() {
+            let rank_score = 1.0 / (k as ScoreType + rank as ScoreType + 1.0);
+            match fused.entry(point.id) {
+                Entry::Occupied(mut entry) => entry.get_mut().score += rank_score,
+                Entry::Vacant(entry) => {
+                    entry.insert(ScoredPoint {
+                        score: rank_score,
+                        score_breakdown: None,
+                        ..point
+                    });
+                }
+            }
+        }
+    }
+
+    let mut result: Vec<_> = fused.into_values().collect();
+    result.sort_unstable_by(|a, b| b.cmp(a).then_with(|| a.id.cmp(&b.id)));
+    result.truncate(limit);
+    result
+}
+
+/// Normalize scores of a single search result in place, see [`ScoreNormalizerType`].
+///
+/// `order` is the order of the original scores, normalized scores are always larger for better points.
+/// Order of the points is not changed.
+pub fn normalize_scores(points: &mut [ScoredPoint], normalizer: ScoreNormalizerType, order: Order) {
+    let (min, max) = points.iter().fold(
+        (ScoreType::INFINITY, ScoreType::NEG_INFINITY),
+        |(min, max), point| (min.min(point.score), max.max(point.score)),
+    );
+    let range = max - min;
+
+    // Distance of the score from the worst score of the result
+    let from_worst = |score: ScoreType| match order {
+        Order::LargeBetter => score - min,
+        Order::SmallBetter => max - score,
+    };
+
+    for point in points {
+        point.score = match normalizer {
+            ScoreNormalizerType::None => return,
+            // All points are equally good
+            ScoreNormalizerType::MinMax if range <= 0.0 => 1.0,
+            ScoreNormalizerType::MinMax => from_worst(point.score) / range,
+            ScoreNormalizerType::Sigmoid if range <= 0.0 => 0.5,
+            ScoreNormalizerType::Sigmoid => {
+                let centered = (from_worst(point.score) - range / 2.0) / (range / 2.0);
+                1.0 / (1.0 + (-centered).exp())
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::types::PointIdType;
+
+    use super::*;
+
+    fn scored(ids: &[u64]) -> Vec<ScoredPoint> {
+        ids.iter()
+            .enumerate()
+            .map(|(i, id)| ScoredPoint {
+                id: PointIdType::from(*id),
+                version: 0,
+                score: 1.0 - i as ScoreType * 0.1,
+                payload: None,
+                vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rrf_scoring() {
+        let fused = rrf_scoring([scored(&[1, 2, 3]), scored(&[3, 1, 4])], DEFAULT_RRF_K, 10);
+
+        let ids: Vec<_> = fused.iter().map(|point| point.id).collect();
+        assert_eq!(
+            ids,
+            [1, 3, 2, 4].map(PointIdType::from).to_vec(),
+            "points found by both searches are ranked first",
+        );
+        assert!((fused[0].score - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-6);
+
+        let fused = rrf_scoring([scored(&[1, 2, 3]), scored(&[3, 1, 4])], DEFAULT_RRF_K, 2);
+        assert_eq!(fused.len(), 2);
+    }
+
+    #[test]
+    fn test_normalize_scores() {
+        let scores = |points: &[ScoredPoint]| points.iter().map(|p| p.score).collect::<Vec<_>>();
+
+        let mut points = scored(&[1, 2, 3]);
+        normalize_scores(&mut points, ScoreNormalizerType::MinMax, Order::LargeBetter);
+        let normalized = scores(&points);
+        assert!((normalized[0] - 1.0).abs() < 1e-6);
+        assert!((normalized[1] - 0.5).abs() < 1e-6);
+        assert!(normalized[2].abs() < 1e-6);
+
+        // Smaller distance is better, so the first point becomes the worst
+        let mut points = scored(&[1, 2, 3]);
+        normalize_scores(&mut points, ScoreNormalizerType::MinMax, Order::SmallBetter);
+        assert!(scores(&points)[0].abs() < 1e-6);
+
+        let mut points = scored(&[1, 2, 3]);
+        normalize_scores(
+            &mut points,
+            ScoreNormalizerType::Sigmoid,
+            Order::LargeBetter,
+        );
+        let normalized = scores(&points);
+        assert!((normalized[1] - 0.5).abs() < 1e-6);
+        assert!(normalized[0] > normalized[1] && normalized[1] > normalized[2]);
+        assert!(normalized.iter().all(|score| 0.0 < *score && *score < 1.0));
+
+        let mut points = scored(&[1]);
+        normalize_scores(&mut points, ScoreNormalizerType::MinMax, Order::LargeBetter);
+        assert_eq!(scores(&points), vec![1.0]);
+
+        let mut points = scored(&[1, 2]);
+        normalize_scores(&mut points, ScoreNormalizerType::None, Order::LargeBetter);
+        assert_eq!(scores(&points), scores(&scored(&[1, 2])));
+    }
+}
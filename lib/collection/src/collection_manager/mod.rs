@@ -1,6 +1,8 @@
 pub mod collection_updater;
+pub mod fusion;
 pub mod holders;
 pub mod optimizers;
+pub mod rescoring;
 pub mod segments_searcher;
 
 mod probabilistic_segment_search_sampling;
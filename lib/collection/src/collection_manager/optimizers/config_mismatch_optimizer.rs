@@ -11,7 +11,7 @@ use segment::types::{HnswConfig, Indexes, QuantizationConfig, SegmentType, VECTO
 
 use crate::collection_manager::holders::segment_holder::{LockedSegmentHolder, SegmentId};
 use crate::collection_manager::optimizers::segment_optimizer::{
-    OptimizerThresholds, SegmentOptimizer,
+    OptimizerKind, OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
 use crate::operations::config_diff::DiffConfig;
@@ -195,6 +195,10 @@ impl SegmentOptimizer for ConfigMismatchOptimizer {
         "config mismatch"
     }
 
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Indexing
+    }
+
     fn collection_path(&self) -> &Path {
         self.segments_path.as_path()
     }
@@ -291,6 +295,8 @@ mod tests {
             on_disk_payload: false,
             replication_factor: 1.try_into().unwrap(),
             write_consistency_factor: 1.try_into().unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         // Base segment
@@ -438,6 +444,8 @@ mod tests {
             on_disk_payload: false,
             replication_factor: 1.try_into().unwrap(),
             write_consistency_factor: 1.try_into().unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         // Base segment
@@ -606,6 +614,8 @@ mod tests {
             on_disk_payload: false,
             replication_factor: 1.try_into().unwrap(),
             write_consistency_factor: 1.try_into().unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         // Base segment
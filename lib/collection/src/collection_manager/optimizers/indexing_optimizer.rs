@@ -12,7 +12,7 @@ use crate::collection_manager::holders::segment_holder::{
     LockedSegmentHolder, SegmentHolder, SegmentId,
 };
 use crate::collection_manager::optimizers::segment_optimizer::{
-    OptimizerThresholds, SegmentOptimizer,
+    OptimizerKind, OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
 
@@ -195,6 +195,10 @@ impl SegmentOptimizer for IndexingOptimizer {
         "indexing"
     }
 
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Indexing
+    }
+
     fn collection_path(&self) -> &Path {
         self.segments_path.as_path()
     }
@@ -321,6 +325,8 @@ mod tests {
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                max_payload_size_bytes: None,
+                read_only: false,
             },
             Default::default(),
             Default::default(),
@@ -425,6 +431,8 @@ mod tests {
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                max_payload_size_bytes: None,
+                read_only: false,
             },
             Default::default(),
             Default::default(),
@@ -13,7 +13,7 @@ use crate::collection_manager::holders::segment_holder::{
     LockedSegment, LockedSegmentHolder, SegmentId,
 };
 use crate::collection_manager::optimizers::segment_optimizer::{
-    OptimizerThresholds, SegmentOptimizer,
+    OptimizerKind, OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
 
@@ -63,6 +63,10 @@ impl SegmentOptimizer for MergeOptimizer {
         "merge"
     }
 
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Merge
+    }
+
     fn collection_path(&self) -> &Path {
         self.segments_path.as_path()
     }
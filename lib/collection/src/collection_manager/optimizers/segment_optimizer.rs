@@ -35,6 +35,17 @@ pub struct OptimizerThresholds {
     pub indexing_threshold: usize,
 }
 
+/// Kind of segment optimizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptimizerKind {
+    /// Builds vector indexes and rebuilds segments with outdated index configuration
+    Indexing,
+    /// Merges small segments into larger ones
+    Merge,
+    /// Rebuilds segments with many deleted points
+    Vacuum,
+}
+
 /// SegmentOptimizer - trait implementing common functionality of the optimizers
 ///
 /// It provides functions which allow to re-build specified segments into a new, better one.
@@ -47,6 +58,9 @@ pub trait SegmentOptimizer {
     /// Get name describing this optimizer
     fn name(&self) -> &str;
 
+    /// Get kind of this optimizer
+    fn kind(&self) -> OptimizerKind;
+
     /// Get path of the whole collection
     fn collection_path(&self) -> &Path;
 
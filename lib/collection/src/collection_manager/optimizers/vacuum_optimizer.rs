@@ -16,7 +16,7 @@ use crate::collection_manager::holders::segment_holder::{
     LockedSegment, LockedSegmentHolder, SegmentId,
 };
 use crate::collection_manager::optimizers::segment_optimizer::{
-    OptimizerThresholds, SegmentOptimizer,
+    OptimizerKind, OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
 
@@ -169,6 +169,10 @@ impl SegmentOptimizer for VacuumOptimizer {
         "vacuum"
     }
 
+    fn kind(&self) -> OptimizerKind {
+        OptimizerKind::Vacuum
+    }
+
     fn collection_path(&self) -> &Path {
         self.segments_path.as_path()
     }
@@ -315,6 +319,8 @@ mod tests {
                 on_disk_payload: false,
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
+                max_payload_size_bytes: None,
+                read_only: false,
             },
             Default::default(),
             Default::default(),
@@ -420,6 +426,8 @@ mod tests {
             on_disk_payload: false,
             replication_factor: 1.try_into().unwrap(),
             write_consistency_factor: 1.try_into().unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         // Base segment
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use segment::data_types::named_vectors::NamedVectors;
+use segment::types::{Distance, ScoreType, ScoredPoint, WithVector};
+
+use crate::operations::types::{CollectionError, CollectionResult, SearchRequest, VectorQuery};
+
+/// Re-ranking of the approximate search results by the exact distance to the query.
+///
+/// Implements the two-phase search of `SearchParams::rescore_with_exact`:
+/// the approximate search pre-selects `limit * rescore_oversampling` candidates together with
+/// their vectors, which are then scored with the full-precision vectors.
+#[derive(Debug)]
+pub struct ExactRescoring {
+    vector_name: String,
+    query: Vec<f32>,
+    distance: Distance,
+    limit: usize,
+    offset: usize,
+    score_threshold: Option<ScoreType>,
+    /// Original `with_vector` of the request, if it was changed to retrieve the candidate vectors
+    original_with_vector: Option<Option<WithVector>>,
+}
+
+impl ExactRescoring {
+    /// Widen the request to pre-select the candidates for rescoring, if it is enabled.
+    ///
+    /// `distance` resolves the distance function of a vector by name.
+    pub fn prepare(
+        request: &mut SearchRequest,
+        distance: impl FnOnce(&str) -> CollectionResult<Distance>,
+    ) -> CollectionResult<Option<Self>> {
+        let Some(params) = request.params.filter(|params| params.rescore_with_exact) else {
+            return Ok(None);
+        };
+        let VectorQuery::Single(vector) = &request.vector else {
+            return Err(CollectionError::bad_input(
+                "Exact rescoring is not supported by ensemble search".to_string(),
+            ));
+        };
+        let vector_name = vector.get_name().to_string();
+        let distance = distance(&vector_name)?;
+        let query = distance.preprocess_vector(vector.get_vector().clone());
+
+        let oversampling = params.rescore_oversampling.unwrap_or(1.0).max(1.0);
+        let candidates = ((request.limit + request.offset) as f64 * oversampling).ceil() as usize;
+
+        let includes_vector = match &request.with_vector {
+            Some(WithVector::Bool(with_vector)) => *with_vector,
+            Some(WithVector::Selector(names)) => names.contains(&vector_name),
+            None => false,
+        };
+        let original_with_vector =
+            (!includes_vector).then(|| request.with_vector.replace(WithVector::Bool(true)));
+
+        let rescoring = Self {
+            vector_name,
+            query,
+            distance,
+            limit: request.limit,
+            offset: request.offset,
+            score_threshold: request.score_threshold,
+            original_with_vector,
+        };
+        request.limit = candidates;
+        request.offset = 0;
+        Ok(Some(rescoring))
+    }
+
+    /// Score the candidates with the exact distance and take the requested page of them
+    pub fn apply(&self, candidates: Vec<ScoredPoint>) -> Vec<ScoredPoint> {
+        let mut rescored: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|mut point| {
+                let similarity = point
+                    .vector
+                    .as_ref()
+                    .and_then(|vector| vector.get(&self.vector_name))
+                    .map(|vector| self.distance.similarity(&self.query, vector))?;
+                point.score = similarity;
+                Some(point)
+            })
+            .collect();
+
+        // Raw similarity is always larger-is-better, the order is fixed before postprocessing
+        rescored.sort_unstable_by(|a, b| b.cmp(a).then_with(|| a.id.cmp(&b.id)));
+
+        rescored
+            .into_iter()
+            .map(|mut point| {
+                point.score = self.distance.postprocess_score(point.score);
+                self.restore_vectors(&mut point);
+                point
+            })
+            .filter(|point| match self.score_threshold {
+                Some(threshold) => self.distance.check_threshold(point.score, threshold),
+                None => true,
+            })
+            .skip(self.offset)
+            .take(self.limit)
+            .collect()
+    }
+
+    fn restore_vectors(&self, point: &mut ScoredPoint) {
+        let Some(original_with_vector) = &self.original_with_vector else {
+            return;
+        };
+        point.vector = match original_with_vector {
+            Some(WithVector::Selector(names)) => point.vector.take().and_then(|vector| {
+                let vectors: HashMap<_, _> = vector
+                    .into_all_vectors()
+                    .into_owned_map()
+                    .into_iter()
+                    .filter(|(name, _)| names.contains(name))
+                    .collect();
+                (!vectors.is_empty()).then(|| NamedVectors::from_map(vectors).into())
+            }),
+            Some(WithVector::Bool(_)) | None => None,
+        };
+    }
+}
@@ -314,6 +314,7 @@ impl SegmentsSearcher {
                                 Some(selected_vectors.into())
                             }
                         },
+                        shard_id: None,
                     },
                 );
                 point_version.insert(id, version);
@@ -580,7 +581,7 @@ fn is_search_optimized(
 /// Find the HNSW ef_construct for a named vector
 ///
 /// If the given named vector has no HNSW index, `None` is returned.
-fn get_hnsw_ef_construct(config: &SegmentConfig, vector_name: &str) -> Option<usize> {
+pub(crate) fn get_hnsw_ef_construct(config: &SegmentConfig, vector_name: &str) -> Option<usize> {
     config
         .vector_data
         .get(vector_name)
@@ -704,6 +705,7 @@ mod tests {
                 filter: None,
                 params: None,
                 score_threshold: None,
+                ..Default::default()
             };
             let req2 = SearchRequest {
                 vector: random_vector(&mut rnd, 4).into(),
@@ -714,6 +716,7 @@ mod tests {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             };
 
             let batch_request = CoreSearchRequestBatch {
@@ -9,6 +9,8 @@ fn score_point(id: usize, score: ScoreType, version: SeqNumberType) -> ScoredPoi
         score,
         payload: None,
         vector: None,
+        score_breakdown: None,
+        search_params_echo: None,
     }
 }
 
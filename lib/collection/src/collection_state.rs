@@ -1,11 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::collection::Collection;
 use crate::config::CollectionConfig;
-use crate::operations::types::CollectionResult;
+use crate::operations::types::{CollectionError, CollectionResult};
 use crate::shards::replica_set::ReplicaState;
 use crate::shards::shard::{PeerId, ShardId};
 use crate::shards::transfer::shard_transfer::ShardTransfer;
@@ -22,6 +22,48 @@ pub struct State {
     pub shards: HashMap<ShardId, ShardInfo>,
     #[serde(default)]
     pub transfers: HashSet<ShardTransfer>,
+    /// BLAKE3 hash of the state, see [`state_checksum`]
+    #[serde(default)]
+    pub state_checksum: String,
+}
+
+/// Canonical representation of the collection state, only used to compute its checksum.
+/// Unordered collections are sorted, so that equal states always have equal checksums.
+#[derive(Serialize)]
+struct CanonicalState<'a> {
+    config: &'a CollectionConfig,
+    shards: BTreeMap<ShardId, BTreeMap<PeerId, ReplicaState>>,
+    transfers: Vec<&'a ShardTransfer>,
+}
+
+/// Compute BLAKE3 hash of the collection state, encoded as hex string.
+///
+/// Allows to cheaply detect if the states of two peers diverged.
+pub fn state_checksum<'a>(
+    config: &CollectionConfig,
+    shards: impl IntoIterator<Item = (ShardId, &'a HashMap<PeerId, ReplicaState>)>,
+    transfers: impl IntoIterator<Item = &'a ShardTransfer>,
+) -> CollectionResult<String> {
+    let mut transfers: Vec<_> = transfers.into_iter().collect();
+    transfers.sort_by_key(|transfer| (transfer.shard_id, transfer.from, transfer.to));
+
+    let canonical_state = CanonicalState {
+        config,
+        shards: shards
+            .into_iter()
+            .map(|(shard_id, replicas)| {
+                let replicas = replicas.iter().map(|(k, v)| (*k, *v)).collect();
+                (shard_id, replicas)
+            })
+            .collect(),
+        transfers,
+    };
+
+    let mut hasher = blake3::Hasher::new();
+    serde_json::to_writer(&mut hasher, &canonical_state).map_err(|err| {
+        CollectionError::service_error(format!("Failed to serialize collection state: {err}"))
+    })?;
+    Ok(hasher.finalize().to_hex().to_string())
 }
 
 impl State {
@@ -51,19 +93,21 @@ impl State {
             .shard_transfers
             .read()
             .clone();
-        for transfer in shard_transfers.difference(&old_transfers) {
+        for transfer in &shard_transfers {
+            if old_transfers.get(&transfer.key()) == Some(transfer) {
+                continue;
+            }
             if transfer.from == this_peer_id {
                 // Abort transfer as sender should not learn about the transfer from snapshot
                 // If this happens it mean the sender is probably outdated and it is safer to abort
                 abort_transfer(transfer.clone())
             }
         }
-        collection
-            .shards_holder
-            .write()
-            .await
+        let shards_holder = collection.shards_holder.write().await;
+        shards_holder
             .shard_transfers
-            .write(|transfers| *transfers = shard_transfers)?;
+            .write(|transfers| *transfers = shard_transfers.into_iter().collect())?;
+        collection.refresh_transfers_cache(&shards_holder);
         Ok(())
     }
 
@@ -83,7 +127,7 @@ impl State {
             config.params.write_consistency_factor = new_config.params.write_consistency_factor;
         }
 
-        collection.recreate_optimizers_blocking().await?;
+        collection.recreate_optimizers_blocking(None).await?;
 
         Ok(())
     }
@@ -92,15 +136,75 @@ impl State {
         shards: HashMap<ShardId, ShardInfo>,
         collection: &Collection,
     ) -> CollectionResult<()> {
-        for (shard_id, shard_info) in shards {
+        let (missing_shards, extra_shards) = {
             let shards_holder = collection.shards_holder.read().await;
-            match shards_holder.get_shard(&shard_id) {
-                Some(replica_set) => replica_set.apply_state(shard_info.replicas).await?,
-                None => {
-                    // If collection exists - it means it should know about all of its shards.
-                    // This holds true until shard number scaling is implemented.
-                    log::warn!("Shard number scaling is not yet implemented. Failed to add shard {shard_id} to an initialized collection {}", collection.name());
-                }
+            let known_shards: HashSet<_> = shards_holder
+                .get_shards()
+                .map(|(shard_id, _)| *shard_id)
+                .collect();
+
+            if shards.len() != known_shards.len() {
+                log::warn!(
+                    "ShardCountMismatch: collection {} has {} shards, but the applied state has {}",
+                    collection.name(),
+                    known_shards.len(),
+                    shards.len(),
+                );
+            }
+
+            let missing_shards: Vec<_> = shards
+                .keys()
+                .filter(|shard_id| !known_shards.contains(shard_id))
+                .copied()
+                .collect();
+            let extra_shards: Vec<_> = known_shards
+                .into_iter()
+                .filter(|shard_id| !shards.contains_key(shard_id))
+                .collect();
+            (missing_shards, extra_shards)
+        };
+
+        // If collection exists - it should know about all of its shards.
+        // A shard may still be missing locally, e.g. if this peer missed its creation.
+        // The applied state is authoritative, so the replica set is created empty here
+        // and its replicas are added by `apply_state` below.
+        if !missing_shards.is_empty() {
+            let mut new_shards = Vec::with_capacity(missing_shards.len());
+            for shard_id in missing_shards {
+                log::warn!(
+                    "Shard {shard_id} of collection {} is missing locally, creating it from the applied state",
+                    collection.name(),
+                );
+                new_shards.push((
+                    shard_id,
+                    collection.create_replica_set(shard_id, &[]).await?,
+                ));
+            }
+
+            let mut shards_holder = collection.shards_holder.write().await;
+            for (shard_id, replica_set) in new_shards {
+                shards_holder.add_shard(shard_id, replica_set);
+            }
+        }
+
+        let shards_holder = collection.shards_holder.read().await;
+
+        for shard_id in extra_shards {
+            let Some(replica_set) = shards_holder.get_shard(&shard_id) else {
+                continue;
+            };
+            log::warn!(
+                "Shard {shard_id} of collection {} is not present in the applied state, marking its replicas as dead",
+                collection.name(),
+            );
+            for peer_id in replica_set.peers().into_keys() {
+                replica_set.set_replica_state(&peer_id, ReplicaState::Dead)?;
+            }
+        }
+
+        for (shard_id, shard_info) in shards {
+            if let Some(replica_set) = shards_holder.get_shard(&shard_id) {
+                replica_set.apply_state(shard_info.replicas).await?;
             }
         }
         Ok(())
@@ -0,0 +1,55 @@
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use fs4::FileExt;
+
+use crate::operations::types::{CollectionError, CollectionResult};
+
+/// Exclusive advisory lock on a file.
+///
+/// Used to prevent several processes from opening the same directory simultaneously.
+/// The lock is released when the `FileLock` is dropped, or when the holding process dies.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Create the lock file, if it doesn't exist, and acquire an exclusive lock on it without blocking.
+    ///
+    /// Returns `CollectionError::Unavailable` if the lock is held by someone else.
+    pub fn try_acquire(path: &Path) -> CollectionResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)
+            .map_err(|err| {
+                CollectionError::service_error(format!(
+                    "Can't open lock file {}: {err}",
+                    path.display(),
+                ))
+            })?;
+
+        file.try_lock_exclusive().map_err(|err| {
+            CollectionError::unavailable(format!(
+                "Lock file {} is held by another process: {err}",
+                path.display(),
+            ))
+        })?;
+
+        Ok(Self {
+            file,
+            path: path.to_owned(),
+        })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(err) = self.file.unlock() {
+            log::warn!("Failed to release lock {}: {err}", self.path.display());
+        }
+    }
+}
@@ -30,6 +30,15 @@ pub async fn move_dir(from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Colle
     Ok(())
 }
 
+/// Whether the error is caused by renaming a file across file systems
+pub fn is_cross_device_error(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const CROSS_DEVICE_ERROR: i32 = 18; // EXDEV
+    #[cfg(not(unix))]
+    const CROSS_DEVICE_ERROR: i32 = 17; // ERROR_NOT_SAME_DEVICE
+    err.raw_os_error() == Some(CROSS_DEVICE_ERROR)
+}
+
 pub async fn move_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> CollectionResult<()> {
     // Try to rename first and fallback to copy to prevert TOCTOU
     let from = from.as_ref();
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::operations::types::{CollectionError, CollectionResult, UpdateResult};
+use crate::operations::CollectionUpdateOperations;
+
+/// Results of recently applied client updates, indexed by their idempotency keys
+///
+/// Used to respond to retried requests without applying the same operation twice.
+/// Key is reserved before the operation is applied, so that concurrent retries wait for the
+/// result of the first request instead of applying the operation again.
+/// Applied entries older than `ttl` are evicted by a background task.
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Uuid, Entry>>,
+}
+
+enum Entry {
+    /// Operation is being applied, its result is sent to the receiver once applied
+    InProgress {
+        operation_hash: blake3::Hash,
+        result: watch::Receiver<Option<UpdateResult>>,
+    },
+    Applied {
+        operation_hash: blake3::Hash,
+        applied_at: Instant,
+        result: UpdateResult,
+    },
+}
+
+pub enum Reservation<'a> {
+    /// Key is reserved by this request, the operation should be applied
+    Reserved(ReservationGuard<'a>),
+    /// Operation with this key is already applied
+    Applied(UpdateResult),
+    /// Operation with this key is being applied by another request
+    InProgress(watch::Receiver<Option<UpdateResult>>),
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Cache is disabled if TTL is zero
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// Hash of the operation, used to detect reuse of the key with a different operation
+    pub fn operation_hash(
+        operation: &CollectionUpdateOperations,
+    ) -> CollectionResult<blake3::Hash> {
+        let operation_bytes = serde_json::to_vec(operation)?;
+        Ok(blake3::hash(&operation_bytes))
+    }
+
+    /// Atomically check the key and reserve it, if it is not known yet.
+    ///
+    /// Returns an error if the key is known, but was used for a different operation.
+    pub fn reserve(
+        &self,
+        key: Uuid,
+        operation_hash: blake3::Hash,
+    ) -> CollectionResult<Reservation<'_>> {
+        let mut entries = self.entries.lock();
+        match entries.get(&key) {
+            Some(Entry::InProgress {
+                operation_hash: known_hash,
+                result,
+            }) => {
+                check_operation_hash(key, known_hash, &operation_hash)?;
+                return Ok(Reservation::InProgress(result.clone()));
+            }
+            Some(Entry::Applied {
+                operation_hash: known_hash,
+                applied_at,
+                result,
+            }) if applied_at.elapsed() < self.ttl => {
+                check_operation_hash(key, known_hash, &operation_hash)?;
+                return Ok(Reservation::Applied(result.clone()));
+            }
+            _ => {}
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        entries.insert(
+            key,
+            Entry::InProgress {
+                operation_hash,
+                result: receiver,
+            },
+        );
+        Ok(Reservation::Reserved(ReservationGuard {
+            cache: self,
+            key,
+            operation_hash,
+            sender: Some(sender),
+        }))
+    }
+
+    pub fn evict_expired(&self) {
+        self.entries.lock().retain(|_, entry| match entry {
+            Entry::InProgress { .. } => true,
+            Entry::Applied { applied_at, .. } => applied_at.elapsed() < self.ttl,
+        });
+    }
+
+    /// Spawn a task, which periodically evicts expired entries.
+    /// The task stops once the cache is dropped.
+    pub fn spawn_eviction_task(cache: &Arc<Self>) {
+        if !cache.is_enabled() {
+            return;
+        }
+        let cache: Weak<Self> = Arc::downgrade(cache);
+        tokio::spawn(async move {
+            loop {
+                let Some(ttl) = cache.upgrade().map(|cache| cache.ttl) else {
+                    break;
+                };
+                tokio::time::sleep(ttl).await;
+                match cache.upgrade() {
+                    Some(cache) => cache.evict_expired(),
+                    None => break,
+                }
+            }
+        });
+    }
+}
+
+fn check_operation_hash(
+    key: Uuid,
+    known_hash: &blake3::Hash,
+    operation_hash: &blake3::Hash,
+) -> CollectionResult<()> {
+    if known_hash != operation_hash {
+        return Err(CollectionError::bad_input(format!(
+            "Idempotency key {key} was already used for another operation"
+        )));
+    }
+    Ok(())
+}
+
+/// Reservation of the idempotency key by the request, which applies the operation.
+///
+/// If dropped without completion, e.g. the operation failed, the key is released,
+/// so that the operation can be retried.
+pub struct ReservationGuard<'a> {
+    cache: &'a IdempotencyCache,
+    key: Uuid,
+    operation_hash: blake3::Hash,
+    sender: Option<watch::Sender<Option<UpdateResult>>>,
+}
+
+impl ReservationGuard<'_> {
+    /// Remember the result of the applied operation and pass it to the waiting requests
+    pub fn complete(mut self, result: UpdateResult) {
+        self.cache.entries.lock().insert(
+            self.key,
+            Entry::Applied {
+                operation_hash: self.operation_hash,
+                applied_at: Instant::now(),
+                result: result.clone(),
+            },
+        );
+        if let Some(sender) = self.sender.take() {
+            sender.send_replace(Some(result));
+        }
+    }
+}
+
+impl Drop for ReservationGuard<'_> {
+    fn drop(&mut self) {
+        if self.sender.is_some() {
+            self.cache.entries.lock().remove(&self.key);
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use std::time::Duration;
 
 use parking_lot::{Condvar, Mutex};
+use tokio::sync::watch;
 
 pub struct IsReady {
     condvar: Condvar,
     value: Mutex<bool>,
+    /// Mirrors `value` for async waiters
+    watch: watch::Sender<bool>,
 }
 
 impl Default for IsReady {
@@ -12,6 +15,7 @@ impl Default for IsReady {
         Self {
             condvar: Condvar::new(),
             value: Mutex::new(false),
+            watch: watch::Sender::new(false),
         }
     }
 }
@@ -21,12 +25,15 @@ impl IsReady {
         let mut is_ready = self.value.lock();
         if !*is_ready {
             *is_ready = true;
+            self.watch.send_replace(true);
             self.condvar.notify_all();
         }
     }
 
     pub fn make_not_ready(&self) {
-        *self.value.lock() = false;
+        let mut is_ready = self.value.lock();
+        *is_ready = false;
+        self.watch.send_replace(false);
     }
 
     pub fn check_ready(&self) -> bool {
@@ -49,4 +56,65 @@ impl IsReady {
             true
         }
     }
+
+    /// Async version of `await_ready_for_timeout`, yields to the executor while waiting.
+    ///
+    /// Return `true` if ready, `false` if timed out.
+    pub async fn await_ready_for_timeout_async(&self, timeout: Duration) -> bool {
+        let mut receiver = self.watch.subscribe();
+        matches!(
+            tokio::time::timeout(timeout, receiver.wait_for(|is_ready| *is_ready)).await,
+            Ok(Ok(_)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_await_ready_for_timeout_async() {
+        let is_ready = Arc::new(IsReady::default());
+
+        let waiter = {
+            let is_ready = is_ready.clone();
+            tokio::spawn(async move {
+                is_ready
+                    .await_ready_for_timeout_async(Duration::from_secs(10))
+                    .await
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        is_ready.make_ready();
+        assert!(waiter.await.unwrap());
+
+        // Already ready, returns immediately
+        assert!(
+            is_ready
+                .await_ready_for_timeout_async(Duration::from_millis(1))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_await_ready_for_timeout_async_timeout() {
+        let is_ready = IsReady::default();
+        assert!(
+            !is_ready
+                .await_ready_for_timeout_async(Duration::from_millis(10))
+                .await
+        );
+
+        is_ready.make_ready();
+        is_ready.make_not_ready();
+        assert!(
+            !is_ready
+                .await_ready_for_timeout_async(Duration::from_millis(10))
+                .await
+        );
+    }
 }
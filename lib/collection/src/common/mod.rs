@@ -1,5 +1,10 @@
+pub mod file_lock;
 pub mod file_utils;
+pub mod idempotency_cache;
 pub mod is_ready;
+pub mod points_count_cache;
+pub mod process_memory;
 pub mod stoppable_task;
 pub mod stoppable_task_async;
 pub mod stopping_guard;
+pub mod vector_ops;
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Approximate number of points in the collection, cached between writes
+///
+/// Every write bumps the generation counter, which invalidates the cached value.
+/// Value computed concurrently with a write is not stored, as its generation is outdated.
+/// Cached values older than `ttl` are not used, as writes to remote shards are not tracked.
+pub struct PointsCountCache {
+    ttl: Duration,
+    generation: AtomicUsize,
+    cached: Mutex<Option<CachedCount>>,
+}
+
+struct CachedCount {
+    generation: usize,
+    count: usize,
+    computed_at: Instant,
+}
+
+impl PointsCountCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            generation: Default::default(),
+            cached: Default::default(),
+        }
+    }
+
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Current generation, should be obtained before computing the value to cache
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn get(&self) -> Option<usize> {
+        let generation = self.generation();
+        match &*self.cached.lock() {
+            Some(cached)
+                if cached.generation == generation && cached.computed_at.elapsed() < self.ttl =>
+            {
+                Some(cached.count)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set(&self, generation: usize, count: usize) {
+        let mut cached = self.cached.lock();
+        if generation == self.generation() {
+            *cached = Some(CachedCount {
+                generation,
+                count,
+                computed_at: Instant::now(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_count_cache_ttl() {
+        let cache = PointsCountCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get(), None);
+
+        cache.set(cache.generation(), 10);
+        assert_eq!(cache.get(), Some(10));
+
+        // Write invalidates the cached value
+        cache.invalidate();
+        assert_eq!(cache.get(), None);
+
+        // Value computed before the write is outdated and not stored
+        let generation = cache.generation();
+        cache.invalidate();
+        cache.set(generation, 20);
+        assert_eq!(cache.get(), None);
+
+        // Expired value is not used
+        let cache = PointsCountCache::new(Duration::from_millis(10));
+        cache.set(cache.generation(), 10);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.get(), None);
+
+        // Zero TTL disables the cache
+        let cache = PointsCountCache::new(Duration::ZERO);
+        cache.set(cache.generation(), 10);
+        assert_eq!(cache.get(), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+const PROC_SELF_STATUS: &str = "/proc/self/status";
+
+/// Resident memory usage of the current process, as reported by `/proc/self/status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessMemory {
+    /// Current resident set size
+    pub rss_bytes: u64,
+    /// Peak resident set size since the start of the process
+    pub peak_rss_bytes: u64,
+}
+
+impl ProcessMemory {
+    /// Read memory usage of the current process.
+    ///
+    /// Returns `None` if it is not available, e.g. on systems without procfs.
+    pub fn current() -> Option<Self> {
+        let status = fs::read_to_string(PROC_SELF_STATUS).ok()?;
+        Self::parse_status(&status)
+    }
+
+    fn parse_status(status: &str) -> Option<Self> {
+        let mut rss_bytes = None;
+        let mut peak_rss_bytes = None;
+        for line in status.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let target = match key {
+                "VmRSS" => &mut rss_bytes,
+                "VmHWM" => &mut peak_rss_bytes,
+                _ => continue,
+            };
+            // Values are reported in kB, e.g. `VmRSS:	   12345 kB`
+            let kb: u64 = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+            *target = Some(kb * 1024);
+        }
+        Some(Self {
+            rss_bytes: rss_bytes?,
+            peak_rss_bytes: peak_rss_bytes?,
+        })
+    }
+
+    /// Peak memory usage between `before` and `after` measurements.
+    ///
+    /// If the peak of the process was reached in between, it is reported precisely,
+    /// otherwise the larger of two measurements is used as an estimation.
+    pub fn peak_between(before: Self, after: Self) -> u64 {
+        if after.peak_rss_bytes > before.peak_rss_bytes {
+            after.peak_rss_bytes
+        } else {
+            before.rss_bytes.max(after.rss_bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = "Name:\tqdrant\nVmHWM:\t    2048 kB\nVmRSS:\t    1024 kB\nThreads:\t8\n";
+        let memory = ProcessMemory::parse_status(status).unwrap();
+        assert_eq!(memory.rss_bytes, 1024 * 1024);
+        assert_eq!(memory.peak_rss_bytes, 2048 * 1024);
+
+        assert_eq!(ProcessMemory::parse_status("Name:\tqdrant\n"), None);
+    }
+}
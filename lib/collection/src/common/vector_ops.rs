@@ -0,0 +1,35 @@
+use segment::data_types::vectors::VectorElementType;
+
+/// Scale the vector to unit length (L2 norm) in place.
+///
+/// Returns `false` and leaves the vector unchanged if its length is zero.
+pub fn normalize(vector: &mut [VectorElementType]) -> bool {
+    let norm = vector
+        .iter()
+        .map(|value| value * value)
+        .sum::<VectorElementType>()
+        .sqrt();
+    if norm == 0.0 {
+        return false;
+    }
+    for value in vector.iter_mut() {
+        *value /= norm;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let mut vector = vec![3.0, 0.0, 4.0];
+        assert!(normalize(&mut vector));
+        assert_eq!(vector, vec![0.6, 0.0, 0.8]);
+
+        let mut zero = vec![0.0; 3];
+        assert!(!normalize(&mut zero));
+        assert_eq!(zero, vec![0.0; 3]);
+    }
+}
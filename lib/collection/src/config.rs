@@ -10,13 +10,14 @@ use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
 use segment::types::{
-    HnswConfig, Indexes, QuantizationConfig, VectorDataConfig, VectorStorageType,
+    Distance, HnswConfig, Indexes, QuantizationConfig, VectorDataConfig, VectorStorageType,
 };
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 use wal::WalOptions;
 
 use crate::operations::config_diff::{DiffConfig, QuantizationConfigDiff};
+use crate::operations::shared_storage_config::DEFAULT_MIN_INDEXING_THRESHOLD_KB;
 use crate::operations::types::{
     CollectionError, CollectionResult, VectorParams, VectorParamsDiff, VectorsConfig,
     VectorsConfigDiff,
@@ -77,6 +78,17 @@ pub struct CollectionParams {
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default = "default_on_disk_payload")]
     pub on_disk_payload: bool,
+    /// Max size of the payload of a single point, serialized as JSON.
+    /// Updates with larger payloads are rejected. Default: no limit
+    /// Only the payload sent with the update is checked: payload set by filter, and payload
+    /// merged with the existing payload of a point, may exceed the limit.
+    #[serde(default)]
+    pub max_payload_size_bytes: Option<usize>,
+    /// If true - updates from clients are rejected, e.g. for archived collections.
+    /// Updates from other peers, like shard transfers, are still applied.
+    /// Reads, like search, scroll and retrieval, are not affected. Default: false
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl Anonymize for CollectionParams {
@@ -87,6 +99,8 @@ impl Anonymize for CollectionParams {
             replication_factor: self.replication_factor,
             write_consistency_factor: self.write_consistency_factor,
             on_disk_payload: self.on_disk_payload,
+            max_payload_size_bytes: self.max_payload_size_bytes,
+            read_only: self.read_only,
         }
     }
 }
@@ -119,6 +133,10 @@ pub struct CollectionConfig {
     pub wal_config: WalConfig,
     #[serde(default)]
     pub quantization_config: Option<QuantizationConfig>,
+    /// If the temporary directory and the snapshots directory are on different file systems,
+    /// copy created snapshots to the snapshots directory instead of failing. Default: true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_cross_device_snapshot_copy: Option<bool>,
 }
 
 impl CollectionConfig {
@@ -150,6 +168,79 @@ impl CollectionConfig {
         if let Err(ref errs) = self.validate() {
             validation::warn_validation_errors("Collection configuration file", errs);
         }
+        if let Err(err) = check_hnsw_config(&self.hnsw_config) {
+            log::warn!("Collection configuration file: {err}");
+        }
+        if let Err(err) =
+            check_optimizers_config(&self.optimizer_config, DEFAULT_MIN_INDEXING_THRESHOLD_KB)
+        {
+            log::warn!("Collection configuration file: {err}");
+        }
+    }
+}
+
+/// Check that the indexing threshold is not too low to be practical
+///
+/// `indexing_threshold = 0` is allowed, as it disables vector indexing.
+pub fn check_optimizers_config(
+    optimizers_config: &OptimizersConfig,
+    min_indexing_threshold: usize,
+) -> CollectionResult<()> {
+    match optimizers_config.indexing_threshold {
+        Some(threshold) if threshold != 0 && threshold < min_indexing_threshold => {
+            Err(CollectionError::bad_input(format!(
+                "Invalid optimizers config: indexing_threshold must be 0 or at least {min_indexing_threshold}, got {threshold}",
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check constraints between HNSW parameters, which can't be validated for each field separately
+///
+/// `m = 0` is allowed, as it disables building of the HNSW graph.
+pub fn check_hnsw_config(hnsw_config: &HnswConfig) -> CollectionResult<()> {
+    if hnsw_config.m == 0 {
+        return Ok(());
+    }
+    if hnsw_config.m < 2 {
+        return Err(CollectionError::bad_input(format!(
+            "Invalid HNSW config: m must be at least 2, got {}",
+            hnsw_config.m,
+        )));
+    }
+    if hnsw_config.ef_construct < hnsw_config.m {
+        return Err(CollectionError::bad_input(format!(
+            "Invalid HNSW config: ef_construct ({}) must not be less than m ({})",
+            hnsw_config.ef_construct, hnsw_config.m,
+        )));
+    }
+    Ok(())
+}
+
+fn vector_params_not_found(vector_name: &str) -> CollectionError {
+    CollectionError::BadInput {
+        description: if vector_name == DEFAULT_VECTOR_NAME {
+            "Default vector params are not specified in config".into()
+        } else {
+            format!("Vector params for {vector_name} are not specified in config")
+        },
+    }
+}
+
+/// Distance functions of all vectors of the collection.
+///
+/// Allows to look up distances of many requests without holding the config lock
+/// and without cloning vector params for each lookup.
+#[derive(Debug, Clone, Default)]
+pub struct VectorDistances(HashMap<String, Distance>);
+
+impl VectorDistances {
+    pub fn get(&self, vector_name: &str) -> CollectionResult<Distance> {
+        self.0
+            .get(vector_name)
+            .copied()
+            .ok_or_else(|| vector_params_not_found(vector_name))
     }
 }
 
@@ -158,13 +249,53 @@ impl CollectionParams {
         self.vectors
             .get_params(vector_name)
             .cloned()
-            .ok_or_else(|| CollectionError::BadInput {
-                description: if vector_name == DEFAULT_VECTOR_NAME {
-                    "Default vector params are not specified in config".into()
-                } else {
-                    format!("Vector params for {vector_name} are not specified in config")
-                },
-            })
+            .ok_or_else(|| vector_params_not_found(vector_name))
+    }
+
+    pub fn vector_distances(&self) -> VectorDistances {
+        VectorDistances(
+            self.vectors
+                .params_iter()
+                .map(|(name, params)| (name.to_string(), params.distance))
+                .collect(),
+        )
+    }
+
+    /// Check that all given vector names are present in config, reporting all unknown names at once
+    pub fn check_vector_names_exist<'a>(
+        &self,
+        vector_names: impl IntoIterator<Item = &'a str>,
+    ) -> CollectionResult<()> {
+        fn display_name(name: &str) -> &str {
+            if name == DEFAULT_VECTOR_NAME {
+                "default"
+            } else {
+                name
+            }
+        }
+
+        let mut unknown_names: Vec<&str> = vec![];
+        for vector_name in vector_names {
+            if self.vectors.get_params(vector_name).is_none()
+                && !unknown_names.contains(&display_name(vector_name))
+            {
+                unknown_names.push(display_name(vector_name));
+            }
+        }
+        if unknown_names.is_empty() {
+            return Ok(());
+        }
+
+        let available_names: Vec<&str> = self
+            .vectors
+            .params_iter()
+            .map(|(name, _)| display_name(name))
+            .collect();
+        Err(CollectionError::bad_input(format!(
+            "Unknown vector names: [{}]. Available: [{}]",
+            unknown_names.join(", "),
+            available_names.join(", "),
+        )))
     }
 
     fn get_vector_params_mut(&mut self, vector_name: &str) -> CollectionResult<&mut VectorParams> {
@@ -180,11 +311,33 @@ impl CollectionParams {
     }
 
     /// Update collection vectors from the given update vectors config
+    ///
+    /// New vectors can only be added to collections with named vectors.
     pub fn update_vectors_from_diff(
         &mut self,
         update_vectors_diff: &VectorsConfigDiff,
     ) -> CollectionResult<()> {
-        for (vector_name, update_params) in update_vectors_diff.0.iter() {
+        let diff_map = match update_vectors_diff {
+            VectorsConfigDiff::Add(params_map) => {
+                let VectorsConfig::Multi(vectors) = &mut self.vectors else {
+                    return Err(CollectionError::bad_input(
+                        "Vectors can only be added to collections with named vectors".to_string(),
+                    ));
+                };
+                for (vector_name, params) in params_map {
+                    if vectors.contains_key(vector_name) {
+                        return Err(CollectionError::bad_input(format!(
+                            "Vector {vector_name} already exists"
+                        )));
+                    }
+                    vectors.insert(vector_name.clone(), params.clone());
+                }
+                return Ok(());
+            }
+            VectorsConfigDiff::Update(diff_map) => diff_map,
+        };
+
+        for (vector_name, update_params) in diff_map.iter() {
             let vector_params = self.get_vector_params_mut(vector_name)?;
 
             let VectorParamsDiff {
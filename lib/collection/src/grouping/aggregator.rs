@@ -197,6 +197,8 @@ mod unit_tests {
             score,
             payload: Some(Payload::from(serde_json::json!({ "docId": payloads }))),
             vector: None,
+            score_breakdown: None,
+            search_params_echo: None,
         }
     }
 
@@ -207,6 +209,8 @@ mod unit_tests {
             score,
             payload: None,
             vector: None,
+            score_breakdown: None,
+            search_params_echo: None,
         }
     }
 
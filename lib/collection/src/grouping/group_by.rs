@@ -33,7 +33,12 @@ pub enum SourceRequest {
 impl SourceRequest {
     fn vector_field_name(&self) -> &str {
         match self {
-            SourceRequest::Search(request) => request.vector.get_name(),
+            SourceRequest::Search(request) => request
+                .vector
+                .vector_names()
+                .first()
+                .copied()
+                .unwrap_or(DEFAULT_VECTOR_NAME),
             SourceRequest::Recommend(request) => {
                 if let Some(UsingVector::Name(name)) = &request.using {
                     name
@@ -199,6 +204,7 @@ impl From<SearchGroupsRequest> for GroupRequest {
             with_payload,
             with_vector,
             score_threshold,
+            ..Default::default()
         };
 
         GroupRequest {
@@ -480,6 +486,8 @@ mod tests {
                         score: 1.0,
                         payload: None,
                         vector: None,
+                        score_breakdown: None,
+                        search_params_echo: None,
                     },
                     ScoredPoint {
                         id: 2.into(),
@@ -487,6 +495,8 @@ mod tests {
                         score: 1.0,
                         payload: None,
                         vector: None,
+                        score_breakdown: None,
+                        search_params_echo: None,
                     },
                 ],
             ),
@@ -499,6 +509,8 @@ mod tests {
                         score: 1.0,
                         payload: None,
                         vector: None,
+                        score_breakdown: None,
+                        search_params_echo: None,
                     },
                     ScoredPoint {
                         id: 4.into(),
@@ -506,6 +518,8 @@ mod tests {
                         score: 1.0,
                         payload: None,
                         vector: None,
+                        score_breakdown: None,
+                        search_params_echo: None,
                     },
                 ],
             ),
@@ -529,6 +543,8 @@ mod tests {
                 score: 1.0,
                 payload: Some(payload_a.clone()),
                 vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
             },
             ScoredPoint {
                 id: 2.into(),
@@ -536,6 +552,8 @@ mod tests {
                 score: 1.0,
                 payload: Some(payload_a.clone()),
                 vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
             },
             ScoredPoint {
                 id: 3.into(),
@@ -543,6 +561,8 @@ mod tests {
                 score: 1.0,
                 payload: Some(payload_b.clone()),
                 vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
             },
             ScoredPoint {
                 id: 4.into(),
@@ -550,6 +570,8 @@ mod tests {
                 score: 1.0,
                 payload: Some(payload_b.clone()),
                 vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
             },
         ];
 
@@ -1,4 +1,17 @@
-use std::hash::Hash;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+use siphasher::sip::SipHasher;
+
+/// Hasher used by `hashring` to place nodes and keys on the ring
+type RingHashBuilder = BuildHasherDefault<SipHasher>;
+
+/// Position of the value on the ring, same as computed by `hashring`
+fn ring_position<U: Hash>(value: &U) -> u64 {
+    let mut hasher = RingHashBuilder::default().build_hasher();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
 
 pub enum HashRing<T: Hash + Copy> {
     Raw(hashring::HashRing<T>),
@@ -55,4 +68,88 @@ impl<T: Hash + Copy> HashRing<T> {
             HashRing::Fair { ring, .. } => ring.get(key).map(|(shard, _)| shard),
         }
     }
+
+    /// Shard which should hold the data of the given tenant
+    pub fn shard_for_tenant(&self, tenant_id: &str) -> Option<T> {
+        self.get(&tenant_id).copied()
+    }
+
+    /// Ranges of ring positions assigned to each of the given `shards`, as inclusive `(start, end)` pairs.
+    ///
+    /// A key is assigned to the first node at or after its position, wrapping around the ring.
+    /// `shards` must be the shards added to the ring.
+    pub fn ranges(&self, shards: impl IntoIterator<Item = T>) -> HashMap<T, Vec<(u64, u64)>>
+    where
+        T: Eq,
+    {
+        let mut nodes: Vec<(u64, T)> = match self {
+            HashRing::Raw(_) => shards
+                .into_iter()
+                .map(|shard| (ring_position(&shard), shard))
+                .collect(),
+            HashRing::Fair { scale, .. } => shards
+                .into_iter()
+                .flat_map(|shard| (0..*scale).map(move |i| (ring_position(&(shard, i)), shard)))
+                .collect(),
+        };
+        nodes.sort_unstable_by_key(|(position, _)| *position);
+
+        let mut ranges: HashMap<T, Vec<(u64, u64)>> = HashMap::new();
+        let mut start = 0;
+        for &(position, shard) in &nodes {
+            if start <= position {
+                ranges.entry(shard).or_default().push((start, position));
+            }
+            start = position.saturating_add(1);
+        }
+
+        // Positions after the last node wrap around to the first one
+        if let (Some(&(_, first_shard)), Some(&(last_position, _))) = (nodes.first(), nodes.last())
+        {
+            if last_position < u64::MAX {
+                ranges
+                    .entry(first_shard)
+                    .or_default()
+                    .push((last_position + 1, u64::MAX));
+            }
+        }
+
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges_match_ring_assignment() {
+        for mut ring in [HashRing::raw(), HashRing::fair(10)] {
+            for shard in 0..5u32 {
+                ring.add(shard);
+            }
+            let ranges = ring.ranges(0..5);
+            assert_eq!(ranges.len(), 5);
+
+            let covered: u128 = ranges
+                .values()
+                .flatten()
+                .map(|(start, end)| (end - start) as u128 + 1)
+                .sum();
+            assert_eq!(covered, u64::MAX as u128 + 1, "ranges cover the whole ring");
+
+            for key in 0..1000u64 {
+                let position = ring_position(&key);
+                let shard = ranges
+                    .iter()
+                    .find(|(_, ranges)| {
+                        ranges
+                            .iter()
+                            .any(|(start, end)| (*start..=*end).contains(&position))
+                    })
+                    .map(|(shard, _)| shard);
+                assert_eq!(shard, ring.get(&key));
+            }
+        }
+    }
 }
@@ -65,6 +65,7 @@ where
         ids,
         with_payload: request.with_payload,
         with_vector: request.with_vectors.unwrap_or_default(),
+        strict: false,
     };
 
     let result = collection
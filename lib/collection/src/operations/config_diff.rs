@@ -105,6 +105,10 @@ pub struct CollectionParamsDiff {
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default)]
     pub on_disk_payload: Option<bool>,
+    /// If true - updates from clients are rejected, e.g. for archived collections.
+    /// Reads, like search, scroll and retrieval, are not affected.
+    #[serde(default)]
+    pub read_only: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone, Merge)]
@@ -343,12 +347,15 @@ mod tests {
             replication_factor: NonZeroU32::new(1).unwrap(),
             write_consistency_factor: NonZeroU32::new(1).unwrap(),
             on_disk_payload: false,
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         let diff = CollectionParamsDiff {
             replication_factor: None,
             write_consistency_factor: Some(NonZeroU32::new(2).unwrap()),
             on_disk_payload: None,
+            read_only: Some(true),
         };
 
         let new_params = diff.update(&params).unwrap();
@@ -356,6 +363,7 @@ mod tests {
         assert_eq!(new_params.replication_factor.get(), 1);
         assert_eq!(new_params.write_consistency_factor.get(), 2);
         assert!(!new_params.on_disk_payload);
+        assert!(new_params.read_only);
     }
 
     #[test]
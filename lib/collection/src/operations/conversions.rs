@@ -27,14 +27,16 @@ use crate::operations::config_diff::{
     CollectionParamsDiff, HnswConfigDiff, OptimizersConfigDiff, QuantizationConfigDiff,
     WalConfigDiff,
 };
+use crate::operations::consistency_params::ReadConsistency;
 use crate::operations::point_ops::PointsSelector::PointIdsSelector;
 use crate::operations::point_ops::{
     Batch, FilterSelector, PointIdsList, PointStruct, PointsSelector, WriteOrdering,
 };
 use crate::operations::types::{
-    AliasDescription, CollectionClusterInfo, CollectionInfo, CollectionStatus, CountResult,
-    LocalShardInfo, LookupLocation, OptimizersStatus, RecommendRequest, Record, RemoteShardInfo,
-    SearchRequest, ShardTransferInfo, UpdateResult, UpdateStatus, VectorParams, VectorsConfig,
+    default_shards_written, AliasDescription, CollectionClusterInfo, CollectionError,
+    CollectionInfo, CollectionStatus, CountResult, LocalShardInfo, LookupLocation,
+    OptimizersStatus, RecommendRequest, Record, RemoteShardInfo, SearchDryRunResult, SearchRequest,
+    ShardTransferInfo, TiebreakerMode, UpdateResult, UpdateStatus, VectorParams, VectorsConfig,
 };
 use crate::optimizers_builder::OptimizersConfig;
 use crate::shards::remote_shard::CollectionSearchRequest;
@@ -74,6 +76,31 @@ pub fn write_ordering_from_proto(
     })
 }
 
+pub fn tiebreaker_to_proto(tiebreaker: TiebreakerMode) -> i32 {
+    match tiebreaker {
+        TiebreakerMode::IdAscending => api::grpc::qdrant::TiebreakerMode::IdAscending as i32,
+        TiebreakerMode::IdDescending => api::grpc::qdrant::TiebreakerMode::IdDescending as i32,
+    }
+}
+
+pub fn tiebreaker_from_proto(tiebreaker: Option<i32>) -> Result<Option<TiebreakerMode>, Status> {
+    let Some(tiebreaker) = tiebreaker else {
+        return Ok(None);
+    };
+
+    match api::grpc::qdrant::TiebreakerMode::from_i32(tiebreaker) {
+        None => Err(Status::invalid_argument(format!(
+            "cannot convert tiebreaker: {tiebreaker}"
+        ))),
+        Some(api::grpc::qdrant::TiebreakerMode::IdAscending) => {
+            Ok(Some(TiebreakerMode::IdAscending))
+        }
+        Some(api::grpc::qdrant::TiebreakerMode::IdDescending) => {
+            Ok(Some(TiebreakerMode::IdDescending))
+        }
+    }
+}
+
 pub fn try_record_from_grpc(
     point: api::grpc::qdrant::RetrievedPoint,
     with_payload: bool,
@@ -99,6 +126,7 @@ pub fn try_record_from_grpc(
         id,
         payload,
         vector,
+        shard_id: None,
     })
 }
 
@@ -158,6 +186,7 @@ impl TryFrom<api::grpc::qdrant::CollectionParamsDiff> for CollectionParamsDiff {
                 })
                 .transpose()?,
             on_disk_payload: value.on_disk_payload,
+            read_only: value.read_only,
         })
     }
 }
@@ -206,6 +235,10 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
             segments_count,
             config,
             payload_schema,
+            disk_data_size_bytes,
+            degraded_shards,
+            partial_result,
+            unavailable_shards,
         } = value;
 
         api::grpc::qdrant::CollectionInfo {
@@ -256,6 +289,7 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                     replication_factor: Some(config.params.replication_factor.get()),
                     on_disk_payload: config.params.on_disk_payload,
                     write_consistency_factor: Some(config.params.write_consistency_factor.get()),
+                    read_only: Some(config.params.read_only),
                 }),
                 hnsw_config: Some(api::grpc::qdrant::HnswConfigDiff {
                     m: Some(config.hnsw_config.m as u64),
@@ -294,6 +328,10 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
+            disk_data_size_bytes,
+            degraded_shards,
+            partial_result,
+            unavailable_shards,
         }
     }
 }
@@ -387,7 +425,14 @@ impl TryFrom<api::grpc::qdrant::vectors_config_diff::Config> for VectorsConfigDi
                 for (name, params) in vectors_params.map {
                     params_map.insert(name, params.try_into()?);
                 }
-                VectorsConfigDiff(params_map)
+                VectorsConfigDiff::Update(params_map)
+            }
+            api::grpc::qdrant::vectors_config_diff::Config::Add(vectors_params) => {
+                let mut params_map = BTreeMap::new();
+                for (name, params) in vectors_params.map {
+                    params_map.insert(name, params.try_into()?);
+                }
+                VectorsConfigDiff::Add(params_map)
             }
         })
     }
@@ -499,6 +544,8 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                     .ok_or_else(|| {
                         Status::invalid_argument("`write_consistency_factor` cannot be zero")
                     })?,
+                    max_payload_size_bytes: None,
+                    read_only: params.read_only.unwrap_or_default(),
                 },
             },
             hnsw_config: match config.hnsw_config {
@@ -561,6 +608,10 @@ impl TryFrom<api::grpc::qdrant::GetCollectionInfoResponse> for CollectionInfo {
                     .into_iter()
                     .map(|(k, v)| v.try_into().map(|v| (k, v)))
                     .try_collect()?,
+                disk_data_size_bytes: collection_info_response.disk_data_size_bytes,
+                degraded_shards: collection_info_response.degraded_shards,
+                partial_result: collection_info_response.partial_result,
+                unavailable_shards: collection_info_response.unavailable_shards,
             }),
         }
     }
@@ -676,6 +727,7 @@ impl From<UpdateResult> for api::grpc::qdrant::UpdateResult {
                 UpdateStatus::Acknowledged => api::grpc::qdrant::UpdateStatus::Acknowledged as i32,
                 UpdateStatus::Completed => api::grpc::qdrant::UpdateStatus::Completed as i32,
             },
+            shards_written: Some(value.shards_written),
         }
     }
 }
@@ -695,6 +747,7 @@ impl TryFrom<api::grpc::qdrant::UpdateResult> for UpdateResult {
                 }
                 _ => return Err(Status::invalid_argument("Malformed UpdateStatus type")),
             },
+            shards_written: value.shards_written.unwrap_or_else(default_shards_written),
         })
     }
 }
@@ -707,6 +760,20 @@ impl From<api::grpc::qdrant::CountResult> for CountResult {
     }
 }
 
+impl From<SearchDryRunResult> for api::grpc::qdrant::SearchDryRunResult {
+    fn from(value: SearchDryRunResult) -> Self {
+        Self {
+            shards_touched: value.shards_touched as u64,
+            estimated_candidates_per_shard: value
+                .estimated_candidates_per_shard
+                .into_iter()
+                .map(|candidates| candidates as u64)
+                .collect(),
+            estimated_payload_bytes: value.estimated_payload_bytes,
+        }
+    }
+}
+
 impl From<CountResult> for api::grpc::qdrant::CountResult {
     fn from(value: CountResult) -> Self {
         Self {
@@ -716,26 +783,35 @@ impl From<CountResult> for api::grpc::qdrant::CountResult {
 }
 
 // Use wrapper type to bundle CollectionId & SearchRequest
-impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
-    fn from(value: CollectionSearchRequest<'a>) -> Self {
+impl<'a> TryFrom<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
+    type Error = CollectionError;
+
+    /// Ensemble searches must be split with `SearchRequestBatch::split_ensembles` first
+    fn try_from(value: CollectionSearchRequest<'a>) -> Result<Self, Self::Error> {
         let (collection_id, request) = value.0;
+        let vector = request.vector.as_single().ok_or_else(|| {
+            CollectionError::bad_request(
+                "Ensemble search must be split into single vector searches".to_string(),
+            )
+        })?;
 
-        Self {
+        Ok(Self {
             collection_name: collection_id,
-            vector: request.vector.get_vector().to_vec(),
+            vector: vector.get_vector().to_vec(),
             filter: request.filter.clone().map(|f| f.into()),
             limit: request.limit as u64,
             with_vectors: request.with_vector.clone().map(|wv| wv.into()),
             with_payload: request.with_payload.clone().map(|wp| wp.into()),
-            params: request.params.map(|sp| sp.into()),
+            params: request.search_params().map(|sp| sp.into()),
             score_threshold: request.score_threshold,
             offset: Some(request.offset as u64),
-            vector_name: match request.vector.get_name() {
+            vector_name: match vector.get_name() {
                 DEFAULT_VECTOR_NAME => None,
                 vector_name => Some(vector_name.to_string()),
             },
             read_consistency: None,
-        }
+            tiebreaker: request.tiebreaker.map(tiebreaker_to_proto),
+        })
     }
 }
 
@@ -790,6 +866,9 @@ impl TryFrom<api::grpc::qdrant::SearchPoints> for SearchRequest {
                     .unwrap_or_default(),
             ),
             score_threshold: value.score_threshold,
+            read_consistency: ReadConsistency::try_from_optional(value.read_consistency)?,
+            tiebreaker: tiebreaker_from_proto(value.tiebreaker)?,
+            ..Default::default()
         })
     }
 }
@@ -810,6 +889,7 @@ impl TryFrom<api::grpc::qdrant::SearchPointGroups> for SearchGroupsRequest {
             offset: None,
             collection_name: String::new(),
             read_consistency: None,
+            tiebreaker: None,
         };
 
         let SearchRequest {
@@ -821,6 +901,24 @@ impl TryFrom<api::grpc::qdrant::SearchPointGroups> for SearchGroupsRequest {
             with_payload,
             with_vector,
             score_threshold,
+            tiebreaker: _,
+            read_consistency: _,
+            shard_filter: _,
+            tenant_id: _,
+            quantization: _,
+            negative: _,
+            alpha: _,
+            hnsw_ef: _,
+            lookup_from: _,
+            echo_params: _,
+            include_fields: _,
+            exclude_fields: _,
+            estimate_segment_reads: _,
+            return_quality_hint: _,
+            context_pairs: _,
+            normalize_vector: _,
+            fusion: _,
+            rrf_k: _,
         } = search_points.try_into()?;
 
         Ok(SearchGroupsRequest {
@@ -13,11 +13,13 @@ pub mod vector_ops;
 
 use std::collections::HashMap;
 
-use segment::types::{ExtendedPointId, PayloadFieldSchema};
+use segment::types::{ExtendedPointId, Payload, PayloadFieldSchema};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::hash_ring::HashRing;
+use crate::operations::payload_ops::PayloadOps;
+use crate::operations::point_ops::{PointInsertOperations, PointOperations};
 use crate::shards::shard::ShardId;
 
 #[derive(Debug, Deserialize, Serialize, Validate, Default, Clone)]
@@ -176,6 +178,49 @@ impl CollectionUpdateOperations {
             }
         }
     }
+
+    /// Serialized size of the payload assigned to each point by the operation.
+    ///
+    /// Only includes inserted points and points explicitly listed in payload updates.
+    /// Points selected by a filter are not known before the operation is applied, and
+    /// `SetPayload` is measured without the existing payload it is merged into.
+    pub fn payload_sizes(&self) -> Vec<(ExtendedPointId, usize)> {
+        fn payload_size(payload: &Payload) -> usize {
+            serde_json::to_vec(payload).map_or(0, |bytes| bytes.len())
+        }
+
+        match self {
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsBatch(batch),
+            )) => match &batch.payloads {
+                Some(payloads) => batch
+                    .ids
+                    .iter()
+                    .zip(payloads)
+                    .filter_map(|(id, payload)| Some((*id, payload_size(payload.as_ref()?))))
+                    .collect(),
+                None => vec![],
+            },
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsList(points),
+            )) => points
+                .iter()
+                .filter_map(|point| Some((point.id, payload_size(point.payload.as_ref()?))))
+                .collect(),
+            CollectionUpdateOperations::PayloadOperation(
+                PayloadOps::SetPayload(set_payload) | PayloadOps::OverwritePayload(set_payload),
+            ) => {
+                let size = payload_size(&set_payload.payload);
+                set_payload
+                    .points
+                    .iter()
+                    .flatten()
+                    .map(|id| (*id, size))
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -7,6 +7,15 @@ use crate::operations::types::NodeType;
 const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(60);
 const DEFAULT_UPDATE_QUEUE_SIZE: usize = 100;
 const DEFAULT_UPDATE_QUEUE_SIZE_LISTENER: usize = 10_000;
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(300);
+pub const DEFAULT_MAX_CONCURRENT_SNAPSHOTS: usize = 2;
+pub const DEFAULT_SHARD_INFO_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_STALE_REPLICA_TIMEOUT: Duration = Duration::from_secs(600);
+pub const DEFAULT_SNAPSHOT_MEMORY_WARNING_THRESHOLD_BYTES: u64 = 512 * 1024 * 1024;
+pub const DEFAULT_SHARD_TRANSFER_INIT_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_MIN_INDEXING_THRESHOLD_KB: usize = 1000;
+pub const DEFAULT_PARALLEL_MERGE_THRESHOLD: usize = 32;
+pub const DEFAULT_POINTS_COUNT_CACHE_TTL: Duration = Duration::from_secs(1);
 
 /// Storage configuration shared between all collections.
 /// Represents a per-node configuration, which might be changes with restart.
@@ -18,6 +27,28 @@ pub struct SharedStorageConfig {
     pub handle_collection_load_errors: bool,
     pub recovery_mode: Option<String>,
     pub search_timeout: Duration,
+    /// Serialize concurrent client updates of the same point ID
+    pub serialized_point_updates: bool,
+    /// How long results of updates with idempotency keys are remembered
+    pub idempotency_ttl: Duration,
+    /// Max number of snapshots of a single collection, which can be created at the same time
+    pub max_concurrent_snapshots: usize,
+    /// How long to wait for the information of a single shard, e.g. while it is initializing
+    pub shard_info_timeout: Duration,
+    /// Active local replica, which has pending updates but didn't apply any of them for this long,
+    /// is considered stuck and marked as dead. Zero disables the check.
+    pub stale_replica_timeout: Duration,
+    /// Warn if memory usage grows by more than this while archiving a snapshot. Zero disables the warning.
+    pub snapshot_memory_warning_threshold_bytes: u64,
+    /// Max time to wait for consensus to register a shard transfer to this peer, in seconds
+    pub shard_transfer_init_timeout_secs: u64,
+    /// Lowest non-zero indexing threshold, which can be set by updating collection optimizers, in kilobytes
+    pub min_indexing_threshold: usize,
+    /// Search batches with more searches than this merge results of shards in parallel
+    pub parallel_merge_threshold: usize,
+    /// How long approximate unfiltered point count of a collection is served from cache.
+    /// Zero disables the cache.
+    pub points_count_cache_ttl: Duration,
 }
 
 impl Default for SharedStorageConfig {
@@ -28,6 +59,17 @@ impl Default for SharedStorageConfig {
             handle_collection_load_errors: false,
             recovery_mode: None,
             search_timeout: DEFAULT_SEARCH_TIMEOUT,
+            serialized_point_updates: false,
+            idempotency_ttl: DEFAULT_IDEMPOTENCY_TTL,
+            max_concurrent_snapshots: DEFAULT_MAX_CONCURRENT_SNAPSHOTS,
+            shard_info_timeout: DEFAULT_SHARD_INFO_TIMEOUT,
+            stale_replica_timeout: DEFAULT_STALE_REPLICA_TIMEOUT,
+            snapshot_memory_warning_threshold_bytes:
+                DEFAULT_SNAPSHOT_MEMORY_WARNING_THRESHOLD_BYTES,
+            shard_transfer_init_timeout_secs: DEFAULT_SHARD_TRANSFER_INIT_TIMEOUT_SECS,
+            min_indexing_threshold: DEFAULT_MIN_INDEXING_THRESHOLD_KB,
+            parallel_merge_threshold: DEFAULT_PARALLEL_MERGE_THRESHOLD,
+            points_count_cache_ttl: DEFAULT_POINTS_COUNT_CACHE_TTL,
         }
     }
 }
@@ -39,6 +81,16 @@ impl SharedStorageConfig {
         handle_collection_load_errors: bool,
         recovery_mode: Option<String>,
         search_timeout: Option<Duration>,
+        serialized_point_updates: bool,
+        idempotency_ttl: Duration,
+        max_concurrent_snapshots: usize,
+        shard_info_timeout: Duration,
+        stale_replica_timeout: Duration,
+        snapshot_memory_warning_threshold_bytes: u64,
+        shard_transfer_init_timeout_secs: u64,
+        min_indexing_threshold: usize,
+        parallel_merge_threshold: usize,
+        points_count_cache_ttl: Duration,
     ) -> Self {
         let update_queue_size = update_queue_size.unwrap_or(match node_type {
             NodeType::Normal => DEFAULT_UPDATE_QUEUE_SIZE,
@@ -51,6 +103,16 @@ impl SharedStorageConfig {
             handle_collection_load_errors,
             recovery_mode,
             search_timeout: search_timeout.unwrap_or(DEFAULT_SEARCH_TIMEOUT),
+            serialized_point_updates,
+            idempotency_ttl,
+            max_concurrent_snapshots,
+            shard_info_timeout,
+            stale_replica_timeout,
+            snapshot_memory_warning_threshold_bytes,
+            shard_transfer_init_timeout_secs,
+            min_indexing_threshold,
+            parallel_merge_threshold,
+            points_count_cache_ttl,
         }
     }
 }
@@ -1,14 +1,22 @@
+use std::collections::HashMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use api::grpc::conversions::date_time_to_proto;
+use atomicwrites::AtomicFile;
+use atomicwrites::OverwriteBehavior::AllowOverwrite;
 use chrono::NaiveDateTime;
+use parking_lot::Mutex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tar::Builder as TarBuilder;
 use url::Url;
 use validator::Validate;
 
-use crate::operations::types::CollectionResult;
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::shards::shard::ShardId;
+use crate::shards::CollectionId;
 
 /// Defines source of truth for snapshot recovery:
 /// `NoSync` means - restore snapshot without *any* additional synchronization.
@@ -23,6 +31,19 @@ pub enum SnapshotPriority {
     Replica,
 }
 
+/// Defines how local shards are recovered from a snapshot:
+/// `Blocking` means - replace the local shard in place, the replica is unavailable until recovery is finished.
+/// `Online` means - route reads and updates to other active replicas, while the local replica is recovered,
+/// updates missed during recovery are synchronized from an active replica afterwards.
+/// Requires another active replica of each recovered shard and `Replica` priority.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryMode {
+    #[default]
+    Blocking,
+    Online,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone)]
 pub struct SnapshotRecover {
     /// Examples:
@@ -35,6 +56,16 @@ pub struct SnapshotRecover {
     /// If set to `Replica`, the current state will be used as a source of truth, and after recovery if will be synchronized with the snapshot.
     #[serde(default)]
     pub priority: Option<SnapshotPriority>,
+
+    /// Allow recovering a snapshot created from another collection.
+    /// By default, recovery fails if the snapshot belongs to a different collection.
+    #[serde(default)]
+    pub force: bool,
+
+    /// Defines how local shards are recovered, see `RecoveryMode`.
+    /// Default is `Blocking`.
+    #[serde(default)]
+    pub recovery_mode: Option<RecoveryMode>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -42,6 +73,27 @@ pub struct SnapshotDescription {
     pub name: String,
     pub creation_time: Option<NaiveDateTime>,
     pub size: u64,
+    /// BLAKE3 hash of the snapshot content, if known
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Peak resident memory of the process while archiving the snapshot.
+    /// Only reported on snapshot creation, `0` if unknown.
+    #[serde(default)]
+    pub peak_memory_bytes_during_archiving: u64,
+    /// Version of Qdrant which created the snapshot, if known.
+    /// Only reported for shard snapshots.
+    #[serde(default)]
+    pub qdrant_version: Option<String>,
+}
+
+/// Single page of the collection snapshots list, ordered by creation time
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct SnapshotListPage {
+    pub snapshots: Vec<SnapshotDescription>,
+    /// Total number of snapshots in the collection
+    pub total: usize,
+    /// Offset of the next page, if there are more snapshots
+    pub next_offset: Option<usize>,
 }
 
 impl From<SnapshotDescription> for api::grpc::qdrant::SnapshotDescription {
@@ -54,6 +106,304 @@ impl From<SnapshotDescription> for api::grpc::qdrant::SnapshotDescription {
     }
 }
 
+pub const SNAPSHOT_MANIFEST_FILE: &str = "collection.json";
+
+/// File in the snapshots directory, which maps content hashes to snapshot names
+pub const SNAPSHOT_HASHES_FILE: &str = "snapshot_hashes.json";
+
+/// Symlink in the snapshots directory, which points to the most recently created snapshot
+pub const LATEST_SNAPSHOT_LINK: &str = "latest.snapshot";
+
+/// Metadata of the collection snapshot, stored in the root of the snapshot archive
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SnapshotManifest {
+    /// Name of the collection the snapshot was created from
+    pub collection_id: CollectionId,
+    /// BLAKE3 hash of the archived content, excluding the manifest itself
+    #[serde(default)]
+    pub content_hash: Option<String>,
+}
+
+impl SnapshotManifest {
+    /// Read manifest from the collection snapshot archive without unpacking it.
+    /// Returns `None` if the snapshot was created without a manifest.
+    ///
+    /// This method performs blocking IO.
+    pub fn read_from_archive(snapshot_path: &Path) -> CollectionResult<Option<Self>> {
+        let mut archive = tar::Archive::new(std::fs::File::open(snapshot_path)?);
+        // Manifest is the last entry, seek over the content of other entries
+        for entry in archive.entries_with_seek()? {
+            let entry = entry?;
+            if entry.path()? == Path::new(SNAPSHOT_MANIFEST_FILE) {
+                return Ok(Some(serde_json::from_reader(entry)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// File in the root of the shard snapshot archive, which describes the snapshot
+pub const SHARD_SNAPSHOT_MANIFEST_FILE: &str = "shard_snapshot_manifest.json";
+
+/// Metadata of the shard snapshot, stored as the first entry of the snapshot archive,
+/// so it can be read without unpacking the whole snapshot
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ShardSnapshotManifest {
+    pub created_at: NaiveDateTime,
+    /// Version of Qdrant which created the snapshot
+    pub qdrant_version: String,
+    pub shard_id: ShardId,
+    pub collection_id: CollectionId,
+}
+
+impl ShardSnapshotManifest {
+    /// Append manifest to the archive. Must be called before any other entry is appended.
+    pub(crate) fn append_to<W: Write>(&self, builder: &mut TarBuilder<W>) -> CollectionResult<()> {
+        let manifest_bytes = serde_json::to_vec(self)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest_bytes.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(
+            &mut header,
+            SHARD_SNAPSHOT_MANIFEST_FILE,
+            manifest_bytes.as_slice(),
+        )?;
+        Ok(())
+    }
+
+    /// Read manifest from the shard snapshot archive.
+    /// Returns `None` if the snapshot was created without a manifest.
+    ///
+    /// This method performs blocking IO.
+    pub fn read_from_archive(snapshot_path: &Path) -> CollectionResult<Option<Self>> {
+        let mut archive = tar::Archive::new(std::fs::File::open(snapshot_path)?);
+        let Some(entry) = archive.entries()?.next() else {
+            return Ok(None);
+        };
+        let entry = entry?;
+        if entry.path()? != Path::new(SHARD_SNAPSHOT_MANIFEST_FILE) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_reader(entry)?))
+    }
+}
+
+/// Archive content of `snapshot_dir` into `writer` and append the manifest as the last entry.
+///
+/// Archive headers do not include timestamps and ownership, so identical content always produces
+/// the same hash. Returns the content hash along with the writer.
+pub(crate) fn archive_snapshot_dir<W: Write>(
+    writer: W,
+    snapshot_dir: &Path,
+    mut manifest: SnapshotManifest,
+) -> CollectionResult<(String, W)> {
+    let mut builder = TarBuilder::new(HashingWriter {
+        inner: writer,
+        hasher: Some(blake3::Hasher::new()),
+    });
+    builder.mode(tar::HeaderMode::Deterministic);
+    builder.append_dir_all(".", snapshot_dir)?;
+
+    let content_hash = builder.get_mut().take_hash();
+    manifest.content_hash = Some(content_hash.clone());
+    let manifest_bytes = serde_json::to_vec(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_bytes.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(
+        &mut header,
+        SNAPSHOT_MANIFEST_FILE,
+        manifest_bytes.as_slice(),
+    )?;
+
+    let writer = builder.into_inner()?;
+    Ok((content_hash, writer.inner))
+}
+
+/// Writer wrapper which hashes all written data until the hash is taken
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Option<blake3::Hasher>,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn take_hash(&mut self) -> String {
+        let hasher = self.hasher.take().unwrap_or_default();
+        hasher.finalize().to_hex().to_string()
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Mapping of snapshot content hashes to snapshot names, stored in the snapshots directory
+pub struct SnapshotHashes {
+    snapshots_path: PathBuf,
+    /// Serializes read-modify-write of the hashes file
+    update_lock: Mutex<()>,
+}
+
+impl SnapshotHashes {
+    pub fn new(snapshots_path: &Path) -> Self {
+        Self {
+            snapshots_path: snapshots_path.to_path_buf(),
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Load mapping of snapshot content hashes to snapshot names
+    pub fn load(&self) -> CollectionResult<HashMap<String, String>> {
+        let hashes_path = self.snapshots_path.join(SNAPSHOT_HASHES_FILE);
+        if !hashes_path.exists() {
+            return Ok(HashMap::new());
+        }
+        let contents = std::fs::read(hashes_path)?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+
+    /// Store content hash of the snapshot.
+    /// Entries of snapshots which no longer exist are dropped.
+    pub fn insert(&self, content_hash: String, snapshot_name: String) -> CollectionResult<()> {
+        let _update_guard = self.update_lock.lock();
+
+        let mut hashes = self.load()?;
+        hashes.insert(content_hash, snapshot_name);
+        hashes.retain(|_, name| self.snapshots_path.join(name).exists());
+
+        let hashes_path = self.snapshots_path.join(SNAPSHOT_HASHES_FILE);
+        let af = AtomicFile::new(&hashes_path, AllowOverwrite);
+        let hashes_bytes = serde_json::to_vec(&hashes)?;
+        af.write(|f| f.write_all(&hashes_bytes)).map_err(|err| {
+            CollectionError::service_error(format!("Can't write {hashes_path:?}, error: {err}"))
+        })?;
+        Ok(())
+    }
+}
+
+/// Point the `latest.snapshot` symlink in the snapshots directory to the given snapshot.
+///
+/// The link is replaced atomically: a new link is created under a temporary name
+/// and renamed over the existing one.
+/// Symlinks are only supported on Unix, on other platforms the link is not created.
+pub fn update_latest_snapshot_link(
+    snapshots_path: &Path,
+    snapshot_name: &str,
+) -> CollectionResult<()> {
+    #[cfg(unix)]
+    {
+        let link_path = snapshots_path.join(LATEST_SNAPSHOT_LINK);
+        let link_path_tmp = link_path.with_extension("snapshot.tmp");
+        // Leftover of an interrupted update
+        if link_path_tmp.symlink_metadata().is_ok() {
+            std::fs::remove_file(&link_path_tmp)?;
+        }
+        // Relative target, so the link survives moving the snapshots directory
+        std::os::unix::fs::symlink(snapshot_name, &link_path_tmp)?;
+        std::fs::rename(&link_path_tmp, &link_path)?;
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (snapshots_path, snapshot_name);
+    }
+    Ok(())
+}
+
+/// Stage of the collection snapshot creation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotPhase {
+    /// Shards are being copied into the temporary snapshot directory
+    CapturingShards,
+    /// Temporary snapshot directory is being packed into a single archive
+    Archiving,
+    /// Archive is being moved into the snapshots directory
+    Moving,
+    /// Snapshot is created
+    Complete,
+}
+
+/// Progress of the collection snapshot creation, reported through [`SnapshotProgressCallback`]
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotProgress {
+    pub phase: SnapshotPhase,
+    /// Number of bytes written into the archive so far
+    pub bytes_archived: u64,
+    /// Estimated size of the archive. Is `0` until the size of captured data is known.
+    pub total_bytes_estimate: u64,
+}
+
+pub type SnapshotProgressCallback = Box<dyn Fn(SnapshotProgress) + Send + 'static>;
+
+/// Minimal amount of bytes written between two `Archiving` progress reports
+const ARCHIVING_REPORT_INTERVAL_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Writer wrapper which reports archiving progress into a [`SnapshotProgressCallback`]
+pub(crate) struct ArchivingProgressWriter<'a, W: Write> {
+    inner: W,
+    callback: &'a SnapshotProgressCallback,
+    total_bytes_estimate: u64,
+    bytes_archived: u64,
+    last_reported: u64,
+}
+
+impl<'a, W: Write> ArchivingProgressWriter<'a, W> {
+    pub fn new(
+        inner: W,
+        callback: &'a SnapshotProgressCallback,
+        total_bytes_estimate: u64,
+    ) -> Self {
+        Self {
+            inner,
+            callback,
+            total_bytes_estimate,
+            bytes_archived: 0,
+            last_reported: 0,
+        }
+    }
+
+    fn report(&mut self) {
+        self.last_reported = self.bytes_archived;
+        (self.callback)(SnapshotProgress {
+            phase: SnapshotPhase::Archiving,
+            bytes_archived: self.bytes_archived,
+            total_bytes_estimate: self.total_bytes_estimate,
+        });
+    }
+
+    /// Report final progress and return number of archived bytes
+    pub fn finish(mut self) -> u64 {
+        if self.bytes_archived != self.last_reported {
+            self.report();
+        }
+        self.bytes_archived
+    }
+}
+
+impl<'a, W: Write> Write for ArchivingProgressWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes_archived += written as u64;
+        if self.bytes_archived - self.last_reported >= ARCHIVING_REPORT_INTERVAL_BYTES {
+            self.report();
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 pub async fn get_snapshot_description(path: &Path) -> CollectionResult<SnapshotDescription> {
     let name = path.file_name().unwrap().to_str().unwrap();
     let file_meta = tokio::fs::metadata(&path).await?;
@@ -70,6 +420,9 @@ pub async fn get_snapshot_description(path: &Path) -> CollectionResult<SnapshotD
         name: name.to_string(),
         creation_time,
         size,
+        content_hash: None,
+        peak_memory_bytes_during_archiving: 0,
+        qdrant_version: None,
     })
 }
 
@@ -82,6 +435,11 @@ pub async fn list_snapshots_in_directory(
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
 
+        // Skip `latest.snapshot`, it only points to one of the listed snapshots
+        if entry.file_type().await?.is_symlink() {
+            continue;
+        }
+
         if !path.is_dir() && path.extension().map_or(false, |ext| ext == "snapshot") {
             snapshots.push(get_snapshot_description(&path).await?);
         }
@@ -1,5 +1,5 @@
 use std::backtrace::Backtrace;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error as _;
 use std::fmt::Write as _;
 use std::iter;
@@ -9,19 +9,22 @@ use std::time::SystemTimeError;
 use api::grpc::transport_channel_pool::RequestError;
 use common::validation::validate_range_generic;
 use futures::io;
+use itertools::Itertools;
 use merge::Merge;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use segment::common::file_operations::FileStorageError;
 use segment::data_types::groups::GroupId;
 use segment::data_types::vectors::{
-    NamedVectorStruct, QueryVector, VectorElementType, VectorStruct, VectorType,
+    NamedVector, NamedVectorStruct, QueryVector, VectorElementType, VectorStruct, VectorType,
     DEFAULT_VECTOR_NAME,
 };
 use segment::entry::entry_point::OperationError;
 use segment::types::{
-    Distance, Filter, Payload, PayloadIndexInfo, PayloadKeyType, PointIdType, QuantizationConfig,
-    ScoreType, ScoredPoint, SearchParams, SeqNumberType, WithPayloadInterface, WithVector,
+    Distance, Filter, Payload, PayloadIndexInfo, PayloadKeyType, PayloadSelector,
+    PayloadSelectorExclude, PayloadSelectorInclude, PointIdType, QuantizationConfig,
+    QuantizationSearchParams, ScoreType, ScoredPoint, SearchParams, SeqNumberType,
+    WithPayloadInterface, WithVector,
 };
 use serde;
 use serde::{Deserialize, Serialize};
@@ -34,12 +37,15 @@ use tonic::codegen::http::uri::InvalidUri;
 use validator::{Validate, ValidationError, ValidationErrors};
 
 use super::config_diff;
+use crate::common::vector_ops;
 use crate::config::{CollectionConfig, CollectionParams};
 use crate::lookup::types::WithLookupInterface;
 use crate::operations::config_diff::{HnswConfigDiff, QuantizationConfigDiff};
+use crate::operations::consistency_params::ReadConsistency;
 use crate::save_on_disk;
 use crate::shards::replica_set::ReplicaState;
 use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::CollectionId;
 use crate::wal::WalError;
 
 /// Current state of the collection.
@@ -81,6 +87,9 @@ pub struct Record {
     pub payload: Option<Payload>,
     /// Vector of the point
     pub vector: Option<VectorStruct>,
+    /// Shard the point was read from, only returned if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
 }
 
 /// Current statistics and configuration of the collection
@@ -110,6 +119,20 @@ pub struct CollectionInfo {
     pub config: CollectionConfig,
     /// Types of stored payload
     pub payload_schema: HashMap<PayloadKeyType, PayloadIndexInfo>,
+    /// Size of the collection data on disk in bytes.
+    /// Snapshots are not included.
+    #[serde(default)]
+    pub disk_data_size_bytes: u64,
+    /// Shards which could not be queried for the information
+    #[serde(default)]
+    pub degraded_shards: Vec<ShardId>,
+    /// If true, information of some shards is missing from the result
+    #[serde(default)]
+    pub partial_result: bool,
+    /// Shards which did not respond in time, e.g. because they are still initializing.
+    /// These shards are not included into the statistics.
+    #[serde(default)]
+    pub unavailable_shards: Vec<ShardId>,
 }
 
 /// Current clustering distribution for the collection
@@ -125,6 +148,9 @@ pub struct CollectionClusterInfo {
     pub remote_shards: Vec<RemoteShardInfo>,
     /// Shard transfers
     pub shard_transfers: Vec<ShardTransferInfo>,
+    /// Ranges of the hash ring positions assigned to each shard, as inclusive `(start, end)` pairs.
+    /// Point is stored in the shard, whose range contains the hash of its ID.
+    pub shard_key_ranges: HashMap<ShardId, Vec<(u64, u64)>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -168,21 +194,51 @@ pub enum UpdateStatus {
     Completed,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct UpdateResult {
-    /// Sequential number of the operation
-    pub operation_id: SeqNumberType,
+    /// Sequential number of the operation.
+    /// Not reported if the update was requested without result.
+    pub operation_id: Option<SeqNumberType>,
     /// Update status
     pub status: UpdateStatus,
+    /// Number of shard replicas which acknowledged the operation, summed over the shards
+    /// it was routed to. Results of the update of a single replica report 1.
+    #[serde(default = "default_shards_written")]
+    pub shards_written: u32,
+}
+
+pub const fn default_shards_written() -> u32 {
+    1
+}
+
+impl UpdateResult {
+    /// Result of an update requested without result, reported right after the operation
+    /// is written into the WAL
+    pub fn acknowledged(shards_written: u32) -> Self {
+        Self {
+            operation_id: None,
+            status: UpdateStatus::Acknowledged,
+            shards_written,
+        }
+    }
+}
+
+/// Result of the collection config update
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateConfigResult {
+    /// Whether optimizers have to be recreated to apply the updated config
+    pub requires_optimizer_restart: bool,
+    /// Warning for the caller about actions required after the update
+    pub warning: Option<String>,
 }
 
 /// Scroll request - paginate over all points which matches given condition
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ScrollRequest {
-    /// Start ID to read points from.
-    pub offset: Option<PointIdType>,
+    /// Start ID to read points from, or a cursor returned as `next_page_key` of the previous page.
+    pub offset: Option<ScrollKey>,
     /// Page size. Default: 10
     #[validate(range(min = 1))]
     pub limit: Option<usize>,
@@ -193,6 +249,16 @@ pub struct ScrollRequest {
     /// Whether to return the point vector with the result?
     #[serde(default, alias = "with_vectors")]
     pub with_vector: WithVector,
+    /// Whether to return the id of the shard each point is stored in. Default: false
+    #[serde(default)]
+    pub with_shard_key: bool,
+    /// Scroll only points stored in this shard. If not provided - all shards.
+    #[serde(default)]
+    pub shard_key: Option<ShardId>,
+    /// Whether to return the exact number of points matching the filter in `total_count`.
+    /// Default: false
+    #[serde(default)]
+    pub with_total_count: bool,
 }
 
 impl Default for ScrollRequest {
@@ -203,10 +269,35 @@ impl Default for ScrollRequest {
             filter: None,
             with_payload: Some(WithPayloadInterface::Bool(true)),
             with_vector: WithVector::Bool(false),
+            with_shard_key: false,
+            shard_key: None,
+            with_total_count: false,
         }
     }
 }
 
+/// Position to scroll points from
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ScrollKey {
+    /// Read points starting from this ID, inclusive
+    PointId(PointIdType),
+    /// Read points after the last seen one, matching the filter of the original request.
+    /// Unlike the point ID offset, the cursor stays valid if points are deleted between pages.
+    Composite {
+        /// ID of the last point of the previous page
+        last_id: PointIdType,
+        /// Filter applied in the original request
+        filter: Filter,
+    },
+}
+
+impl From<PointIdType> for ScrollKey {
+    fn from(id: PointIdType) -> Self {
+        ScrollKey::PointId(id)
+    }
+}
+
 /// Result of the points read request
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -215,6 +306,12 @@ pub struct ScrollResult {
     pub points: Vec<Record>,
     /// Offset which should be used to retrieve a next page result
     pub next_page_offset: Option<PointIdType>,
+    /// Cursor which may be used instead of `next_page_offset` to retrieve a next page result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_page_key: Option<ScrollKey>,
+    /// Number of points matching the filter, only if requested with `with_total_count`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<usize>,
 }
 
 /// Search request.
@@ -224,7 +321,7 @@ pub struct ScrollResult {
 #[serde(rename_all = "snake_case")]
 pub struct SearchRequest {
     /// Look for vectors closest to this
-    pub vector: NamedVectorStruct,
+    pub vector: VectorQuery,
     /// Look only for points which satisfies this conditions
     pub filter: Option<Filter>,
     /// Additional search params
@@ -249,6 +346,379 @@ pub struct SearchRequest {
     /// Score of the returned result might be higher or smaller than the threshold depending on the
     /// Distance function used. E.g. for cosine similarity only higher scores will be returned.
     pub score_threshold: Option<ScoreType>,
+    /// Defines how to order points with equal scores. Default: by ascending point ID
+    #[serde(default)]
+    pub tiebreaker: Option<TiebreakerMode>,
+    /// Read consistency of this particular search.
+    /// If not specified, the read consistency of the whole request is used.
+    #[serde(default)]
+    #[validate]
+    pub read_consistency: Option<ReadConsistency>,
+    /// Limit the search to a subset of shards.
+    /// Useful if it is known in advance which shards may contain the matching points.
+    #[serde(default)]
+    pub shard_filter: Option<ShardFilter>,
+    /// Tenant whose data is searched.
+    /// If the tenant is assigned to a shard, other shards are skipped.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Quantization params of this search.
+    /// Take precedence over quantization params specified in `params`.
+    #[serde(default)]
+    #[validate]
+    pub quantization: Option<QuantizationSearchParams>,
+    /// Size of the beam of the HNSW graph traversal for this search.
+    /// Takes precedence over `hnsw_ef` specified in `params` and over `ef` of the HNSW config.
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub hnsw_ef: Option<usize>,
+    /// Look for vectors far from these examples.
+    /// Query vector is moved away from the centroid of negative examples before the search.
+    #[serde(default)]
+    pub negative: Vec<NamedVectorStruct>,
+    /// Weight of negative examples. Default: 1.0
+    #[serde(default = "default_negative_alpha")]
+    pub alpha: f32,
+    /// Use the vector of the point with this ID from another collection as the query vector.
+    /// `vector` of the request only selects which of the named vectors is looked up and searched.
+    #[serde(default)]
+    pub lookup_from: Option<(CollectionId, PointIdType)>,
+    /// Return effective search params with each found point, see `ScoredPoint::search_params_echo`.
+    /// Not forwarded to remote shards, so only points of local shards carry the params
+    #[serde(default)]
+    pub echo_params: bool,
+    /// Payload fields to return, in addition to the ones selected by `with_payload`.
+    /// If `with_payload` is not set, only these fields are returned.
+    #[serde(default)]
+    pub include_fields: Vec<PayloadKeyType>,
+    /// Payload fields to exclude from the ones selected by `with_payload`.
+    /// If `with_payload` is not set, all other fields are returned.
+    #[serde(default)]
+    pub exclude_fields: Vec<PayloadKeyType>,
+    /// Estimate which segments of local shards are read by this search,
+    /// see `Collection::search_batch_debug`
+    #[serde(default)]
+    pub estimate_segment_reads: bool,
+    /// Return the range of HNSW `ef` used by the queried shards in the search metadata,
+    /// see `SearchMeta::quality_hint`
+    #[serde(default)]
+    pub return_quality_hint: bool,
+    /// Pairs of positive and negative examples of the discovery search.
+    /// If set, the query vector is the average of positive examples minus the average of negative
+    /// ones, normalized to unit length.
+    /// `vector` of the request only selects which of the named vectors is searched.
+    #[serde(default)]
+    pub context_pairs: Vec<(PositiveExample, NegativeExample)>,
+    /// Scale the query vector to unit length before the search.
+    /// Applied after the vector is taken from `lookup_from` and negative examples are applied.
+    /// Vectors of zero length are searched as is.
+    #[serde(default)]
+    pub normalize_vector: bool,
+    /// How results of the vectors of an ensemble search are combined.
+    /// Default: weighted sum of the scores
+    #[serde(default)]
+    pub fusion: Option<Fusion>,
+    /// Constant `k` of the Reciprocal Rank Fusion, see `Fusion::Rrf`. Default: 60
+    #[serde(default)]
+    pub rrf_k: Option<u32>,
+}
+
+/// Request with an empty query vector and all options disabled.
+/// Callers are expected to set at least `vector` and `limit`.
+impl Default for SearchRequest {
+    fn default() -> Self {
+        SearchRequest {
+            vector: NamedVectorStruct::Default(vec![]).into(),
+            filter: None,
+            params: None,
+            limit: 10,
+            offset: 0,
+            with_payload: None,
+            with_vector: None,
+            score_threshold: None,
+            tiebreaker: None,
+            read_consistency: None,
+            shard_filter: None,
+            tenant_id: None,
+            quantization: None,
+            hnsw_ef: None,
+            negative: vec![],
+            alpha: default_negative_alpha(),
+            lookup_from: None,
+            echo_params: false,
+            include_fields: vec![],
+            exclude_fields: vec![],
+            estimate_segment_reads: false,
+            return_quality_hint: false,
+            context_pairs: vec![],
+            normalize_vector: false,
+            fusion: None,
+            rrf_k: None,
+        }
+    }
+}
+
+/// Example of the discovery search: ID of an existing point or a raw vector
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ContextExample {
+    PointId(PointIdType),
+    Vector(VectorType),
+}
+
+/// Example of what the search should find, see `SearchRequest::context_pairs`
+pub type PositiveExample = ContextExample;
+/// Example of what the search should avoid, see `SearchRequest::context_pairs`
+pub type NegativeExample = ContextExample;
+
+pub const fn default_negative_alpha() -> f32 {
+    1.0
+}
+
+/// Vector query of the search request
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum VectorQuery {
+    /// Look for vectors closest to a single vector
+    Single(NamedVectorStruct),
+    /// Look for points closest to several named vectors at once.
+    /// Results of the vectors are combined as selected by `SearchRequest::fusion`.
+    /// Points which are not among the best `limit + offset` points by some vector get no score from it.
+    Ensemble(Vec<WeightedVector>),
+}
+
+/// Named vector with the weight of its score in the ensemble search
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct WeightedVector {
+    /// Name of the vector to search in
+    pub name: String,
+    /// Vector to search for
+    pub vector: VectorType,
+    /// Weight of the score of this vector in the combined score
+    pub weight: f32,
+}
+
+/// Method of combining results of the vectors of an ensemble search
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Fusion {
+    /// Score of a point is the weighted sum of its scores for each of the vectors.
+    /// Only distances where larger score is better are supported.
+    #[default]
+    WeightedSum,
+    /// Reciprocal Rank Fusion: score of a point is the sum of `1 / (k + rank)` over the results
+    /// of the vectors it is found by, with ranks starting from 1.
+    /// Original scores and weights of the vectors are ignored, so any distance is supported.
+    Rrf,
+}
+
+impl VectorQuery {
+    /// Names of all vectors used by the query
+    pub fn vector_names(&self) -> Vec<&str> {
+        match self {
+            VectorQuery::Single(vector) => vec![vector.get_name()],
+            VectorQuery::Ensemble(vectors) => {
+                vectors.iter().map(|vector| vector.name.as_str()).collect()
+            }
+        }
+    }
+
+    /// Vector of the query, `None` for ensemble queries
+    pub fn as_single(&self) -> Option<&NamedVectorStruct> {
+        match self {
+            VectorQuery::Single(vector) => Some(vector),
+            VectorQuery::Ensemble(_) => None,
+        }
+    }
+}
+
+impl From<NamedVectorStruct> for VectorQuery {
+    fn from(vector: NamedVectorStruct) -> Self {
+        VectorQuery::Single(vector)
+    }
+}
+
+impl From<VectorType> for VectorQuery {
+    fn from(vector: VectorType) -> Self {
+        VectorQuery::Single(vector.into())
+    }
+}
+
+impl From<NamedVector> for VectorQuery {
+    fn from(vector: NamedVector) -> Self {
+        VectorQuery::Single(vector.into())
+    }
+}
+
+impl SearchRequest {
+    /// Search params with the request level overrides applied
+    pub fn search_params(&self) -> Option<SearchParams> {
+        if self.quantization.is_none() && self.hnsw_ef.is_none() {
+            return self.params;
+        }
+        let params = self.params.unwrap_or_default();
+        Some(SearchParams {
+            quantization: self.quantization.or(params.quantization),
+            hnsw_ef: self.hnsw_ef.or(params.hnsw_ef),
+            ..params
+        })
+    }
+
+    /// Move query vector away from negative examples and clear them.
+    ///
+    /// Query vector becomes `query + alpha * (query - centroid(negative))`.
+    pub fn apply_negative_examples(&mut self) -> CollectionResult<()> {
+        if self.negative.is_empty() {
+            return Ok(());
+        }
+
+        let Some(vector) = self.vector.as_single() else {
+            return Err(CollectionError::bad_input(
+                "Negative examples are not supported by ensemble search".to_string(),
+            ));
+        };
+        let vector_name = vector.get_name();
+        let query = vector.get_vector();
+        let mut centroid = vec![0.0; query.len()];
+        for negative in &self.negative {
+            if negative.get_name() != vector_name {
+                return Err(CollectionError::bad_input(format!(
+                    "Negative example uses vector {}, but query uses vector {vector_name}",
+                    negative.get_name(),
+                )));
+            }
+            let negative = negative.get_vector();
+            if negative.len() != query.len() {
+                return Err(CollectionError::bad_input(format!(
+                    "Negative example has dimension {}, but query has dimension {}",
+                    negative.len(),
+                    query.len(),
+                )));
+            }
+            for (sum, value) in centroid.iter_mut().zip(negative) {
+                *sum += value;
+            }
+        }
+
+        let negative_count = self.negative.len() as VectorElementType;
+        let combined: VectorType = query
+            .iter()
+            .zip(&centroid)
+            .map(|(query, sum)| query + self.alpha * (query - sum / negative_count))
+            .collect();
+
+        self.vector = match vector {
+            NamedVectorStruct::Default(_) => NamedVectorStruct::Default(combined),
+            NamedVectorStruct::Named(named) => NamedVectorStruct::Named(NamedVector {
+                name: named.name.clone(),
+                vector: combined,
+            }),
+        }
+        .into();
+        self.negative.clear();
+        Ok(())
+    }
+
+    /// Normalize the query vectors if `normalize_vector` is set, and clear the flag.
+    ///
+    /// Each vector of an ensemble query is normalized separately.
+    pub fn apply_vector_normalization(&mut self) {
+        if !self.normalize_vector {
+            return;
+        }
+        match &mut self.vector {
+            VectorQuery::Single(NamedVectorStruct::Default(vector))
+            | VectorQuery::Single(NamedVectorStruct::Named(NamedVector { vector, .. })) => {
+                vector_ops::normalize(vector);
+            }
+            VectorQuery::Ensemble(vectors) => {
+                for weighted in vectors {
+                    vector_ops::normalize(&mut weighted.vector);
+                }
+            }
+        }
+        self.normalize_vector = false;
+    }
+
+    /// Merge `include_fields` and `exclude_fields` into `with_payload` and clear them.
+    ///
+    /// Excluded fields take precedence over included ones.
+    pub fn apply_payload_fields(&mut self) {
+        if self.include_fields.is_empty() && self.exclude_fields.is_empty() {
+            return;
+        }
+
+        let include_fields = std::mem::take(&mut self.include_fields);
+        let exclude_fields = std::mem::take(&mut self.exclude_fields);
+
+        let selected_fields = match self.with_payload.take() {
+            None | Some(WithPayloadInterface::Bool(false)) if include_fields.is_empty() => None,
+            None | Some(WithPayloadInterface::Bool(false)) => Some(vec![]),
+            Some(WithPayloadInterface::Bool(true)) => None,
+            Some(WithPayloadInterface::Fields(fields))
+            | Some(WithPayloadInterface::Selector(PayloadSelector::Include(
+                PayloadSelectorInclude { include: fields },
+            ))) => Some(fields),
+            Some(WithPayloadInterface::Selector(PayloadSelector::Exclude(
+                PayloadSelectorExclude { exclude },
+            ))) => {
+                let exclude = exclude
+                    .into_iter()
+                    .filter(|field| !include_fields.contains(field))
+                    .chain(exclude_fields)
+                    .unique()
+                    .collect();
+                self.with_payload = Some(PayloadSelectorExclude::new(exclude).into());
+                return;
+            }
+        };
+
+        self.with_payload = Some(match selected_fields {
+            // All fields are selected
+            None if exclude_fields.is_empty() => WithPayloadInterface::Bool(true),
+            None => PayloadSelectorExclude::new(exclude_fields).into(),
+            Some(fields) => {
+                let include = fields
+                    .into_iter()
+                    .chain(include_fields)
+                    .filter(|field| !exclude_fields.contains(field))
+                    .unique()
+                    .collect();
+                PayloadSelectorInclude::new(include).into()
+            }
+        });
+    }
+}
+
+/// Subset of shards to query
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShardFilter {
+    /// Shards with IDs in the inclusive range `[from, to]`
+    Range { from: ShardId, to: ShardId },
+    /// Shards with IDs from the set
+    Set(HashSet<ShardId>),
+}
+
+impl ShardFilter {
+    pub fn check(&self, shard_id: ShardId) -> bool {
+        match self {
+            ShardFilter::Range { from, to } => (*from..=*to).contains(&shard_id),
+            ShardFilter::Set(shard_ids) => shard_ids.contains(&shard_id),
+        }
+    }
+}
+
+/// Order of points with equal scores in the search result
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TiebreakerMode {
+    /// Points with equal scores are ordered by ascending point ID
+    #[default]
+    IdAscending,
+    /// Points with equal scores are ordered by descending point ID
+    IdDescending,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone)]
@@ -256,6 +726,265 @@ pub struct SearchRequest {
 pub struct SearchRequestBatch {
     #[validate]
     pub searches: Vec<SearchRequest>,
+    /// Keep raw results of each shard, see `Collection::search_batch_debug`
+    #[serde(default)]
+    pub debug: bool,
+    /// Do not execute the search, only estimate its cost, see `Collection::estimate_search_cost`
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Return components of the score of each point, see `ScoredPoint::score_breakdown`
+    #[serde(default)]
+    pub explain_score: bool,
+    /// If set, shards which failed to respond are skipped, as long as at least
+    /// this many shards responded successfully
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub min_shards: Option<usize>,
+    /// If set, results are returned once this many shards responded successfully.
+    /// Results of slower shards are not included. By default, all shards are awaited.
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub wait_for_shards: Option<usize>,
+    /// If set, the whole batch is aborted after this many milliseconds.
+    /// If payloads of the results are retrieved in a separate step, which doesn't complete in time,
+    /// results without payload are returned instead, see `Collection::search_batch_with_timeout`
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Normalize scores of the results to make them comparable across collections,
+    /// see `ScoreNormalizerType`
+    #[serde(default)]
+    pub score_normalizer: Option<ScoreNormalizerType>,
+}
+
+/// Normalization of the scores of a search result.
+///
+/// Coefficients are computed from the lowest and highest score of the result merged from all shards.
+/// Normalized scores are larger for better points, regardless of the distance.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalizerType {
+    /// Keep original scores
+    #[default]
+    None,
+    /// Scale scores linearly into `[0, 1]`, the best point gets 1
+    MinMax,
+    /// Logistic function of the score, centered at the middle of the scores range
+    /// and scaled by half of the range. Scores are in `(0, 1)`.
+    Sigmoid,
+}
+
+impl SearchRequestBatch {
+    /// Split ensemble searches into single vector searches, one per vector of the ensemble.
+    ///
+    /// Each part looks for `limit + offset` best points by its vector without a score threshold,
+    /// so that the combined result can be computed from the parts.
+    /// Returns `None` if there are no ensemble searches in the batch.
+    pub fn split_ensembles(&self) -> Option<SearchRequestBatch> {
+        if !self
+            .searches
+            .iter()
+            .any(|search| matches!(search.vector, VectorQuery::Ensemble(_)))
+        {
+            return None;
+        }
+
+        let searches = self
+            .searches
+            .iter()
+            .flat_map(|search| match &search.vector {
+                VectorQuery::Single(_) => vec![search.clone()],
+                VectorQuery::Ensemble(vectors) => vectors
+                    .iter()
+                    .map(|weighted| SearchRequest {
+                        vector: NamedVector {
+                            name: weighted.name.clone(),
+                            vector: weighted.vector.clone(),
+                        }
+                        .into(),
+                        limit: search.limit + search.offset,
+                        offset: 0,
+                        score_threshold: None,
+                        ..search.clone()
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Some(SearchRequestBatch {
+            searches,
+            debug: self.debug,
+            dry_run: self.dry_run,
+            explain_score: self.explain_score,
+            min_shards: self.min_shards,
+            wait_for_shards: self.wait_for_shards,
+            timeout_ms: self.timeout_ms,
+            score_normalizer: self.score_normalizer,
+        })
+    }
+}
+
+/// Name of a vector of the collection
+pub type VectorName = String;
+
+/// Searches of the same points by several named vectors in a single request
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct MultiVectorSearchRequest {
+    /// Searches by the name of the vector the query vector is searched in
+    pub searches: Vec<(VectorName, SearchRequest)>,
+}
+
+/// Estimated cost of a search batch, computed without executing the search
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq)]
+pub struct SearchDryRunResult {
+    /// Number of shards the batch would be sent to
+    pub shards_touched: usize,
+    /// Estimated number of points matching the filters of the batch, per shard
+    pub estimated_candidates_per_shard: Vec<usize>,
+    /// Estimated size of payloads returned by the batch
+    pub estimated_payload_bytes: u64,
+}
+
+/// Number of shard searches served from and bypassing the shard search cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// Merged results of a search batch along with information about the shards involved
+#[derive(Debug, Clone, Default)]
+pub struct SearchBatchResult {
+    pub results: Vec<Vec<ScoredPoint>>,
+    pub cache_stats: CacheStats,
+    /// Number of shards, which responded successfully
+    pub shards_successful: usize,
+    /// Set if the batch timed out while retrieving payloads of the results.
+    /// Results are then returned without payloads and vectors.
+    pub timeout: Option<CollectionError>,
+    pub meta: SearchMeta,
+    /// Estimated cost of the batch, set instead of the results if `dry_run` is requested
+    pub dry_run: Option<SearchDryRunResult>,
+    /// Lowest HNSW `ef` used by the merged shards for each search, `None` if it was exact.
+    /// May be empty if no shard reported it.
+    pub hnsw_ef_used: Vec<Option<usize>>,
+}
+
+/// Information about the shards involved in a search
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq)]
+pub struct SearchMeta {
+    /// Shards whose results were merged into the search result, ordered by ID.
+    /// Shards which failed or were not awaited are not included
+    pub shards_queried: Vec<ShardId>,
+    /// Shards the search was not sent to, because none of their replicas can serve reads.
+    /// Ordered by ID
+    #[serde(default)]
+    pub shards_skipped: Vec<ShardId>,
+    /// Time each shard took to respond. Shards which failed or were not awaited are not included
+    pub latency_ms_per_shard: HashMap<ShardId, u64>,
+    /// Range of HNSW `ef` used by the shards whose results were merged, as reported by the shards.
+    /// Only populated if some search of the batch has `return_quality_hint`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_hint: Option<SearchQualityHint>,
+}
+
+impl SearchMeta {
+    pub fn merge(&mut self, other: SearchMeta) {
+        self.shards_queried.extend(other.shards_queried);
+        self.shards_queried.sort_unstable();
+        self.shards_queried.dedup();
+        self.shards_skipped.extend(other.shards_skipped);
+        self.shards_skipped.sort_unstable();
+        self.shards_skipped.dedup();
+        for (shard_id, latency_ms) in other.latency_ms_per_shard {
+            let shard_latency_ms = self.latency_ms_per_shard.entry(shard_id).or_default();
+            *shard_latency_ms = (*shard_latency_ms).max(latency_ms);
+        }
+        self.quality_hint = match (self.quality_hint, other.quality_hint) {
+            (Some(hint), Some(other_hint)) => Some(hint.merge(&other_hint)),
+            (hint, other_hint) => hint.or(other_hint),
+        };
+    }
+}
+
+/// Size of the beam of the HNSW search on the queried shards.
+///
+/// Effective `ef` of a shard is the lowest one among its segments with HNSW index.
+/// A shard with low `ef` is the first suspect if the recall of the search drops.
+/// Shards without HNSW index, e.g. small ones, are searched exactly and not included.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+pub struct SearchQualityHint {
+    pub min_ef_used: usize,
+    pub max_ef_used: usize,
+    pub avg_ef_used: f64,
+    /// Number of shards the hint is computed from
+    pub shards_count: usize,
+}
+
+impl SearchQualityHint {
+    /// Hint from the effective `ef` of each shard, `None` if there are no shards
+    pub fn from_shards_ef(shards_ef: &[usize]) -> Option<Self> {
+        let min_ef_used = *shards_ef.iter().min()?;
+        let max_ef_used = *shards_ef.iter().max()?;
+        let avg_ef_used = shards_ef.iter().sum::<usize>() as f64 / shards_ef.len() as f64;
+        Some(Self {
+            min_ef_used,
+            max_ef_used,
+            avg_ef_used,
+            shards_count: shards_ef.len(),
+        })
+    }
+
+    pub fn merge(&self, other: &Self) -> Self {
+        let shards_count = self.shards_count + other.shards_count;
+        Self {
+            min_ef_used: self.min_ef_used.min(other.min_ef_used),
+            max_ef_used: self.max_ef_used.max(other.max_ef_used),
+            avg_ef_used: (self.avg_ef_used * self.shards_count as f64
+                + other.avg_ef_used * other.shards_count as f64)
+                / shards_count.max(1) as f64,
+            shards_count,
+        }
+    }
+}
+
+impl SearchBatchResult {
+    pub fn results(self) -> Vec<Vec<ScoredPoint>> {
+        self.results
+    }
+}
+
+/// Result of a search batch along with raw results of each shard before merging
+#[derive(Debug, Clone, Default)]
+pub struct SearchBatchDebugResult {
+    pub merged: Vec<Vec<ScoredPoint>>,
+    /// Results of each shard, only populated if `debug` is requested
+    pub per_shard: HashMap<ShardId, Vec<Vec<ScoredPoint>>>,
+    /// Estimation of the segments read by each search, summed over local shards.
+    /// Only populated for searches with `estimate_segment_reads`
+    pub segment_read_estimations: Vec<Option<SegmentReadEstimation>>,
+}
+
+/// Estimation of the segments read by a search.
+///
+/// Computed from the cardinality estimation of the filter in each segment, not collected by the
+/// search itself. Only segments of shards local to the peer are included.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SegmentReadEstimation {
+    /// Number of segments in the searched local shards
+    pub segments_total: u32,
+    /// Number of segments, in which no point can match the filter
+    pub segments_without_matches: u32,
+    /// Estimated number of points matching the filter in the other segments
+    pub estimated_points_matching: u64,
+}
+
+impl SegmentReadEstimation {
+    pub fn merge(&mut self, other: &Self) {
+        self.segments_total += other.segments_total;
+        self.segments_without_matches += other.segments_without_matches;
+        self.estimated_points_matching += other.estimated_points_matching;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -355,6 +1084,9 @@ pub struct PointRequest {
     /// Whether to return the point vector with the result?
     #[serde(default, alias = "with_vectors")]
     pub with_vector: WithVector,
+    /// If true, return an error if any of the requested points doesn't exist. Default: false
+    #[serde(default)]
+    pub strict: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -507,6 +1239,9 @@ pub struct CountRequest {
     /// Approximate count might be unreliable during the indexing process. Default: true
     #[serde(default = "default_exact_count")]
     pub exact: bool,
+    /// Count only points stored in this shard. If not provided - all shards.
+    #[serde(default)]
+    pub shard_key: Option<ShardId>,
 }
 
 pub const fn default_exact_count() -> bool {
@@ -554,6 +1289,40 @@ pub enum CollectionError {
     OutOfMemory { description: String, free: u64 },
     #[error("Timeout error: {description}")]
     Timeout { description: String },
+    #[error("Service unavailable: {description}")]
+    Unavailable { description: String },
+    #[error("Forbidden: {description}")]
+    Forbidden { description: String },
+    #[error("Failed to finalize transfer of shard {shard_id}: {error}")]
+    TransferFinalizationFailed {
+        shard_id: ShardId,
+        error: TransferFinalizationError,
+    },
+}
+
+/// Error of the shard transfer finalization, along with errors of actions compensating it
+#[derive(Debug, Clone)]
+pub struct TransferFinalizationError {
+    /// Error, which interrupted the finalization
+    pub error: Box<CollectionError>,
+    /// Errors of compensating actions, which failed to roll back the finalization
+    pub compensation_errors: Vec<CollectionError>,
+}
+
+impl std::fmt::Display for TransferFinalizationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)?;
+        if !self.compensation_errors.is_empty() {
+            write!(f, ", rollback failed: ")?;
+            for (i, error) in self.compensation_errors.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "; ")?;
+                }
+                write!(f, "{error}")?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl CollectionError {
@@ -581,6 +1350,18 @@ impl CollectionError {
         CollectionError::BadRequest { description }
     }
 
+    pub fn unavailable(description: impl Into<String>) -> CollectionError {
+        CollectionError::Unavailable {
+            description: description.into(),
+        }
+    }
+
+    pub fn forbidden(description: impl Into<String>) -> CollectionError {
+        CollectionError::Forbidden {
+            description: description.into(),
+        }
+    }
+
     pub fn bad_shard_selection(description: String) -> CollectionError {
         CollectionError::BadShardSelection { description }
     }
@@ -592,6 +1373,20 @@ impl CollectionError {
         }
     }
 
+    pub fn transfer_finalization_failed(
+        shard_id: ShardId,
+        error: impl Into<Self>,
+        compensation_errors: Vec<CollectionError>,
+    ) -> Self {
+        Self::TransferFinalizationFailed {
+            shard_id,
+            error: TransferFinalizationError {
+                error: Box::new(error.into()),
+                compensation_errors,
+            },
+        }
+    }
+
     pub fn remote_peer_id(&self) -> Option<PeerId> {
         match self {
             Self::ForwardProxyError { peer_id, .. } => Some(*peer_id),
@@ -608,14 +1403,18 @@ impl CollectionError {
             Self::Timeout { .. } => true,
             Self::Cancelled { .. } => true,
             Self::OutOfMemory { .. } => true,
+            Self::Unavailable { .. } => true,
             // Not transient
             Self::BadInput { .. } => false,
             Self::NotFound { .. } => false,
             Self::PointNotFound { .. } => false,
             Self::BadRequest { .. } => false,
             Self::BadShardSelection { .. } => false,
+            Self::Forbidden { .. } => false,
             Self::InconsistentShardFailure { .. } => false,
             Self::ForwardProxyError { .. } => false,
+            // Depends on the cause
+            Self::TransferFinalizationFailed { error, .. } => error.error.is_transient(),
         }
     }
 }
@@ -769,6 +1568,9 @@ impl From<tonic::Status> for CollectionError {
             tonic::Code::DeadlineExceeded => CollectionError::Timeout {
                 description: format!("Deadline Exceeded: {err}"),
             },
+            tonic::Code::PermissionDenied => CollectionError::Forbidden {
+                description: format!("PermissionDenied: {err}"),
+            },
             other => CollectionError::ServiceError {
                 error: format!("Tonic status error: {other}"),
                 backtrace: Some(Backtrace::force_capture().to_string()),
@@ -1075,45 +1877,78 @@ pub struct VectorParamsDiff {
 ///         "hnsw_config": { "m": 8 }
 ///     }
 /// }
+///
+/// New vectors are added if full params are given for each of them:
+///
+/// {
+///     "new_vector_name": { "size": 4, "distance": "Dot" }
+/// }
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
-pub struct VectorsConfigDiff(pub BTreeMap<String, VectorParamsDiff>);
+#[serde(untagged)]
+pub enum VectorsConfigDiff {
+    /// Params of new named vectors to add to the collection.
+    /// Existing points get zero vectors
+    Add(BTreeMap<String, VectorParams>),
+    /// Params of existing vectors to update
+    Update(BTreeMap<String, VectorParamsDiff>),
+}
 
 impl VectorsConfigDiff {
-    /// Check that the vector names in this config are part of the given collection.
+    /// Check that the vectors to update are part of the given collection,
+    /// and the vectors to add are not.
     ///
     /// Returns an error if incompatible.
     pub fn check_vector_names(&self, collection: &CollectionParams) -> CollectionResult<()> {
-        for vector_name in self.0.keys() {
-            collection
-                .vectors
-                .get_params(vector_name)
-                .map(|_| ())
-                .ok_or_else(|| OperationError::VectorNameNotExists {
-                    received_name: vector_name.into(),
-                })?;
+        match self {
+            VectorsConfigDiff::Add(params_map) => {
+                for vector_name in params_map.keys() {
+                    if collection.vectors.get_params(vector_name).is_some() {
+                        return Err(CollectionError::bad_input(format!(
+                            "Vector {vector_name} already exists"
+                        )));
+                    }
+                }
+            }
+            VectorsConfigDiff::Update(diff_map) => {
+                for vector_name in diff_map.keys() {
+                    collection
+                        .vectors
+                        .get_params(vector_name)
+                        .map(|_| ())
+                        .ok_or_else(|| OperationError::VectorNameNotExists {
+                            received_name: vector_name.into(),
+                        })?;
+                }
+            }
         }
         Ok(())
     }
 }
 
 impl Validate for VectorsConfigDiff {
-    #[allow(clippy::manual_try_fold)] // `try_fold` can't be used because it shortcuts on Err
     fn validate(&self) -> Result<(), ValidationErrors> {
-        let errors = self
-            .0
-            .values()
-            .filter_map(|v| v.validate().err())
-            .fold(Err(ValidationErrors::new()), |bag, err| {
-                ValidationErrors::merge(bag, "?", Err(err))
-            })
-            .unwrap_err();
-        errors.errors().is_empty().then_some(()).ok_or(errors)
+        match self {
+            VectorsConfigDiff::Add(params_map) => validate_map_values(params_map),
+            VectorsConfigDiff::Update(diff_map) => validate_map_values(diff_map),
+        }
     }
 }
 
+#[allow(clippy::manual_try_fold)] // `try_fold` can't be used because it shortcuts on Err
+fn validate_map_values<V: Validate>(map: &BTreeMap<String, V>) -> Result<(), ValidationErrors> {
+    let errors = map
+        .values()
+        .filter_map(|v| v.validate().err())
+        .fold(Err(ValidationErrors::new()), |bag, err| {
+            ValidationErrors::merge(bag, "?", Err(err))
+        })
+        .unwrap_err();
+    errors.errors().is_empty().then_some(()).ok_or(errors)
+}
+
 impl From<VectorParamsDiff> for VectorsConfigDiff {
     fn from(params: VectorParamsDiff) -> Self {
-        VectorsConfigDiff(BTreeMap::from([("".into(), params)]))
+        VectorsConfigDiff::Update(BTreeMap::from([("".into(), params)]))
     }
 }
 
@@ -1161,30 +1996,43 @@ pub struct BaseGroupRequest {
     pub with_lookup: Option<WithLookupInterface>,
 }
 
-impl From<SearchRequestBatch> for CoreSearchRequestBatch {
-    fn from(batch: SearchRequestBatch) -> Self {
-        CoreSearchRequestBatch {
+impl TryFrom<SearchRequestBatch> for CoreSearchRequestBatch {
+    type Error = CollectionError;
+
+    fn try_from(batch: SearchRequestBatch) -> Result<Self, Self::Error> {
+        Ok(CoreSearchRequestBatch {
             searches: batch
                 .searches
                 .into_iter()
-                .map(CoreSearchRequest::from)
-                .collect(),
-        }
+                .map(CoreSearchRequest::try_from)
+                .collect::<CollectionResult<_>>()?,
+        })
     }
 }
 
-impl From<SearchRequest> for CoreSearchRequest {
-    fn from(request: SearchRequest) -> Self {
-        Self {
-            query: QueryEnum::Nearest(request.vector),
+impl TryFrom<SearchRequest> for CoreSearchRequest {
+    type Error = CollectionError;
+
+    /// Ensemble searches must be split with [`SearchRequestBatch::split_ensembles`] first
+    fn try_from(request: SearchRequest) -> Result<Self, Self::Error> {
+        let vector = match request.vector {
+            VectorQuery::Single(vector) => vector,
+            VectorQuery::Ensemble(_) => {
+                return Err(CollectionError::bad_request(
+                    "Ensemble search must be split into single vector searches".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            params: request.search_params(),
+            query: QueryEnum::Nearest(vector),
             filter: request.filter,
-            params: request.params,
             limit: request.limit,
             offset: request.offset,
             with_payload: request.with_payload,
             with_vector: request.with_vector,
             score_threshold: request.score_threshold,
-        }
+        })
     }
 }
 
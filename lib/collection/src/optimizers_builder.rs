@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -10,7 +11,9 @@ use validator::Validate;
 use crate::collection_manager::optimizers::config_mismatch_optimizer::ConfigMismatchOptimizer;
 use crate::collection_manager::optimizers::indexing_optimizer::IndexingOptimizer;
 use crate::collection_manager::optimizers::merge_optimizer::MergeOptimizer;
-use crate::collection_manager::optimizers::segment_optimizer::OptimizerThresholds;
+use crate::collection_manager::optimizers::segment_optimizer::{
+    OptimizerKind, OptimizerThresholds,
+};
 use crate::collection_manager::optimizers::vacuum_optimizer::VacuumOptimizer;
 use crate::config::CollectionParams;
 use crate::update_handler::Optimizer;
@@ -111,6 +114,96 @@ impl OptimizersConfig {
     }
 }
 
+/// Changed optimizer parameter, which requires optimizers to be recreated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptimizerRestartReason {
+    DeletedThreshold,
+    VacuumMinVectorNumber,
+    DefaultSegmentNumber,
+    MaxSegmentSize,
+    MemmapThreshold,
+    IndexingThreshold,
+    FlushInterval,
+    MaxOptimizationThreads,
+}
+
+impl OptimizerRestartReason {
+    /// Parameters which differ between `old` and `new` configs
+    pub fn from_configs(old: &OptimizersConfig, new: &OptimizersConfig) -> Vec<Self> {
+        let mut reasons = vec![];
+        if old.deleted_threshold != new.deleted_threshold {
+            reasons.push(Self::DeletedThreshold);
+        }
+        if old.vacuum_min_vector_number != new.vacuum_min_vector_number {
+            reasons.push(Self::VacuumMinVectorNumber);
+        }
+        if old.default_segment_number != new.default_segment_number {
+            reasons.push(Self::DefaultSegmentNumber);
+        }
+        if old.max_segment_size != new.max_segment_size {
+            reasons.push(Self::MaxSegmentSize);
+        }
+        if old.memmap_threshold != new.memmap_threshold {
+            reasons.push(Self::MemmapThreshold);
+        }
+        if old.indexing_threshold != new.indexing_threshold {
+            reasons.push(Self::IndexingThreshold);
+        }
+        if old.flush_interval_sec != new.flush_interval_sec {
+            reasons.push(Self::FlushInterval);
+        }
+        if old.max_optimization_threads != new.max_optimization_threads {
+            reasons.push(Self::MaxOptimizationThreads);
+        }
+        reasons
+    }
+
+    /// Optimizers which have to be recreated to apply the change
+    pub fn affected_optimizers(&self) -> &'static [OptimizerKind] {
+        match self {
+            Self::DeletedThreshold | Self::VacuumMinVectorNumber => &[OptimizerKind::Vacuum],
+            Self::DefaultSegmentNumber => &[OptimizerKind::Merge],
+            // Thresholds are shared by all optimizers
+            Self::MaxSegmentSize | Self::MemmapThreshold | Self::IndexingThreshold => &[
+                OptimizerKind::Indexing,
+                OptimizerKind::Merge,
+                OptimizerKind::Vacuum,
+            ],
+            // Applied to the update handler, not to optimizers
+            Self::FlushInterval | Self::MaxOptimizationThreads => &[],
+        }
+    }
+}
+
+/// Replace optimizers of the kinds listed in `restart_mask` with the new ones, keep the rest
+///
+/// Returns `None` if none of the optimizers has to be replaced.
+pub fn replace_optimizers(
+    old_optimizers: &[Arc<Optimizer>],
+    new_optimizers: &[Arc<Optimizer>],
+    restart_mask: &HashSet<OptimizerKind>,
+) -> Option<Arc<Vec<Arc<Optimizer>>>> {
+    let mut replaced = false;
+    let optimizers = new_optimizers
+        .iter()
+        .map(|new_optimizer| {
+            let old_optimizer = old_optimizers
+                .iter()
+                .find(|old_optimizer| old_optimizer.name() == new_optimizer.name());
+            match old_optimizer {
+                Some(old_optimizer) if !restart_mask.contains(&new_optimizer.kind()) => {
+                    old_optimizer.clone()
+                }
+                _ => {
+                    replaced = true;
+                    new_optimizer.clone()
+                }
+            }
+        })
+        .collect();
+    replaced.then(|| Arc::new(optimizers))
+}
+
 pub fn clear_temp_segments(shard_path: &Path) {
     let temp_segments_path = shard_path.join(TEMP_SEGMENTS_PATH);
     if temp_segments_path.exists() {
@@ -190,3 +283,81 @@ pub fn build_optimizers(
         )),
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroU64};
+
+    use segment::types::Distance;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::operations::types::{VectorParams, VectorsConfig};
+
+    fn collection_params() -> CollectionParams {
+        CollectionParams {
+            vectors: VectorsConfig::Single(VectorParams {
+                size: NonZeroU64::new(4).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+            }),
+            shard_number: NonZeroU32::new(1).unwrap(),
+            on_disk_payload: false,
+            replication_factor: NonZeroU32::new(1).unwrap(),
+            write_consistency_factor: NonZeroU32::new(1).unwrap(),
+            max_payload_size_bytes: None,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_replace_optimizers() {
+        let dir = Builder::new().prefix("shard").tempdir().unwrap();
+        let params = collection_params();
+
+        let old_config = OptimizersConfig::fixture();
+        let new_config = OptimizersConfig {
+            deleted_threshold: old_config.deleted_threshold / 2.0,
+            flush_interval_sec: old_config.flush_interval_sec + 1,
+            ..old_config.clone()
+        };
+
+        let restart_mask: HashSet<_> =
+            OptimizerRestartReason::from_configs(&old_config, &new_config)
+                .iter()
+                .flat_map(|reason| reason.affected_optimizers().iter().copied())
+                .collect();
+        assert_eq!(restart_mask, HashSet::from([OptimizerKind::Vacuum]));
+
+        let old_optimizers =
+            build_optimizers(dir.path(), &params, &old_config, &Default::default(), &None);
+        let new_optimizers =
+            build_optimizers(dir.path(), &params, &new_config, &Default::default(), &None);
+
+        // Only vacuum optimizer is replaced
+        let optimizers = replace_optimizers(&old_optimizers, &new_optimizers, &restart_mask)
+            .expect("vacuum optimizer must be replaced");
+        assert_eq!(optimizers.len(), old_optimizers.len());
+        for (optimizer, (old_optimizer, new_optimizer)) in optimizers
+            .iter()
+            .zip(old_optimizers.iter().zip(new_optimizers.iter()))
+        {
+            let expected = if optimizer.kind() == OptimizerKind::Vacuum {
+                new_optimizer
+            } else {
+                old_optimizer
+            };
+            assert_eq!(
+                Arc::as_ptr(optimizer).cast::<()>(),
+                Arc::as_ptr(expected).cast::<()>(),
+                "{}",
+                optimizer.name(),
+            );
+        }
+
+        // Nothing to replace if no optimizer is affected
+        assert!(replace_optimizers(&old_optimizers, &new_optimizers, &HashSet::new()).is_none());
+    }
+}
@@ -3,13 +3,16 @@ use std::future::Future;
 
 use futures::future::try_join_all;
 use itertools::Itertools;
-use segment::data_types::vectors::{NamedVector, VectorElementType, DEFAULT_VECTOR_NAME};
+use segment::data_types::vectors::{
+    NamedVector, VectorElementType, VectorType, DEFAULT_VECTOR_NAME,
+};
 use segment::types::{
     Condition, Filter, HasIdCondition, PointIdType, ScoredPoint, WithPayloadInterface, WithVector,
 };
 use tokio::sync::RwLockReadGuard;
 
 use crate::collection::Collection;
+use crate::common::vector_ops;
 use crate::operations::consistency_params::ReadConsistency;
 use crate::operations::types::{
     CollectionError, CollectionResult, PointRequest, RecommendRequest, RecommendRequestBatch,
@@ -39,6 +42,41 @@ fn avg_vectors<'a>(
     avg_vector
 }
 
+/// Query vector of the discovery search: average of `positives` minus average of `negatives`,
+/// normalized to unit length
+pub(crate) fn context_query_vector(
+    positives: &[VectorType],
+    negatives: &[VectorType],
+) -> CollectionResult<VectorType> {
+    if let Some(dim) = positives.first().map(Vec::len) {
+        if positives
+            .iter()
+            .chain(negatives)
+            .any(|vector| vector.len() != dim)
+        {
+            return Err(CollectionError::bad_input(
+                "Context examples must have the same dimension".to_string(),
+            ));
+        }
+    }
+
+    let positive = avg_vectors(positives.iter());
+    let negative = avg_vectors(negatives.iter());
+    let mut query: VectorType = positive
+        .iter()
+        .zip(&negative)
+        .map(|(positive, negative)| positive - negative)
+        .collect();
+
+    if !vector_ops::normalize(&mut query) {
+        return Err(CollectionError::bad_input(
+            "Positive and negative context examples cancel each other out".to_string(),
+        ));
+    }
+
+    Ok(query)
+}
+
 pub async fn recommend_by<'a, F, Fut>(
     request: RecommendRequest,
     collection: &Collection,
@@ -78,6 +116,7 @@ async fn retrieve_points(
                 ids,
                 with_payload: Some(WithPayloadInterface::Bool(false)),
                 with_vector: WithVector::Selector(vector_names),
+                strict: false,
             },
             read_consistency,
             None,
@@ -302,11 +341,21 @@ where
             limit: request.limit,
             score_threshold: request.score_threshold,
             offset: request.offset,
+            ..Default::default()
         };
         searches.push(search_request)
     }
 
-    let search_batch_request = SearchRequestBatch { searches };
+    let search_batch_request = SearchRequestBatch {
+        searches,
+        debug: false,
+        dry_run: false,
+        explain_score: false,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms: None,
+        score_normalizer: None,
+    };
 
     collection
         .search_batch(search_batch_request, read_consistency, None)
@@ -1,10 +1,27 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
+use api::grpc::qdrant::collections_internal_client::CollectionsInternalClient;
+use api::grpc::qdrant::{
+    CheckDiskSpaceRequest, CheckDiskSpaceResponse, UploadShardSnapshotRequest,
+};
 use api::grpc::transport_channel_pool::TransportChannelPool;
+use futures::{future, StreamExt as _};
+use tokio_util::io::ReaderStream;
 use tonic::transport::Uri;
+use tonic::Status;
 
-use crate::shards::shard::PeerId;
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::CollectionId;
+
+/// Size of the chunks a shard snapshot is streamed to another peer in
+const SHARD_SNAPSHOT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Maximum time to stream a shard snapshot to another peer
+const SHARD_SNAPSHOT_UPLOAD_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Clone, Default)]
 pub struct ChannelService {
@@ -30,4 +47,121 @@ impl ChannelService {
             self.channel_pool.drop_pool(&uri).await;
         }
     }
+
+    /// Request available disk space for the collection storage on the given peer.
+    ///
+    /// If `shard_id` is specified, the peer also estimates the size of its local replica of the shard.
+    pub async fn check_disk_space(
+        &self,
+        peer_id: PeerId,
+        collection_id: &CollectionId,
+        shard_id: Option<ShardId>,
+    ) -> CollectionResult<CheckDiskSpaceResponse> {
+        let peer_address = self.id_to_address.read().get(&peer_id).cloned();
+        let Some(peer_address) = peer_address else {
+            return Err(CollectionError::service_error(format!(
+                "no address found for peer {peer_id}"
+            )));
+        };
+
+        let response = self
+            .channel_pool
+            .with_channel(&peer_address, |channel| async move {
+                CollectionsInternalClient::new(channel)
+                    .check_disk_space(CheckDiskSpaceRequest {
+                        collection_name: collection_id.clone(),
+                        shard_id,
+                    })
+                    .await
+            })
+            .await?
+            .into_inner();
+
+        Ok(response)
+    }
+
+    /// Stream a shard snapshot archive to the given peer, which restores its local shard from it
+    ///
+    /// The peer only accepts the snapshot while a transfer of the shard from `from_peer_id`
+    /// to it is running.
+    pub async fn upload_shard_snapshot(
+        &self,
+        peer_id: PeerId,
+        from_peer_id: PeerId,
+        collection_id: &CollectionId,
+        shard_id: ShardId,
+        snapshot_path: &Path,
+    ) -> CollectionResult<()> {
+        let peer_address = self.id_to_address.read().get(&peer_id).cloned();
+        let Some(peer_address) = peer_address else {
+            return Err(CollectionError::service_error(format!(
+                "no address found for peer {peer_id}"
+            )));
+        };
+
+        // Streamed requests can't carry errors, so reading is stopped on the first error,
+        // which is reported after the request is finished
+        let read_error = Arc::new(parking_lot::Mutex::new(None));
+
+        self.channel_pool
+            .with_channel_timeout(
+                &peer_address,
+                |channel| {
+                    let read_error = read_error.clone();
+                    async move {
+                        let file = tokio::fs::File::open(snapshot_path).await.map_err(|err| {
+                            Status::internal(format!(
+                                "Can't open shard snapshot {}: {err}",
+                                snapshot_path.display(),
+                            ))
+                        })?;
+                        let snapshot_size = file
+                            .metadata()
+                            .await
+                            .map_err(|err| {
+                                Status::internal(format!(
+                                    "Can't read size of shard snapshot {}: {err}",
+                                    snapshot_path.display(),
+                                ))
+                            })?
+                            .len();
+
+                        let collection_name = collection_id.clone();
+                        let chunks = ReaderStream::with_capacity(file, SHARD_SNAPSHOT_CHUNK_SIZE)
+                            .scan((), move |_, chunk| {
+                                let request = match chunk {
+                                    Ok(data) => Some(UploadShardSnapshotRequest {
+                                        collection_name: collection_name.clone(),
+                                        shard_id,
+                                        data: data.to_vec(),
+                                        from_peer_id,
+                                        snapshot_size,
+                                    }),
+                                    Err(err) => {
+                                        *read_error.lock() = Some(err);
+                                        None
+                                    }
+                                };
+                                future::ready(request)
+                            });
+
+                        CollectionsInternalClient::new(channel)
+                            .upload_shard_snapshot(chunks)
+                            .await
+                    }
+                },
+                Some(SHARD_SNAPSHOT_UPLOAD_TIMEOUT),
+                0,
+            )
+            .await?;
+
+        if let Some(err) = read_error.lock().take() {
+            return Err(CollectionError::service_error(format!(
+                "Failed to read shard snapshot {}: {err}",
+                snapshot_path.display(),
+            )));
+        }
+
+        Ok(())
+    }
 }
@@ -64,6 +64,7 @@ pub fn internal_upsert_points(
                     .collect::<Result<Vec<_>, Status>>()?,
             },
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     })
 }
@@ -86,6 +87,7 @@ pub fn internal_delete_points(
                 })),
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -106,6 +108,7 @@ pub fn internal_delete_points_by_filter(
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -131,6 +134,7 @@ pub fn internal_update_vectors(
                 })
                 .collect(),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -157,6 +161,7 @@ pub fn internal_delete_vectors(
                 names: vector_names,
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -181,6 +186,7 @@ pub fn internal_delete_vectors_by_filter(
                 names: vector_names,
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -212,6 +218,7 @@ pub fn internal_set_payload(
             payload: payload_to_proto(set_payload.payload),
             points_selector,
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -243,6 +250,7 @@ pub fn internal_delete_payload(
             keys: delete_payload.keys,
             points_selector,
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -265,6 +273,7 @@ pub fn internal_clear_payload(
                 })),
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -285,6 +294,7 @@ pub fn internal_clear_payload_by_filter(
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
             ordering: ordering.map(write_ordering_to_proto),
+            idempotency_key: None,
         }),
     }
 }
@@ -384,11 +394,45 @@ pub fn try_scored_point_from_grpc(
         .map(|vectors| vectors.try_into())
         .transpose()?;
 
+    let score_breakdown = (!point.score_breakdown.is_empty()).then_some(point.score_breakdown);
+
     Ok(ScoredPoint {
         id,
         version: point.version,
         score: point.score,
         payload,
         vector,
+        score_breakdown,
+        search_params_echo: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_scored_point_score_breakdown_from_grpc() {
+        let point = ScoredPoint {
+            id: 1.into(),
+            version: 2,
+            score: 0.5,
+            payload: None,
+            vector: None,
+            score_breakdown: Some(HashMap::from([("raw_distance".to_string(), 0.5)])),
+            search_params_echo: None,
+        };
+
+        let converted = try_scored_point_from_grpc(point.clone().into(), false).unwrap();
+        assert_eq!(converted.score_breakdown, point.score_breakdown);
+
+        let point = ScoredPoint {
+            score_breakdown: None,
+            ..point
+        };
+        let converted = try_scored_point_from_grpc(point.into(), false).unwrap();
+        assert_eq!(converted.score_breakdown, None);
+    }
+}
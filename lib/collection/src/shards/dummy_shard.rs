@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -7,6 +8,7 @@ use segment::types::{
 };
 use tokio::runtime::Handle;
 
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
     Record, SearchRequestBatch, UpdateResult,
@@ -36,7 +38,19 @@ impl DummyShard {
         self.dummy()
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
+    pub async fn filtered_snapshot(
+        &self,
+        _temp_path: &Path,
+        _target_path: &Path,
+        _filter: &Filter,
+    ) -> CollectionResult<()> {
+        self.dummy()
+    }
+
+    pub async fn on_optimizer_config_update(
+        &self,
+        _restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
         self.dummy()
     }
 
@@ -45,6 +59,7 @@ impl DummyShard {
             variant_name: Some("dummy shard".into()),
             segments: vec![],
             optimizations: Default::default(),
+            optimizer_queue: Default::default(),
         }
     }
 
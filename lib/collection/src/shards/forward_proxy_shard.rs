@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -9,6 +10,7 @@ use segment::types::{
 use tokio::runtime::Handle;
 use tokio::sync::Mutex;
 
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::operations::point_ops::{PointOperations, PointStruct, PointSyncOperation};
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
@@ -17,7 +19,7 @@ use crate::operations::types::{
 use crate::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
 use crate::shards::local_shard::LocalShard;
 use crate::shards::remote_shard::RemoteShard;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::shards::telemetry::LocalShardTelemetry;
 
 /// ForwardProxyShard
@@ -133,8 +135,25 @@ impl ForwardProxyShard {
             .await
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
-        self.wrapped_shard.on_optimizer_config_update().await
+    /// Forward `filtered_snapshot` to `wrapped_shard`
+    pub async fn filtered_snapshot(
+        &self,
+        temp_path: &Path,
+        target_path: &Path,
+        filter: &Filter,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .filtered_snapshot(temp_path, target_path, filter)
+            .await
+    }
+
+    pub async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .on_optimizer_config_update(restart_mask)
+            .await
     }
 
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
@@ -199,6 +218,17 @@ impl ShardOperation for ForwardProxyShard {
         local_shard.search(request, search_runtime_handle).await
     }
 
+    async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
+        let local_shard = &self.wrapped_shard;
+        local_shard
+            .search_with_info(request, search_runtime_handle)
+            .await
+    }
+
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         let local_shard = &self.wrapped_shard;
         local_shard.count(request).await
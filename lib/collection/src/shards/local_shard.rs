@@ -1,50 +1,63 @@
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::mem::size_of;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
 use parking_lot::{Mutex as ParkingMutex, RwLock};
 use segment::data_types::vectors::VectorElementType;
+use segment::entry::entry_point::SegmentEntry;
 use segment::index::field_index::CardinalityEstimation;
-use segment::segment::Segment;
+use segment::index::hnsw_index::{graph_search_ef, graph_search_top};
+use segment::segment::{Segment, SEGMENT_STATE_FILE};
 use segment::segment_constructor::{build_segment, load_segment};
 use segment::types::{
     CompressionRatio, Filter, PayloadIndexInfo, PayloadKeyType, PayloadStorageType, PointIdType,
-    QuantizationConfig, SegmentConfig, SegmentType,
+    QuantizationConfig, SegmentConfig, SegmentType, SeqNumberType,
 };
 use segment::utils::mem::Mem;
 use tokio::fs::{copy, create_dir_all, remove_dir_all};
 use tokio::runtime::Handle;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::{mpsc, oneshot, Mutex, RwLock as TokioRwLock};
+use tokio_util::sync::CancellationToken;
 use wal::{Wal, WalOptions};
 
 use crate::collection_manager::collection_updater::CollectionUpdater;
 use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder};
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::collection_manager::optimizers::TrackerLog;
+use crate::collection_manager::segments_searcher::get_hnsw_ef_construct;
 use crate::common::file_utils::move_dir;
-use crate::config::CollectionConfig;
+use crate::config::{CollectionConfig, CollectionParams};
 use crate::operations::shared_storage_config::SharedStorageConfig;
 use crate::operations::types::{
-    CollectionError, CollectionInfo, CollectionResult, CollectionStatus, OptimizersStatus,
+    CollectionError, CollectionInfo, CollectionResult, CollectionStatus, CountRequest, CountResult,
+    OptimizersStatus, SearchRequest, SegmentReadEstimation,
 };
 use crate::operations::CollectionUpdateOperations;
-use crate::optimizers_builder::{build_optimizers, clear_temp_segments};
+use crate::optimizers_builder::{build_optimizers, clear_temp_segments, replace_optimizers};
 use crate::shards::shard::ShardId;
 use crate::shards::shard_config::{ShardConfig, SHARD_CONFIG_FILE};
-use crate::shards::telemetry::{LocalShardTelemetry, OptimizerTelemetry};
+use crate::shards::telemetry::{LocalShardTelemetry, OptimizerQueueStats, OptimizerTelemetry};
 use crate::shards::CollectionId;
-use crate::update_handler::{Optimizer, UpdateHandler, UpdateSignal};
+use crate::update_handler::{Optimizer, PendingUpdates, UpdateHandler, UpdateSignal};
 use crate::wal::SerdeWal;
 
 pub type LockedWal = Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>;
 
+/// Number of points read from a segment by a cancellable count between checks of the token
+const COUNT_CANCEL_CHECK_BATCH: usize = 10_000;
+
+/// How long the size of the shard data on disk is cached, see [`LocalShard::size_bytes`]
+const DISK_SIZE_CACHE_TTL: Duration = Duration::from_secs(30);
+
 /// LocalShard
 ///
 /// LocalShard is an entity that can be moved between peers and contains some part of one collections data.
@@ -60,6 +73,10 @@ pub struct LocalShard {
     pub(super) path: PathBuf,
     pub(super) optimizers: Arc<Vec<Arc<Optimizer>>>,
     pub(super) optimizers_log: Arc<ParkingMutex<TrackerLog>>,
+    /// Operations sent to the update worker, but not applied yet, see `is_stale`
+    pending_updates: PendingUpdates,
+    /// Moment and result of the last computation of the shard size on disk
+    disk_size_cache: ParkingMutex<Option<(Instant, u64)>>,
     update_runtime: Handle,
 }
 
@@ -84,6 +101,40 @@ impl LocalShard {
         wal_path.exists() && segments_path.exists()
     }
 
+    /// Checks that shard data at `shard_path` is compatible with the given collection config.
+    ///
+    /// Every named vector of every stored segment must exist in the collection config
+    /// and have the same size and distance.
+    pub fn check_data_compatibility(
+        shard_path: &Path,
+        collection_config: &CollectionConfig,
+    ) -> CollectionResult<()> {
+        let segments_path = Self::segments_path(shard_path);
+        for entry in std::fs::read_dir(segments_path)? {
+            let segment_path = entry?.path();
+            if !segment_path.join(SEGMENT_STATE_FILE).exists() {
+                continue;
+            }
+            let segment_state = Segment::load_state(&segment_path)?;
+            for (vector_name, vector_config) in &segment_state.config.vector_data {
+                let vector_params = collection_config.params.get_vector_params(vector_name)?;
+                if vector_params.size.get() as usize != vector_config.size
+                    || vector_params.distance != vector_config.distance
+                {
+                    return Err(CollectionError::bad_input(format!(
+                        "Shard data is not compatible with collection: vector {vector_name} has size {} and distance {:?}, \
+                         but collection expects size {} and distance {:?}",
+                        vector_config.size,
+                        vector_config.distance,
+                        vector_params.size,
+                        vector_params.distance,
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Clear local shard related data.
     ///
     /// Do NOT remove config file.
@@ -130,6 +181,7 @@ impl LocalShard {
         let (update_sender, update_receiver) =
             mpsc::channel(shared_storage_config.update_queue_size);
         update_handler.run_workers(update_receiver);
+        let pending_updates = update_handler.pending_updates.clone();
 
         drop(config); // release `shared_config` from borrow checker
 
@@ -144,6 +196,8 @@ impl LocalShard {
             update_runtime,
             optimizers,
             optimizers_log,
+            pending_updates,
+            disk_size_cache: ParkingMutex::new(None),
         }
     }
 
@@ -151,6 +205,22 @@ impl LocalShard {
         self.segments.deref()
     }
 
+    /// Check if the oldest pending update of the shard was sent to the update worker longer than
+    /// `timeout` ago.
+    ///
+    /// Idle shards without pending updates are never considered stale.
+    pub fn is_stale(&self, timeout: Duration) -> bool {
+        self.pending_updates
+            .lock()
+            .first_key_value()
+            .map_or(false, |(_, sent_at)| sent_at.elapsed() > timeout)
+    }
+
+    /// Register operation sent to the update worker, see `is_stale`
+    pub(crate) fn register_pending_update(&self, op_num: SeqNumberType) {
+        self.pending_updates.lock().insert(op_num, Instant::now());
+    }
+
     /// Recovers shard from disk.
     pub async fn load(
         id: ShardId,
@@ -222,6 +292,9 @@ impl LocalShard {
             log::debug!("Deduplicated {} points", res);
         }
 
+        // Vectors may have been added to the collection before the shard was stopped
+        add_missing_vectors(&segment_holder, &collection_config_read.params)?;
+
         clear_temp_segments(shard_path);
         let optimizers = build_optimizers(
             shard_path,
@@ -473,10 +546,39 @@ impl LocalShard {
         Ok(())
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
+    /// Recreate optimizers with the current collection config and restart update workers
+    ///
+    /// If `restart_mask` is specified, only optimizers of the listed kinds are recreated,
+    /// the rest are kept as is. Workers are not restarted if neither optimizers nor the flush
+    /// interval have changed.
+    pub async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
         let config = self.collection_config.read().await;
         let mut update_handler = self.update_handler.lock().await;
 
+        let new_optimizers = build_optimizers(
+            &self.path,
+            &config.params,
+            &config.optimizer_config,
+            &config.hnsw_config,
+            &config.quantization_config,
+        );
+        let new_optimizers = match restart_mask {
+            None => Some(new_optimizers),
+            Some(restart_mask) => {
+                replace_optimizers(&update_handler.optimizers, &new_optimizers, restart_mask)
+            }
+        };
+        let flush_interval_changed =
+            update_handler.flush_interval_sec != config.optimizer_config.flush_interval_sec;
+
+        if new_optimizers.is_none() && !flush_interval_changed {
+            // Running workers are not affected, don't interrupt ongoing optimizations
+            return Ok(());
+        }
+
         let (update_sender, update_receiver) =
             mpsc::channel(self.shared_storage_config.update_queue_size);
         // makes sure that the Stop signal is the last one in this channel
@@ -485,14 +587,11 @@ impl LocalShard {
         update_handler.stop_flush_worker();
 
         update_handler.wait_workers_stops().await?;
-        let new_optimizers = build_optimizers(
-            &self.path,
-            &config.params,
-            &config.optimizer_config,
-            &config.hnsw_config,
-            &config.quantization_config,
-        );
-        update_handler.optimizers = new_optimizers;
+        // No segment is optimized or updated while workers are stopped
+        add_missing_vectors(&self.segments.read(), &config.params)?;
+        if let Some(new_optimizers) = new_optimizers {
+            update_handler.optimizers = new_optimizers;
+        }
         update_handler.flush_interval_sec = config.optimizer_config.flush_interval_sec;
         update_handler.run_workers(update_receiver);
         self.update_sender.load().send(UpdateSignal::Nop).await?;
@@ -561,6 +660,9 @@ impl LocalShard {
             let plunger = UpdateSignal::Plunger(tx);
             self.update_sender.load().send(plunger).await?;
             rx.await?;
+        } else {
+            // Persist all operations accepted so far, so none of them is lost on recovery
+            self.flush_wal()?;
         }
 
         let temp_path = temp_path.to_owned();
@@ -587,6 +689,89 @@ impl LocalShard {
         Ok(())
     }
 
+    /// Create a snapshot of the shard which contains only points matching the given filter
+    ///
+    /// Matching points are copied into a single freshly built segment, so the snapshot does not
+    /// share any segment files with the original shard. WAL is never included, only an empty
+    /// WAL compatible with the current operation number is generated.
+    pub async fn filtered_snapshot(
+        &self,
+        temp_path: &Path,
+        target_path: &Path,
+        filter: &Filter,
+    ) -> CollectionResult<()> {
+        let snapshot_shard_path = target_path;
+
+        let snapshot_segments_shard_path = snapshot_shard_path.join("segments");
+        create_dir_all(&snapshot_segments_shard_path).await?;
+
+        // Make sure that all updates submitted so far have made it to the segments
+        let (tx, rx) = oneshot::channel();
+        let plunger = UpdateSignal::Plunger(tx);
+        self.update_sender.load().send(plunger).await?;
+        rx.await?;
+
+        let segment_config = {
+            let config = self.collection_config.read().await;
+            SegmentConfig {
+                vector_data: config.params.into_base_vector_data()?,
+                payload_storage_type: if config.params.on_disk_payload {
+                    PayloadStorageType::OnDisk
+                } else {
+                    PayloadStorageType::InMemory
+                },
+            }
+        };
+
+        let segments = self.segments.clone();
+        let wal = self.wal.clone();
+        let snapshot_shard_path_owned = snapshot_shard_path.to_owned();
+        let temp_path = temp_path.to_owned();
+        let filter = filter.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let build_dir = tempfile::Builder::new()
+                .prefix("filtered-segment-")
+                .tempdir_in(&temp_path)?;
+            let mut filtered_segment = build_segment(build_dir.path(), &segment_config, true)?;
+
+            // Only take the list of segments, so updates and optimizations are not blocked
+            // while points are copied. If a point is found in multiple segments, e.g. because
+            // of an optimization, the filtered segment keeps the latest version of it.
+            let segments_list = segments
+                .read()
+                .iter()
+                .map(|(_, segment)| segment.clone())
+                .collect_vec();
+
+            for segment in segments_list {
+                let segment_guard = segment.get();
+                let segment_read = segment_guard.read();
+                for point_id in segment_read.read_filtered(None, None, Some(&filter)) {
+                    let Some(version) = segment_read.point_version(point_id) else {
+                        continue;
+                    };
+                    let vectors = segment_read.all_vectors(point_id)?;
+                    let payload = segment_read.payload(point_id)?;
+                    filtered_segment.upsert_point(version, point_id, vectors)?;
+                    filtered_segment.set_full_payload(version, point_id, &payload)?;
+                }
+            }
+
+            filtered_segment.flush(true)?;
+            filtered_segment.take_snapshot(&temp_path, &snapshot_segments_shard_path)?;
+
+            Self::snapshot_empty_wal(wal, &snapshot_shard_path_owned)
+        })
+        .await??;
+
+        // copy shard's config
+        let shard_config_path = ShardConfig::get_config_path(&self.path);
+        let target_shard_config_path = snapshot_shard_path.join(SHARD_CONFIG_FILE);
+        copy(&shard_config_path, &target_shard_config_path).await?;
+        Ok(())
+    }
+
     /// Create empty WAL which is compatible with currently stored data
     pub fn snapshot_empty_wal(wal: LockedWal, snapshot_shard_path: &Path) -> CollectionResult<()> {
         let (segment_capacity, latest_op_num) = {
@@ -618,6 +803,12 @@ impl LocalShard {
         })
     }
 
+    /// Write all buffered operations of the WAL to disk
+    pub fn flush_wal(&self) -> CollectionResult<()> {
+        self.wal.lock().flush()?;
+        Ok(())
+    }
+
     /// snapshot WAL
     ///
     /// copies all WAL files into `snapshot_shard_path/wal`
@@ -659,6 +850,55 @@ impl LocalShard {
         Ok(cardinality)
     }
 
+    /// Size of the beam of the HNSW search, effectively used by the given search.
+    ///
+    /// `ef` is resolved per segment the same way as the HNSW index does it, see
+    /// [`graph_search_ef`], including the oversampling of rescored quantized vectors.
+    /// Lowest `ef` among the segments is returned, `None` if the search is exact,
+    /// or if no segment has HNSW index built.
+    pub fn hnsw_ef_used(&self, search: &SearchRequest) -> Option<usize> {
+        let vector_name = search.vector.as_single()?.get_name();
+        let params = search.search_params();
+        if params.map_or(false, |params| params.exact) {
+            return None;
+        }
+        let quantization_params = params
+            .and_then(|params| params.quantization)
+            .unwrap_or_default();
+        let top = search.limit + search.offset;
+        let segments = self.segments().read();
+        segments
+            .iter()
+            .filter_map(|(_id, segment)| {
+                let segment = segment.get();
+                let segment = segment.read();
+                let config = segment.config();
+                let ef_construct = get_hnsw_ef_construct(config, vector_name)?;
+                let rescore = config.quantization_config(vector_name).is_some()
+                    && !quantization_params.ignore
+                    && quantization_params.rescore;
+                let graph_top = graph_search_top(top, params.as_ref(), rescore);
+                Some(graph_search_ef(ef_construct, graph_top, params.as_ref()))
+            })
+            .min()
+    }
+
+    /// Estimation of the segments which would be read by a search with the given filter
+    pub fn segment_read_estimation(&self, filter: Option<&Filter>) -> SegmentReadEstimation {
+        let segments = self.segments().read();
+        let mut read_estimation = SegmentReadEstimation::default();
+        for (_id, segment) in segments.iter() {
+            let estimation = segment.get().read().estimate_point_count(filter);
+            read_estimation.segments_total += 1;
+            if estimation.max == 0 {
+                read_estimation.segments_without_matches += 1;
+            } else {
+                read_estimation.estimated_points_matching += estimation.exp as u64;
+            }
+        }
+        read_estimation
+    }
+
     pub fn read_filtered<'a>(
         &'a self,
         filter: Option<&'a Filter>,
@@ -676,6 +916,58 @@ impl LocalShard {
         Ok(all_points)
     }
 
+    /// Same as `count`, but checks the `cancel` token while scanning the segments.
+    ///
+    /// Exact count scans the segments on a blocking thread, reading the points of each segment in
+    /// batches of `COUNT_CANCEL_CHECK_BATCH` and checking the token between batches.
+    /// Returns `CollectionError::Cancelled` instead of a partial result if the token is cancelled.
+    pub async fn count_cancellable(
+        &self,
+        request: Arc<CountRequest>,
+        cancel: &CancellationToken,
+    ) -> CollectionResult<CountResult> {
+        check_count_cancelled(cancel)?;
+
+        if !request.exact {
+            let count = self.estimate_cardinality(request.filter.as_ref())?.exp;
+            return Ok(CountResult { count });
+        }
+
+        let segments = self.segments.clone();
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || {
+            let filter = request.filter.as_ref();
+            let segments = segments.read();
+            let mut all_points = BTreeSet::new();
+            for (_id, segment) in segments.iter() {
+                let segment = segment.get();
+                let mut offset = None;
+                loop {
+                    check_count_cancelled(&cancel)?;
+                    let mut batch = segment.read().read_filtered(
+                        offset,
+                        Some(COUNT_CANCEL_CHECK_BATCH + 1),
+                        filter,
+                    );
+                    // The extra point is the offset of the next batch
+                    offset = if batch.len() > COUNT_CANCEL_CHECK_BATCH {
+                        batch.pop()
+                    } else {
+                        None
+                    };
+                    all_points.extend(batch);
+                    if offset.is_none() {
+                        break;
+                    }
+                }
+            }
+            Ok(CountResult {
+                count: all_points.len(),
+            })
+        })
+        .await?
+    }
+
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
         let segments_read_guard = self.segments.read();
         let segments: Vec<_> = segments_read_guard
@@ -702,9 +994,34 @@ impl LocalShard {
                 optimizations,
                 log: self.optimizers_log.lock().to_telemetry(),
             },
+            optimizer_queue: self.optimizer_queue_stats(),
         }
     }
 
+    /// Count segments, which currently satisfy the conditions of each optimizer
+    fn optimizer_queue_stats(&self) -> OptimizerQueueStats {
+        let mut stats = OptimizerQueueStats::default();
+        for optimizer in self.optimizers.iter() {
+            // Same as scheduling optimizations: exclude already selected segments and ask again
+            let mut pending = HashSet::new();
+            loop {
+                let candidates = optimizer.check_condition(self.segments.clone(), &pending);
+                let pending_before = pending.len();
+                pending.extend(candidates);
+                if pending.len() == pending_before {
+                    break;
+                }
+            }
+            let pending = pending.len() as u32;
+            match optimizer.kind() {
+                OptimizerKind::Indexing => stats.indexing_pending += pending,
+                OptimizerKind::Merge => stats.merge_pending += pending,
+                OptimizerKind::Vacuum => stats.vacuum_pending += pending,
+            }
+        }
+        stats
+    }
+
     /// Returns estimated size of vector data in bytes
     async fn estimate_vector_data_size(&self) -> usize {
         let info = self.local_shard_info().await;
@@ -742,6 +1059,42 @@ impl LocalShard {
         vector_size * info.points_count
     }
 
+    /// Returns size of the shard data on disk in bytes
+    ///
+    /// Walking the shard directory is expensive, so the size is recomputed at most once per
+    /// `DISK_SIZE_CACHE_TTL`. If the size can't be computed, the error is logged and the last
+    /// known size is returned.
+    pub async fn size_bytes(&self) -> u64 {
+        let cached = *self.disk_size_cache.lock();
+        if let Some((computed_at, size)) = cached {
+            if computed_at.elapsed() < DISK_SIZE_CACHE_TTL {
+                return size;
+            }
+        }
+
+        let shard_path = self.path.clone();
+        let size = tokio::task::spawn_blocking(move || {
+            fs_extra::dir::get_size(shard_path).map_err(|err| err.to_string())
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|size| size);
+
+        match size {
+            Ok(size) => {
+                *self.disk_size_cache.lock() = Some((Instant::now(), size));
+                size
+            }
+            Err(err) => {
+                log::warn!(
+                    "Can't get size of shard directory {}: {err}",
+                    self.path.display(),
+                );
+                cached.map_or(0, |(_, size)| size)
+            }
+        }
+    }
+
     pub async fn local_shard_info(&self) -> CollectionInfo {
         let collection_config = self.collection_config.read().await.clone();
         let segments = self.segments().read();
@@ -791,6 +1144,10 @@ impl LocalShard {
             segments_count,
             config: collection_config,
             payload_schema: schema,
+            disk_data_size_bytes: 0,
+            degraded_shards: vec![],
+            unavailable_shards: vec![],
+            partial_result: false,
         }
     }
 }
@@ -809,3 +1166,41 @@ impl Drop for LocalShard {
         })
     }
 }
+
+/// Add vectors of the collection missing in the segments, with zero vectors for existing points,
+/// see [`Segment::add_vector`].
+///
+/// Segments must not be optimized meanwhile, so that none of them is wrapped in a proxy.
+fn add_missing_vectors(
+    segments: &SegmentHolder,
+    collection_params: &CollectionParams,
+) -> CollectionResult<()> {
+    let vector_data = collection_params.into_base_vector_data()?;
+    for (_, segment) in segments.iter() {
+        let LockedSegment::Original(segment) = segment else {
+            return Err(CollectionError::service_error(
+                "Can't add vectors to a segment under optimization".to_string(),
+            ));
+        };
+        let mut segment = segment.write();
+        for (vector_name, vector_config) in &vector_data {
+            if !segment.segment_config.vector_data.contains_key(vector_name) {
+                log::debug!(
+                    "Adding vector {vector_name} to segment {}",
+                    segment.current_path.display(),
+                );
+                segment.add_vector(vector_name, vector_config.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_count_cancelled(cancel: &CancellationToken) -> CollectionResult<()> {
+    if cancel.is_cancelled() {
+        return Err(CollectionError::Cancelled {
+            description: "Count operation was cancelled".to_string(),
+        });
+    }
+    Ok(())
+}
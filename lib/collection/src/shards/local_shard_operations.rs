@@ -1,26 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::future::try_join_all;
 use itertools::Itertools;
 use segment::types::{
-    ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
+    ExtendedPointId, Filter, ScoreType, ScoredPoint, SearchParamsEcho, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 use tokio::sync::oneshot;
 
 use crate::collection_manager::segments_searcher::SegmentsSearcher;
 use crate::common::stopping_guard::StoppingGuard;
+use crate::config::CollectionConfig;
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CoreSearchRequestBatch, CountRequest,
-    CountResult, PointRequest, Record, SearchRequestBatch, UpdateResult, UpdateStatus,
+    CountResult, PointRequest, Record, SearchRequest, SearchRequestBatch, UpdateResult,
+    UpdateStatus,
 };
 use crate::operations::CollectionUpdateOperations;
 use crate::optimizers_builder::DEFAULT_INDEXING_THRESHOLD_KB;
 use crate::shards::local_shard::LocalShard;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::update_handler::{OperationData, UpdateSignal};
 
+/// Score breakdown key of the distance, as computed by the vector index
+const SCORE_RAW_DISTANCE: &str = "raw_distance";
+/// Score breakdown key, `1.0` if the score was rescored with original vectors after searching
+/// quantized vectors, `0.0` otherwise
+const SCORE_QUANTIZATION_RESCORE: &str = "quantization_rescore";
+/// Score breakdown key, ratio of the requested number of points to the `ef` of the HNSW search.
+/// The closer to `1.0`, the fewer candidates were explored to find the point.
+/// `0.0` for exact search.
+const SCORE_GRAPH_EF_PENALTY: &str = "graph_ef_penalty";
+
 #[async_trait]
 impl ShardOperation for LocalShard {
     /// Imply interior mutability.
@@ -43,6 +57,7 @@ impl ShardOperation for LocalShard {
             let channel_permit = update_sender.reserve().await?;
             let mut wal_lock = self.wal.lock();
             let operation_id = wal_lock.write(&operation)?;
+            self.register_pending_update(operation_id);
             channel_permit.send(UpdateSignal::Operation(OperationData {
                 op_num: operation_id,
                 operation,
@@ -55,13 +70,15 @@ impl ShardOperation for LocalShard {
         if let Some(receiver) = callback_receiver {
             let _res = receiver.await??;
             Ok(UpdateResult {
-                operation_id,
+                operation_id: Some(operation_id),
                 status: UpdateStatus::Completed,
+                shards_written: 1,
             })
         } else {
             Ok(UpdateResult {
-                operation_id,
+                operation_id: Some(operation_id),
                 status: UpdateStatus::Acknowledged,
+                shards_written: 1,
             })
         }
     }
@@ -113,7 +130,9 @@ impl ShardOperation for LocalShard {
 
     /// Collect overview information about the shard
     async fn info(&self) -> CollectionResult<CollectionInfo> {
-        Ok(self.local_shard_info().await)
+        let mut info = self.local_shard_info().await;
+        info.disk_data_size_bytes = self.size_bytes().await;
+        Ok(info)
     }
 
     async fn search(
@@ -121,19 +140,35 @@ impl ShardOperation for LocalShard {
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
-        let (collection_params, indexing_threshold_kb) = {
+        let core_request = Arc::new(CoreSearchRequestBatch::try_from(request.as_ref().clone())?);
+
+        let (collection_params, indexing_threshold_kb, params_echoes) = {
             let collection_config = self.collection_config.read().await;
+            let params_echoes: Vec<_> = request
+                .searches
+                .iter()
+                .zip(&core_request.searches)
+                .map(|(search, core_search)| {
+                    (search.echo_params || request.explain_score).then(|| {
+                        search_params_echo(
+                            &collection_config,
+                            search,
+                            core_search.query.get_vector_name(),
+                            self.hnsw_ef_used(search),
+                        )
+                    })
+                })
+                .collect();
             (
                 collection_config.params.clone(),
                 collection_config
                     .optimizer_config
                     .indexing_threshold
                     .unwrap_or(DEFAULT_INDEXING_THRESHOLD_KB),
+                params_echoes,
             )
         };
 
-        let core_request = Arc::new(CoreSearchRequestBatch::from(request.as_ref().clone()));
-
         // check vector names existing
         for req in &core_request.searches {
             collection_params.get_vector_params(req.query.get_vector_name())?;
@@ -162,14 +197,27 @@ impl ShardOperation for LocalShard {
         let top_results = res
             .into_iter()
             .zip(core_request.searches.iter())
-            .map(|(vector_res, req)| {
+            .zip(request.searches.iter().zip(params_echoes))
+            .map(|((vector_res, req), (search, params_echo))| {
                 let vector_name = req.query.get_vector_name();
                 let distance = collection_params
                     .get_vector_params(vector_name)
                     .unwrap()
                     .distance;
+                let score_breakdown = params_echo
+                    .as_ref()
+                    .filter(|_| request.explain_score)
+                    .map(|params_echo| search_score_breakdown(search, params_echo));
+                let params_echo = params_echo.filter(|_| search.echo_params);
                 let processed_res = vector_res.into_iter().map(|mut scored_point| {
-                    scored_point.score = distance.postprocess_score(scored_point.score);
+                    let raw_distance = scored_point.score;
+                    scored_point.score = distance.postprocess_score(raw_distance);
+                    if let Some(score_breakdown) = &score_breakdown {
+                        let mut score_breakdown = score_breakdown.clone();
+                        score_breakdown.insert(SCORE_RAW_DISTANCE.to_string(), raw_distance);
+                        scored_point.score_breakdown = Some(score_breakdown);
+                    }
+                    scored_point.search_params_echo = params_echo;
                     scored_point
                 });
 
@@ -187,6 +235,24 @@ impl ShardOperation for LocalShard {
         Ok(top_results)
     }
 
+    async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
+        let hnsw_ef_used = request
+            .searches
+            .iter()
+            .map(|search| self.hnsw_ef_used(search))
+            .collect();
+        let results = self.search(request, search_runtime_handle).await?;
+        Ok(ShardSearchResult {
+            results,
+            hnsw_ef_used,
+            cache_hit: false,
+        })
+    }
+
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         let total_count = if request.exact {
             let all_points = self.read_filtered(request.filter.as_ref())?;
@@ -206,3 +272,61 @@ impl ShardOperation for LocalShard {
         SegmentsSearcher::retrieve(self.segments(), &request.ids, with_payload, with_vector)
     }
 }
+
+/// Effective params of the search on this shard, resolved from the request and the collection
+/// configuration.
+///
+/// `hnsw_ef` is the beam size of the HNSW index of the segments, see [`LocalShard::hnsw_ef_used`].
+fn search_params_echo(
+    config: &CollectionConfig,
+    request: &SearchRequest,
+    vector_name: &str,
+    hnsw_ef: Option<usize>,
+) -> SearchParamsEcho {
+    let params = request.search_params().unwrap_or_default();
+    let vector_params = config.params.get_vector_params(vector_name).ok();
+
+    let has_quantization = vector_params
+        .and_then(|vector_params| vector_params.quantization_config)
+        .or_else(|| config.quantization_config.clone())
+        .is_some();
+    let quantization_params = params.quantization.unwrap_or_default();
+    let quantization_applied = has_quantization && !params.exact && !quantization_params.ignore;
+
+    let oversampling_used = if quantization_applied && quantization_params.rescore {
+        quantization_params.oversampling.unwrap_or(1.0).max(1.0)
+    } else {
+        1.0
+    };
+
+    SearchParamsEcho {
+        ef_used: hnsw_ef,
+        oversampling_used,
+        quantization_applied,
+    }
+}
+
+/// Components of the score, which are the same for all points found by the search
+fn search_score_breakdown(
+    request: &SearchRequest,
+    params_echo: &SearchParamsEcho,
+) -> HashMap<String, ScoreType> {
+    let params = request.search_params().unwrap_or_default();
+
+    let rescored =
+        params_echo.quantization_applied && params.quantization.unwrap_or_default().rescore;
+    let graph_ef_penalty = match params_echo.ef_used {
+        Some(ef_used) if ef_used > 0 => {
+            (request.limit + request.offset) as ScoreType / ef_used as ScoreType
+        }
+        _ => 0.0,
+    };
+
+    HashMap::from([
+        (
+            SCORE_QUANTIZATION_RESCORE.to_string(),
+            if rescored { 1.0 } else { 0.0 },
+        ),
+        (SCORE_GRAPH_EF_PENALTY.to_string(), graph_ef_penalty),
+    ])
+}
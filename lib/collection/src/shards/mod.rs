@@ -5,6 +5,7 @@ pub mod dummy_shard;
 pub mod forward_proxy_shard;
 pub mod local_shard;
 pub mod local_shard_operations;
+pub mod pending_operations;
 pub mod proxy_shard;
 pub mod queue_proxy_shard;
 pub mod remote_shard;
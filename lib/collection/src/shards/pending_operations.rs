@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use segment::common::file_operations::{atomic_save_json, read_json};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, Mutex, MutexGuard};
+use wal::WalOptions;
+
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::operations::CollectionUpdateOperations;
+use crate::shards::shard::ShardId;
+use crate::wal::SerdeWal;
+
+const PENDING_OPERATIONS_PATH: &str = "pending_operations";
+
+const APPLIED_INDEX_FILE: &str = "applied.json";
+
+/// Maximal number of updates buffered during a single shard transfer
+pub const MAX_PENDING_OPERATIONS: u64 = 100_000;
+
+const PENDING_OPERATIONS_SEGMENT_CAPACITY: usize = 32 * 1024 * 1024;
+
+/// Client updates received by a local replica in `Partial` state.
+///
+/// While a shard is being transferred into the replica, its data is incomplete, so incoming
+/// client updates are kept here and applied in order once the transfer is finished.
+/// Batches of the transfer itself are not buffered.
+///
+/// Operations are persisted in a WAL next to the shard data, so they survive a restart of the peer.
+/// The buffer is bounded by [`MAX_PENDING_OPERATIONS`], updates exceeding it are rejected.
+#[derive(Debug)]
+pub struct PendingOperationsBuffer {
+    operations: Mutex<PendingOperations>,
+    /// Held while the buffered operations are applied, so only one drain runs at a time
+    drain_lock: Mutex<()>,
+}
+
+impl PendingOperationsBuffer {
+    /// Open the buffer of the shard, loading operations which were not applied before a restart
+    pub fn load(shard_path: &Path) -> CollectionResult<Self> {
+        Ok(Self {
+            operations: Mutex::new(PendingOperations::load(
+                shard_path.join(PENDING_OPERATIONS_PATH),
+            )?),
+            drain_lock: Default::default(),
+        })
+    }
+
+    /// Lock the buffer.
+    ///
+    /// The guard must be held while the replica state is checked and the buffer is used,
+    /// so that no operation is buffered after the buffer was drained.
+    pub async fn lock(&self) -> MutexGuard<'_, PendingOperations> {
+        self.operations.lock().await
+    }
+
+    /// Try to become the only task applying the buffered operations
+    pub fn try_lock_drain(&self) -> Option<MutexGuard<'_, ()>> {
+        self.drain_lock.try_lock().ok()
+    }
+
+    pub async fn len(&self) -> u64 {
+        self.operations.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Drop all buffered operations, e.g. if the transfer was aborted
+    pub async fn clear(&self) -> CollectionResult<()> {
+        self.operations.lock().await.clear()
+    }
+}
+
+/// Persisted progress of applying the buffered operations
+#[derive(Debug, Deserialize, Serialize)]
+struct AppliedIndex {
+    next_index: u64,
+}
+
+/// Buffered operations, the WAL is only created once the first operation is buffered
+pub struct PendingOperations {
+    path: PathBuf,
+    wal: Option<SerdeWal<CollectionUpdateOperations>>,
+    /// Index of the first operation which is not applied yet.
+    /// Persisted on every ack, the WAL itself is only truncated by whole segments.
+    next_index: u64,
+    /// Updates waiting for their buffered operation to be applied
+    waiters: BTreeMap<u64, oneshot::Sender<()>>,
+}
+
+impl std::fmt::Debug for PendingOperations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingOperations")
+            .field("path", &self.path)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl PendingOperations {
+    fn load(path: PathBuf) -> CollectionResult<Self> {
+        let mut operations = Self {
+            path,
+            wal: None,
+            next_index: 0,
+            waiters: BTreeMap::new(),
+        };
+        if operations.path.exists() {
+            let wal = operations.open_wal()?;
+            operations.next_index = operations.load_applied_index()?.max(wal.first_index());
+            operations.wal = Some(wal);
+        }
+        Ok(operations)
+    }
+
+    fn load_applied_index(&self) -> CollectionResult<u64> {
+        let path = self.path.join(APPLIED_INDEX_FILE);
+        if !path.exists() {
+            return Ok(0);
+        }
+        let applied: AppliedIndex = read_json(&path).map_err(|err| {
+            CollectionError::service_error(format!(
+                "Can't read applied index of pending operations {}: {err}",
+                path.display(),
+            ))
+        })?;
+        Ok(applied.next_index)
+    }
+
+    fn save_applied_index(&self) -> CollectionResult<()> {
+        let path = self.path.join(APPLIED_INDEX_FILE);
+        let applied = AppliedIndex {
+            next_index: self.next_index,
+        };
+        atomic_save_json(&path, &applied).map_err(|err| {
+            CollectionError::service_error(format!(
+                "Can't save applied index of pending operations {}: {err}",
+                path.display(),
+            ))
+        })
+    }
+
+    fn open_wal(&self) -> CollectionResult<SerdeWal<CollectionUpdateOperations>> {
+        std::fs::create_dir_all(&self.path).map_err(|err| {
+            CollectionError::service_error(format!(
+                "Can't create directory {}: {err}",
+                self.path.display(),
+            ))
+        })?;
+        Ok(SerdeWal::new(
+            &self.path.to_string_lossy(),
+            WalOptions {
+                segment_capacity: PENDING_OPERATIONS_SEGMENT_CAPACITY,
+                segment_queue_len: 0,
+            },
+        )?)
+    }
+
+    /// Number of buffered operations which are not applied yet
+    pub fn len(&self) -> u64 {
+        self.wal.as_ref().map_or(0, |wal| {
+            (wal.first_index() + wal.len()).saturating_sub(self.next_index)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Persist the operation in the buffer and return its sequence number
+    pub fn push(
+        &mut self,
+        shard_id: ShardId,
+        operation: &CollectionUpdateOperations,
+    ) -> CollectionResult<u64> {
+        if self.len() >= MAX_PENDING_OPERATIONS {
+            return Err(CollectionError::service_error(format!(
+                "Can't buffer update for shard {shard_id}: \
+                 {MAX_PENDING_OPERATIONS} updates are already waiting for the shard transfer to finish",
+            )));
+        }
+
+        let wal = match self.wal.take() {
+            Some(wal) => wal,
+            None => {
+                let wal = self.open_wal()?;
+                self.next_index = wal.first_index();
+                wal
+            }
+        };
+        let wal = self.wal.insert(wal);
+        let operation_id = wal.write(operation)?;
+        wal.flush()?;
+        Ok(operation_id)
+    }
+
+    /// Get notified once the buffered operation is applied.
+    ///
+    /// The sender is dropped if the operation is dropped before, e.g. because the shard transfer
+    /// was aborted.
+    pub fn notify_applied(&mut self, operation_id: u64) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.waiters.insert(operation_id, sender);
+        receiver
+    }
+
+    /// Read up to `limit` operations which are not applied yet, in order
+    pub fn read(&self, limit: usize) -> Vec<(u64, CollectionUpdateOperations)> {
+        match &self.wal {
+            Some(wal) if !self.is_empty() => wal.read(self.next_index).take(limit).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Mark operations up to `operation_id` as applied.
+    ///
+    /// If no operations are left, the buffer is removed from disk.
+    pub fn ack(&mut self, operation_id: u64) -> CollectionResult<()> {
+        self.next_index = self.next_index.max(operation_id + 1);
+
+        let waiting = self.waiters.split_off(&self.next_index);
+        for (_, waiter) in std::mem::replace(&mut self.waiters, waiting) {
+            // The update may have been cancelled meanwhile
+            let _ = waiter.send(());
+        }
+
+        if self.is_empty() {
+            return self.clear();
+        }
+        self.save_applied_index()?;
+        if let Some(wal) = &mut self.wal {
+            wal.ack(self.next_index)?;
+        }
+        Ok(())
+    }
+
+    /// Drop all buffered operations and remove the buffer from disk
+    pub fn clear(&mut self) -> CollectionResult<()> {
+        self.next_index = 0;
+        self.waiters.clear();
+        if self.wal.take().is_some() || self.path.exists() {
+            std::fs::remove_dir_all(&self.path).map_err(|err| {
+                CollectionError::service_error(format!(
+                    "Can't remove pending operations {}: {err}",
+                    self.path.display(),
+                ))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::operations::point_ops::PointOperations;
+
+    fn delete_point(id: u64) -> CollectionUpdateOperations {
+        CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+            ids: vec![id.into()],
+        })
+    }
+
+    #[test]
+    fn test_applied_operations_are_not_loaded_again() {
+        let dir = Builder::new()
+            .prefix("pending_operations")
+            .tempdir()
+            .unwrap();
+        let path = dir.path().join(PENDING_OPERATIONS_PATH);
+
+        let mut operations = PendingOperations::load(path.clone()).unwrap();
+        let ids: Vec<_> = (0..3)
+            .map(|id| operations.push(0, &delete_point(id)).unwrap())
+            .collect();
+        operations.ack(ids[0]).unwrap();
+        drop(operations);
+
+        let operations = PendingOperations::load(path).unwrap();
+        assert_eq!(operations.len(), 2);
+        let pending: Vec<_> = operations.read(10).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(pending, ids[1..]);
+    }
+
+    #[tokio::test]
+    async fn test_notify_applied() {
+        let dir = Builder::new()
+            .prefix("pending_operations")
+            .tempdir()
+            .unwrap();
+        let mut operations =
+            PendingOperations::load(dir.path().join(PENDING_OPERATIONS_PATH)).unwrap();
+
+        let first = operations.push(0, &delete_point(0)).unwrap();
+        let second = operations.push(0, &delete_point(1)).unwrap();
+        let first_applied = operations.notify_applied(first);
+        let second_applied = operations.notify_applied(second);
+
+        operations.ack(first).unwrap();
+        first_applied.await.unwrap();
+
+        // Dropped operations are never applied
+        operations.clear().unwrap();
+        assert!(second_applied.await.is_err());
+    }
+}
@@ -13,6 +13,7 @@ use tokio::runtime::Handle;
 use tokio::sync::{oneshot, RwLock};
 use tokio::time::timeout;
 
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::operations::operation_effect::{
     EstimateOperationEffectArea, OperationEffectArea, PointsOperationEffect,
 };
@@ -22,7 +23,7 @@ use crate::operations::types::{
 };
 use crate::operations::CollectionUpdateOperations;
 use crate::shards::local_shard::LocalShard;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::shards::telemetry::LocalShardTelemetry;
 use crate::update_handler::UpdateSignal;
 
@@ -73,8 +74,25 @@ impl ProxyShard {
             .await
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
-        self.wrapped_shard.on_optimizer_config_update().await
+    /// Forward `filtered_snapshot` to `wrapped_shard`
+    pub async fn filtered_snapshot(
+        &self,
+        temp_path: &Path,
+        target_path: &Path,
+        filter: &Filter,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .filtered_snapshot(temp_path, target_path, filter)
+            .await
+    }
+
+    pub async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .on_optimizer_config_update(restart_mask)
+            .await
     }
 
     pub async fn reinit_changelog(&self) -> CollectionResult<()> {
@@ -206,6 +224,18 @@ impl ShardOperation for ProxyShard {
         local_shard.search(request, search_runtime_handle).await
     }
 
+    /// Forward read-only `search_with_info` to `wrapped_shard`
+    async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
+        let local_shard = &self.wrapped_shard;
+        local_shard
+            .search_with_info(request, search_runtime_handle)
+            .await
+    }
+
     /// Forward read-only `count` to `wrapped_shard`
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         let local_shard = &self.wrapped_shard;
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -10,6 +11,7 @@ use tokio::runtime::Handle;
 use tokio::sync::Mutex;
 
 use super::remote_shard::RemoteShard;
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::operations::point_ops::WriteOrdering;
 use crate::operations::types::{
     CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest, Record,
@@ -17,7 +19,7 @@ use crate::operations::types::{
 };
 use crate::operations::CollectionUpdateOperations;
 use crate::shards::local_shard::LocalShard;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::shards::telemetry::LocalShardTelemetry;
 
 /// ForwardQueue shard
@@ -74,6 +76,18 @@ impl QueueProxyShard {
             .await
     }
 
+    /// Forward `filtered_snapshot` to `wrapped_shard`
+    pub async fn filtered_snapshot(
+        &self,
+        temp_path: &Path,
+        target_path: &Path,
+        filter: &Filter,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .filtered_snapshot(temp_path, target_path, filter)
+            .await
+    }
+
     /// Transfer all updates that the remote missed from WAL
     pub async fn transfer_all_missed_updates(
         &self,
@@ -150,8 +164,13 @@ impl QueueProxyShard {
         *max_ack_version = max_version;
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
-        self.wrapped_shard.on_optimizer_config_update().await
+    pub async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
+        self.wrapped_shard
+            .on_optimizer_config_update(restart_mask)
+            .await
     }
 
     pub fn get_telemetry_data(&self) -> LocalShardTelemetry {
@@ -213,6 +232,18 @@ impl ShardOperation for QueueProxyShard {
         local_shard.search(request, search_runtime_handle).await
     }
 
+    /// Forward read-only `search_with_info` to `wrapped_shard`
+    async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
+        let local_shard = &self.wrapped_shard;
+        local_shard
+            .search_with_info(request, search_runtime_handle)
+            .await
+    }
+
     /// Forward read-only `count` to `wrapped_shard`
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         let local_shard = &self.wrapped_shard;
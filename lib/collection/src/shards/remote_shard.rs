@@ -41,7 +41,7 @@ use crate::shards::conversions::{
     internal_upsert_points, try_scored_point_from_grpc,
 };
 use crate::shards::shard::{PeerId, ShardId};
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::shards::telemetry::RemoteShardTelemetry;
 use crate::shards::CollectionId;
 
@@ -483,19 +483,31 @@ impl ShardOperation for RemoteShard {
         batch_request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        let result = self
+            .search_with_info(batch_request, search_runtime_handle)
+            .await?;
+        Ok(result.results)
+    }
+
+    async fn search_with_info(
+        &self,
+        batch_request: Arc<SearchRequestBatch>,
+        _search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
         let mut timer = ScopeDurationMeasurer::new(&self.telemetry_search_durations);
         timer.set_success(false);
 
         let search_points = batch_request
             .searches
             .iter()
-            .map(|s| CollectionSearchRequest((self.collection_id.clone(), s)).into())
-            .collect();
+            .map(|s| CollectionSearchRequest((self.collection_id.clone(), s)).try_into())
+            .collect::<CollectionResult<_>>()?;
 
         let request = &SearchBatchPointsInternal {
             collection_name: self.collection_id.clone(),
             search_points,
             shard_id: Some(self.id),
+            explain_score: Some(batch_request.explain_score),
         };
         let search_batch_response = self
             .with_points_client(|mut client| async move {
@@ -506,7 +518,12 @@ impl ShardOperation for RemoteShard {
             .await?
             .into_inner();
 
-        let result: Result<Vec<Vec<ScoredPoint>>, Status> = search_batch_response
+        let hnsw_ef_used = search_batch_response
+            .result
+            .iter()
+            .map(|batch_result| batch_result.hnsw_ef_used.map(|ef| ef as usize))
+            .collect();
+        let results: Result<Vec<Vec<ScoredPoint>>, Status> = search_batch_response
             .result
             .into_iter()
             .zip(batch_request.searches.iter())
@@ -523,11 +540,13 @@ impl ShardOperation for RemoteShard {
                     .collect()
             })
             .collect();
-        let result = result.map_err(|e| e.into());
-        if result.is_ok() {
-            timer.set_success(true);
-        }
-        result
+        let results = results?;
+        timer.set_success(true);
+        Ok(ShardSearchResult {
+            results,
+            hnsw_ef_used,
+            cache_hit: false,
+        })
     }
 
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
@@ -2,8 +2,10 @@ use std::cmp;
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write as _;
+use std::future::Future;
 use std::ops::Deref as _;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -18,21 +20,27 @@ use segment::types::{
     WithVector,
 };
 use serde::{Deserialize, Serialize};
+use tar::Builder as TarBuilder;
+use tempfile::NamedTempFile;
 use tokio::runtime::Handle;
 use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 
 use super::local_shard::LocalShard;
+use super::pending_operations::PendingOperationsBuffer;
 use super::queue_proxy_shard::QueueProxyShard;
 use super::remote_shard::RemoteShard;
 use super::resolve::{Resolve, ResolveCondition};
 use super::{create_shard_dir, CollectionId};
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::config::CollectionConfig;
 use crate::operations::consistency_params::{ReadConsistency, ReadConsistencyType};
-use crate::operations::point_ops::WriteOrdering;
+use crate::operations::point_ops::{PointOperations, WriteOrdering};
 use crate::operations::shared_storage_config::SharedStorageConfig;
+use crate::operations::snapshot_ops::ShardSnapshotManifest;
 use crate::operations::types::{
     CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
-    Record, SearchRequestBatch, UpdateResult,
+    Record, SearchRequestBatch, SegmentReadEstimation, UpdateResult, UpdateStatus,
 };
 use crate::operations::CollectionUpdateOperations;
 use crate::save_on_disk::SaveOnDisk;
@@ -42,7 +50,7 @@ use crate::shards::forward_proxy_shard::ForwardProxyShard;
 use crate::shards::shard::Shard::{Dummy, ForwardProxy, Local, QueueProxy};
 use crate::shards::shard::{PeerId, Shard, ShardId};
 use crate::shards::shard_config::ShardConfig;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardSearchResult};
 use crate::shards::telemetry::ReplicaSetTelemetry;
 
 pub type ActivatePeer = Arc<dyn Fn(PeerId, ShardId) + Send + Sync>;
@@ -55,6 +63,13 @@ const READ_REMOTE_REPLICAS: u32 = 2;
 
 const REPLICA_STATE_FILE: &str = "replica_state.json";
 
+/// Number of buffered operations applied at once after a shard transfer
+const PENDING_OPERATIONS_BATCH_SIZE: usize = 100;
+
+/// Directory inside of the shard directory, which keeps previous shard data while
+/// the local replica is restored, so it can be loaded back if the restore fails
+const RESTORE_BACKUP_DIR: &str = "restore_backup";
+
 //    │    Collection Created
 //    │
 //    ▼
@@ -106,11 +121,17 @@ pub enum ReplicaState {
     // A shard which receives data, but is not used for search
     // Useful for backup shards
     Listener,
+    // The shard is being recovered from a snapshot in online mode
+    // Doesn't receive updates nor reads, missed updates are transferred from other active
+    // replicas once the snapshot is recovered
+    Recovering,
 }
 
 /// Represents a change in replica set, due to scaling of `replication_factor`
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
 pub enum Change {
+    /// Add a new replica of the shard on the peer, data is populated with a shard transfer
+    Add(ShardId, PeerId),
     Remove(ShardId, PeerId),
 }
 
@@ -185,6 +206,8 @@ pub struct ShardReplicaSet {
     search_runtime: Handle,
     /// Lock to serialized write operations on the replicaset when a write ordering is used.
     write_ordering_lock: Mutex<()>,
+    /// Updates received while the local replica is `Partial`, applied when the transfer is finished
+    pending_operations: PendingOperationsBuffer,
 }
 
 impl ShardReplicaSet {
@@ -202,6 +225,14 @@ impl ShardReplicaSet {
         self.local.read().await.is_some()
     }
 
+    /// Check if the local shard has pending updates, but didn't apply any of them for longer than `timeout`
+    pub async fn is_local_stale(&self, timeout: Duration) -> bool {
+        match &*self.local.read().await {
+            Some(Local(local_shard)) => local_shard.is_stale(timeout),
+            _ => false,
+        }
+    }
+
     pub fn peers(&self) -> HashMap<PeerId, ReplicaState> {
         self.replica_state.read().peers()
     }
@@ -210,6 +241,16 @@ impl ShardReplicaSet {
         self.replica_state.read().this_peer_id
     }
 
+    /// Whether any replica of the shard is in a state which allows to serve reads
+    pub fn has_readable_replica(&self) -> bool {
+        self.replica_state.read().peers.values().any(|state| {
+            matches!(
+                state,
+                ReplicaState::Active | ReplicaState::Listener | ReplicaState::Partial
+            )
+        })
+    }
+
     pub fn highest_replica_peer_id(&self) -> Option<PeerId> {
         self.replica_state.read().peers.keys().max().cloned()
     }
@@ -225,6 +266,14 @@ impl ShardReplicaSet {
             .max()
     }
 
+    /// Estimated size of the local shard data, if the local shard is present and not proxied
+    pub async fn estimate_local_shard_size(&self) -> Option<u64> {
+        match &*self.local.read().await {
+            Some(Local(local_shard)) => Some(local_shard.size_bytes().await),
+            _ => None,
+        }
+    }
+
     pub async fn remote_peers(&self) -> Vec<PeerId> {
         self.remotes
             .read()
@@ -353,6 +402,8 @@ impl ShardReplicaSet {
         let replica_set_shard_config = ShardConfig::new_replica_set();
         replica_set_shard_config.save(&shard_path)?;
 
+        let pending_operations = PendingOperationsBuffer::load(&shard_path)?;
+
         Ok(Self {
             shard_id,
             local: RwLock::new(local),
@@ -370,6 +421,7 @@ impl ShardReplicaSet {
             update_runtime,
             search_runtime,
             write_ordering_lock: Mutex::new(()),
+            pending_operations,
         })
     }
 
@@ -410,6 +462,17 @@ impl ShardReplicaSet {
     }
 
     pub async fn remove_local(&self) -> CollectionResult<()> {
+        if let Some(removing_local) = self.detach_local().await? {
+            self.clear_detached_local(removing_local).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove local shard from the replica set, but keep its data.
+    ///
+    /// Returned shard has to be either put back with `attach_local`,
+    /// or dropped with `clear_detached_local`.
+    pub async fn detach_local(&self) -> CollectionResult<Option<Shard>> {
         self.replica_state.write(|rs| {
             rs.is_local = false;
             let this_peer_id = rs.this_peer_id;
@@ -418,19 +481,32 @@ impl ShardReplicaSet {
 
         self.update_locally_disabled(self.this_peer_id());
 
-        let removing_local = {
-            let mut local = self.local.write().await;
-            local.take()
-        };
+        Ok(self.local.write().await.take())
+    }
 
-        if let Some(removing_local) = removing_local {
-            // stop ongoing tasks and delete data
-            drop(removing_local);
-            LocalShard::clear(&self.shard_path).await?;
+    /// Put back local shard removed with `detach_local`.
+    ///
+    /// State of the local replica has to be restored separately.
+    pub async fn attach_local(&self, local: Shard) -> CollectionResult<()> {
+        let mut local_write = self.local.write().await;
+        if local_write.is_some() {
+            return Err(CollectionError::service_error(format!(
+                "Cannot attach local shard {} because local shard already exists",
+                self.shard_id,
+            )));
         }
+        self.replica_state.write(|rs| rs.is_local = true)?;
+        let _ = local_write.insert(local);
         Ok(())
     }
 
+    /// Drop local shard removed with `detach_local` and delete its data
+    pub async fn clear_detached_local(&self, local: Shard) -> CollectionResult<()> {
+        // stop ongoing tasks and delete data
+        drop(local);
+        LocalShard::clear(&self.shard_path).await
+    }
+
     pub async fn set_local(
         &self,
         local: LocalShard,
@@ -574,6 +650,12 @@ impl ShardReplicaSet {
             None
         };
 
+        let pending_operations = PendingOperationsBuffer::load(shard_path)
+            .map_err(|e| {
+                panic!("Failed to load pending operations in {shard_path:?}: {e}");
+            })
+            .unwrap();
+
         let replica_set = Self {
             shard_id,
             local: RwLock::new(local),
@@ -591,6 +673,7 @@ impl ShardReplicaSet {
             update_runtime,
             search_runtime,
             write_ordering_lock: Mutex::new(()),
+            pending_operations,
         };
 
         if local_load_failure && replica_set.active_remote_shards().await.is_empty() {
@@ -709,6 +792,12 @@ impl ShardReplicaSet {
                             .await?;
                         self.notify_peer_failure(peer_id);
                     }
+                    ReplicaState::Recovering => {
+                        // Recovery was interrupted, so the data is not complete
+                        self.set_local(local_shard, Some(ReplicaState::Recovering))
+                            .await?;
+                        self.notify_peer_failure(peer_id);
+                    }
                 }
                 continue;
             }
@@ -743,6 +832,7 @@ impl ShardReplicaSet {
             Some(ReplicaState::Initializing) => true,
             Some(ReplicaState::Dead) => false,
             Some(ReplicaState::Listener) => true,
+            Some(ReplicaState::Recovering) => false,
             None => false,
         };
         res && !self.is_locally_disabled(peer_id)
@@ -859,6 +949,12 @@ impl ShardReplicaSet {
         captured_error.expect("at this point `captured_error` must be defined by construction")
     }
 
+    /// Execute read op. on replica set and resolve responses of the queried replicas
+    /// according to the `read_consistency`.
+    ///
+    /// With `ReadConsistencyType::Quorum` the operation is sent to all active replicas at once.
+    /// If the first quorum of responses does not agree, responses of the remaining replicas are
+    /// awaited as well, and only the items confirmed by a quorum of replicas are returned.
     pub async fn execute_and_resolve_read_operation<F, Res>(
         &self,
         read_operation: F,
@@ -897,7 +993,8 @@ impl ShardReplicaSet {
             }
 
             ReadConsistency::Type(ReadConsistencyType::Quorum) => {
-                (total_count / 2 + 1, ResolveCondition::All)
+                let quorum = total_count / 2 + 1;
+                (quorum, ResolveCondition::Quorum(quorum))
             }
 
             ReadConsistency::Factor(factor) => {
@@ -941,9 +1038,18 @@ impl ShardReplicaSet {
             .into_iter()
             .map(|remote| read_operation(remote).right_future());
 
-        let mut operations = local_operation.chain(remote_operations);
+        let operations = local_operation.chain(remote_operations);
+
+        let reconcile = matches!(
+            read_consistency,
+            ReadConsistency::Type(ReadConsistencyType::Quorum)
+        );
 
-        let mut required_reads = if active_local_count > 0 {
+        let mut required_reads = if reconcile {
+            // Quorum reads are sent to all active replicas, so that conflicting responses
+            // can be reconciled without another round trip.
+            active_count
+        } else if active_local_count > 0 {
             // If there is a local shard, we can ignore fan-out `read_remote_replicas` param,
             // as we already know that the local peer is working.
             factor
@@ -951,78 +1057,28 @@ impl ShardReplicaSet {
             max(factor, usize::try_from(self.read_remote_replicas).unwrap())
         };
 
-        if !is_local_ready {
+        if !is_local_ready && !reconcile {
             required_reads += 1;
         }
 
-        let mut pending_operations: FuturesUnordered<_> =
-            operations.by_ref().take(required_reads).collect();
-
-        let mut responses = Vec::new();
-        let mut errors = Vec::new();
-
-        while let Some(result) = pending_operations.next().await {
-            match result {
-                Ok(resp) => responses.push(resp),
-
-                Err(err) => {
-                    let is_transient = err.is_transient();
-
-                    if is_transient {
-                        log::debug!("Read operation failed: {err}");
-                        errors.push(err);
-                    } else {
-                        return Err(err);
-                    }
-                }
-            }
-
-            if responses.len() >= factor {
-                break;
-            }
-
-            let maybe_responses = responses.len() + pending_operations.len();
-
-            let schedule = factor.saturating_sub(maybe_responses);
-            pending_operations.extend(operations.by_ref().take(schedule));
-
-            let maybe_responses = responses.len() + pending_operations.len();
-
-            if maybe_responses < factor {
-                break;
-            }
-        }
+        let responses =
+            collect_read_responses(self.shard_id, operations, required_reads, factor, reconcile)
+                .await?;
 
-        if responses.len() >= factor {
-            if factor == 1 {
-                Ok(responses.into_iter().next().unwrap())
-            } else {
-                Ok(Res::resolve(responses, condition))
-            }
+        if factor == 1 {
+            Ok(responses.into_iter().next().unwrap())
         } else {
-            let success_count = responses.len();
-            let error_count = errors.len();
-
-            Err(CollectionError::service_error(format!(
-                "{error_count} of {} shards failed with: {}",
-                success_count + error_count,
-                errors.into_iter().fold(String::new(), |mut msg, err| {
-                    if msg.is_empty() {
-                        msg = err.to_string();
-                    } else {
-                        write!(&mut msg, ", {err}").unwrap(); // Writing into `String` never fails
-                    }
-
-                    msg
-                })
-            )))
+            Ok(Res::resolve(responses, condition))
         }
     }
 
-    pub(crate) async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
+    pub(crate) async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
         let read_local = self.local.read().await;
         if let Some(shard) = &*read_local {
-            shard.on_optimizer_config_update().await
+            shard.on_optimizer_config_update(restart_mask).await
         } else {
             Ok(())
         }
@@ -1048,80 +1104,136 @@ impl ShardReplicaSet {
     }
 
     /// Returns if local shard was recovered from path
+    ///
+    /// The lock on the local shard is not held during the restore, operations on the local
+    /// replica fail until the restore is finished. Replica state is not changed here,
+    /// the caller is expected to switch it through consensus.
+    ///
+    /// If the restore fails, previous data of the local shard is loaded back.
     pub async fn restore_local_replica_from(&self, replica_path: &Path) -> CollectionResult<bool> {
         if !LocalShard::check_data(replica_path) {
             return Ok(false);
         }
 
-        let mut local = self.local.write().await;
+        // Drop `LocalShard` instance to free resources
+        let previous_local = self
+            .local
+            .write()
+            .await
+            .replace(Dummy(DummyShard::new("Local replica is being restored")));
+        let has_backup = previous_local.is_some() && LocalShard::check_data(&self.shard_path);
+        drop(previous_local);
 
-        // Drop `LocalShard` instance to free resources and clear shard data
-        let clear = local.take().is_some();
+        let backup_path = self.shard_path.join(RESTORE_BACKUP_DIR);
 
         // Try to restore local replica from specified shard snapshot directory
         let restore = async {
-            if clear {
+            if has_backup {
+                // Leftover of an interrupted restore
+                if backup_path.exists() {
+                    tokio::fs::remove_dir_all(&backup_path).await?;
+                }
+                tokio::fs::create_dir_all(&backup_path).await?;
+                LocalShard::move_data(&self.shard_path, &backup_path).await?;
+            } else {
                 LocalShard::clear(&self.shard_path).await?;
             }
 
             LocalShard::move_data(replica_path, &self.shard_path).await?;
 
-            LocalShard::load(
-                self.shard_id,
-                self.collection_id.clone(),
-                &self.shard_path,
-                self.collection_config.clone(),
-                self.shared_storage_config.clone(),
-                self.update_runtime.clone(),
-            )
-            .await
+            self.load_local_shard().await
         };
 
-        match restore.await {
+        let restore_err = match restore.await {
             Ok(new_local) => {
-                local.replace(Local(new_local));
-                Ok(true)
+                self.local.write().await.replace(Local(new_local));
+                if has_backup {
+                    if let Err(err) = tokio::fs::remove_dir_all(&backup_path).await {
+                        log::warn!(
+                            "Failed to remove backup of shard {} at {}: {err}",
+                            self.shard_id,
+                            backup_path.display(),
+                        );
+                    }
+                }
+                return Ok(true);
+            }
+            Err(restore_err) => restore_err,
+        };
+
+        if has_backup {
+            let rollback = async {
+                LocalShard::clear(&self.shard_path).await?;
+                LocalShard::move_data(&backup_path, &self.shard_path).await?;
+                tokio::fs::remove_dir_all(&backup_path).await?;
+                self.load_local_shard().await
+            };
+
+            match rollback.await {
+                Ok(previous_local) => {
+                    self.local.write().await.replace(Local(previous_local));
+                    return Err(restore_err);
+                }
+                Err(rollback_err) => {
+                    log::error!(
+                        "Failed to load previous data of shard {} after restore failed: {rollback_err}",
+                        self.shard_id,
+                    );
+                }
             }
+        }
 
-            Err(restore_err) => {
-                // Initialize "dummy" replica
-                local.replace(Dummy(DummyShard::new("Failed to restore local replica")));
+        // Initialize "dummy" replica
+        self.local
+            .write()
+            .await
+            .replace(Dummy(DummyShard::new("Failed to restore local replica")));
 
-                // TODO: Handle single-node mode!? (How!? 😰)
+        // TODO: Handle single-node mode!? (How!? 😰)
 
-                // Mark this peer as "locally disabled"...
-                let has_other_active_peers = self.active_remote_shards().await.is_empty();
+        // Mark this peer as "locally disabled"...
+        let has_other_active_peers = self.active_remote_shards().await.is_empty();
 
-                // ...if this peer is *not* the last active replica
-                if has_other_active_peers {
-                    self.locally_disabled_peers
-                        .write()
-                        .insert(self.this_peer_id()); // TODO: Blocking `write` call in async context
+        // ...if this peer is *not* the last active replica
+        if has_other_active_peers {
+            self.locally_disabled_peers
+                .write()
+                .insert(self.this_peer_id()); // TODO: Blocking `write` call in async context
 
-                    // Notify peer failure
-                    self.notify_peer_failure_cb.deref()(self.this_peer_id(), self.shard_id);
-                }
+            // Notify peer failure
+            self.notify_peer_failure_cb.deref()(self.this_peer_id(), self.shard_id);
+        }
 
-                // Remove shard directory, so we don't leave empty directory/corrupted data
-                match tokio::fs::remove_dir_all(&self.shard_path).await {
-                    Ok(()) => Err(restore_err),
+        // Remove shard directory, so we don't leave empty directory/corrupted data
+        match tokio::fs::remove_dir_all(&self.shard_path).await {
+            Ok(()) => Err(restore_err),
 
-                    Err(cleanup_err) => {
-                        log::error!(
-                            "Failed to cleanup shard {} directory ({}) after restore failed: \
-                             {cleanup_err}",
-                            self.shard_id,
-                            self.shard_path.display(),
-                        );
+            Err(cleanup_err) => {
+                log::error!(
+                    "Failed to cleanup shard {} directory ({}) after restore failed: \
+                     {cleanup_err}",
+                    self.shard_id,
+                    self.shard_path.display(),
+                );
 
-                        // TODO: Contextualize `restore_err` with `cleanup_err` details!?
-                        Err(restore_err)
-                    }
-                }
+                // TODO: Contextualize `restore_err` with `cleanup_err` details!?
+                Err(restore_err)
             }
         }
     }
 
+    async fn load_local_shard(&self) -> CollectionResult<LocalShard> {
+        LocalShard::load(
+            self.shard_id,
+            self.collection_id.clone(),
+            &self.shard_path,
+            self.collection_config.clone(),
+            self.shared_storage_config.clone(),
+            self.update_runtime.clone(),
+        )
+        .await
+    }
+
     pub fn restore_snapshot(
         snapshot_path: &Path,
         this_peer_id: PeerId,
@@ -1160,18 +1272,32 @@ impl ShardReplicaSet {
         Ok(())
     }
 
+    /// Create snapshot of the local replica
+    ///
+    /// If `filter` is specified, only points matching it are included into the snapshot.
+    /// Filtered snapshots never contain WAL, so `save_wal` is ignored in this case.
     pub async fn create_snapshot(
         &self,
         temp_path: &Path,
         target_path: &Path,
         save_wal: bool,
+        filter: Option<&Filter>,
     ) -> CollectionResult<()> {
         let local_read = self.local.read().await;
 
         if let Some(local) = &*local_read {
-            local
-                .create_snapshot(temp_path, target_path, save_wal)
-                .await?
+            match filter {
+                Some(filter) => {
+                    local
+                        .filtered_snapshot(temp_path, target_path, filter)
+                        .await?
+                }
+                None => {
+                    local
+                        .create_snapshot(temp_path, target_path, save_wal)
+                        .await?
+                }
+            }
         }
 
         self.replica_state
@@ -1182,6 +1308,70 @@ impl ShardReplicaSet {
         Ok(())
     }
 
+    /// Create a snapshot of the local shard and pack it into a temporary archive in `temp_dir`,
+    /// which starts with the snapshot manifest
+    pub async fn create_snapshot_archive(
+        &self,
+        collection_id: &CollectionId,
+        temp_dir: &Path,
+        snapshot_file_name: &str,
+        include_wal: bool,
+        filter: Option<&Filter>,
+    ) -> CollectionResult<NamedTempFile> {
+        let snapshot_temp_dir = tempfile::Builder::new()
+            .prefix(&format!("{snapshot_file_name}-temp-"))
+            .tempdir_in(temp_dir)?;
+
+        let snapshot_target_dir = tempfile::Builder::new()
+            .prefix(&format!("{snapshot_file_name}-target-"))
+            .tempdir_in(temp_dir)?;
+
+        self.create_snapshot(
+            snapshot_temp_dir.path(),
+            snapshot_target_dir.path(),
+            include_wal,
+            filter,
+        )
+        .await?;
+
+        if let Err(err) = snapshot_temp_dir.close() {
+            log::error!("Failed to remove temporary directory: {err}");
+        }
+
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(&format!("{snapshot_file_name}-"))
+            .tempfile_in(temp_dir)?;
+
+        let manifest = ShardSnapshotManifest {
+            created_at: chrono::Utc::now().naive_utc(),
+            qdrant_version: env!("CARGO_PKG_VERSION").to_string(),
+            shard_id: self.shard_id,
+            collection_id: collection_id.clone(),
+        };
+
+        let task = {
+            let snapshot_target_dir = snapshot_target_dir.path().to_path_buf();
+
+            tokio::task::spawn_blocking(move || -> CollectionResult<_> {
+                let mut tar = TarBuilder::new(temp_file.as_file_mut());
+                manifest.append_to(&mut tar)?;
+                tar.append_dir_all(".", &snapshot_target_dir)?;
+                tar.finish()?;
+                drop(tar);
+
+                Ok(temp_file)
+            })
+        };
+
+        let task_result = task.await;
+
+        if let Err(err) = snapshot_target_dir.close() {
+            log::error!("Failed to remove temporary directory: {err}");
+        }
+
+        task_result?
+    }
+
     pub async fn proxify_local(&self, remote_shard: RemoteShard) -> CollectionResult<()> {
         let mut local_write = self.local.write().await;
 
@@ -1350,24 +1540,153 @@ impl ShardReplicaSet {
     }
 
     /// Update local shard if any without forwarding to remote shards
+    ///
+    /// If `with_result` is false, the update is not waited for and
+    /// [`UpdateResult::acknowledged`] is returned right after it is written into the WAL.
     pub async fn update_local(
         &self,
         operation: CollectionUpdateOperations,
         wait: bool,
+        with_result: bool,
     ) -> CollectionResult<Option<UpdateResult>> {
-        if let Some(local_shard) = &*self.local.read().await {
+        let wait = wait && with_result;
+        let result = if let Some(local_shard) = &*self.local.read().await {
             match self.peer_state(&self.this_peer_id()) {
                 Some(ReplicaState::Active | ReplicaState::Partial | ReplicaState::Initializing) => {
-                    Ok(Some(local_shard.get().update(operation, wait).await?))
+                    Some(local_shard.get().update(operation, wait).await?)
                 }
                 Some(ReplicaState::Listener) => {
-                    Ok(Some(local_shard.get().update(operation, false).await?))
+                    Some(local_shard.get().update(operation, false).await?)
                 }
-                Some(ReplicaState::Dead) | None => Ok(None),
+                Some(ReplicaState::Dead | ReplicaState::Recovering) | None => None,
             }
         } else {
-            Ok(None)
+            None
+        };
+        Ok(result.map(|result| {
+            if with_result {
+                result
+            } else {
+                UpdateResult::acknowledged(result.shards_written)
+            }
+        }))
+    }
+
+    /// Same as `update_local`, but if the local replica is `Partial`, client updates are buffered
+    /// and applied only once the shard transfer into the replica is finished.
+    ///
+    /// Batches of the stream transfer are applied right away. Buffering continues after the
+    /// replica became `Active`, until all buffered operations are applied, to keep their order.
+    ///
+    /// Buffered operations are persisted before responding. If `wait` is set, the response is
+    /// delayed until the operation is applied, which may take until the transfer is finished.
+    pub async fn update_local_or_buffer(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+        with_result: bool,
+    ) -> CollectionResult<Option<UpdateResult>> {
+        let is_transfer_batch = matches!(
+            operation,
+            CollectionUpdateOperations::PointOperation(PointOperations::SyncPoints(_)),
+        );
+        if !is_transfer_batch {
+            let mut pending_operations = self.pending_operations.lock().await;
+            let buffer = match self.peer_state(&self.this_peer_id()) {
+                Some(ReplicaState::Partial) => true,
+                Some(ReplicaState::Active) => !pending_operations.is_empty(),
+                _ => false,
+            };
+            if buffer && self.has_local_shard().await {
+                let operation_id = pending_operations.push(self.shard_id, &operation)?;
+                if !with_result {
+                    return Ok(Some(UpdateResult::acknowledged(1)));
+                }
+                if !wait {
+                    return Ok(Some(UpdateResult {
+                        operation_id: Some(operation_id),
+                        status: UpdateStatus::Acknowledged,
+                        shards_written: 1,
+                    }));
+                }
+
+                let applied = pending_operations.notify_applied(operation_id);
+                drop(pending_operations);
+                applied.await.map_err(|_| {
+                    CollectionError::service_error(format!(
+                        "Update buffered for shard {} was dropped before it was applied, \
+                         the shard transfer was aborted",
+                        self.shard_id,
+                    ))
+                })?;
+                return Ok(Some(UpdateResult {
+                    operation_id: Some(operation_id),
+                    status: UpdateStatus::Completed,
+                    shards_written: 1,
+                }));
+            }
         }
+        self.update_local(operation, wait, with_result).await
+    }
+
+    pub async fn has_pending_operations(&self) -> bool {
+        !self.pending_operations.is_empty().await
+    }
+
+    /// Apply the operations buffered while the local replica was `Partial`.
+    ///
+    /// Must be called once the replica is `Active`. Operations are applied in batches, the buffer
+    /// is only locked while a batch is read, so new updates are buffered behind it meanwhile.
+    /// Does nothing if the buffer is already being applied by another task.
+    pub async fn apply_pending_operations(&self) -> CollectionResult<()> {
+        let Some(_drain_guard) = self.pending_operations.try_lock_drain() else {
+            return Ok(());
+        };
+        if self.peer_state(&self.this_peer_id()) != Some(ReplicaState::Active) {
+            return Ok(());
+        }
+
+        loop {
+            let batch = self
+                .pending_operations
+                .lock()
+                .await
+                .read(PENDING_OPERATIONS_BATCH_SIZE);
+            let Some((last_operation_id, _)) = batch.last() else {
+                return Ok(());
+            };
+            let last_operation_id = *last_operation_id;
+
+            log::debug!(
+                "Applying {} operations buffered during transfer of shard {}",
+                batch.len(),
+                self.shard_id,
+            );
+            for (operation_id, operation) in batch {
+                // Local updates are applied in order, waiting for the last one is enough
+                let wait = operation_id == last_operation_id;
+                match self.update_local(operation, wait, true).await? {
+                    Some(_) => {}
+                    None => {
+                        return Err(CollectionError::service_error(format!(
+                            "Local replica of shard {} is not active, \
+                             buffered operations can't be applied",
+                            self.shard_id,
+                        )));
+                    }
+                }
+            }
+
+            self.pending_operations
+                .lock()
+                .await
+                .ack(last_operation_id)?;
+        }
+    }
+
+    /// Drop the operations buffered while the local replica was `Partial`
+    pub async fn clear_pending_operations(&self) -> CollectionResult<()> {
+        self.pending_operations.clear().await
     }
 
     /// Custom operation for transferring data from one shard to another during transfer
@@ -1478,11 +1797,14 @@ impl ShardReplicaSet {
         Ok(())
     }
 
+    /// If `with_result` is false, the update is not waited for and
+    /// [`UpdateResult::acknowledged`] is returned right after it is written into the WAL.
     pub async fn update_with_consistency(
         &self,
         operation: CollectionUpdateOperations,
         wait: bool,
         ordering: WriteOrdering,
+        with_result: bool,
     ) -> CollectionResult<UpdateResult> {
         match self.leader_peer_for_update(ordering) {
             None => Err(CollectionError::service_error(format!(
@@ -1497,11 +1819,18 @@ impl ShardReplicaSet {
                         WriteOrdering::Weak => None, // no locking required
                         WriteOrdering::Medium | WriteOrdering::Strong => Some(self.write_ordering_lock.lock().await), // one request at a time
                     };
-                    self.update(operation, wait).await
+                    self.update(operation, wait, with_result).await
                 } else {
                     // forward the update to the designated leader
-                    self.forward_update(leader_peer, operation, wait, ordering)
+                    self.forward_update(leader_peer, operation, wait && with_result, ordering)
                         .await
+                        .map(|result| {
+                            if with_result {
+                                result
+                            } else {
+                                UpdateResult::acknowledged(result.shards_written)
+                            }
+                        })
                         .map_err(|err| {
                             if err.is_transient() {
                                 // Deactivate the peer if forwarding failed with transient error
@@ -1557,7 +1886,9 @@ impl ShardReplicaSet {
         &self,
         operation: CollectionUpdateOperations,
         wait: bool,
+        with_result: bool,
     ) -> CollectionResult<UpdateResult> {
+        let wait = wait && with_result;
         let all_res: Vec<Result<_, _>> = {
             let remotes = self.remotes.read().await;
             let local = self.local.read().await;
@@ -1686,12 +2017,19 @@ impl ShardReplicaSet {
                 return Err(err);
             }
         }
-        // there are enough successes, return the first one
+        // there are enough successes, return the first one with the number of acknowledgements
+        let shards_written = successes.iter().map(|res| res.shards_written).sum();
+        if !with_result {
+            return Ok(UpdateResult::acknowledged(shards_written));
+        }
         let res = successes
             .into_iter()
             .next()
             .expect("successes is not empty");
-        Ok(res)
+        Ok(UpdateResult {
+            shards_written,
+            ..res
+        })
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1756,6 +2094,110 @@ impl ShardReplicaSet {
         .await
     }
 
+    /// Same as `search`, but also reports the HNSW `ef` used by each search and whether
+    /// the results were served from the shard cache, see [`ShardOperation::search_with_info`].
+    ///
+    /// If several replicas are queried, the lowest `ef` among them is reported for each search.
+    /// Results are considered to be served from cache only if all queried replicas report a hit.
+    pub async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        read_consistency: Option<ReadConsistency>,
+    ) -> CollectionResult<ShardSearchResult> {
+        let hnsw_ef_used = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let cache_misses = Arc::new(AtomicUsize::new(0));
+
+        let results = self
+            .execute_and_resolve_read_operation(
+                |shard| {
+                    let request = request.clone();
+                    let search_runtime = self.search_runtime.clone();
+                    let hnsw_ef_used = hnsw_ef_used.clone();
+                    let cache_misses = cache_misses.clone();
+
+                    async move {
+                        let result = shard.search_with_info(request, &search_runtime).await?;
+                        ShardSearchResult::merge_hnsw_ef_used(
+                            &mut hnsw_ef_used.lock(),
+                            &result.hnsw_ef_used,
+                        );
+                        if !result.cache_hit {
+                            cache_misses.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Ok(result.results)
+                    }
+                    .boxed()
+                },
+                read_consistency.unwrap_or_default(),
+            )
+            .await?;
+
+        let hnsw_ef_used = std::mem::take(&mut *hnsw_ef_used.lock());
+        Ok(ShardSearchResult {
+            results,
+            hnsw_ef_used,
+            cache_hit: cache_misses.load(Ordering::Relaxed) == 0,
+        })
+    }
+
+    /// Estimate the cost of the search batch on this shard without executing it.
+    ///
+    /// Returns the estimated number of candidate points and the estimated size of returned payloads.
+    pub async fn estimate_search_cost(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        read_consistency: Option<ReadConsistency>,
+    ) -> CollectionResult<(usize, u64)> {
+        /// Number of points used to estimate the average payload size
+        const PAYLOAD_SAMPLE_SIZE: usize = 16;
+
+        let mut candidates = 0;
+        let mut returned_points = 0;
+        for search in &request.searches {
+            let count_request = Arc::new(CountRequest {
+                filter: search.filter.clone(),
+                exact: false,
+                shard_key: None,
+            });
+            let search_candidates = self.count(count_request).await?.count;
+            candidates += search_candidates;
+
+            let with_payload = search
+                .with_payload
+                .as_ref()
+                .map_or(false, |with_payload| with_payload.is_required());
+            if with_payload {
+                returned_points += search_candidates.min(search.limit + search.offset);
+            }
+        }
+
+        if returned_points == 0 {
+            return Ok((candidates, 0));
+        }
+
+        let sample = self
+            .scroll_by(
+                None,
+                PAYLOAD_SAMPLE_SIZE,
+                &WithPayloadInterface::Bool(true),
+                &WithVector::Bool(false),
+                None,
+                read_consistency,
+            )
+            .await?;
+        if sample.is_empty() {
+            return Ok((candidates, 0));
+        }
+        let sample_bytes: usize = sample
+            .iter()
+            .filter_map(|record| record.payload.as_ref())
+            .map(|payload| serde_json::to_vec(payload).map_or(0, |bytes| bytes.len()))
+            .sum();
+        let avg_payload_bytes = sample_bytes as u64 / sample.len() as u64;
+
+        Ok((candidates, avg_payload_bytes * returned_points as u64))
+    }
+
     pub async fn count_local(
         &self,
         request: Arc<CountRequest>,
@@ -1767,6 +2209,19 @@ impl ShardReplicaSet {
         }
     }
 
+    /// Estimation of the segments of the local shard, which would be read by a search with the
+    /// given filter. `None` if there is no local shard, or if it is being transferred.
+    pub async fn segment_read_estimation_local(
+        &self,
+        filter: Option<&Filter>,
+    ) -> Option<SegmentReadEstimation> {
+        let local = self.local.read().await;
+        match &*local {
+            Some(Local(local_shard)) => Some(local_shard.segment_read_estimation(filter)),
+            _ => None,
+        }
+    }
+
     pub async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         self.execute_read_operation(|shard| {
             let request = request.clone();
@@ -1775,6 +2230,33 @@ impl ShardReplicaSet {
         .await
     }
 
+    /// Same as `count`, but stops and returns `CollectionError::Cancelled` once `cancel` is cancelled.
+    ///
+    /// The active local shard checks the token during its scan, remote replicas are abandoned
+    /// on cancellation.
+    pub async fn count_cancellable(
+        &self,
+        request: Arc<CountRequest>,
+        cancel: &CancellationToken,
+    ) -> CollectionResult<CountResult> {
+        {
+            let local = self.local.read().await;
+            if let Some(Local(local_shard)) = &*local {
+                if self.peer_is_active(&self.this_peer_id()) {
+                    return local_shard.count_cancellable(request, cancel).await;
+                }
+            }
+        }
+
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(CollectionError::Cancelled {
+                description: "Count operation was cancelled".to_string(),
+            }),
+            result = self.count(request) => result,
+        }
+    }
+
     pub async fn retrieve(
         &self,
         request: Arc<PointRequest>,
@@ -1799,6 +2281,101 @@ impl ShardReplicaSet {
     }
 }
 
+/// Await read operations until `factor` of them succeed.
+///
+/// First `required_reads` operations are started at once, the rest are only started to replace
+/// the failed ones. If `reconcile` is set and the first `factor` responses are not consistent,
+/// responses of all remaining operations are awaited as well.
+async fn collect_read_responses<Fut, Res>(
+    shard_id: ShardId,
+    mut operations: impl Iterator<Item = Fut>,
+    required_reads: usize,
+    factor: usize,
+    reconcile: bool,
+) -> CollectionResult<Vec<Res>>
+where
+    Fut: Future<Output = CollectionResult<Res>>,
+    Res: Resolve,
+{
+    let mut pending_operations: FuturesUnordered<_> =
+        operations.by_ref().take(required_reads).collect();
+
+    let mut responses = Vec::new();
+    let mut errors = Vec::new();
+    let mut reconciling = false;
+
+    while let Some(result) = pending_operations.next().await {
+        match result {
+            Ok(resp) => responses.push(resp),
+
+            Err(err) => {
+                let is_transient = err.is_transient();
+
+                if is_transient {
+                    log::debug!("Read operation failed: {err}");
+                    errors.push(err);
+                } else {
+                    return Err(err);
+                }
+            }
+        }
+
+        if responses.len() >= factor {
+            if !reconcile || pending_operations.is_empty() {
+                break;
+            }
+
+            if !reconciling {
+                if Res::is_consistent(&responses) {
+                    break;
+                }
+
+                log::warn!(
+                    "Responses of {} replicas of shard {shard_id} are inconsistent, \
+                     waiting for {} more replicas to reconcile them",
+                    responses.len(),
+                    pending_operations.len(),
+                );
+                reconciling = true;
+            }
+
+            continue;
+        }
+
+        let maybe_responses = responses.len() + pending_operations.len();
+
+        let schedule = factor.saturating_sub(maybe_responses);
+        pending_operations.extend(operations.by_ref().take(schedule));
+
+        let maybe_responses = responses.len() + pending_operations.len();
+
+        if maybe_responses < factor {
+            break;
+        }
+    }
+
+    if responses.len() >= factor {
+        Ok(responses)
+    } else {
+        let success_count = responses.len();
+        let error_count = errors.len();
+
+        Err(CollectionError::service_error(format!(
+            "{error_count} of {} shards failed with: {}",
+            success_count + error_count,
+            errors.into_iter().fold(String::new(), |mut msg, err| {
+                if msg.is_empty() {
+                    msg = err.to_string();
+                } else {
+                    write!(&mut msg, ", {err}").unwrap(); // Writing into `String` never fails
+                }
+
+                msg
+            })
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::{NonZeroU32, NonZeroU64};
@@ -1808,8 +2385,15 @@ mod tests {
 
     use super::*;
     use crate::config::*;
+    use crate::hash_ring::HashRing;
+    use crate::operations::point_ops::{Batch, PointStruct, PointSyncOperation};
     use crate::operations::types::{VectorParams, VectorsConfig};
     use crate::optimizers_builder::OptimizersConfig;
+    use crate::shards::shard_holder::ShardHolder;
+    use crate::shards::transfer::shard_transfer::{
+        handle_transferred_shard_proxy, TransferCompensation,
+    };
+    use crate::shards::HASH_RING_SHARD_SCALE;
 
     const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
         deleted_threshold: 0.9,
@@ -1826,7 +2410,7 @@ mod tests {
         Arc::new(move |_peer_id, _shard_id| {})
     }
 
-    async fn new_shard_replica_set(collection_dir: &TempDir) -> ShardReplicaSet {
+    async fn new_shard_replica_set(collection_dir: &TempDir, local: bool) -> ShardReplicaSet {
         let update_runtime = Handle::current();
         let search_runtime = Handle::current();
 
@@ -1847,6 +2431,8 @@ mod tests {
             replication_factor: NonZeroU32::new(3).unwrap(),
             write_consistency_factor: NonZeroU32::new(2).unwrap(),
             on_disk_payload: false,
+            max_payload_size_bytes: None,
+            read_only: false,
         };
 
         let config = CollectionConfig {
@@ -1855,6 +2441,7 @@ mod tests {
             wal_config,
             hnsw_config: Default::default(),
             quantization_config: None,
+            use_cross_device_snapshot_copy: None,
         };
 
         let shared_config = Arc::new(RwLock::new(config.clone()));
@@ -1863,7 +2450,7 @@ mod tests {
             1,
             "test_collection".to_string(),
             1,
-            false,
+            local,
             remotes,
             dummy_on_replica_failure(),
             collection_dir.path(),
@@ -1880,7 +2467,7 @@ mod tests {
     #[tokio::test]
     async fn test_highest_replica_peer_id() {
         let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
-        let rs = new_shard_replica_set(&collection_dir).await;
+        let rs = new_shard_replica_set(&collection_dir, false).await;
 
         assert_eq!(rs.highest_replica_peer_id(), Some(5));
         // at build time the replicas are all dead, they need to be activated
@@ -1894,4 +2481,242 @@ mod tests {
         assert_eq!(rs.highest_replica_peer_id(), Some(5));
         assert_eq!(rs.highest_alive_replica_peer_id(), Some(4));
     }
+
+    #[tokio::test]
+    async fn test_partial_replica_buffers_updates() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let rs = new_shard_replica_set(&collection_dir, true).await;
+        rs.set_replica_state(&1, ReplicaState::Partial).unwrap();
+
+        // Batches of the transfer itself are applied right away
+        let transfer_batch = CollectionUpdateOperations::PointOperation(
+            PointOperations::SyncPoints(PointSyncOperation {
+                from_id: None,
+                to_id: None,
+                points: vec![PointStruct {
+                    id: 3.into(),
+                    vector: vec![0.0, 1.0, 0.0, 1.0].into(),
+                    payload: None,
+                }],
+            }),
+        );
+        let result = rs
+            .update_local_or_buffer(transfer_batch, true, true)
+            .await
+            .unwrap();
+        assert_eq!(result.unwrap().status, UpdateStatus::Completed);
+        assert!(rs.pending_operations.is_empty().await);
+
+        let upsert = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![1.into(), 2.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        );
+        let result = rs
+            .update_local_or_buffer(upsert, false, true)
+            .await
+            .unwrap();
+        assert_eq!(result.unwrap().status, UpdateStatus::Acknowledged);
+        assert_eq!(rs.pending_operations.len().await, 1);
+
+        let count_request = Arc::new(CountRequest {
+            filter: None,
+            exact: true,
+            shard_key: None,
+        });
+
+        // Waiting for a buffered update responds only once it is applied
+        let upsert = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![4.into()],
+                vectors: vec![vec![0.0, 0.0, 1.0, 1.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        );
+        let (result, ()) = tokio::join!(rs.update_local_or_buffer(upsert, true, true), async {
+            while rs.pending_operations.len().await < 2 {
+                tokio::task::yield_now().await;
+            }
+            let count = rs
+                .count_local(count_request.clone())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(count.count, 1, "buffered updates must not be applied yet");
+
+            rs.set_replica_state(&1, ReplicaState::Active).unwrap();
+            rs.apply_pending_operations().await.unwrap();
+        });
+        assert_eq!(result.unwrap().unwrap().status, UpdateStatus::Completed);
+        assert!(rs.pending_operations.is_empty().await);
+        assert!(!rs.shard_path.join("pending_operations").exists());
+
+        let count = rs.count_local(count_request).await.unwrap().unwrap();
+        assert_eq!(count.count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_revert_moved_shard_proxy() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let rs = new_shard_replica_set(&collection_dir, true).await;
+        rs.set_replica_state(&1, ReplicaState::Active).unwrap();
+
+        let upsert = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![1.into(), 2.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        );
+        rs.update_local(upsert, true, true).await.unwrap();
+
+        let remote_state = rs.peer_state(&2);
+        let remote_shard = RemoteShard::new(
+            1,
+            "test_collection".to_string(),
+            2,
+            ChannelService::default(),
+        );
+        rs.proxify_local(remote_shard).await.unwrap();
+
+        let mut shard_holder =
+            ShardHolder::new(collection_dir.path(), HashRing::fair(HASH_RING_SHARD_SCALE)).unwrap();
+        shard_holder.add_shard(1, rs);
+
+        // Move shard to peer 2, local shard is detached but its data is kept
+        let mut detached_local = None;
+        let promoted =
+            handle_transferred_shard_proxy(&shard_holder, 1, 2, false, &mut detached_local)
+                .await
+                .unwrap();
+        assert!(promoted);
+        assert!(detached_local.is_some());
+
+        let rs = shard_holder.get_shard(&1).unwrap();
+        assert!(!rs.has_local_shard().await);
+        assert_eq!(rs.peer_state(&1), None);
+        assert!(LocalShard::check_data(&rs.shard_path));
+
+        // Roll back the finished transfer, compensations are applied in reverse order
+        let compensations = [
+            TransferCompensation::RestorePeerState {
+                peer_id: 1,
+                state: Some(ReplicaState::Active),
+            },
+            TransferCompensation::RestorePeerState {
+                peer_id: 2,
+                state: remote_state,
+            },
+            TransferCompensation::RevertProxy,
+        ];
+        for compensation in compensations.iter().rev() {
+            compensation
+                .apply(&shard_holder, 1, &mut detached_local)
+                .await
+                .unwrap();
+        }
+        assert!(detached_local.is_none());
+
+        assert!(rs.is_local().await);
+        assert_eq!(rs.peer_state(&1), Some(ReplicaState::Active));
+        assert_eq!(rs.peer_state(&2), remote_state);
+
+        let count_request = Arc::new(CountRequest {
+            filter: None,
+            exact: true,
+            shard_key: None,
+        });
+        let count = rs.count_local(count_request).await.unwrap().unwrap();
+        assert_eq!(count.count, 2);
+    }
+
+    type SearchResponse = CollectionResult<Vec<Vec<ScoredPoint>>>;
+
+    fn search_response(ids: &[u64]) -> SearchResponse {
+        let points = ids
+            .iter()
+            .map(|&id| ScoredPoint {
+                id: id.into(),
+                version: 1,
+                score: id as f32,
+                payload: None,
+                vector: None,
+                score_breakdown: None,
+                search_params_echo: None,
+            })
+            .collect();
+        Ok(vec![points])
+    }
+
+    fn ready_response(ids: &[u64]) -> BoxFuture<'static, SearchResponse> {
+        futures::future::ready(search_response(ids)).boxed()
+    }
+
+    fn delayed_response(ids: &[u64]) -> BoxFuture<'static, SearchResponse> {
+        let response = search_response(ids);
+        async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            response
+        }
+        .boxed()
+    }
+
+    #[tokio::test]
+    async fn test_quorum_read_reconciliation() {
+        // Consistent quorum is returned without waiting for the slow replica
+        let operations = vec![
+            ready_response(&[1, 2]),
+            ready_response(&[1, 2]),
+            futures::future::pending().boxed(),
+        ];
+        let responses = collect_read_responses(1, operations.into_iter(), 3, 2, true)
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 2);
+
+        // Conflicting quorum waits for the remaining replica to reconcile responses
+        let operations = vec![
+            ready_response(&[1, 2]),
+            ready_response(&[1, 3]),
+            delayed_response(&[1, 2]),
+        ];
+        let responses = collect_read_responses(1, operations.into_iter(), 3, 2, true)
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 3);
+
+        let resolved = Vec::<Vec<ScoredPoint>>::resolve(responses, ResolveCondition::Quorum(2));
+        assert_eq!(resolved, search_response(&[1, 2]).unwrap());
+
+        // Without reconciliation the first responses are resolved as they are
+        let operations = vec![
+            ready_response(&[1, 2]),
+            ready_response(&[1, 3]),
+            delayed_response(&[1, 2]),
+        ];
+        let responses = collect_read_responses(1, operations.into_iter(), 2, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 2);
+
+        // Quorum can not be reached if too many replicas fail
+        let operations = vec![
+            ready_response(&[1, 2]),
+            futures::future::ready(Err(CollectionError::service_error(
+                "replica is down".into(),
+            )))
+            .boxed(),
+            futures::future::ready(Err(CollectionError::service_error(
+                "replica is down".into(),
+            )))
+            .boxed(),
+        ];
+        let result = collect_read_responses(1, operations.into_iter(), 3, 2, true).await;
+        assert!(result.is_err());
+    }
 }
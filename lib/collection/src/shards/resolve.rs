@@ -10,10 +10,15 @@ use crate::operations::types::Record;
 pub enum ResolveCondition {
     All,
     Majority,
+    /// Item has to be present in at least the given number of responses
+    Quorum(usize),
 }
 
 pub trait Resolve: Sized {
     fn resolve(responses: Vec<Self>, condition: ResolveCondition) -> Self;
+
+    /// Check if all responses are identical, so that no resolution is required
+    fn is_consistent(responses: &[Self]) -> bool;
 }
 
 impl Resolve for Vec<Record> {
@@ -22,6 +27,10 @@ impl Resolve for Vec<Record> {
         resolved.sort_unstable_by_key(|record| record.id);
         resolved
     }
+
+    fn is_consistent(records: &[Self]) -> bool {
+        responses_eq(records, record_eq)
+    }
 }
 
 impl Resolve for Vec<Vec<ScoredPoint>> {
@@ -42,6 +51,20 @@ impl Resolve for Vec<Vec<ScoredPoint>> {
             })
             .collect()
     }
+
+    fn is_consistent(batches: &[Self]) -> bool {
+        let Some(first) = batches.first() else {
+            return true;
+        };
+
+        batches.iter().all(|batch| {
+            batch.len() == first.len()
+                && batch
+                    .iter()
+                    .zip(first)
+                    .all(|(points, first_points)| items_eq(points, first_points, scored_point_eq))
+        })
+    }
 }
 
 fn transpose<T>(vec: Vec<Vec<T>>) -> Vec<Vec<T>> {
@@ -58,6 +81,18 @@ fn transpose<T>(vec: Vec<Vec<T>>) -> Vec<Vec<T>> {
         .collect()
 }
 
+fn responses_eq<T>(responses: &[Vec<T>], eq: impl Fn(&T, &T) -> bool) -> bool {
+    let Some(first) = responses.first() else {
+        return true;
+    };
+
+    responses.iter().all(|items| items_eq(items, first, &eq))
+}
+
+fn items_eq<T>(this: &[T], other: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    this.len() == other.len() && this.iter().zip(other).all(|(this, other)| eq(this, other))
+}
+
 fn record_eq(this: &Record, other: &Record) -> bool {
     this.id == other.id && this.vector == other.vector && payload_eq(&this.payload, &other.payload)
 }
@@ -101,6 +136,7 @@ where
         let resolution_count = match condition {
             ResolveCondition::All => items.len(),
             ResolveCondition::Majority => items.len() / 2 + 1,
+            ResolveCondition::Quorum(count) => count,
         };
 
         let mut resolver = Resolver::new(items.first().map_or(0, Vec::len), identify, compare);
@@ -125,6 +161,11 @@ where
         if is_consistent {
             items.into_iter().next().unwrap_or_default()
         } else {
+            log::warn!(
+                "Responses of {} replicas are inconsistent, only {} items confirmed by at least {resolution_count} of them are returned",
+                items.len(),
+                resolved_items.len(),
+            );
             items
                 .into_iter()
                 .enumerate()
@@ -251,6 +292,8 @@ mod test {
             score,
             payload: None,
             vector: None,
+            score_breakdown: None,
+            search_params_echo: None,
         }
     }
 
@@ -497,6 +540,46 @@ mod test {
         test_resolve_simple(input_4(), expected_4_majority(), ResolveCondition::Majority);
     }
 
+    #[test]
+    fn resolve_scored_points_disagreement() {
+        // Third replica has an outdated score of point 3 and misses point 2
+        let input = vec![
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            vec![vec![point(1, 0.1), point(3, 0.5)]],
+        ];
+
+        // Majority of replicas agrees on every point
+        test_resolve(
+            input.clone(),
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            ResolveCondition::Majority,
+        );
+
+        // Only the point all replicas agree on is returned
+        test_resolve(input, vec![vec![point(1, 0.1)]], ResolveCondition::All);
+    }
+
+    #[test]
+    fn resolve_scored_points_quorum() {
+        // 5 replicas in total, 4 of them responded, 3 of them form a quorum
+        let input = vec![
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            vec![vec![point(1, 0.1), point(3, 0.3), point(4, 0.4)]],
+            vec![vec![point(1, 0.1), point(2, 0.2), point(4, 0.4)]],
+        ];
+
+        assert!(!Vec::<Vec<ScoredPoint>>::is_consistent(&input));
+        assert!(Vec::<Vec<ScoredPoint>>::is_consistent(&input[..2]));
+
+        test_resolve(
+            input,
+            vec![vec![point(1, 0.1), point(2, 0.2), point(3, 0.3)]],
+            ResolveCondition::Quorum(3),
+        );
+    }
+
     fn test_resolve<T, E>(input: Vec<T>, expected: E, condition: ResolveCondition)
     where
         T: Resolve + Clone + PartialEq<E> + fmt::Debug,
@@ -542,5 +625,9 @@ mod test {
             resolved.sort_unstable();
             resolved
         }
+
+        fn is_consistent(values: &[Self]) -> bool {
+            responses_eq(values, PartialEq::eq)
+        }
     }
 }
@@ -1,6 +1,10 @@
 use core::marker::{Send, Sync};
+use std::collections::HashSet;
 use std::path::Path;
 
+use segment::types::Filter;
+
+use crate::collection_manager::optimizers::segment_optimizer::OptimizerKind;
 use crate::operations::types::CollectionResult;
 use crate::shards::dummy_shard::DummyShard;
 use crate::shards::forward_proxy_shard::ForwardProxyShard;
@@ -95,13 +99,55 @@ impl Shard {
         }
     }
 
-    pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
+    pub async fn filtered_snapshot(
+        &self,
+        temp_path: &Path,
+        target_path: &Path,
+        filter: &Filter,
+    ) -> CollectionResult<()> {
         match self {
-            Shard::Local(local_shard) => local_shard.on_optimizer_config_update().await,
-            Shard::Proxy(proxy_shard) => proxy_shard.on_optimizer_config_update().await,
-            Shard::ForwardProxy(proxy_shard) => proxy_shard.on_optimizer_config_update().await,
-            Shard::QueueProxy(proxy_shard) => proxy_shard.on_optimizer_config_update().await,
-            Shard::Dummy(dummy_shard) => dummy_shard.on_optimizer_config_update().await,
+            Shard::Local(local_shard) => {
+                local_shard
+                    .filtered_snapshot(temp_path, target_path, filter)
+                    .await
+            }
+            Shard::Proxy(proxy_shard) => {
+                proxy_shard
+                    .filtered_snapshot(temp_path, target_path, filter)
+                    .await
+            }
+            Shard::ForwardProxy(proxy_shard) => {
+                proxy_shard
+                    .filtered_snapshot(temp_path, target_path, filter)
+                    .await
+            }
+            Shard::QueueProxy(proxy_shard) => {
+                proxy_shard
+                    .filtered_snapshot(temp_path, target_path, filter)
+                    .await
+            }
+            Shard::Dummy(dummy_shard) => {
+                dummy_shard
+                    .filtered_snapshot(temp_path, target_path, filter)
+                    .await
+            }
+        }
+    }
+
+    pub async fn on_optimizer_config_update(
+        &self,
+        restart_mask: Option<&HashSet<OptimizerKind>>,
+    ) -> CollectionResult<()> {
+        match self {
+            Shard::Local(local_shard) => local_shard.on_optimizer_config_update(restart_mask).await,
+            Shard::Proxy(proxy_shard) => proxy_shard.on_optimizer_config_update(restart_mask).await,
+            Shard::ForwardProxy(proxy_shard) => {
+                proxy_shard.on_optimizer_config_update(restart_mask).await
+            }
+            Shard::QueueProxy(proxy_shard) => {
+                proxy_shard.on_optimizer_config_update(restart_mask).await
+            }
+            Shard::Dummy(dummy_shard) => dummy_shard.on_optimizer_config_update(restart_mask).await,
         }
     }
 }
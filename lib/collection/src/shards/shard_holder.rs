@@ -2,13 +2,17 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
+use dashmap::DashMap;
+use itertools::Itertools;
+use parking_lot::Mutex;
+use segment::types::PointIdType;
 use tokio::runtime::Handle;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex as TokioMutex, OwnedMutexGuard, RwLock};
 
 use crate::config::CollectionConfig;
 use crate::hash_ring::HashRing;
 use crate::operations::shared_storage_config::SharedStorageConfig;
-use crate::operations::types::{CollectionResult, ShardTransferInfo};
+use crate::operations::types::{CollectionError, CollectionResult, ShardFilter, ShardTransferInfo};
 use crate::operations::{OperationToShard, SplitByShard};
 use crate::save_on_disk::SaveOnDisk;
 use crate::shards::channel_service::ChannelService;
@@ -17,25 +21,59 @@ use crate::shards::replica_set::{ChangePeerState, ReplicaState, ShardReplicaSet}
 use crate::shards::shard::{PeerId, ShardId};
 use crate::shards::shard_config::{ShardConfig, ShardType};
 use crate::shards::shard_versioning::latest_shard_paths;
-use crate::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey};
+use crate::shards::transfer::shard_transfer::{
+    AbortedTransfer, ShardTransfer, ShardTransferKey, ShardTransfers,
+};
 use crate::shards::CollectionId;
 
 const SHARD_TRANSFERS_FILE: &str = "shard_transfers";
 
 pub struct ShardHolder {
     shards: HashMap<ShardId, ShardReplicaSet>,
-    pub(crate) shard_transfers: SaveOnDisk<HashSet<ShardTransfer>>,
+    pub(crate) shard_transfers: SaveOnDisk<ShardTransfers>,
+    /// Last aborted transfer of each shard, not persisted
+    aborted_transfers: Mutex<HashMap<ShardId, AbortedTransfer>>,
+    /// Locks used to serialize client updates of the same point
+    point_locks: Arc<PointLocks>,
+    /// Shards known to exclusively hold the data of a tenant, used as a search routing hint.
+    /// Not persisted
+    tenant_to_shard: HashMap<String, ShardId>,
     ring: HashRing<ShardId>,
 }
 
 pub type LockedShardHolder = RwLock<ShardHolder>;
 
+type PointLocks = DashMap<PointIdType, Arc<TokioMutex<()>>>;
+
+/// Exclusive lock of a single point, see [`ShardHolder::lock_points`]
+///
+/// Removes the lock of the point from the holder on drop, if nobody else is holding or waiting for it.
+pub struct PointLockGuard {
+    point_id: PointIdType,
+    point_locks: Arc<PointLocks>,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for PointLockGuard {
+    fn drop(&mut self) {
+        // Release the lock first, so that the map holds the only reference if it is unused
+        self.guard.take();
+        self.point_locks
+            .remove_if(&self.point_id, |_, point_mutex| {
+                Arc::strong_count(point_mutex) == 1
+            });
+    }
+}
+
 impl ShardHolder {
     pub fn new(collection_path: &Path, hashring: HashRing<ShardId>) -> CollectionResult<Self> {
         let shard_transfers = SaveOnDisk::load_or_init(collection_path.join(SHARD_TRANSFERS_FILE))?;
         Ok(Self {
             shards: HashMap::new(),
             shard_transfers,
+            aborted_transfers: Default::default(),
+            point_locks: Default::default(),
+            tenant_to_shard: HashMap::new(),
             ring: hashring,
         })
     }
@@ -48,9 +86,54 @@ impl ShardHolder {
     pub fn remove_shard(&mut self, shard_id: ShardId) -> Option<ShardReplicaSet> {
         let shard = self.shards.remove(&shard_id);
         self.ring.remove(&shard_id);
+        self.tenant_to_shard
+            .retain(|_, tenant_shard_id| *tenant_shard_id != shard_id);
         shard
     }
 
+    /// Ranges of the hash ring positions assigned to each shard, see [`HashRing::ranges`]
+    pub fn shard_key_ranges(&self) -> HashMap<ShardId, Vec<(u64, u64)>> {
+        self.ring.ranges(self.shards.keys().copied())
+    }
+
+    /// Assign tenant to the shard determined by the hash ring.
+    /// Once assigned, searches with this tenant are only routed to the assigned shard.
+    pub fn assign_tenant(&mut self, tenant_id: String) -> Option<ShardId> {
+        if let Some(shard_id) = self.tenant_to_shard.get(&tenant_id) {
+            return Some(*shard_id);
+        }
+        let shard_id = self.ring.shard_for_tenant(&tenant_id)?;
+        self.tenant_to_shard.insert(tenant_id, shard_id);
+        Some(shard_id)
+    }
+
+    pub fn unassign_tenant(&mut self, tenant_id: &str) -> Option<ShardId> {
+        self.tenant_to_shard.remove(tenant_id)
+    }
+
+    pub fn get_tenant_shard(&self, tenant_id: &str) -> Option<ShardId> {
+        self.tenant_to_shard.get(tenant_id).copied()
+    }
+
+    /// Combine explicit shard filter with the routing hint of the tenant.
+    ///
+    /// Unknown tenants do not restrict the set of shards.
+    pub fn resolve_shard_filter(
+        &self,
+        shard_filter: Option<&ShardFilter>,
+        tenant_id: Option<&str>,
+    ) -> Option<ShardFilter> {
+        let Some(tenant_shard_id) = tenant_id.and_then(|tenant| self.get_tenant_shard(tenant))
+        else {
+            return shard_filter.cloned();
+        };
+        let shard_ids = match shard_filter {
+            Some(shard_filter) if !shard_filter.check(tenant_shard_id) => HashSet::new(),
+            _ => HashSet::from([tenant_shard_id]),
+        };
+        Some(ShardFilter::Set(shard_ids))
+    }
+
     /// Take shard
     ///
     /// remove shard and return ownership
@@ -110,20 +193,70 @@ impl ShardHolder {
     pub fn register_start_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<bool> {
         Ok(self
             .shard_transfers
-            .write(|transfers| transfers.insert(transfer))?)
+            .write(|transfers| transfers.insert(transfer.key(), transfer).is_none())?)
     }
 
     pub fn register_finish_transfer(&self, key: &ShardTransferKey) -> CollectionResult<bool> {
-        Ok(self.shard_transfers.write(|transfers| {
-            let before_remove = transfers.len();
-            transfers.retain(|transfer| !key.check(transfer));
-            before_remove != transfers.len() // `true` if something was removed
-        })?)
+        Ok(self
+            .shard_transfers
+            .write(|transfers| transfers.remove(key).is_some())?)
+    }
+
+    /// Unregister the transfer and record it in the abort history with the given reason
+    pub fn register_abort_transfer(
+        &self,
+        key: &ShardTransferKey,
+        reason: Option<&str>,
+    ) -> CollectionResult<bool> {
+        let transfer = self.shard_transfers.read().get(key).cloned();
+
+        let removed = self.register_finish_transfer(key)?;
+
+        if let Some(transfer) = transfer {
+            self.aborted_transfers.lock().insert(
+                key.shard_id,
+                AbortedTransfer {
+                    transfer,
+                    abort_reason: reason.map(ToString::to_string),
+                    aborted_at: chrono::Utc::now(),
+                },
+            );
+        }
+
+        Ok(removed)
+    }
+
+    /// Last aborted transfer of the shard, if any
+    pub fn get_last_aborted_transfer(&self, shard_id: ShardId) -> Option<AbortedTransfer> {
+        self.aborted_transfers.lock().get(&shard_id).cloned()
+    }
+
+    /// Acquire exclusive locks for the given points
+    ///
+    /// Locks are taken in ascending ID order to avoid deadlocks between overlapping operations.
+    /// Locks are released when returned guards are dropped.
+    pub async fn lock_points(&self, mut point_ids: Vec<PointIdType>) -> Vec<PointLockGuard> {
+        point_ids.sort_unstable();
+        point_ids.dedup();
+
+        let mut guards = Vec::with_capacity(point_ids.len());
+        for point_id in point_ids {
+            // Created before waiting, so that the lock is also forgotten if the wait is cancelled
+            let mut guard = PointLockGuard {
+                point_id,
+                point_locks: self.point_locks.clone(),
+                guard: None,
+            };
+            let point_mutex = self.point_locks.entry(point_id).or_default().clone();
+            guard.guard = Some(point_mutex.lock_owned().await);
+            guards.push(guard);
+        }
+        guards
     }
 
     pub fn get_shard_transfer_info(&self) -> Vec<ShardTransferInfo> {
         let mut shard_transfers = vec![];
-        for shard_transfer in self.shard_transfers.read().iter() {
+        for shard_transfer in self.shard_transfers.read().values() {
             let shard_id = shard_transfer.shard_id;
             let to = shard_transfer.to;
             let from = shard_transfer.from;
@@ -146,7 +279,7 @@ impl ShardHolder {
     ) -> Vec<ShardTransfer> {
         self.shard_transfers
             .read()
-            .iter()
+            .values()
             .filter(|transfer| transfer.shard_id == *shard_id)
             .filter(|transfer| transfer.from == *peer_id || transfer.to == *peer_id)
             .cloned()
@@ -170,6 +303,48 @@ impl ShardHolder {
         }
     }
 
+    /// Shards to send a search to, only those passing `shard_filter` are returned.
+    ///
+    /// Without explicit `shard_selection`, shards without any replica able to serve reads are
+    /// skipped, as the search would always fail on them. IDs of skipped shards are returned
+    /// along with the target shards.
+    /// Fails if there are shards, but none of them is available.
+    pub fn search_target_shards(
+        &self,
+        shard_selection: Option<ShardId>,
+        shard_filter: Option<&ShardFilter>,
+    ) -> CollectionResult<(Vec<&ShardReplicaSet>, Vec<ShardId>)> {
+        let mut shards = self.target_shard(shard_selection)?;
+        if let Some(shard_filter) = shard_filter {
+            shards.retain(|shard| shard_filter.check(shard.shard_id));
+        }
+
+        if shard_selection.is_some() {
+            return Ok((shards, vec![]));
+        }
+
+        let (available, unavailable): (Vec<_>, Vec<_>) = shards
+            .into_iter()
+            .partition(|shard| shard.has_readable_replica());
+
+        let unavailable_ids = unavailable
+            .iter()
+            .map(|shard| shard.shard_id)
+            .sorted()
+            .collect_vec();
+        if !unavailable_ids.is_empty() {
+            log::warn!("Skipping shards {unavailable_ids:?}, which have no available replicas");
+
+            if available.is_empty() {
+                return Err(CollectionError::service_error(
+                    "All shards are unavailable".to_string(),
+                ));
+            }
+        }
+
+        Ok((available, unavailable_ids))
+    }
+
     pub fn len(&self) -> usize {
         self.shards.len()
     }
@@ -273,3 +448,68 @@ impl ShardHolder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::Builder;
+
+    use super::*;
+
+    const LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+    #[tokio::test]
+    async fn test_lock_points() {
+        let dir = Builder::new().prefix("collection").tempdir().unwrap();
+        let shard_holder = Arc::new(ShardHolder::new(dir.path(), HashRing::fair(100)).unwrap());
+
+        let guards = shard_holder
+            .lock_points(vec![2u64.into(), 1u64.into(), 2u64.into()])
+            .await;
+        assert_eq!(guards.len(), 2);
+
+        // Other points are not blocked
+        let other_guards = tokio::time::timeout(
+            LOCK_TIMEOUT,
+            shard_holder.lock_points(vec![3u64.into(), 4u64.into()]),
+        )
+        .await
+        .expect("different points must not contend");
+        drop(other_guards);
+
+        // Same point waits until the lock is released
+        let waiting = tokio::spawn({
+            let shard_holder = shard_holder.clone();
+            async move {
+                shard_holder
+                    .lock_points(vec![4u64.into(), 1u64.into()])
+                    .await
+                    .len()
+            }
+        });
+        tokio::time::sleep(LOCK_TIMEOUT).await;
+        assert!(!waiting.is_finished());
+
+        drop(guards);
+        let locked = tokio::time::timeout(LOCK_TIMEOUT, waiting)
+            .await
+            .expect("lock must be acquired after release")
+            .unwrap();
+        assert_eq!(locked, 2);
+
+        // Released locks are forgotten
+        assert!(shard_holder.point_locks.is_empty());
+
+        // Including the ones of cancelled waits
+        let guards = shard_holder.lock_points(vec![5u64.into()]).await;
+        let cancelled = tokio::time::timeout(
+            LOCK_TIMEOUT,
+            shard_holder.lock_points(vec![6u64.into(), 5u64.into()]),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        drop(guards);
+        assert!(shard_holder.point_locks.is_empty());
+    }
+}
@@ -12,6 +12,32 @@ use crate::operations::types::{
 };
 use crate::operations::CollectionUpdateOperations;
 
+/// Results of a search batch on a shard, along with how the searches were executed
+#[derive(Debug, Clone, Default)]
+pub struct ShardSearchResult {
+    pub results: Vec<Vec<ScoredPoint>>,
+    /// HNSW `ef` effectively used by each search of the batch, see `LocalShard::hnsw_ef_used`.
+    /// `None` if the search was exact. Empty if the shard doesn't report it.
+    pub hnsw_ef_used: Vec<Option<usize>>,
+    /// Whether the results were served from the shard search cache
+    pub cache_hit: bool,
+}
+
+impl ShardSearchResult {
+    /// Merge `ef` reported for the same batch, keeping the lowest one of each search
+    pub fn merge_hnsw_ef_used(hnsw_ef_used: &mut Vec<Option<usize>>, other: &[Option<usize>]) {
+        if hnsw_ef_used.len() < other.len() {
+            hnsw_ef_used.resize(other.len(), None);
+        }
+        for (ef, other_ef) in hnsw_ef_used.iter_mut().zip(other) {
+            *ef = match (*ef, *other_ef) {
+                (Some(ef), Some(other_ef)) => Some(ef.min(other_ef)),
+                (ef, other_ef) => ef.or(other_ef),
+            };
+        }
+    }
+}
+
 #[async_trait]
 pub trait ShardOperation {
     async fn update(
@@ -39,6 +65,24 @@ pub trait ShardOperation {
         search_runtime_handle: &Handle,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>>;
 
+    /// Same as `search`, but also reports the HNSW `ef` used by each search
+    /// and whether the results were served from the shard search cache.
+    ///
+    /// Shards which don't know how the search was executed report no `ef`.
+    /// Shards without a search cache always report a cache miss.
+    async fn search_with_info(
+        &self,
+        request: Arc<SearchRequestBatch>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<ShardSearchResult> {
+        let results = self.search(request, search_runtime_handle).await?;
+        Ok(ShardSearchResult {
+            results,
+            hnsw_ef_used: Vec::new(),
+            cache_hit: false,
+        })
+    }
+
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult>;
 
     async fn retrieve(
@@ -32,6 +32,24 @@ pub struct LocalShardTelemetry {
     pub variant_name: Option<String>,
     pub segments: Vec<SegmentTelemetry>,
     pub optimizations: OptimizerTelemetry,
+    #[serde(default)]
+    pub optimizer_queue: OptimizerQueueStats,
+}
+
+/// Number of segments waiting to be processed by each kind of optimizer
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default, PartialEq, Eq)]
+pub struct OptimizerQueueStats {
+    pub indexing_pending: u32,
+    pub merge_pending: u32,
+    pub vacuum_pending: u32,
+}
+
+impl std::ops::AddAssign<&OptimizerQueueStats> for OptimizerQueueStats {
+    fn add_assign(&mut self, other: &OptimizerQueueStats) {
+        self.indexing_pending += other.indexing_pending;
+        self.merge_pending += other.merge_pending;
+        self.vacuum_pending += other.vacuum_pending;
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default)]
@@ -57,6 +75,7 @@ impl Anonymize for LocalShardTelemetry {
             variant_name: self.variant_name.clone(),
             segments: self.segments.anonymize(),
             optimizations: self.optimizations.anonymize(),
+            optimizer_queue: self.optimizer_queue.clone(),
         }
     }
 }
@@ -1,10 +1,14 @@
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
@@ -14,7 +18,7 @@ use crate::operations::types::{CollectionError, CollectionResult};
 use crate::shards::channel_service::ChannelService;
 use crate::shards::remote_shard::RemoteShard;
 use crate::shards::replica_set::ReplicaState;
-use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::shard::{PeerId, Shard, ShardId};
 use crate::shards::shard_holder::{LockedShardHolder, ShardHolder};
 use crate::shards::CollectionId;
 
@@ -33,7 +37,9 @@ pub struct ShardTransfer {
 }
 
 /// Unique identifier of a transfer
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[derive(
+    Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
 pub struct ShardTransferKey {
     pub shard_id: ShardId,
     pub from: PeerId,
@@ -46,6 +52,22 @@ impl ShardTransferKey {
     }
 }
 
+/// Transfer which was aborted before completion
+#[derive(Debug, Clone)]
+pub struct AbortedTransfer {
+    pub transfer: ShardTransfer,
+    /// Reason why the transfer was aborted
+    pub abort_reason: Option<String>,
+    pub aborted_at: DateTime<Utc>,
+}
+
+/// Progress of a running shard transfer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Number of point batches sent to the remote shard, including retries
+    pub batches_transferred: usize,
+}
+
 impl ShardTransfer {
     pub fn key(&self) -> ShardTransferKey {
         ShardTransferKey {
@@ -56,10 +78,54 @@ impl ShardTransfer {
     }
 }
 
+/// Registered shard transfers by their keys.
+///
+/// Stored as a plain list of transfers, so the on-disk format is the same as of a set of transfers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<ShardTransfer>", into = "Vec<ShardTransfer>")]
+pub struct ShardTransfers(HashMap<ShardTransferKey, ShardTransfer>);
+
+impl Deref for ShardTransfers {
+    type Target = HashMap<ShardTransferKey, ShardTransfer>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ShardTransfers {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl FromIterator<ShardTransfer> for ShardTransfers {
+    fn from_iter<I: IntoIterator<Item = ShardTransfer>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|transfer| (transfer.key(), transfer))
+                .collect(),
+        )
+    }
+}
+
+impl From<Vec<ShardTransfer>> for ShardTransfers {
+    fn from(transfers: Vec<ShardTransfer>) -> Self {
+        transfers.into_iter().collect()
+    }
+}
+
+impl From<ShardTransfers> for Vec<ShardTransfer> {
+    fn from(transfers: ShardTransfers) -> Self {
+        transfers.0.into_values().collect()
+    }
+}
+
 async fn transfer_batches(
     shard_holder: Arc<LockedShardHolder>,
     shard_id: ShardId,
     stopped: Arc<AtomicBool>,
+    progress: Arc<Mutex<TransferProgress>>,
 ) -> CollectionResult<()> {
     // Create payload indexes on the remote shard.
     {
@@ -91,6 +157,7 @@ async fn transfer_batches(
             offset = replica_set
                 .transfer_batch(offset, TRANSFER_BATCH_SIZE)
                 .await?;
+            progress.lock().batches_transferred += 1;
             if offset.is_none() {
                 // That was the last batch, all look good
                 break;
@@ -144,6 +211,58 @@ pub async fn change_remote_shard_route(
     Ok(true)
 }
 
+/// Inverse action of a shard transfer finalization step
+///
+/// Recorded before each step of `Collection::finish_shard_transfer`, so partially applied
+/// finalization can be rolled back by applying recorded compensations in reverse order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TransferCompensation {
+    /// Revert forward proxy back to the local shard.
+    /// If the local shard was detached from the replica set, it is put back first.
+    RevertProxy,
+    /// Restore state of the peer in the replica set, remove the peer if it was not present
+    RestorePeerState {
+        peer_id: PeerId,
+        state: Option<ReplicaState>,
+    },
+    /// Mark the local replica as dead, so it is recovered with a new transfer
+    DeactivateLocalReplica,
+}
+
+impl TransferCompensation {
+    /// `detached_local` is the local shard detached by `handle_transferred_shard_proxy`, if any
+    pub(crate) async fn apply(
+        &self,
+        shard_holder: &ShardHolder,
+        shard_id: ShardId,
+        detached_local: &mut Option<Shard>,
+    ) -> CollectionResult<()> {
+        let Some(replica_set) = shard_holder.get_shard(&shard_id) else {
+            return Ok(());
+        };
+
+        match self {
+            Self::RevertProxy => {
+                if let Some(local) = detached_local.take() {
+                    replica_set.attach_local(local).await?;
+                }
+                revert_proxy_shard_to_local(shard_holder, shard_id).await?;
+            }
+            Self::RestorePeerState { peer_id, state } => match state {
+                Some(state) if *peer_id == replica_set.this_peer_id() => {
+                    replica_set.set_replica_state(peer_id, *state)?;
+                }
+                Some(state) => replica_set.add_remote(*peer_id, *state).await?,
+                None => replica_set.remove_remote(*peer_id).await?,
+            },
+            Self::DeactivateLocalReplica => {
+                replica_set.set_replica_state(&replica_set.this_peer_id(), ReplicaState::Dead)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Mark partial shard as ready
 ///
 /// Returns `true` if the shard was promoted, `false` if the shard was not found.
@@ -166,12 +285,17 @@ pub async fn finalize_partial_shard(
 
 /// Promotes wrapped local shard to remote shard
 ///
+/// If the transfer is a move, the local shard is detached from the replica set and stored in
+/// `detached_local`. Its data is kept, so the promotion can be reverted until the data is
+/// cleared with `ShardReplicaSet::clear_detached_local`.
+///
 /// Returns true if the shard was promoted, false if it was already handled
 pub async fn handle_transferred_shard_proxy(
     shard_holder: &ShardHolder,
     shard_id: ShardId,
     to: PeerId,
     sync: bool,
+    detached_local: &mut Option<Shard>,
 ) -> CollectionResult<bool> {
     let replica_set = match shard_holder.get_shard(&shard_id) {
         None => return Ok(false),
@@ -185,12 +309,13 @@ pub async fn handle_transferred_shard_proxy(
         replica_set.un_proxify_local().await?;
     } else {
         // Remove local proxy
-        replica_set.remove_local().await?;
+        *detached_local = replica_set.detach_local().await?;
     }
 
     Ok(true)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn transfer_shard(
     shard_holder: Arc<LockedShardHolder>,
     shard_id: ShardId,
@@ -198,14 +323,16 @@ pub async fn transfer_shard(
     peer_id: PeerId,
     channel_service: ChannelService,
     stopped: Arc<AtomicBool>,
+    progress: Arc<Mutex<TransferProgress>>,
+    snapshot_temp_dir: Option<&Path>,
 ) -> CollectionResult<()> {
     // Initiate shard on a remote peer
-    let remote_shard = RemoteShard::new(shard_id, collection_id.clone(), peer_id, channel_service);
-
-    // ToDo: Initial fast file-based transfer (optional)
-    // * Create shard snapshot - save the latest version of point updates in the snapshot
-    // * Initiate shard, use snapshot link for initialization
-    // * Transfer difference between snapshot and current shard state
+    let remote_shard = RemoteShard::new(
+        shard_id,
+        collection_id.clone(),
+        peer_id,
+        channel_service.clone(),
+    );
 
     remote_shard.initiate_transfer().await?;
     {
@@ -220,8 +347,60 @@ pub async fn transfer_shard(
         }
     }
 
-    // Transfer contents batch by batch
-    transfer_batches(shard_holder.clone(), shard_id, stopped.clone()).await
+    match snapshot_temp_dir {
+        // Updates are forwarded by the proxy from now on, the snapshot doesn't miss any of them
+        Some(temp_dir) => {
+            transfer_snapshot(
+                shard_holder,
+                shard_id,
+                &collection_id,
+                peer_id,
+                &channel_service,
+                temp_dir,
+            )
+            .await
+        }
+        // Transfer contents batch by batch
+        None => transfer_batches(shard_holder.clone(), shard_id, stopped.clone(), progress).await,
+    }
+}
+
+/// Push a snapshot of the proxified local shard to the remote peer, which restores its replica
+/// from it. Forwarded updates are buffered by the remote peer and applied on top of the snapshot
+/// once the transfer is finished.
+async fn transfer_snapshot(
+    shard_holder: Arc<LockedShardHolder>,
+    shard_id: ShardId,
+    collection_id: &CollectionId,
+    peer_id: PeerId,
+    channel_service: &ChannelService,
+    temp_dir: &Path,
+) -> CollectionResult<()> {
+    let (this_peer_id, snapshot) = {
+        let shard_holder_guard = shard_holder.read().await;
+        let Some(replica_set) = shard_holder_guard.get_shard(&shard_id) else {
+            return Err(CollectionError::service_error(format!(
+                "Shard {shard_id} cannot be transferred because it does not exist"
+            )));
+        };
+        let snapshot_file_name =
+            format!("{collection_id}-shard-{shard_id}-transfer-to-{peer_id}.snapshot");
+        let snapshot = replica_set
+            .create_snapshot_archive(collection_id, temp_dir, &snapshot_file_name, false, None)
+            .await?;
+        (replica_set.this_peer_id(), snapshot)
+    };
+
+    // Temporary snapshot file is removed once it is dropped
+    channel_service
+        .upload_shard_snapshot(
+            peer_id,
+            this_peer_id,
+            collection_id,
+            shard_id,
+            snapshot.path(),
+        )
+        .await
 }
 
 pub fn validate_transfer_exists(
@@ -448,11 +627,14 @@ pub fn suggest_peer_to_remove_replica(
     candidates.first().map(|(peer_id, _, _)| *peer_id)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_transfer_task<T, F>(
     shards_holder: Arc<LockedShardHolder>,
     transfer: ShardTransfer,
     collection_id: CollectionId,
     channel_service: ChannelService,
+    progress: Arc<Mutex<TransferProgress>>,
+    snapshot_temp_dir: Option<PathBuf>,
     on_finish: T,
     on_error: F,
 ) -> StoppableAsyncTaskHandle<bool>
@@ -471,6 +653,8 @@ where
                 transfer.to,
                 channel_service.clone(),
                 stopped.clone(),
+                progress.clone(),
+                snapshot_temp_dir.as_deref(),
             )
             .await;
             finished = match transfer_result {
@@ -515,3 +699,30 @@ where
         finished
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_transfers_serialization() {
+        // Transfers used to be stored as a set, which is serialized into a list
+        let stored = r#"[
+            {"shard_id": 0, "from": 1, "to": 2, "sync": false},
+            {"shard_id": 1, "from": 2, "to": 1, "sync": true}
+        ]"#;
+        let transfers: ShardTransfers = serde_json::from_str(stored).unwrap();
+        assert_eq!(transfers.len(), 2);
+
+        let key = ShardTransferKey {
+            shard_id: 1,
+            from: 2,
+            to: 1,
+        };
+        assert!(transfers[&key].sync);
+
+        let serialized = serde_json::to_string(&transfers).unwrap();
+        let deserialized: ShardTransfers = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, transfers);
+    }
+}
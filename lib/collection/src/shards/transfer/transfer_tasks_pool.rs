@@ -1,12 +1,39 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use parking_lot::Mutex;
 
 use crate::common::stoppable_task_async::StoppableAsyncTaskHandle;
-use crate::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey};
+use crate::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey, TransferProgress};
 use crate::shards::CollectionId;
 
 pub struct TransferTasksPool {
     collection_id: CollectionId,
-    tasks: HashMap<ShardTransferKey, StoppableAsyncTaskHandle<bool>>,
+    tasks: HashMap<ShardTransferKey, TransferTaskItem>,
+}
+
+struct TransferTaskItem {
+    task: StoppableAsyncTaskHandle<bool>,
+    transfer: ShardTransfer,
+    started_at: Instant,
+    progress: Arc<Mutex<TransferProgress>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TaskStatus {
+    Running,
+    Finished,
+    Failed,
+}
+
+/// Point-in-time status of a transfer task
+#[derive(Debug, Clone)]
+pub struct TransferTaskStatus {
+    pub transfer: ShardTransfer,
+    pub status: TaskStatus,
+    pub started_at: Instant,
+    pub progress: Option<TransferProgress>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -33,8 +60,8 @@ impl TransferTasksPool {
 
     /// Returns true if transfer task is still running
     pub fn check_if_still_running(&self, transfer_key: &ShardTransferKey) -> bool {
-        if let Some(task) = self.tasks.get(transfer_key) {
-            !task.is_finished()
+        if let Some(item) = self.tasks.get(transfer_key) {
+            !item.task.is_finished()
         } else {
             false
         }
@@ -44,8 +71,8 @@ impl TransferTasksPool {
     /// Return false if task failed or stopped
     /// Return None if task not found or not finished
     pub fn get_task_result(&self, transfer_key: &ShardTransferKey) -> Option<bool> {
-        if let Some(task) = self.tasks.get(transfer_key) {
-            task.get_result()
+        if let Some(item) = self.tasks.get(transfer_key) {
+            item.task.get_result()
         } else {
             None
         }
@@ -54,8 +81,8 @@ impl TransferTasksPool {
     /// Returns true if the task was actually stopped
     /// Returns false if the task was not found
     pub async fn stop_if_exists(&mut self, transfer_key: &ShardTransferKey) -> TaskResult {
-        if let Some(task) = self.tasks.remove(transfer_key) {
-            match task.stop().await {
+        if let Some(item) = self.tasks.remove(transfer_key) {
+            match item.task.stop().await {
                 Ok(res) => {
                     if res {
                         log::info!(
@@ -95,7 +122,112 @@ impl TransferTasksPool {
         &mut self,
         shard_transfer: &ShardTransfer,
         task: StoppableAsyncTaskHandle<bool>,
+        progress: Arc<Mutex<TransferProgress>>,
     ) {
-        self.tasks.insert(shard_transfer.key(), task);
+        self.tasks.insert(
+            shard_transfer.key(),
+            TransferTaskItem {
+                task,
+                transfer: shard_transfer.clone(),
+                started_at: Instant::now(),
+                progress,
+            },
+        );
+    }
+
+    /// Status of all tasks in the pool
+    pub fn snapshot(&self) -> Vec<TransferTaskStatus> {
+        self.tasks
+            .values()
+            .map(|item| {
+                let status = if !item.task.is_finished() {
+                    TaskStatus::Running
+                } else if item.task.get_result().unwrap_or(false) {
+                    TaskStatus::Finished
+                } else {
+                    TaskStatus::Failed
+                };
+                TransferTaskStatus {
+                    transfer: item.transfer.clone(),
+                    status,
+                    started_at: item.started_at,
+                    progress: Some(item.progress.lock().clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use tokio::time::sleep;
+
+    use super::*;
+    use crate::common::stoppable_task_async::spawn_async_stoppable;
+
+    fn transfer(shard_id: u32) -> ShardTransfer {
+        ShardTransfer {
+            shard_id,
+            from: 1,
+            to: 2,
+            sync: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let mut pool = TransferTasksPool::new("test".to_string());
+
+        let finished = spawn_async_stoppable(|_stop| async { true });
+        let failed = spawn_async_stoppable(|_stop| async { false });
+        let running = spawn_async_stoppable(|stop| async move {
+            while !stop.load(Ordering::Relaxed) {
+                sleep(Duration::from_millis(5)).await;
+            }
+            true
+        });
+
+        let progress = Arc::new(Mutex::new(TransferProgress {
+            batches_transferred: 3,
+        }));
+        pool.add_task(&transfer(0), finished, progress);
+        pool.add_task(&transfer(1), failed, Default::default());
+        pool.add_task(&transfer(2), running, Default::default());
+
+        sleep(Duration::from_millis(50)).await;
+
+        let mut statuses = pool.snapshot();
+        statuses.sort_by_key(|status| status.transfer.shard_id);
+        let shard_statuses = statuses
+            .iter()
+            .map(|status| (status.transfer.shard_id, status.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shard_statuses,
+            vec![
+                (0, TaskStatus::Finished),
+                (1, TaskStatus::Failed),
+                (2, TaskStatus::Running),
+            ],
+        );
+        assert_eq!(
+            statuses[0].progress,
+            Some(TransferProgress {
+                batches_transferred: 3,
+            }),
+        );
+
+        // Stopped tasks are removed from the pool
+        pool.stop_if_exists(&transfer(2).key()).await;
+        assert_eq!(pool.snapshot().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_empty() {
+        let pool = TransferTasksPool::new("test".to_string());
+        assert!(pool.snapshot().is_empty());
     }
 }
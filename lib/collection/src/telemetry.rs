@@ -3,8 +3,8 @@ use segment::common::anonymize::Anonymize;
 use serde::{Deserialize, Serialize};
 
 use crate::config::CollectionConfig;
-use crate::operations::types::ShardTransferInfo;
-use crate::shards::telemetry::ReplicaSetTelemetry;
+use crate::operations::types::{SearchQualityHint, ShardTransferInfo};
+use crate::shards::telemetry::{OptimizerQueueStats, ReplicaSetTelemetry};
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct CollectionTelemetry {
@@ -13,6 +13,12 @@ pub struct CollectionTelemetry {
     pub config: CollectionConfig,
     pub shards: Vec<ReplicaSetTelemetry>,
     pub transfers: Vec<ShardTransferInfo>,
+    /// Segments waiting for optimization, summed over all local shards
+    #[serde(default)]
+    pub optimizer_queue: OptimizerQueueStats,
+    /// HNSW `ef` range of all searches since the start, which requested a quality hint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_quality_hint: Option<SearchQualityHint>,
 }
 
 impl CollectionTelemetry {
@@ -34,6 +40,8 @@ impl Anonymize for CollectionTelemetry {
             init_time_ms: self.init_time_ms,
             shards: self.shards.anonymize(),
             transfers: vec![],
+            optimizer_queue: self.optimizer_queue.clone(),
+            search_quality_hint: self.search_quality_hint,
         }
     }
 }
@@ -46,6 +54,7 @@ impl Anonymize for CollectionConfig {
             optimizer_config: self.optimizer_config.clone(),
             wal_config: self.wal_config.clone(),
             quantization_config: self.quantization_config.clone(),
+            use_cross_device_snapshot_copy: self.use_cross_device_snapshot_copy,
         }
     }
 }
@@ -2,13 +2,15 @@ use std::collections::{HashMap, HashSet};
 use std::num::{NonZeroU32, NonZeroU64};
 use std::sync::Arc;
 
-use segment::types::Distance;
+use parking_lot::Mutex;
+use segment::types::{Distance, Filter};
 use tempfile::Builder;
 
 use crate::collection::{Collection, RequestShardTransfer};
 use crate::config::{CollectionConfig, CollectionParams, WalConfig};
 use crate::operations::shared_storage_config::SharedStorageConfig;
-use crate::operations::types::{NodeType, VectorParams, VectorsConfig};
+use crate::operations::snapshot_ops::{SnapshotHashes, SnapshotPhase, SnapshotProgress};
+use crate::operations::types::{CollectionError, NodeType, VectorParams, VectorsConfig};
 use crate::optimizers_builder::OptimizersConfig;
 use crate::shards::channel_service::ChannelService;
 use crate::shards::collection_shard_distribution::CollectionShardDistribution;
@@ -55,6 +57,8 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         replication_factor: NonZeroU32::new(3).unwrap(),
         write_consistency_factor: NonZeroU32::new(2).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
     let config = CollectionConfig {
@@ -63,6 +67,7 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        use_cross_device_snapshot_copy: None,
     };
 
     let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
@@ -96,16 +101,87 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        Default::default(),
+        Default::default(),
+        None,
         None,
     )
     .await
     .unwrap();
 
     let snapshots_temp_dir = Builder::new().prefix("temp_dir").tempdir().unwrap();
+    let reported_phases = Arc::new(Mutex::new(Vec::new()));
+    let reported_phases_clone = reported_phases.clone();
     let snapshot_description = collection
-        .create_snapshot(snapshots_temp_dir.path(), 0)
+        .create_snapshot(
+            snapshots_temp_dir.path(),
+            0,
+            Some(Box::new(move |progress: SnapshotProgress| {
+                reported_phases_clone.lock().push(progress.phase)
+            })),
+        )
+        .await
+        .unwrap();
+
+    let reported_phases = reported_phases.lock().clone();
+    assert_eq!(
+        reported_phases.first(),
+        Some(&SnapshotPhase::CapturingShards)
+    );
+    assert!(reported_phases.contains(&SnapshotPhase::Archiving));
+    assert_eq!(reported_phases.last(), Some(&SnapshotPhase::Complete));
+
+    #[cfg(target_os = "linux")]
+    assert!(snapshot_description.peak_memory_bytes_during_archiving > 0);
+
+    // `latest.snapshot` points to the new snapshot, but is not listed itself
+    #[cfg(unix)]
+    assert_eq!(
+        std::fs::read_link(
+            snapshots_path
+                .path()
+                .join(crate::operations::snapshot_ops::LATEST_SNAPSHOT_LINK)
+        )
+        .unwrap(),
+        std::path::PathBuf::from(&snapshot_description.name),
+    );
+    let snapshots = collection.list_snapshots().await.unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].name, snapshot_description.name);
+
+    // Snapshots directory of the shard is created on demand
+    let shard_snapshots_path = collection.get_snapshots_path_for_shard(0).await.unwrap();
+    assert!(shard_snapshots_path.is_dir());
+    assert!(collection.list_shard_snapshots(0).await.unwrap().is_empty());
+
+    // Shard snapshots with WAL are distinguished by name
+    let shard_snapshot = collection
+        .create_shard_snapshot(0, snapshots_temp_dir.path(), None, true)
         .await
         .unwrap();
+    assert!(shard_snapshot.name.ends_with("_with_wal.snapshot"));
+
+    let shard_snapshot = collection
+        .create_shard_snapshot(0, snapshots_temp_dir.path(), None, false)
+        .await
+        .unwrap();
+    assert!(!shard_snapshot.name.contains("_with_wal"));
+
+    // Shard snapshots report the version of Qdrant which created them
+    let shard_snapshots = collection.list_shard_snapshots(0).await.unwrap();
+    assert_eq!(shard_snapshots.len(), 2);
+    for snapshot in &shard_snapshots {
+        assert_eq!(
+            snapshot.qdrant_version.as_deref(),
+            Some(env!("CARGO_PKG_VERSION")),
+        );
+    }
+
+    // Filtered snapshots never contain WAL
+    assert!(collection
+        .create_shard_snapshot(0, snapshots_temp_dir.path(), Some(&Filter::default()), true)
+        .await
+        .is_err());
 
     // Do not recover in local mode if some shards are remote
     assert!(Collection::restore_snapshot(
@@ -113,14 +189,40 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         recover_dir.path(),
         0,
         false,
+        None,
     )
     .is_err());
 
+    // Do not recover into another collection if the name is checked
+    let wrong_collection_dir = Builder::new()
+        .prefix("test_wrong_collection")
+        .tempdir()
+        .unwrap();
+    let result = Collection::restore_snapshot(
+        &snapshots_path.path().join(&snapshot_description.name),
+        wrong_collection_dir.path(),
+        0,
+        true,
+        Some(&collection_name_rec),
+    );
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "{result:?}"
+    );
+    // Checked before unpacking
+    assert_eq!(
+        std::fs::read_dir(wrong_collection_dir.path())
+            .unwrap()
+            .count(),
+        0
+    );
+
     if let Err(err) = Collection::restore_snapshot(
         &snapshots_path.path().join(snapshot_description.name),
         recover_dir.path(),
         0,
         true,
+        Some("test"),
     ) {
         panic!("Failed to restore snapshot: {err}")
     }
@@ -135,9 +237,13 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        Default::default(),
+        Default::default(),
+        None,
         None,
     )
-    .await;
+    .await
+    .unwrap();
 
     {
         let shards_holder = &recovered_collection.shards_holder.read().await;
@@ -163,3 +269,45 @@ async fn test_snapshot_collection() {
     _test_snapshot_collection(NodeType::Normal).await;
     _test_snapshot_collection(NodeType::Listener).await;
 }
+
+#[test]
+fn test_snapshot_hashes_concurrent_insert() {
+    let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
+    let snapshot_hashes = Arc::new(SnapshotHashes::new(snapshots_path.path()));
+
+    let threads: Vec<_> = (0..16)
+        .map(|i| {
+            let snapshot_name = format!("snapshot-{i}.snapshot");
+            std::fs::write(snapshots_path.path().join(&snapshot_name), b"").unwrap();
+            let snapshot_hashes = snapshot_hashes.clone();
+            std::thread::spawn(move || {
+                snapshot_hashes
+                    .insert(format!("hash-{i}"), snapshot_name)
+                    .unwrap()
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    // No concurrent insert is lost
+    let hashes = snapshot_hashes.load().unwrap();
+    assert_eq!(hashes.len(), 16);
+    for i in 0..16 {
+        assert_eq!(
+            hashes[&format!("hash-{i}")],
+            format!("snapshot-{i}.snapshot")
+        );
+    }
+
+    // Entries of removed snapshots are dropped
+    std::fs::remove_file(snapshots_path.path().join("snapshot-0.snapshot")).unwrap();
+    std::fs::write(snapshots_path.path().join("snapshot-16.snapshot"), b"").unwrap();
+    snapshot_hashes
+        .insert("hash-16".to_string(), "snapshot-16.snapshot".to_string())
+        .unwrap();
+    let hashes = snapshot_hashes.load().unwrap();
+    assert_eq!(hashes.len(), 16);
+    assert!(!hashes.contains_key("hash-0"));
+}
@@ -1,5 +1,6 @@
 use std::num::{NonZeroU32, NonZeroU64};
 use std::sync::Arc;
+use std::time::Duration;
 
 use segment::types::{Distance, PayloadFieldSchema, PayloadSchemaType};
 use tempfile::Builder;
@@ -32,6 +33,8 @@ fn create_collection_config() -> CollectionConfig {
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
     let mut optimizer_config = TEST_OPTIMIZERS_CONFIG.clone();
@@ -45,6 +48,7 @@ fn create_collection_config() -> CollectionConfig {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        use_cross_device_snapshot_copy: None,
     }
 }
 
@@ -183,3 +187,36 @@ async fn test_delete_from_indexed_payload() {
     assert_eq!(number_of_indexed_points, 4);
     assert_eq!(number_of_indexed_points_after_load, 3);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_local_shard_is_stale() {
+    let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+
+    let shard = LocalShard::build(
+        0,
+        "test".to_string(),
+        collection_dir.path(),
+        Arc::new(RwLock::new(create_collection_config())),
+        Arc::new(Default::default()),
+        Handle::current(),
+    )
+    .await
+    .unwrap();
+
+    // Idle shard is never stale
+    assert!(!shard.is_stale(Duration::ZERO));
+
+    // Applied updates are not pending anymore
+    shard.update(upsert_operation(), true).await.unwrap();
+    assert!(!shard.is_stale(Duration::ZERO));
+
+    // Operation which is never applied, e.g. stuck update worker
+    shard.register_pending_update(u64::MAX);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Staleness is measured from the oldest pending operation,
+    // not from the last applied one
+    shard.update(delete_point_operation(1), true).await.unwrap();
+    assert!(shard.is_stale(Duration::from_millis(10)));
+    assert!(!shard.is_stale(Duration::from_secs(60)));
+}
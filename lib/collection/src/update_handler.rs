@@ -1,6 +1,7 @@
 use std::cmp::min;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
 use itertools::Itertools;
 use log::{debug, error, info, trace, warn};
@@ -34,6 +35,10 @@ const OPTIMIZER_CLEANUP_INTERVAL: Duration = Duration::from_secs(5);
 
 pub type Optimizer = dyn SegmentOptimizer + Sync + Send;
 
+/// Moment each operation was sent to the update worker, by operation number.
+/// Operations are removed once applied, so the first entry is the oldest pending operation.
+pub type PendingUpdates = Arc<Mutex<BTreeMap<SeqNumberType, Instant>>>;
+
 /// Information, required to perform operation and notify regarding the result
 #[derive(Debug)]
 pub struct OperationData {
@@ -96,6 +101,8 @@ pub struct UpdateHandler {
     /// This is used when another part still relies on part of the WAL, such as the queue proxy
     /// shard.
     pub(super) max_ack_version: Arc<TokioMutex<Option<u64>>>,
+    /// Operations sent to the update worker, but not applied yet, with the moment they were sent
+    pub(super) pending_updates: PendingUpdates,
     optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
     max_optimization_threads: usize,
 }
@@ -124,6 +131,7 @@ impl UpdateHandler {
             runtime_handle,
             wal,
             max_ack_version: Default::default(),
+            pending_updates: Default::default(),
             flush_interval_sec,
             optimization_handles: Arc::new(TokioMutex::new(vec![])),
             max_optimization_threads,
@@ -147,6 +155,7 @@ impl UpdateHandler {
             tx,
             self.wal.clone(),
             self.segments.clone(),
+            self.pending_updates.clone(),
         )));
         let (flush_tx, flush_rx) = oneshot::channel();
         self.flush_worker = Some(self.runtime_handle.spawn(Self::flush_worker(
@@ -414,6 +423,7 @@ impl UpdateHandler {
         optimize_sender: Sender<OptimizerSignal>,
         wal: LockedWal,
         segments: LockedSegmentHolder,
+        pending_updates: PendingUpdates,
     ) {
         while let Some(signal) = receiver.recv().await {
             match signal {
@@ -436,6 +446,7 @@ impl UpdateHandler {
 
                     let operation_result = flush_res
                         .and_then(|_| CollectionUpdater::update(&segments, op_num, operation));
+                    pending_updates.lock().remove(&op_num);
 
                     let res = match operation_result {
                         Ok(update_res) => optimize_sender
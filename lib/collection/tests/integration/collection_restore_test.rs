@@ -1,14 +1,19 @@
+use collection::collection::Collection;
 use collection::operations::point_ops::{
     Batch, PointInsertOperations, PointOperations, WriteOrdering,
 };
-use collection::operations::types::ScrollRequest;
+use collection::operations::types::{CollectionError, ScrollRequest};
 use collection::operations::CollectionUpdateOperations;
+use collection::shards::channel_service::ChannelService;
 use itertools::Itertools;
 use segment::types::{PayloadContainer, PayloadSelectorExclude, WithPayloadInterface};
 use serde_json::Value;
 use tempfile::Builder;
 
-use crate::common::{load_local_collection, simple_collection_fixture, N_SHARDS};
+use crate::common::{
+    dummy_on_replica_failure, dummy_request_shard_transfer, load_local_collection,
+    simple_collection_fixture, N_SHARDS,
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_collection_reloading() {
@@ -90,6 +95,9 @@ async fn test_collection_payload_reloading_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: true.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
             },
             None,
             None,
@@ -161,6 +169,9 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Fields(vec![String::from("k2")])),
                 with_vector: true.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
             },
             None,
             None,
@@ -195,6 +206,9 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(PayloadSelectorExclude::new(vec!["k1".to_string()]).into()),
                 with_vector: false.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
             },
             None,
             None,
@@ -229,3 +243,37 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
         _ => panic!("unexpected type"),
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_lock_prevents_concurrent_open() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection_path = collection_dir.path();
+    let snapshots_path = collection_path.join("snapshots");
+
+    let collection = simple_collection_fixture(collection_path, 1).await;
+
+    let load = || {
+        Collection::load(
+            "test".to_string(),
+            0,
+            collection_path,
+            &snapshots_path,
+            Default::default(),
+            ChannelService::default(),
+            dummy_on_replica_failure(),
+            dummy_request_shard_transfer(),
+            None,
+            Default::default(),
+            Default::default(),
+            None,
+            None,
+        )
+    };
+
+    let result = load().await;
+    assert!(matches!(result, Err(CollectionError::Unavailable { .. })));
+
+    // Lock is released once the collection is dropped
+    drop(collection);
+    assert!(load().await.is_ok());
+}
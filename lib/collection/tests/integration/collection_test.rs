@@ -1,20 +1,51 @@
-use std::collections::HashSet;
-
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroU64;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use collection::collection::{Collection, RequestShardTransfer, SearchHooks, UpdateHooks};
+use collection::operations::config_diff::{
+    HnswConfigDiff, OptimizersConfigDiff, QuantizationConfigDiff,
+};
+use collection::operations::consistency_params::{ReadConsistency, ReadConsistencyType};
 use collection::operations::payload_ops::{PayloadOps, SetPayload};
-use collection::operations::point_ops::{Batch, PointOperations, PointStruct, WriteOrdering};
+use collection::operations::point_ops::{
+    Batch, PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+};
+use collection::operations::snapshot_ops::SnapshotListPage;
 use collection::operations::types::{
-    CountRequest, PointRequest, RecommendRequest, ScrollRequest, SearchRequest, UpdateStatus,
+    CacheStats, CollectionError, CountRequest, PointRequest, RecommendRequest, ScoreNormalizerType,
+    ScrollKey, ScrollRequest, SearchQualityHint, SearchRequest, SearchRequestBatch, ShardFilter,
+    UpdateStatus, VectorParams, VectorsConfig,
 };
 use collection::operations::CollectionUpdateOperations;
+use collection::optimizers_builder::OptimizersConfig;
 use collection::recommendations::recommend_by;
+use collection::shards::channel_service::ChannelService;
+use collection::shards::collection_shard_distribution::CollectionShardDistribution;
+use collection::shards::replica_set::{Change, ReplicaState};
+use collection::shards::telemetry::OptimizerQueueStats;
+use collection::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey};
+use futures::future::join_all;
+use futures::{FutureExt as _, StreamExt as _, TryStreamExt as _};
 use itertools::Itertools;
-use segment::data_types::vectors::VectorStruct;
+use segment::data_types::vectors::{
+    NamedVector, NamedVectorStruct, VectorStruct, DEFAULT_VECTOR_NAME,
+};
 use segment::types::{
-    Condition, FieldCondition, Filter, HasIdCondition, Payload, PointIdType, WithPayloadInterface,
+    Condition, Distance, FieldCondition, Filter, HasIdCondition, Payload, PointIdType,
+    QuantizationSearchParams, ScoredPoint, SearchParamsEcho, WithPayloadInterface,
 };
 use tempfile::Builder;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
-use crate::common::{load_local_collection, simple_collection_fixture, N_SHARDS};
+use crate::common::{
+    load_local_collection, new_local_collection, new_local_collection_with_hooks,
+    new_local_collection_with_search_hooks, simple_collection_config, simple_collection_fixture,
+    N_SHARDS, TEST_OPTIMIZERS_CONFIG,
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_collection_updater() {
@@ -52,7 +83,8 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
 
     match insert_result {
         Ok(res) => {
-            assert_eq!(res.status, UpdateStatus::Completed)
+            assert_eq!(res.status, UpdateStatus::Completed);
+            assert!((1..=shard_number).contains(&res.shards_written));
         }
         Err(err) => panic!("operation failed: {err:?}"),
     }
@@ -66,6 +98,7 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
         limit: 3,
         offset: 0,
         score_threshold: None,
+        ..Default::default()
     };
 
     let search_res = collection.search(search_request, None, None).await;
@@ -123,6 +156,7 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
         limit: 3,
         offset: 0,
         score_threshold: None,
+        ..Default::default()
     };
 
     let search_res = collection.search(search_request, None, None).await;
@@ -151,12 +185,162 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
             geo_polygon: None,
         }))),
         exact: true,
+        shard_key: None,
     };
 
-    let count_res = collection.count(count_request, None).await.unwrap();
+    let count_res = collection.count(count_request, None, None).await.unwrap();
     assert_eq!(count_res.count, 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_with_vector_only() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..20).map(|i: u64| i.into()).collect(),
+            vectors: (0..20)
+                .map(|i| vec![1.0, i as f32, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: Some(vec![
+                Some(serde_json::from_str(r#"{ "k": "v" }"#).unwrap());
+                20
+            ]),
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Large offset makes the search fetch vectors in a separate step
+    let search_request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [1.0, 1.0, 0.0, 1.0],
+        "limit": 2,
+        "offset": 10,
+        "with_payload": false,
+        "with_vector": true,
+    }))
+    .unwrap();
+
+    let result = collection.search(search_request, None, None).await.unwrap();
+    assert_eq!(result.len(), 2);
+    for point in &result {
+        assert!(point.payload.is_none());
+        match &point.vector {
+            Some(VectorStruct::Single(v)) => assert_eq!(v.len(), 4),
+            _ => panic!("vector is not returned"),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_indexing_threshold() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let diff = |indexing_threshold: usize| -> OptimizersConfigDiff {
+        serde_json::from_value(serde_json::json!({ "indexing_threshold": indexing_threshold }))
+            .unwrap()
+    };
+
+    let err = collection
+        .update_optimizer_params_from_diff(diff(10))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CollectionError::BadInput { .. }));
+
+    // Disabling indexing is still allowed
+    collection
+        .update_optimizer_params_from_diff(diff(0))
+        .await
+        .unwrap();
+    collection
+        .update_optimizer_params_from_diff(diff(20_000))
+        .await
+        .unwrap();
+    let info = collection.info(None).await.unwrap();
+    assert_eq!(
+        info.config.optimizer_config.indexing_threshold,
+        Some(20_000)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_quality_hint() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |return_quality_hint: bool| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 1.0, 1.0],
+            "limit": 2,
+            "return_quality_hint": return_quality_hint,
+        }))
+        .unwrap()
+    };
+
+    // Small shards have no HNSW index, they are searched exactly
+    let (_, meta) = collection
+        .search_with_meta(request(true), None, None)
+        .await
+        .unwrap();
+    assert_eq!(meta.quality_hint, None);
+    assert_eq!(
+        collection.get_telemetry_data().await.search_quality_hint,
+        None,
+    );
+
+    // Each shard reports the `ef` it used for each search, `None` for exact searches
+    let batch_result = collection
+        .search_batch_with_timeout(
+            SearchRequestBatch {
+                searches: vec![request(true), request(false)],
+                debug: false,
+                dry_run: false,
+                explain_score: false,
+                min_shards: None,
+                wait_for_shards: None,
+                timeout_ms: None,
+                score_normalizer: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(batch_result.results.len(), 2);
+    assert_eq!(batch_result.hnsw_ef_used, vec![None, None]);
+
+    let hint = SearchQualityHint::from_shards_ef(&[64, 100, 200]).unwrap();
+    assert_eq!(hint.min_ef_used, 64);
+    assert_eq!(hint.max_ef_used, 200);
+    assert!((hint.avg_ef_used - 121.333).abs() < 0.001);
+    assert!(SearchQualityHint::from_shards_ef(&[]).is_none());
+
+    let merged = hint.merge(&SearchQualityHint::from_shards_ef(&[32]).unwrap());
+    assert_eq!(merged.min_ef_used, 32);
+    assert_eq!(merged.max_ef_used, 200);
+    assert_eq!(merged.shards_count, 4);
+    assert!((merged.avg_ef_used - 99.0).abs() < 0.001);
+}
+
 // FIXME: dos not work
 #[tokio::test(flavor = "multi_thread")]
 async fn test_collection_loading() {
@@ -219,6 +403,7 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
         ids: vec![1.into(), 2.into()],
         with_payload: Some(WithPayloadInterface::Bool(true)),
         with_vector: true.into(),
+        strict: false,
     };
     let retrieved = loaded_collection
         .retrieve(request, None, None)
@@ -256,6 +441,249 @@ fn test_deserialization() {
     let _read_obj2: CollectionUpdateOperations = rmp_serde::from_slice(&crob_bytes).unwrap();
 }
 
+#[test]
+fn test_search_request_hnsw_ef_override() {
+    let request: SearchRequest = serde_json::from_str(
+        r#"{ "vector": [1.0, 0.0, 1.0, 1.0], "limit": 3, "params": { "hnsw_ef": 16, "exact": true } }"#,
+    )
+    .unwrap();
+    assert_eq!(request.search_params().unwrap().hnsw_ef, Some(16));
+
+    let request = SearchRequest {
+        hnsw_ef: Some(128),
+        ..request
+    };
+    let params = request.search_params().unwrap();
+    assert_eq!(params.hnsw_ef, Some(128));
+    assert!(params.exact);
+}
+
+#[test]
+fn test_merge_searches_parallel() {
+    let distances = simple_collection_config(N_SHARDS).params.vector_distances();
+    let searches: Vec<SearchRequest> = (0..8)
+        .map(|limit| {
+            serde_json::from_value(serde_json::json!({
+                "vector": [1.0, 0.0, 0.0, 1.0],
+                "limit": limit + 1,
+            }))
+            .unwrap()
+        })
+        .collect();
+    let shard_results: Vec<Vec<Vec<ScoredPoint>>> = (0..N_SHARDS as u64)
+        .map(|shard| {
+            (0..searches.len() as u64)
+                .map(|search| {
+                    (0..10u64)
+                        .map(|i| ScoredPoint {
+                            id: (shard * 10 + i).into(),
+                            version: 0,
+                            score: ((shard + i * 7 + search * 3) % 11) as f32,
+                            payload: None,
+                            vector: None,
+                            score_breakdown: None,
+                            search_params_echo: None,
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let sequential = Collection::merge_searches(
+        shard_results.clone(),
+        &searches,
+        &distances,
+        None,
+        usize::MAX,
+    )
+    .unwrap();
+    let parallel =
+        Collection::merge_searches(shard_results, &searches, &distances, None, 0).unwrap();
+
+    assert_eq!(parallel.len(), searches.len());
+    for (search, (parallel, sequential)) in searches.iter().zip(parallel.iter().zip(&sequential)) {
+        assert_eq!(parallel.len(), search.limit);
+        assert_eq!(
+            parallel.iter().map(|point| point.id).collect_vec(),
+            sequential.iter().map(|point| point.id).collect_vec(),
+        );
+    }
+}
+
+#[test]
+fn test_search_request_quantization_override() {
+    let request: SearchRequest = serde_json::from_str(
+        r#"{ "vector": [1.0, 0.0, 1.0, 1.0], "limit": 3, "params": { "hnsw_ef": 16, "quantization": { "rescore": false } } }"#,
+    )
+    .unwrap();
+    assert!(
+        !request
+            .search_params()
+            .unwrap()
+            .quantization
+            .unwrap()
+            .rescore
+    );
+
+    let request = SearchRequest {
+        quantization: Some(QuantizationSearchParams {
+            ignore: true,
+            rescore: true,
+            oversampling: Some(2.0),
+        }),
+        ..request
+    };
+    let params = request.search_params().unwrap();
+    // Request level override takes precedence, other params are kept
+    assert_eq!(
+        params.quantization,
+        Some(QuantizationSearchParams {
+            ignore: true,
+            rescore: true,
+            oversampling: Some(2.0),
+        }),
+    );
+    assert_eq!(params.hnsw_ef, Some(16));
+
+    // Override without params
+    let request = SearchRequest {
+        params: None,
+        ..request
+    };
+    assert!(
+        request
+            .search_params()
+            .unwrap()
+            .quantization
+            .unwrap()
+            .ignore
+    );
+
+    // Invalid override is rejected by validation
+    let request: SearchRequest = serde_json::from_str(
+        r#"{ "vector": [1.0, 0.0, 1.0, 1.0], "limit": 3, "quantization": { "oversampling": 0.5 } }"#,
+    )
+    .unwrap();
+    assert!(validator::Validate::validate(&request).is_err());
+}
+
+#[test]
+fn test_search_request_apply_negative_examples() {
+    let request =
+        |value: serde_json::Value| -> SearchRequest { serde_json::from_value(value).unwrap() };
+
+    let mut search = request(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 0.0],
+        "negative": [[0.0, 1.0, 0.0, 0.0], [0.0, 3.0, 0.0, 0.0]],
+        "alpha": 0.5,
+        "limit": 3,
+    }));
+    search.apply_negative_examples().unwrap();
+    // query + alpha * (query - centroid) = [1, 0] + 0.5 * ([1, 0] - [0, 2])
+    assert_eq!(search.vector.get_vector(), &vec![1.5, -1.0, 0.0, 0.0]);
+    assert!(search.negative.is_empty());
+
+    // Negative example of another vector
+    let mut search = request(serde_json::json!({
+        "vector": { "name": "image", "vector": [1.0, 0.0, 0.0, 0.0] },
+        "negative": [{ "name": "text", "vector": [0.0, 1.0, 0.0, 0.0] }],
+        "limit": 3,
+    }));
+    assert!(matches!(
+        search.apply_negative_examples(),
+        Err(CollectionError::BadInput { .. }),
+    ));
+
+    // Negative example of another dimension
+    let mut search = request(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 0.0],
+        "negative": [[0.0, 1.0]],
+        "limit": 3,
+    }));
+    assert!(matches!(
+        search.apply_negative_examples(),
+        Err(CollectionError::BadInput { .. }),
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_negative_examples() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into()],
+            vectors: vec![vec![1.0, 1.0, 0.0, 0.0], vec![0.9, -0.5, 0.0, 0.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 0.0],
+        "limit": 1,
+    }))
+    .unwrap();
+    let result = collection.search(request, None, None).await.unwrap();
+    assert_eq!(result[0].id, PointIdType::from(0));
+
+    // Pushing the query away from the second axis prefers the other point
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 0.0],
+        "negative": [[0.0, 1.0, 0.0, 0.0]],
+        "limit": 1,
+    }))
+    .unwrap();
+    let result = collection.search(request, None, None).await.unwrap();
+    assert_eq!(result[0].id, PointIdType::from(1));
+
+    // Invalid negative example fails the search
+    let batch: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [{
+            "vector": [1.0, 0.0, 0.0, 0.0],
+            "negative": [[0.0, 1.0]],
+            "limit": 1,
+        }]
+    }))
+    .unwrap();
+    assert!(collection.search_batch(batch, None, None).await.is_err());
+}
+
+#[test]
+fn test_collection_params_vector_distances() {
+    let mut params = simple_collection_config(1).params;
+
+    let distances = params.vector_distances();
+    assert_eq!(distances.get(DEFAULT_VECTOR_NAME).unwrap(), Distance::Dot);
+    assert!(matches!(
+        distances.get("image"),
+        Err(CollectionError::BadInput { .. }),
+    ));
+
+    let vector_params = |distance| VectorParams {
+        size: NonZeroU64::new(4).unwrap(),
+        distance,
+        hnsw_config: None,
+        quantization_config: None,
+        on_disk: None,
+    };
+    params.vectors = VectorsConfig::Multi(BTreeMap::from([
+        ("image".to_string(), vector_params(Distance::Cosine)),
+        ("text".to_string(), vector_params(Distance::Euclid)),
+    ]));
+
+    let distances = params.vector_distances();
+    assert_eq!(distances.get("image").unwrap(), Distance::Cosine);
+    assert_eq!(distances.get("text").unwrap(), Distance::Euclid);
+    assert!(distances.get(DEFAULT_VECTOR_NAME).is_err());
+}
+
 #[test]
 fn test_deserialization2() {
     let insert_points = CollectionUpdateOperations::PointOperation(
@@ -386,6 +814,9 @@ async fn test_read_api_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: false.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
             },
             None,
             None,
@@ -395,6 +826,171 @@ async fn test_read_api_with_shards(shard_number: u32) {
 
     assert_eq!(result.next_page_offset, Some(2.into()));
     assert_eq!(result.points.len(), 2);
+    assert!(result.points.iter().all(|point| point.shard_id.is_none()));
+    assert_eq!(result.total_count, None);
+
+    let result = collection
+        .scroll_by(
+            ScrollRequest {
+                offset: None,
+                limit: Some(2),
+                filter: Some(Filter::new_must(Condition::HasId(HasIdCondition {
+                    has_id: [1, 3, 5, 7].map(PointIdType::from).into(),
+                }))),
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_vector: false.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: true,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.points.len(), 2);
+    assert_eq!(result.total_count, Some(4));
+
+    let result = collection
+        .scroll_by(
+            ScrollRequest {
+                offset: None,
+                limit: Some(2),
+                filter: None,
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: false.into(),
+                with_shard_key: true,
+                shard_key: None,
+                with_total_count: false,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.points.len(), 2);
+    assert!(result.points.iter().all(|point| point.shard_id.is_some()));
+
+    let result = collection
+        .scroll_by(
+            ScrollRequest {
+                offset: None,
+                limit: Some(10),
+                filter: None,
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: false.into(),
+                with_shard_key: true,
+                shard_key: Some(0),
+                with_total_count: false,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert!(result.points.iter().all(|point| point.shard_id == Some(0)));
+
+    let count_request = |shard_key| CountRequest {
+        filter: None,
+        exact: true,
+        shard_key,
+    };
+    let count = collection
+        .count(count_request(Some(0)), None, None)
+        .await
+        .unwrap();
+    assert_eq!(count.count, result.points.len());
+
+    // Shard selection of the call takes precedence over the shard key of the request
+    let count = collection
+        .count(count_request(Some(shard_number + 1)), Some(0), None)
+        .await
+        .unwrap();
+    assert_eq!(count.count, result.points.len());
+
+    let count = collection
+        .count(count_request(None), None, None)
+        .await
+        .unwrap();
+    assert_eq!(count.count, 9);
+
+    // Composite scroll key stays valid if points are deleted between pages
+    let scroll_request = |offset| ScrollRequest {
+        offset,
+        limit: Some(2),
+        filter: None,
+        with_payload: Some(WithPayloadInterface::Bool(false)),
+        with_vector: false.into(),
+        with_shard_key: false,
+        shard_key: None,
+        with_total_count: false,
+    };
+
+    let first_page = collection
+        .scroll_by(scroll_request(None), None, None)
+        .await
+        .unwrap();
+    let next_page_key = first_page.next_page_key.unwrap();
+    assert_eq!(
+        next_page_key,
+        ScrollKey::Composite {
+            last_id: 1.into(),
+            filter: Filter::default(),
+        },
+    );
+
+    let delete_points = CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+        ids: vec![2.into()],
+    });
+    collection
+        .update_from_client(delete_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let second_page = collection
+        .scroll_by(scroll_request(Some(next_page_key)), None, None)
+        .await
+        .unwrap();
+    let ids = second_page
+        .points
+        .iter()
+        .map(|point| point.id)
+        .collect_vec();
+    assert_eq!(ids, vec![3.into(), 4.into()]);
+    assert_eq!(second_page.next_page_offset, Some(5.into()));
+
+    // Strict retrieve reports points which don't exist
+    let retrieve_request = |ids: Vec<u64>, strict| PointRequest {
+        ids: ids.into_iter().map(|id| id.into()).collect(),
+        with_payload: None,
+        with_vector: false.into(),
+        strict,
+    };
+
+    let records = collection
+        .retrieve(retrieve_request(vec![1, 2, 100], false), None, None)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+
+    let records = collection
+        .retrieve(retrieve_request(vec![1, 3], true), None, None)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 2);
+
+    let result = collection
+        .retrieve(retrieve_request(vec![1, 2, 100], true), None, None)
+        .await;
+    match result {
+        Err(CollectionError::NotFound { what }) => {
+            assert_eq!(what, "point ids [NumId(2), NumId(100)]");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -469,6 +1065,9 @@ async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32)
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(false)),
                 with_vector: false.into(),
+                with_shard_key: false,
+                shard_key: None,
+                with_total_count: false,
             },
             None,
             None,
@@ -482,3 +1081,3010 @@ async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32)
     assert_eq!(result.points.get(1).unwrap().id, 2.into());
     assert_eq!(result.points.get(2).unwrap().id, 4.into());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_unknown_vector_names() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let search_request = |name: &str| SearchRequest {
+        vector: NamedVectorStruct::Named(NamedVector {
+            name: name.to_string(),
+            vector: vec![1.0, 1.0, 1.0, 1.0],
+        })
+        .into(),
+        with_payload: None,
+        with_vector: None,
+        filter: None,
+        params: None,
+        limit: 3,
+        offset: 0,
+        score_threshold: None,
+        ..Default::default()
+    };
+
+    let request = SearchRequestBatch {
+        searches: vec![
+            search_request("foo"),
+            search_request("bar"),
+            search_request("foo"),
+        ],
+        debug: false,
+        dry_run: false,
+        explain_score: false,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms: None,
+        score_normalizer: None,
+    };
+
+    let error = collection
+        .search_batch(request, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Wrong input: Unknown vector names: [foo, bar]. Available: [default]",
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_estimate_search_cost() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1, 2, 3, 4]
+                .into_iter()
+                .map(|x| x.into())
+                .collect_vec(),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = SearchRequestBatch {
+        searches: vec![SearchRequest {
+            vector: vec![1.0, 1.0, 1.0, 1.0].into(),
+            with_payload: None,
+            with_vector: None,
+            filter: None,
+            params: None,
+            limit: 3,
+            offset: 0,
+            score_threshold: None,
+            ..Default::default()
+        }],
+        debug: false,
+        dry_run: false,
+        explain_score: false,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms: None,
+        score_normalizer: None,
+    };
+
+    let estimation = collection
+        .estimate_search_cost(request.clone(), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(estimation.shards_touched, N_SHARDS as usize);
+    assert_eq!(
+        estimation
+            .estimated_candidates_per_shard
+            .iter()
+            .sum::<usize>(),
+        5
+    );
+    assert_eq!(estimation.estimated_payload_bytes, 0);
+
+    // Dry run returns the estimation instead of executing the search
+    let result = collection
+        .search_batch_with_timeout(
+            SearchRequestBatch {
+                dry_run: true,
+                ..request.clone()
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.results.len(), 1);
+    assert!(result.results[0].is_empty());
+    assert_eq!(result.dry_run, Some(estimation));
+
+    // Shards skipped by the filter are not estimated
+    let single_shard = ShardFilter::Set(HashSet::from([0]));
+    let estimation = collection
+        .estimate_search_cost(request.clone(), None, None, Some(&single_shard))
+        .await
+        .unwrap();
+    assert_eq!(estimation.shards_touched, 1);
+    assert_eq!(estimation.estimated_candidates_per_shard.len(), 1);
+
+    let no_shards = ShardFilter::Set(HashSet::new());
+    let estimation = collection
+        .estimate_search_cost(request, None, None, Some(&no_shards))
+        .await
+        .unwrap();
+    assert_eq!(estimation.shards_touched, 0);
+    assert_eq!(estimation.estimated_payload_bytes, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_with_shard_filter() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1, 2, 3, 4]
+                .into_iter()
+                .map(|x| x.into())
+                .collect_vec(),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let search_request = |shard_filter| SearchRequest {
+        vector: vec![1.0, 1.0, 1.0, 1.0].into(),
+        with_payload: None,
+        with_vector: None,
+        filter: None,
+        params: None,
+        limit: 5,
+        offset: 0,
+        score_threshold: None,
+        shard_filter,
+        ..Default::default()
+    };
+
+    // All shards are in range
+    let all_shards = ShardFilter::Range {
+        from: 0,
+        to: N_SHARDS - 1,
+    };
+    let result = collection
+        .search(search_request(Some(all_shards)), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 5);
+
+    // No shards selected
+    let no_shards = ShardFilter::Set(HashSet::new());
+    let result = collection
+        .search(search_request(Some(no_shards)), None, None)
+        .await
+        .unwrap();
+    assert!(result.is_empty());
+
+    // Searches of unknown tenants are not restricted
+    let mut tenant_request = search_request(None);
+    tenant_request.tenant_id = Some("tenant".to_string());
+    let result = collection
+        .search(tenant_request.clone(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 5);
+
+    // Searches of assigned tenants are routed to the tenant shard only
+    let tenant_shard = collection
+        .assign_tenant_shard("tenant".to_string())
+        .await
+        .unwrap();
+    let tenant_result = collection.search(tenant_request, None, None).await.unwrap();
+    let shard_result = collection
+        .search(
+            search_request(Some(ShardFilter::Set(HashSet::from([tenant_shard])))),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        tenant_result.iter().map(|point| point.id).collect_vec(),
+        shard_result.iter().map(|point| point.id).collect_vec(),
+    );
+
+    // Each point is found in exactly one shard
+    let mut found_points = 0;
+    for shard_id in 0..N_SHARDS {
+        let single_shard = ShardFilter::Set(HashSet::from([shard_id]));
+        found_points += collection
+            .search(search_request(Some(single_shard)), None, None)
+            .await
+            .unwrap()
+            .len();
+    }
+    assert_eq!(found_points, 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_with_idempotency_key() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_point = |id: u64| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![id.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        )
+    };
+
+    let idempotency_key = Some(Uuid::new_v4());
+    let first_result = collection
+        .update_from_client_idempotent(
+            insert_point(0),
+            true,
+            WriteOrdering::default(),
+            idempotency_key,
+        )
+        .await
+        .unwrap();
+
+    // Retried operation is not applied again
+    let retry_result = collection
+        .update_from_client_idempotent(
+            insert_point(0),
+            true,
+            WriteOrdering::default(),
+            idempotency_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(retry_result.operation_id, first_result.operation_id);
+
+    // Key can't be reused for another operation
+    let result = collection
+        .update_from_client_idempotent(
+            insert_point(1),
+            true,
+            WriteOrdering::default(),
+            idempotency_key,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "unexpected result: {result:?}",
+    );
+
+    // Concurrent requests with the same key apply the operation once
+    let concurrent_key = Some(Uuid::new_v4());
+    let results = join_all((0..8).map(|_| {
+        collection.update_from_client_idempotent(
+            insert_point(2),
+            true,
+            WriteOrdering::default(),
+            concurrent_key,
+        )
+    }))
+    .await;
+    let operation_ids: HashSet<_> = results
+        .into_iter()
+        .map(|result| result.unwrap().operation_id)
+        .collect();
+    assert_eq!(operation_ids.len(), 1);
+
+    let count = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+                shard_key: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 2);
+
+    // Operations without a key are always applied
+    let result = collection
+        .update_from_client_idempotent(insert_point(1), true, WriteOrdering::default(), None)
+        .await
+        .unwrap();
+    assert_ne!(result.operation_id, first_result.operation_id);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_max_payload_size() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshot_path = collection_dir.path().join("snapshots");
+
+    let mut collection_config = simple_collection_config(1);
+    collection_config.params.max_payload_size_bytes = Some(32);
+    let collection = new_local_collection(
+        "test".to_string(),
+        collection_dir.path(),
+        &snapshot_path,
+        &collection_config,
+    )
+    .await
+    .unwrap();
+
+    let insert_points = |payloads: &str| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![0.into(), 1.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+                payloads: serde_json::from_str(payloads).unwrap(),
+            }
+            .into(),
+        )
+    };
+
+    collection
+        .update_from_client(
+            insert_points(r#"[{ "k": "v" }, null]"#),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+
+    // The whole batch is rejected if any payload is too large
+    let err = collection
+        .update_from_client(
+            insert_points(r#"[{ "k": "v" }, { "k": "a value which is too large for the limit" }]"#),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CollectionError::BadInput { .. }));
+    assert!(err.to_string().contains("1 (48 bytes)"), "{err}");
+
+    let count = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+                shard_key: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_without_result() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into(), 2.into()],
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+
+    // The update is not waited for, even if requested
+    let result = collection
+        .update_from_client_with_result(insert_points, true, WriteOrdering::default(), false)
+        .await
+        .unwrap();
+    assert_eq!(result.operation_id, None);
+    assert_eq!(result.status, UpdateStatus::Acknowledged);
+    assert_eq!(result.shards_written, 1);
+
+    let delete_point = CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+        ids: vec![0.into()],
+    });
+
+    let result = collection
+        .update_from_client(delete_point, true, WriteOrdering::default())
+        .await
+        .unwrap();
+    assert!(result.operation_id.is_some());
+    assert_eq!(result.status, UpdateStatus::Completed);
+
+    // Both operations went through the same update queue, so the points are in place
+    let count = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+                shard_key: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_local_shards_with_state() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let shards_with_state = collection.get_local_shards_with_state().await;
+    assert_eq!(shards_with_state.len(), N_SHARDS as usize);
+    assert!(shards_with_state
+        .iter()
+        .all(|(_, state)| *state == ReplicaState::Active));
+
+    let shard_ids = shards_with_state
+        .into_iter()
+        .map(|(shard_id, _)| shard_id)
+        .collect_vec();
+    assert_eq!(collection.get_local_shards().await, shard_ids);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_set_replica_state_from_states() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    let shard_id = collection.get_local_shards().await[0];
+
+    let result = collection
+        .set_shard_replica_state(
+            shard_id,
+            0,
+            ReplicaState::Active,
+            Some(HashSet::from([
+                ReplicaState::Partial,
+                ReplicaState::Initializing,
+            ])),
+        )
+        .await;
+    assert!(result.is_err(), "replica is not in any of expected states");
+
+    collection
+        .set_shard_replica_state(
+            shard_id,
+            0,
+            ReplicaState::Active,
+            Some(HashSet::from([ReplicaState::Partial, ReplicaState::Active])),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_is_fully_stable() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    assert!(collection.is_fully_stable().await);
+
+    let shard_id = collection.get_local_shards().await[0];
+    collection
+        .set_shard_replica_state(shard_id, 0, ReplicaState::Partial, None)
+        .await
+        .unwrap();
+    assert!(!collection.is_fully_stable().await);
+
+    collection
+        .set_shard_replica_state(shard_id, 0, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    assert!(collection.is_fully_stable().await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_recover_local_shard_from_peer_validation() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    let this_peer_id = collection.this_peer_id();
+
+    // Can't recover from itself
+    assert!(collection
+        .recover_local_shard_from_peer(0, this_peer_id)
+        .await
+        .is_err());
+
+    // Source peer has no replica of the shard
+    assert!(collection
+        .recover_local_shard_from_peer(0, this_peer_id + 1)
+        .await
+        .is_err());
+
+    // Unknown shard
+    assert!(collection
+        .recover_local_shard_from_peer(100, this_peer_id + 1)
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_recover_local_shard_from_peer() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshots_path = collection_dir.path().join("snapshots");
+    let config = simple_collection_config(1);
+
+    let (transfer_sender, mut transfer_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let request_shard_transfer: RequestShardTransfer = Arc::new(move |transfer| {
+        transfer_sender.send(transfer).unwrap();
+    });
+
+    let collection = Collection::new(
+        "test".to_string(),
+        0,
+        collection_dir.path(),
+        &snapshots_path,
+        &config,
+        Default::default(),
+        CollectionShardDistribution::all_local(Some(1), 0),
+        ChannelService::default(),
+        Arc::new(|_peer_id, _shard_id| {}),
+        request_shard_transfer,
+        None,
+        Default::default(),
+        Default::default(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let collection = Arc::new(collection);
+    collection
+        .set_shard_replica_state(0, 0, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    // Active replica on the source peer
+    collection
+        .set_shard_replica_state(0, 1, ReplicaState::Active, None)
+        .await
+        .unwrap();
+
+    let recover = tokio::spawn({
+        let collection = collection.clone();
+        async move { collection.recover_local_shard_from_peer(0, 1).await }
+    });
+
+    // Act as consensus, which starts the requested transfer and finishes it once the source
+    // peer reports it is done
+    let transfer = transfer_receiver.recv().await.unwrap();
+    assert_eq!(transfer.shard_id, 0);
+    assert_eq!(transfer.from, 1);
+    assert_eq!(transfer.to, 0);
+    assert!(transfer.sync);
+
+    collection
+        .start_shard_transfer(transfer.clone(), async {}, async {})
+        .await
+        .unwrap();
+    assert_eq!(
+        collection.state().await.unwrap().shards[&0]
+            .replicas
+            .get(&0),
+        Some(&ReplicaState::Partial),
+    );
+
+    // Give the recovery time to see the started transfer, a real transfer takes much longer
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!recover.is_finished());
+
+    collection.finish_shard_transfer(transfer).await.unwrap();
+
+    recover.await.unwrap().unwrap();
+    assert_eq!(
+        collection.state().await.unwrap().shards[&0]
+            .replicas
+            .get(&0),
+        Some(&ReplicaState::Active),
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_snapshot_shard_to_peer_validation() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let temp_dir = Builder::new().prefix("temp").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    let this_peer_id = collection.this_peer_id();
+
+    // Can't push to itself
+    assert!(collection
+        .snapshot_shard_to_peer(0, this_peer_id, temp_dir.path())
+        .await
+        .is_err());
+
+    // Target peer has no replica of the shard
+    assert!(collection
+        .snapshot_shard_to_peer(0, this_peer_id + 1, temp_dir.path())
+        .await
+        .is_err());
+
+    // Unknown shard
+    assert!(collection
+        .snapshot_shard_to_peer(100, this_peer_id + 1, temp_dir.path())
+        .await
+        .is_err());
+
+    // No snapshot is left behind by rejected requests
+    assert!(collection.list_shard_snapshots(0).await.unwrap().is_empty());
+
+    // Snapshots pushed without a running transfer are rejected
+    assert!(collection
+        .check_pushed_shard_snapshot(0, this_peer_id + 1, 0)
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_get_unknown_transfer() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let transfer_key = ShardTransferKey {
+        shard_id: 0,
+        from: 0,
+        to: 1,
+    };
+    assert!(collection.get_transfer(&transfer_key).await.is_none());
+    assert!(!collection.check_transfer_exists(&transfer_key).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_check_transfer_disk_space() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..10)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let this_peer_id = collection.this_peer_id();
+    let other_peer_id = this_peer_id + 1;
+    let transfer = |from, to| ShardTransfer {
+        shard_id: 0,
+        from,
+        to,
+        sync: false,
+    };
+
+    assert!(collection.estimate_local_shard_size(0).await.unwrap() > 0);
+    assert!(collection.estimate_local_shard_size(100).await.is_none());
+
+    // Local shard fits on the local disk
+    collection
+        .check_transfer_disk_space(&transfer(this_peer_id, this_peer_id))
+        .await
+        .unwrap();
+
+    // Unknown receiving peer can't report its disk space, the transfer must not be proposed
+    let result = collection
+        .check_transfer_disk_space(&transfer(this_peer_id, other_peer_id))
+        .await;
+    assert!(result.is_err());
+
+    // Unknown sending peer can't estimate the size of the shard
+    let result = collection
+        .check_transfer_disk_space(&transfer(other_peer_id, this_peer_id))
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_min_shards() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let request = |min_shards: usize| -> SearchRequestBatch {
+        serde_json::from_value(serde_json::json!({
+            "searches": [{ "vector": [1.0, 1.0, 1.0, 1.0], "limit": 3 }],
+            "min_shards": min_shards,
+        }))
+        .unwrap()
+    };
+
+    let result = collection
+        ._search_batch(request(N_SHARDS as usize), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.shards_successful, N_SHARDS as usize);
+    // Local shards have no search cache
+    assert_eq!(
+        result.cache_stats,
+        CacheStats {
+            hits: 0,
+            misses: N_SHARDS as usize,
+        },
+    );
+
+    let result = collection
+        ._search_batch(request(N_SHARDS as usize + 1), None, None, None)
+        .await;
+    assert!(result.is_err(), "not enough shards to satisfy min_shards");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_waits_for_all_shards() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..100u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..100)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |wait_for_shards: Option<usize>| -> SearchRequestBatch {
+        serde_json::from_value(serde_json::json!({
+            "searches": [{ "vector": [1.0, 0.0, 0.0, 1.0], "limit": 100 }],
+            "wait_for_shards": wait_for_shards,
+        }))
+        .unwrap()
+    };
+
+    // Read consistency does not reduce the number of awaited shards
+    let result = collection
+        ._search_batch(
+            request(None),
+            Some(ReadConsistency::Type(ReadConsistencyType::Majority)),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.shards_successful, N_SHARDS as usize);
+    assert_eq!(result.meta.shards_queried, (0..N_SHARDS).collect_vec());
+    assert_eq!(result.results[0].len(), 100);
+
+    // Early return is an explicit opt-in
+    let result = collection
+        ._search_batch(request(Some(1)), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.shards_successful, 1);
+    // Only the shard whose results were merged is reported
+    assert_eq!(result.meta.shards_queried.len(), 1);
+    assert!(result.results[0].len() < 100);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_score_normalizer() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..10)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |score_normalizer: ScoreNormalizerType| -> SearchRequestBatch {
+        serde_json::from_value(serde_json::json!({
+            "searches": [{ "vector": [1.0, 0.0, 0.0, 1.0], "limit": 5 }],
+            "score_normalizer": score_normalizer,
+        }))
+        .unwrap()
+    };
+
+    let raw = collection
+        .search_batch(request(ScoreNormalizerType::None), None, None)
+        .await
+        .unwrap();
+    let normalized = collection
+        .search_batch(request(ScoreNormalizerType::MinMax), None, None)
+        .await
+        .unwrap();
+
+    // Normalized across the results of all shards
+    let ids = |points: &[ScoredPoint]| points.iter().map(|point| point.id).collect_vec();
+    assert_eq!(ids(&normalized[0]), ids(&raw[0]));
+    assert_eq!(normalized[0].first().unwrap().score, 1.0);
+    assert_eq!(normalized[0].last().unwrap().score, 0.0);
+    assert_eq!(raw[0].first().unwrap().score, 10.0);
+
+    let normalized = collection
+        .search_batch(request(ScoreNormalizerType::Sigmoid), None, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(&normalized[0]), ids(&raw[0]));
+    assert!(normalized[0]
+        .iter()
+        .all(|point| 0.0 < point.score && point.score < 1.0));
+
+    // Scores are normalized after exact rescoring
+    let rescored_request: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [{
+            "vector": [1.0, 0.0, 0.0, 1.0],
+            "limit": 5,
+            "params": { "rescore_with_exact": true },
+        }],
+        "score_normalizer": ScoreNormalizerType::MinMax,
+    }))
+    .unwrap();
+    let rescored = collection
+        .search_batch(rescored_request, None, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(&rescored[0]), ids(&raw[0]));
+    assert_eq!(rescored[0].first().unwrap().score, 1.0);
+    assert_eq!(rescored[0].last().unwrap().score, 0.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_with_timeout() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..10)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Large offset with payload goes through the two-step search
+    let request: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [{
+            "vector": [1.0, 0.0, 0.0, 1.0],
+            "limit": 1,
+            "offset": 4,
+            "with_payload": true,
+        }],
+        "timeout_ms": 60_000,
+    }))
+    .unwrap();
+
+    let result = collection
+        .search_batch_with_timeout(request.clone(), None, None)
+        .await
+        .unwrap();
+    assert!(result.timeout.is_none());
+    assert_eq!(result.results.len(), 1);
+    assert_eq!(result.results[0].len(), 1);
+
+    let results = collection.search_batch(request, None, None).await.unwrap();
+    assert_eq!(results, result.results);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_normalize_vector() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..5u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..5)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |vector: [f32; 4], normalize_vector: bool| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": vector,
+            "limit": 3,
+            "normalize_vector": normalize_vector,
+        }))
+        .unwrap()
+    };
+
+    let normalized = collection
+        .search(request([0.6, 0.0, 0.0, 0.8], false), None, None)
+        .await
+        .unwrap();
+    let raw = collection
+        .search(request([3.0, 0.0, 0.0, 4.0], false), None, None)
+        .await
+        .unwrap();
+    let result = collection
+        .search(request([3.0, 0.0, 0.0, 4.0], true), None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(result.len(), 3);
+    for (point, expected) in result.iter().zip(&normalized) {
+        assert_eq!(point.id, expected.id);
+        assert!((point.score - expected.score).abs() < 1e-5);
+    }
+    assert!(raw[0].score > result[0].score + 1.0);
+
+    // Vectors of zero length are searched as is
+    let result = collection
+        .search(request([0.0; 4], true), None, None)
+        .await
+        .unwrap();
+    assert!(result.iter().all(|point| point.score == 0.0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_params_echo() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..5u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..5)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |echo_params: bool| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0, 1.0],
+            "limit": 3,
+            "hnsw_ef": 128,
+            "echo_params": echo_params,
+        }))
+        .unwrap()
+    };
+
+    let result = collection.search(request(false), None, None).await.unwrap();
+    assert_eq!(result.len(), 3);
+    assert!(result
+        .iter()
+        .all(|point| point.search_params_echo.is_none()));
+
+    // Points are in small plain segments, so no HNSW index is used
+    let result = collection.search(request(true), None, None).await.unwrap();
+    assert_eq!(result.len(), 3);
+    for point in result {
+        assert_eq!(
+            point.search_params_echo,
+            Some(SearchParamsEcho {
+                ef_used: None,
+                oversampling_used: 1.0,
+                quantization_applied: false,
+            }),
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_explain_score() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..5u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..5)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |explain_score: bool| -> SearchRequestBatch {
+        serde_json::from_value(serde_json::json!({
+            "searches": [{
+                "vector": [1.0, 0.0, 0.0, 1.0],
+                "limit": 3,
+                "hnsw_ef": 128,
+            }],
+            "explain_score": explain_score,
+        }))
+        .unwrap()
+    };
+
+    let result = collection
+        .search_batch(request(false), None, None)
+        .await
+        .unwrap();
+    assert!(result[0]
+        .iter()
+        .all(|point| point.score_breakdown.is_none()));
+
+    let result = collection
+        .search_batch(request(true), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result[0].len(), 3);
+    for point in &result[0] {
+        let score_breakdown = point.score_breakdown.as_ref().unwrap();
+        assert_eq!(score_breakdown.len(), 3);
+        assert_eq!(score_breakdown["raw_distance"], point.score);
+        assert_eq!(score_breakdown["quantization_rescore"], 0.0);
+        // No HNSW index is built for the small segments
+        assert_eq!(score_breakdown["graph_ef_penalty"], 0.0);
+        // Params echo is returned only if requested
+        assert!(point.search_params_echo.is_none());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_payload_fields() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+            payloads: serde_json::from_str(r#"[{ "a": 1, "b": 2, "c": 3 }]"#).unwrap(),
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let cases = [
+        (
+            serde_json::json!({ "exclude_fields": ["b"] }),
+            vec!["a", "c"],
+        ),
+        (
+            serde_json::json!({ "include_fields": ["a", "b"] }),
+            vec!["a", "b"],
+        ),
+        (
+            serde_json::json!({ "with_payload": ["a"], "include_fields": ["c"] }),
+            vec!["a", "c"],
+        ),
+        (
+            serde_json::json!({ "with_payload": { "exclude": ["a"] }, "exclude_fields": ["c"] }),
+            vec!["b"],
+        ),
+        (
+            serde_json::json!({
+                "with_payload": true,
+                "include_fields": ["a"],
+                "exclude_fields": ["a"],
+            }),
+            vec!["b", "c"],
+        ),
+    ];
+
+    for (mut request, expected_keys) in cases {
+        request["vector"] = serde_json::json!([1.0, 0.0, 1.0, 1.0]);
+        request["limit"] = serde_json::json!(1);
+        let request: SearchRequest = serde_json::from_value(request).unwrap();
+
+        let result = collection.search(request, None, None).await.unwrap();
+        let payload_keys = result[0]
+            .payload
+            .as_ref()
+            .map(|payload| payload.0.keys().cloned().sorted().collect_vec())
+            .unwrap_or_default();
+        assert_eq!(payload_keys, expected_keys);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_segment_read_estimation() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..10)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [
+            {
+                "vector": [1.0, 0.0, 0.0, 1.0],
+                "limit": 3,
+                "estimate_segment_reads": true,
+            },
+            {
+                "vector": [1.0, 0.0, 0.0, 1.0],
+                "limit": 3,
+            },
+        ],
+    }))
+    .unwrap();
+
+    let result = collection
+        .search_batch_debug(request, None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.merged.len(), 2);
+    assert_eq!(result.segment_read_estimations.len(), 2);
+
+    let estimation = result.segment_read_estimations[0].unwrap();
+    assert!(estimation.segments_total > 0);
+    assert_eq!(estimation.segments_without_matches, 0);
+    assert_eq!(estimation.estimated_points_matching, 10);
+
+    assert!(result.segment_read_estimations[1].is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_context_pairs() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into(), 2.into()],
+            vectors: vec![
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Closer to point 2 and the raw vector, farther from point 1
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [0.0, 0.0, 0.0, 0.0],
+        "limit": 3,
+        "context_pairs": [
+            [2, 1],
+            [[0.0, 0.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0]],
+        ],
+    }))
+    .unwrap();
+
+    let result = collection.search(request, None, None).await.unwrap();
+    assert_eq!(result[0].id, 2.into());
+    assert_eq!(result.last().unwrap().id, 1.into());
+    assert!((result[0].score - 2.0 / 5.0f32.sqrt()).abs() < 1e-5);
+
+    // Examples must exist
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [0.0, 0.0, 0.0, 0.0],
+        "limit": 3,
+        "context_pairs": [[2, 100]],
+    }))
+    .unwrap();
+    assert!(collection.search(request, None, None).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_with_meta() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 1.0],
+        "limit": 3,
+    }))
+    .unwrap();
+
+    let (result, meta) = collection
+        .search_with_meta(request.clone(), None, None)
+        .await
+        .unwrap();
+    assert!(result.is_empty());
+    assert_eq!(meta.shards_queried, (0..N_SHARDS).collect_vec());
+    assert_eq!(meta.latency_ms_per_shard.len(), N_SHARDS as usize);
+
+    let (_, meta) = collection
+        .search_with_meta(request, None, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(meta.shards_queried, vec![1]);
+    assert!(meta.latency_ms_per_shard.contains_key(&1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_rescore_with_exact() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..20u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..20)
+                .map(|x| vec![x as f32, 1.0, 0.0, (20 - x) as f32])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |params: serde_json::Value| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0, 0.5],
+            "limit": 3,
+            "offset": 1,
+            "params": params,
+        }))
+        .unwrap()
+    };
+
+    let exact = collection
+        .search(request(serde_json::json!({ "exact": true })), None, None)
+        .await
+        .unwrap();
+    let rescored = collection
+        .search(
+            request(serde_json::json!({
+                "rescore_with_exact": true,
+                "rescore_oversampling": 2.0,
+            })),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(rescored.len(), 3);
+    assert_eq!(
+        rescored.iter().map(|point| point.id).collect_vec(),
+        exact.iter().map(|point| point.id).collect_vec(),
+    );
+    for (rescored, exact) in rescored.iter().zip(&exact) {
+        assert!((rescored.score - exact.score).abs() < 1e-4);
+    }
+    assert!(
+        rescored.iter().all(|point| point.vector.is_none()),
+        "vectors retrieved for rescoring must not be returned",
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_read_only() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_point = |id: u64| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![id.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        )
+    };
+    collection
+        .update_from_client(insert_point(1), true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    collection
+        .update_params_from_diff(
+            serde_json::from_value(serde_json::json!({ "read_only": true })).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let result = collection
+        .update_from_client(insert_point(2), true, WriteOrdering::default())
+        .await;
+    assert!(
+        matches!(result, Err(CollectionError::Forbidden { .. })),
+        "updates of a read-only collection must be rejected: {result:?}",
+    );
+
+    // Reads are still served
+    let count = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+                shard_key: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 1);
+
+    collection
+        .update_params_from_diff(
+            serde_json::from_value(serde_json::json!({ "read_only": false })).unwrap(),
+        )
+        .await
+        .unwrap();
+    collection
+        .update_from_client(insert_point(2), true, WriteOrdering::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_skips_unavailable_shards() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    let this_peer_id = collection.this_peer_id();
+    let other_peer_id = this_peer_id + 1;
+
+    // The last active replica can't be deactivated directly, so hand the shard over to another
+    // peer first, and then remove that peer
+    let deactivate_shard = |shard_id| {
+        let collection = &collection;
+        async move {
+            collection
+                .set_shard_replica_state(shard_id, other_peer_id, ReplicaState::Active, None)
+                .await
+                .unwrap();
+            collection
+                .set_shard_replica_state(shard_id, this_peer_id, ReplicaState::Dead, None)
+                .await
+                .unwrap();
+            collection
+                .remove_shards_at_peer(other_peer_id)
+                .await
+                .unwrap();
+        }
+    };
+
+    let request: SearchRequest = serde_json::from_value(serde_json::json!({
+        "vector": [1.0, 0.0, 0.0, 1.0],
+        "limit": 3,
+    }))
+    .unwrap();
+
+    deactivate_shard(0).await;
+    let (_, meta) = collection
+        .search_with_meta(request.clone(), None, None)
+        .await
+        .unwrap();
+    assert!(!meta.shards_queried.contains(&0));
+    assert_eq!(meta.shards_queried.len(), N_SHARDS as usize - 1);
+    assert_eq!(meta.shards_skipped, vec![0]);
+
+    // Other reads are not silently incomplete, they still fail on the unavailable shard
+    let count_request = CountRequest {
+        filter: None,
+        exact: true,
+        shard_key: None,
+    };
+    assert!(collection.count(count_request, None, None).await.is_err());
+
+    for shard_id in 1..N_SHARDS {
+        deactivate_shard(shard_id).await;
+    }
+    let result = collection.search(request, None, None).await;
+    assert!(
+        matches!(&result, Err(CollectionError::ServiceError { error, .. }) if error == "All shards are unavailable"),
+        "search must fail if no shard is available: {result:?}",
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_count_cancellable() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..100u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..100)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let count_request = || CountRequest {
+        filter: None,
+        exact: true,
+        shard_key: None,
+    };
+
+    let cancel = CancellationToken::new();
+    let count_res = collection
+        .count(count_request(), None, Some(cancel.clone()))
+        .await
+        .unwrap();
+    assert_eq!(count_res.count, 100);
+
+    cancel.cancel();
+    let count_res = collection.count(count_request(), None, Some(cancel)).await;
+    assert!(
+        matches!(count_res, Err(CollectionError::Cancelled { .. })),
+        "cancelled count must not return a partial result: {count_res:?}",
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_hooks() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshot_path = collection_dir.path().join("snapshots");
+
+    let post_search_calls = Arc::new(AtomicUsize::new(0));
+    let search_hooks = SearchHooks {
+        pre_search_hook: Some(Arc::new(|request: &mut SearchRequest| {
+            if request.limit > 10 {
+                return Err(CollectionError::bad_input("limit is too large".to_string()));
+            }
+            request.limit = 1;
+            Ok(())
+        })),
+        post_search_hook: Some({
+            let post_search_calls = post_search_calls.clone();
+            Arc::new(move |_result: &[ScoredPoint]| {
+                post_search_calls.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            })
+        }),
+    };
+    let collection = new_local_collection_with_search_hooks(
+        "test".to_string(),
+        collection_dir.path(),
+        &snapshot_path,
+        &simple_collection_config(1),
+        search_hooks,
+    )
+    .await
+    .unwrap();
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..5u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..5)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |limit: usize| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0, 1.0],
+            "limit": limit,
+        }))
+        .unwrap()
+    };
+
+    // Limit is overridden by the pre-search hook
+    let result = collection.search(request(3), None, None).await.unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(post_search_calls.load(Ordering::Relaxed), 1);
+
+    // Rejected by the pre-search hook
+    let result = collection.search(request(100), None, None).await;
+    assert!(result.is_err());
+    assert_eq!(post_search_calls.load(Ordering::Relaxed), 1);
+
+    // Hooks are applied to each search of a batch
+    let batch = SearchRequestBatch {
+        searches: vec![request(3), request(5)],
+        debug: false,
+        dry_run: false,
+        explain_score: false,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms: None,
+        score_normalizer: None,
+    };
+    let result = collection.search_batch(batch, None, None).await.unwrap();
+    assert_eq!(result[0].len(), 1);
+    assert_eq!(result[1].len(), 1);
+    assert_eq!(post_search_calls.load(Ordering::Relaxed), 3);
+
+    // Internal searches of other peers are not intercepted
+    let batch = SearchRequestBatch {
+        searches: vec![request(3)],
+        debug: false,
+        dry_run: false,
+        explain_score: false,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms: None,
+        score_normalizer: None,
+    };
+    let result = collection.search_batch(batch, None, Some(0)).await.unwrap();
+    assert_eq!(result[0].len(), 3);
+    assert_eq!(post_search_calls.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_hooks() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshot_path = collection_dir.path().join("snapshots");
+
+    let updated_points = Arc::new(AtomicUsize::new(0));
+    let async_hook_calls = Arc::new(AtomicUsize::new(0));
+    let update_hooks = UpdateHooks {
+        on_update_hook: Some({
+            let updated_points = updated_points.clone();
+            Arc::new(move |_shard_id, operation: &CollectionUpdateOperations| {
+                if let CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    upsert,
+                )) = operation
+                {
+                    let points_count = match upsert {
+                        PointInsertOperations::PointsBatch(batch) => batch.ids.len(),
+                        PointInsertOperations::PointsList(points) => points.len(),
+                    };
+                    updated_points.fetch_add(points_count, Ordering::Relaxed);
+                }
+            })
+        }),
+        on_update_hook_async: Some({
+            let async_hook_calls = async_hook_calls.clone();
+            Arc::new(move |_shard_id, _operation| {
+                let async_hook_calls = async_hook_calls.clone();
+                async move {
+                    async_hook_calls.fetch_add(1, Ordering::Relaxed);
+                }
+                .boxed()
+            })
+        }),
+    };
+    let collection = new_local_collection_with_hooks(
+        "test".to_string(),
+        collection_dir.path(),
+        &snapshot_path,
+        &simple_collection_config(N_SHARDS),
+        Default::default(),
+        update_hooks,
+    )
+    .await
+    .unwrap();
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..10)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    let result = collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+    assert_eq!(result.status, UpdateStatus::Completed);
+
+    // Each shard reports its own part of the operation
+    assert_eq!(updated_points.load(Ordering::Relaxed), 10);
+    let async_calls = async_hook_calls.load(Ordering::Relaxed);
+    assert!((1..=N_SHARDS as usize).contains(&async_calls));
+    assert_eq!(result.shards_written as usize, async_calls);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_info_all_shards_available() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let info = collection.info(None).await.unwrap();
+    assert!(info.unavailable_shards.is_empty());
+    assert!(info.degraded_shards.is_empty());
+    assert!(!info.partial_result);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_state_checksum() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let state = collection.state().await.unwrap();
+    assert!(!state.state_checksum.is_empty());
+    assert_eq!(
+        collection.state_checksum().await.unwrap(),
+        state.state_checksum
+    );
+    // Checksum is stable
+    assert_eq!(
+        collection.state().await.unwrap().state_checksum,
+        state.state_checksum
+    );
+
+    collection
+        .update_optimizer_params(OptimizersConfig {
+            indexing_threshold: Some(1_000),
+            ..TEST_OPTIMIZERS_CONFIG
+        })
+        .await
+        .unwrap();
+    assert_ne!(
+        collection.state_checksum().await.unwrap(),
+        state.state_checksum
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_apply_state_shard_count_mismatch() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 2).await;
+
+    let mut state = collection.state().await.unwrap();
+    let shard_info = state.shards.remove(&1).unwrap();
+    state.shards.insert(5, shard_info);
+
+    collection.apply_state(state, 0, |_| {}).await.unwrap();
+
+    let shards_with_state: HashMap<_, _> = collection
+        .get_local_shards_with_state()
+        .await
+        .into_iter()
+        .collect();
+    assert_eq!(shards_with_state.get(&0), Some(&ReplicaState::Active));
+    assert_eq!(
+        shards_with_state.get(&1),
+        Some(&ReplicaState::Dead),
+        "shard missing from the state is marked as dead",
+    );
+    assert_eq!(
+        shards_with_state.get(&5),
+        Some(&ReplicaState::Active),
+        "shard missing locally is created from the state",
+    );
+    assert!(collection_dir.path().join("5").exists());
+
+    // Applying the same state again is a no-op
+    let state = collection.state().await.unwrap();
+    collection
+        .apply_state(state.clone(), 0, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(collection.state().await.unwrap(), state);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_points_stream() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..9)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let records: Vec<_> = collection
+        .points_stream(
+            None,
+            2,
+            WithPayloadInterface::Bool(false),
+            false.into(),
+            None,
+        )
+        .try_collect()
+        .await
+        .unwrap();
+    let ids = records.iter().map(|record| record.id).collect_vec();
+    assert_eq!(ids, (0..9u64).map(PointIdType::from).collect_vec());
+
+    // Invalid batch size is reported as an error
+    let results: Vec<_> = collection
+        .points_stream(
+            None,
+            0,
+            WithPayloadInterface::Bool(false),
+            false.into(),
+            None,
+        )
+        .collect()
+        .await;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_lookup_from_without_callback() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let request: SearchRequest = serde_json::from_str(
+        r#"{ "vector": [1.0, 0.0, 1.0, 1.0], "limit": 3, "lookup_from": ["other", 1] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        request.lookup_from,
+        Some(("other".to_string(), PointIdType::from(1))),
+    );
+
+    // Collection is created without a lookup callback, so the lookup can't be resolved
+    let result = collection.search(request, None, None).await;
+    assert!(result.is_err());
+}
+
+/// Collection with a single shard holding 9 points, used as the source of shard data
+async fn shard_data_fixture(collection_path: &Path) -> Collection {
+    let collection = simple_collection_fixture(collection_path, 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..9)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    collection
+}
+
+fn copy_shard_data(collection_path: &Path, shard_id: u32, target: &Path) {
+    fs_extra::dir::copy(
+        collection_path.join(format!("{shard_id}")),
+        target,
+        &fs_extra::dir::CopyOptions::new().content_only(true),
+    )
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_recover_local_shard_from() {
+    let source_dir = Builder::new().prefix("source").tempdir().unwrap();
+    let _source = shard_data_fixture(source_dir.path()).await;
+
+    let shard_data_dir = Builder::new().prefix("shard_data").tempdir().unwrap();
+    copy_shard_data(source_dir.path(), 0, shard_data_dir.path());
+
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let recovered = collection
+        .recover_local_shard_from(shard_data_dir.path(), 0)
+        .await
+        .unwrap();
+    assert!(recovered);
+
+    let count_request = CountRequest {
+        filter: None,
+        exact: true,
+        shard_key: None,
+    };
+    let count = collection.count(count_request, None, None).await.unwrap();
+    assert_eq!(count.count, 9);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_recover_local_shard_from_incompatible() {
+    let source_dir = Builder::new().prefix("source").tempdir().unwrap();
+    let _source = shard_data_fixture(source_dir.path()).await;
+
+    let shard_data_dir = Builder::new().prefix("shard_data").tempdir().unwrap();
+    copy_shard_data(source_dir.path(), 0, shard_data_dir.path());
+
+    // Same collection, but with 8-dimensional vectors
+    let mut config = simple_collection_config(1);
+    config.params.vectors = VectorParams {
+        size: NonZeroU64::new(8).unwrap(),
+        distance: Distance::Dot,
+        hnsw_config: None,
+        quantization_config: None,
+        on_disk: None,
+    }
+    .into();
+
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshots_path = collection_dir.path().join("snapshots");
+    let collection = new_local_collection(
+        "test".to_string(),
+        collection_dir.path(),
+        &snapshots_path,
+        &config,
+    )
+    .await
+    .unwrap();
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![1.0; 8]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let result = collection
+        .recover_local_shard_from(shard_data_dir.path(), 0)
+        .await;
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "unexpected result: {result:?}",
+    );
+
+    // Existing shard is left untouched, snapshot data is not consumed
+    let count_request = CountRequest {
+        filter: None,
+        exact: true,
+        shard_key: None,
+    };
+    let count = collection.count(count_request, None, None).await.unwrap();
+    assert_eq!(count.count, 1);
+    assert!(shard_data_dir.path().join("segments").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_recover_local_shard_from_rollback() {
+    let source_dir = Builder::new().prefix("source").tempdir().unwrap();
+    let _source = shard_data_fixture(source_dir.path()).await;
+
+    let shard_data_dir = Builder::new().prefix("shard_data").tempdir().unwrap();
+    copy_shard_data(source_dir.path(), 0, shard_data_dir.path());
+
+    // Segment of a newer version can't be loaded, but passes the compatibility check
+    let broken_segment = shard_data_dir.path().join("segments").join("broken");
+    std::fs::create_dir_all(&broken_segment).unwrap();
+    std::fs::write(broken_segment.join("version.info"), "999.0.0").unwrap();
+
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = |ids: Vec<u64>| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: ids.iter().map(|&id| id.into()).collect_vec(),
+                vectors: ids
+                    .iter()
+                    .map(|&id| vec![id as f32, 0.0, 0.0, 1.0])
+                    .collect_vec()
+                    .into(),
+                payloads: None,
+            }
+            .into(),
+        )
+    };
+    collection
+        .update_from_client(
+            insert_points(vec![100, 101]),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+
+    let result = collection
+        .recover_local_shard_from(shard_data_dir.path(), 0)
+        .await;
+    assert!(result.is_err(), "unexpected result: {result:?}");
+
+    // Previous shard data is loaded back and the shard stays operational
+    let count_request = || CountRequest {
+        filter: None,
+        exact: true,
+        shard_key: None,
+    };
+    let count = collection.count(count_request(), None, None).await.unwrap();
+    assert_eq!(count.count, 2);
+
+    collection
+        .update_from_client(insert_points(vec![102]), true, WriteOrdering::default())
+        .await
+        .unwrap();
+    let count = collection.count(count_request(), None, None).await.unwrap();
+    assert_eq!(count.count, 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_from_peer_unknown_shard() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = || {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![0.into()],
+                vectors: vec![vec![1.0, 0.0, 0.0, 1.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        )
+    };
+
+    let result = collection
+        .update_from_peer(insert_points(), 0, true)
+        .await
+        .unwrap();
+    assert_eq!(result.status, UpdateStatus::Completed);
+
+    let error = collection
+        .update_from_peer(insert_points(), N_SHARDS + 5, true)
+        .await
+        .unwrap_err();
+    match error {
+        CollectionError::NotFound { what } => {
+            assert!(what.contains(&format!("shard {}", N_SHARDS + 5)), "{what}");
+            assert!(what.contains("known shards: [0, 1, 2]"), "{what}");
+        }
+        error => panic!("unexpected error: {error:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_score_threshold() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..9)
+                .map(|x| vec![x as f32, 0.0, 0.0, 0.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let request = |score_threshold: f32, offset: usize| -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0, 0.0],
+            "limit": 5,
+            "offset": offset,
+            "score_threshold": score_threshold,
+        }))
+        .unwrap()
+    };
+
+    // Threshold below every score does not prune anything
+    let result = collection
+        .search(request(-1.0, 0), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 5);
+
+    // Fewer than `limit` results are returned, all of them above the threshold
+    let result = collection
+        .search(request(5.5, 0), None, None)
+        .await
+        .unwrap();
+    let ids = result.iter().map(|point| point.id).collect_vec();
+    assert_eq!(ids, [8u64, 7, 6].map(PointIdType::from));
+
+    // Threshold is applied after the offset is removed
+    let result = collection
+        .search(request(5.5, 1), None, None)
+        .await
+        .unwrap();
+    let ids = result.iter().map(|point| point.id).collect_vec();
+    assert_eq!(ids, [7u64, 6].map(PointIdType::from));
+
+    // Threshold above every score returns nothing
+    let result = collection
+        .search(request(100.0, 0), None, None)
+        .await
+        .unwrap();
+    assert!(result.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_per_search_read_consistency() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..9)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Searches of different consistency are interleaved, limits identify their positions
+    let batch: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [
+            { "vector": [1.0, 0.0, 0.0, 0.0], "limit": 1, "read_consistency": "majority" },
+            { "vector": [1.0, 0.0, 0.0, 0.0], "limit": 2 },
+            { "vector": [1.0, 0.0, 0.0, 0.0], "limit": 3, "read_consistency": 1 },
+            { "vector": [1.0, 0.0, 0.0, 0.0], "limit": 4, "read_consistency": "majority" },
+        ]
+    }))
+    .unwrap();
+
+    let results = collection.search_batch(batch, None, None).await.unwrap();
+    let lengths = results.iter().map(Vec::len).collect_vec();
+    assert_eq!(lengths, vec![1, 2, 3, 4]);
+    for result in &results {
+        assert_eq!(result[0].id, PointIdType::from(8));
+    }
+
+    // Failure of a single group fails the whole batch
+    let batch: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [
+            { "vector": [1.0, 0.0, 0.0, 0.0], "limit": 1 },
+            { "vector": [1.0, 0.0], "limit": 2, "read_consistency": "all" },
+        ]
+    }))
+    .unwrap();
+    assert!(collection.search_batch(batch, None, None).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_handle_replica_changes_add() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshots_path = collection_dir.path().join("snapshots");
+    let config = simple_collection_config(1);
+
+    let requested_transfers = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let request_shard_transfer: RequestShardTransfer = {
+        let requested_transfers = requested_transfers.clone();
+        Arc::new(move |transfer| requested_transfers.lock().unwrap().push(transfer))
+    };
+
+    let collection = Collection::new(
+        "test".to_string(),
+        0,
+        collection_dir.path(),
+        &snapshots_path,
+        &config,
+        Default::default(),
+        CollectionShardDistribution::all_local(Some(1), 0),
+        ChannelService::default(),
+        Arc::new(|_peer_id, _shard_id| {}),
+        request_shard_transfer,
+        None,
+        Default::default(),
+        Default::default(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    collection
+        .set_shard_replica_state(0, 0, ReplicaState::Active, None)
+        .await
+        .unwrap();
+
+    collection
+        .handle_replica_changes(vec![Change::Add(0, 100)])
+        .await
+        .unwrap();
+
+    let state = collection.state().await.unwrap();
+    assert_eq!(
+        state.shards[&0].replicas.get(&100),
+        Some(&ReplicaState::Partial),
+    );
+    let requested_transfers = requested_transfers.lock().unwrap().clone();
+    assert_eq!(requested_transfers.len(), 1);
+    assert_eq!(requested_transfers[0].shard_id, 0);
+    assert_eq!(requested_transfers[0].from, 0);
+    assert_eq!(requested_transfers[0].to, 100);
+
+    // Peer already has a replica
+    let result = collection
+        .handle_replica_changes(vec![Change::Add(0, 100)])
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadRequest { .. })));
+
+    // Shard does not exist
+    let result = collection
+        .handle_replica_changes(vec![Change::Add(5, 101)])
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadRequest { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_accessors() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    assert_eq!(collection.this_peer_id(), 0);
+    assert_eq!(collection.path(), collection_dir.path());
+    assert_eq!(
+        collection.snapshots_path(),
+        collection_dir.path().join("snapshots"),
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_info_degraded_shards() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    let this_peer_id = collection.this_peer_id();
+    let other_peer_id = this_peer_id + 1;
+
+    let info = collection.info(None).await.unwrap();
+    assert!(!info.partial_result);
+    assert!(info.degraded_shards.is_empty());
+
+    // Leave shard 0 without any active replica, so it fails to report its info
+    collection
+        .set_shard_replica_state(0, other_peer_id, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    collection
+        .set_shard_replica_state(0, this_peer_id, ReplicaState::Dead, None)
+        .await
+        .unwrap();
+    collection
+        .remove_shards_at_peer(other_peer_id)
+        .await
+        .unwrap();
+
+    let info = collection.info(None).await.unwrap();
+    assert!(info.partial_result);
+    assert_eq!(info.degraded_shards, vec![0]);
+
+    // Nothing to report if all the selected shards failed
+    assert!(collection.info(Some(0)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_telemetry_optimizer_queue() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..9)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let telemetry = collection.get_telemetry_data().await;
+
+    // Small collection is below every optimization threshold
+    let local_queues = telemetry
+        .shards
+        .iter()
+        .map(|shard| shard.local.as_ref().unwrap().optimizer_queue.clone())
+        .collect_vec();
+    assert_eq!(local_queues.len(), N_SHARDS as usize);
+    assert!(local_queues
+        .iter()
+        .all(|queue| *queue == OptimizerQueueStats::default()));
+
+    // Collection reports the sum over local shards
+    let mut total = OptimizerQueueStats::default();
+    for queue in &local_queues {
+        total += queue;
+    }
+    assert_eq!(telemetry.optimizer_queue, total);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_list_snapshots_paginated() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    // Files are created in order, so their order doesn't change, even across a second boundary
+    std::fs::create_dir_all(collection.snapshots_path()).unwrap();
+    for name in ["a", "b", "c", "d", "e"] {
+        std::fs::write(
+            collection.snapshots_path().join(format!("{name}.snapshot")),
+            name,
+        )
+        .unwrap();
+    }
+
+    let page_names = |page: &SnapshotListPage| {
+        page.snapshots
+            .iter()
+            .map(|snapshot| snapshot.name.clone())
+            .collect_vec()
+    };
+
+    let page = collection.list_snapshots_paginated(2, 0).await.unwrap();
+    assert_eq!(page_names(&page), vec!["a.snapshot", "b.snapshot"]);
+    assert_eq!(page.total, 5);
+    assert_eq!(page.next_offset, Some(2));
+
+    let page = collection.list_snapshots_paginated(2, 4).await.unwrap();
+    assert_eq!(page_names(&page), vec!["e.snapshot"]);
+    assert_eq!(page.next_offset, None);
+
+    // Offset past the end gives an empty page
+    let page = collection.list_snapshots_paginated(2, 10).await.unwrap();
+    assert!(page.snapshots.is_empty());
+    assert_eq!(page.total, 5);
+    assert_eq!(page.next_offset, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_hnsw_config_constraints() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let hnsw_diff =
+        |diff: serde_json::Value| -> HnswConfigDiff { serde_json::from_value(diff).unwrap() };
+
+    collection
+        .update_hnsw_config_from_diff(hnsw_diff(serde_json::json!({ "m": 8, "ef_construct": 8 })))
+        .await
+        .unwrap();
+    let hnsw_config = collection.get_telemetry_data().await.config.hnsw_config;
+    assert_eq!((hnsw_config.m, hnsw_config.ef_construct), (8, 8));
+
+    // `ef_construct` lower than `m`
+    let result = collection
+        .update_hnsw_config_from_diff(hnsw_diff(serde_json::json!({ "m": 16, "ef_construct": 8 })))
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadInput { .. })));
+
+    // `m` of 1 doesn't make a graph
+    let result = collection
+        .update_hnsw_config_from_diff(hnsw_diff(serde_json::json!({ "m": 1 })))
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadInput { .. })));
+
+    // Rejected updates don't change the config
+    let hnsw_config = collection.get_telemetry_data().await.config.hnsw_config;
+    assert_eq!((hnsw_config.m, hnsw_config.ef_construct), (8, 8));
+
+    // `m = 0` disables the graph and is always accepted
+    collection
+        .update_hnsw_config_from_diff(hnsw_diff(serde_json::json!({ "m": 0 })))
+        .await
+        .unwrap();
+}
+
+/// Collection with `N_SHARDS` shards and 30 points spread over them
+async fn scroll_fixture(collection_path: &Path) -> Collection {
+    let collection = simple_collection_fixture(collection_path, N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..30u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..30)
+                .map(|x| vec![x as f32, 0.0, 0.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    collection
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_scroll_with_shard_key() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = scroll_fixture(collection_dir.path()).await;
+
+    let request = ScrollRequest {
+        limit: Some(100),
+        with_shard_key: true,
+        ..Default::default()
+    };
+    let result = collection.scroll_by(request, None, None).await.unwrap();
+    assert_eq!(result.points.len(), 30);
+
+    // Reported shard is the one actually holding the point
+    for shard_id in 0..N_SHARDS {
+        let request = ScrollRequest {
+            limit: Some(100),
+            ..Default::default()
+        };
+        let shard_points: HashSet<_> = collection
+            .scroll_by(request, None, Some(shard_id))
+            .await
+            .unwrap()
+            .points
+            .into_iter()
+            .map(|point| point.id)
+            .collect();
+        let reported_points: HashSet<_> = result
+            .points
+            .iter()
+            .filter(|point| point.shard_id == Some(shard_id))
+            .map(|point| point.id)
+            .collect();
+        assert_eq!(shard_points, reported_points);
+    }
+
+    // Shard is not reported unless requested
+    let request = ScrollRequest {
+        limit: Some(100),
+        ..Default::default()
+    };
+    let result = collection.scroll_by(request, None, None).await.unwrap();
+    assert!(result.points.iter().all(|point| point.shard_id.is_none()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_scroll_by_shard_key() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = scroll_fixture(collection_dir.path()).await;
+
+    let scroll_ids = |shard_key, shard_selection| {
+        let collection = &collection;
+        async move {
+            let request = ScrollRequest {
+                limit: Some(100),
+                shard_key,
+                ..Default::default()
+            };
+            collection
+                .scroll_by(request, None, shard_selection)
+                .await
+                .unwrap()
+                .points
+                .into_iter()
+                .map(|point| point.id)
+                .collect::<HashSet<_>>()
+        }
+    };
+
+    // Shards are disjoint and cover all the points
+    let mut all_ids = HashSet::new();
+    for shard_id in 0..N_SHARDS {
+        let shard_ids = scroll_ids(Some(shard_id), None).await;
+        assert!(shard_ids.len() < 30);
+        assert_eq!(shard_ids, scroll_ids(None, Some(shard_id)).await);
+        assert!(all_ids.is_disjoint(&shard_ids));
+        all_ids.extend(shard_ids);
+    }
+    assert_eq!(all_ids.len(), 30);
+
+    // Shard selection of the call takes precedence over the shard key of the request
+    assert_eq!(
+        scroll_ids(Some(1), Some(0)).await,
+        scroll_ids(Some(0), None).await,
+    );
+
+    // Unknown shard has no points
+    assert!(scroll_ids(Some(N_SHARDS + 1), None).await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_remove_shards_at_peer_aborts_transfers() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    let (source_peer_id, removed_peer_id) = (1, 2);
+
+    // Transfer between two other peers, so that no transfer task is started here
+    collection
+        .set_shard_replica_state(0, source_peer_id, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    let transfer = ShardTransfer {
+        shard_id: 0,
+        from: source_peer_id,
+        to: removed_peer_id,
+        sync: false,
+    };
+    let started = collection
+        .start_shard_transfer(transfer, async {}, async {})
+        .await
+        .unwrap();
+    assert!(!started);
+    assert_eq!(collection.get_transfers(|_| true).await.len(), 1);
+
+    // Transfers of other peers are not affected
+    collection.remove_shards_at_peer(3).await.unwrap();
+    assert_eq!(collection.get_transfers(|_| true).await.len(), 1);
+
+    collection
+        .remove_shards_at_peer(removed_peer_id)
+        .await
+        .unwrap();
+    assert!(collection.get_transfers(|_| true).await.is_empty());
+    assert_eq!(
+        collection
+            .get_last_transfer_abort_reason(0)
+            .await
+            .as_deref(),
+        Some("peer 2 is removed"),
+    );
+
+    let state = collection.state().await.unwrap();
+    assert!(!state.shards[&0].replicas.contains_key(&removed_peer_id));
+    assert_eq!(
+        state.shards[&0].replicas.get(&source_peer_id),
+        Some(&ReplicaState::Active),
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_scroll_composite_key_filter() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = scroll_fixture(collection_dir.path()).await;
+
+    let even_ids: HashSet<PointIdType> = (0..30u64).step_by(2).map(PointIdType::from).collect();
+    let filter = Filter::new_must(Condition::HasId(HasIdCondition::from(even_ids.clone())));
+
+    // Scroll through all the pages with the composite key
+    let mut scrolled_ids = vec![];
+    let mut request = ScrollRequest {
+        limit: Some(4),
+        filter: Some(filter.clone()),
+        ..Default::default()
+    };
+    loop {
+        let page = collection
+            .scroll_by(request.clone(), None, None)
+            .await
+            .unwrap();
+        scrolled_ids.extend(page.points.iter().map(|point| point.id));
+        let Some(next_page_key) = page.next_page_key else {
+            break;
+        };
+        assert!(matches!(
+            &next_page_key,
+            ScrollKey::Composite { filter: key_filter, .. } if *key_filter == filter,
+        ));
+        // Filter is carried by the key
+        request = ScrollRequest {
+            offset: Some(next_page_key),
+            limit: Some(4),
+            filter: None,
+            ..Default::default()
+        };
+    }
+    let expected_ids = (0..30u64).step_by(2).map(PointIdType::from).collect_vec();
+    assert_eq!(scrolled_ids, expected_ids);
+
+    // Key can't be combined with another filter
+    let request = ScrollRequest {
+        offset: Some(ScrollKey::Composite {
+            last_id: 4.into(),
+            filter,
+        }),
+        filter: Some(Filter::new_must_not(Condition::HasId(
+            HasIdCondition::from(even_ids),
+        ))),
+        ..Default::default()
+    };
+    let result = collection.scroll_by(request, None, None).await;
+    assert!(matches!(result, Err(CollectionError::BadInput { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_tiebreaker() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    // Even and odd points share the same score, odd points score higher
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..8u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..8)
+                .map(|x| vec![(1 + x % 2) as f32, 0.0, 0.0, 0.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Limit covers all points, so every shard returns all of its points
+    // and the order is defined by the merge only
+    let batch: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [
+            {
+                "vector": [1.0, 0.0, 0.0, 0.0],
+                "limit": 8,
+            },
+            {
+                "vector": [1.0, 0.0, 0.0, 0.0],
+                "limit": 8,
+                "tiebreaker": "id_ascending",
+            },
+            {
+                "vector": [1.0, 0.0, 0.0, 0.0],
+                "limit": 8,
+                "tiebreaker": "id_descending",
+            },
+        ]
+    }))
+    .unwrap();
+
+    let result = collection.search_batch(batch, None, None).await.unwrap();
+    let ids = result
+        .iter()
+        .map(|points| points.iter().map(|point| point.id).collect_vec())
+        .collect_vec();
+
+    let ascending = [1u64, 3, 5, 7, 0, 2, 4, 6].map(PointIdType::from);
+    let descending = [7u64, 5, 3, 1, 6, 4, 2, 0].map(PointIdType::from);
+    assert_eq!(ids[0], ascending);
+    assert_eq!(ids[1], ascending);
+    assert_eq!(ids[2], descending);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_info_disk_data_size() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let size = collection.info(None).await.unwrap().disk_data_size_bytes;
+    assert!(size > 0);
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..100u64).map(|x| x.into()).collect_vec(),
+            vectors: (0..100)
+                .map(|x| vec![x as f32, 0.0, 0.0, 0.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Size is cached and not recomputed on every request
+    let cached_size = collection.info(None).await.unwrap().disk_data_size_bytes;
+    assert_eq!(cached_size, size);
+
+    collection.before_drop().await;
+
+    // Failure to compute the size does not fail the info request
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    std::fs::remove_dir_all(collection_dir.path().join("0")).unwrap();
+
+    let info = collection.info(None).await.unwrap();
+    assert_eq!(info.disk_data_size_bytes, 0);
+
+    collection.before_drop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_filtered_shard_snapshot() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = |ids: std::ops::Range<u64>| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: ids.clone().map(|x| x.into()).collect_vec(),
+                vectors: ids
+                    .clone()
+                    .map(|x| vec![x as f32, 0.0, 0.0, 0.0])
+                    .collect_vec()
+                    .into(),
+                payloads: Some(
+                    ids.map(|x| Some(serde_json::json!({ "num": x }).try_into().unwrap()))
+                        .collect_vec(),
+                ),
+            }
+            .into(),
+        )
+    };
+    collection
+        .update_from_client(insert_points(0..100), true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let filter = Filter::new_must(Condition::Field(FieldCondition::new_range(
+        "num",
+        segment::types::Range {
+            lt: Some(10.0),
+            gt: None,
+            gte: None,
+            lte: None,
+        },
+    )));
+
+    // Updates are not blocked while the filtered snapshot is created
+    let snapshots_temp_dir = Builder::new().prefix("temp_dir").tempdir().unwrap();
+    let (snapshot, updates) = tokio::join!(
+        collection.create_shard_snapshot(0, snapshots_temp_dir.path(), Some(&filter), false),
+        async {
+            for start in (100..200).step_by(10) {
+                collection
+                    .update_from_client(
+                        insert_points(start..start + 10),
+                        true,
+                        WriteOrdering::default(),
+                    )
+                    .await?;
+            }
+            Ok::<_, CollectionError>(())
+        },
+    );
+    let snapshot = snapshot.unwrap();
+    updates.unwrap();
+
+    let snapshot_path = collection
+        .get_snapshots_path_for_shard(0)
+        .await
+        .unwrap()
+        .join(&snapshot.name);
+
+    // Restored shard contains only the points matching the filter
+    let recover_dir = Builder::new().prefix("collection_rec").tempdir().unwrap();
+    let recovered = simple_collection_fixture(recover_dir.path(), 1).await;
+    recovered
+        .restore_shard_snapshot(0, &snapshot_path, 0, false, snapshots_temp_dir.path())
+        .await
+        .unwrap();
+
+    let count_request = |filter| CountRequest {
+        filter,
+        exact: true,
+        shard_key: None,
+    };
+    let count = recovered
+        .count(count_request(None), None, None)
+        .await
+        .unwrap();
+    assert_eq!(count.count, 10);
+    let count = recovered
+        .count(count_request(Some(filter)), None, None)
+        .await
+        .unwrap();
+    assert_eq!(count.count, 10);
+
+    collection.before_drop().await;
+    recovered.before_drop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_abort_shard_transfer_reason() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    let (source_peer_id, target_peer_id) = (1, 2);
+
+    collection
+        .set_shard_replica_state(0, source_peer_id, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    let transfer = ShardTransfer {
+        shard_id: 0,
+        from: source_peer_id,
+        to: target_peer_id,
+        sync: true,
+    };
+    collection
+        .start_shard_transfer(transfer.clone(), async {}, async {})
+        .await
+        .unwrap();
+    assert_eq!(collection.get_last_transfer_abort_reason(0).await, None);
+
+    collection
+        .abort_shard_transfer(transfer.key(), Some("target is out of disk space"))
+        .await
+        .unwrap();
+    assert!(collection.get_transfers(|_| true).await.is_empty());
+    assert_eq!(
+        collection
+            .get_last_transfer_abort_reason(0)
+            .await
+            .as_deref(),
+        Some("target is out of disk space"),
+    );
+
+    // Transfer started again after the abort is not affected by the previous reason
+    collection
+        .start_shard_transfer(transfer.clone(), async {}, async {})
+        .await
+        .unwrap();
+    assert_eq!(
+        collection.get_transfers(|_| true).await,
+        vec![transfer.clone()]
+    );
+
+    // Abort without a reason replaces the previous one
+    collection
+        .abort_shard_transfer(transfer.key(), None)
+        .await
+        .unwrap();
+    assert_eq!(collection.get_last_transfer_abort_reason(0).await, None);
+
+    // Other shards have no abort history
+    assert_eq!(collection.get_last_transfer_abort_reason(1).await, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_finish_shard_transfer_rollback_unregisters() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    let (this_peer_id, target_peer_id) = (0, 1);
+
+    // Without local shard on the sending side, the proxy can't be unwrapped on finish
+    collection
+        .remove_shards_at_peer(this_peer_id)
+        .await
+        .unwrap();
+
+    let transfer = ShardTransfer {
+        shard_id: 0,
+        from: this_peer_id,
+        to: target_peer_id,
+        sync: true,
+    };
+    let started = collection
+        .start_shard_transfer(transfer.clone(), async {}, async {})
+        .await
+        .unwrap();
+    assert!(!started);
+    assert_eq!(
+        collection.get_transfers(|_| true).await,
+        vec![transfer.clone()]
+    );
+
+    let result = collection.finish_shard_transfer(transfer.clone()).await;
+    assert!(matches!(
+        result,
+        Err(CollectionError::TransferFinalizationFailed { shard_id: 0, .. }),
+    ));
+
+    // Rolled back transfer is not registered anymore, and the failure is kept as abort reason
+    assert!(collection.get_transfers(|_| true).await.is_empty());
+    let abort_reason = collection.get_last_transfer_abort_reason(0).await.unwrap();
+    assert!(abort_reason.contains("un-proxify"), "{abort_reason}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_search_batch_debug_preprocessing() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into()],
+            vectors: vec![vec![1.0, 1.0, 0.0, 0.0], vec![0.9, -0.5, 0.0, 0.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Negative examples are applied to the query, same as in the regular search
+    let batch = || -> SearchRequestBatch {
+        serde_json::from_value(serde_json::json!({
+            "searches": [{
+                "vector": [1.0, 0.0, 0.0, 0.0],
+                "negative": [[0.0, 1.0, 0.0, 0.0]],
+                "limit": 1,
+            }],
+            "debug": true,
+        }))
+        .unwrap()
+    };
+    let expected = collection.search_batch(batch(), None, None).await.unwrap();
+    let result = collection
+        .search_batch_debug(batch(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.merged, expected);
+    assert_eq!(result.merged[0][0].id, PointIdType::from(1));
+    assert!(!result.per_shard.is_empty());
+
+    // Unknown vectors are rejected before shards are queried
+    let batch: SearchRequestBatch = serde_json::from_value(serde_json::json!({
+        "searches": [{
+            "vector": { "name": "image", "vector": [1.0, 0.0, 0.0, 0.0] },
+            "limit": 1,
+        }],
+        "debug": true,
+    }))
+    .unwrap();
+    assert!(matches!(
+        collection.search_batch_debug(batch, None, None).await,
+        Err(CollectionError::BadInput { .. }),
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_update_quantization_config_result() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let scalar: QuantizationConfigDiff = serde_json::from_value(serde_json::json!({
+        "scalar": { "type": "int8" },
+    }))
+    .unwrap();
+    let result = collection
+        .update_quantization_config_from_diff(scalar)
+        .await
+        .unwrap();
+    assert!(result.requires_optimizer_restart);
+    assert_eq!(result.warning, None);
+
+    let result = collection
+        .update_quantization_config_from_diff(QuantizationConfigDiff::new_disabled())
+        .await
+        .unwrap();
+    assert!(result.requires_optimizer_restart);
+    assert_eq!(
+        result.warning.as_deref(),
+        Some("Quantization removed; call recreate_optimizers_blocking to rebuild unquantized indexes"),
+    );
+
+    // Nothing to remove
+    let result = collection
+        .update_quantization_config_from_diff(QuantizationConfigDiff::new_disabled())
+        .await
+        .unwrap();
+    assert!(!result.requires_optimizer_restart);
+    assert_eq!(result.warning, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_cluster_info_shard_key_ranges() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let info = collection.cluster_info(0).await.unwrap();
+
+    let shard_ids: HashSet<_> = info.shard_key_ranges.keys().copied().collect();
+    assert_eq!(shard_ids, (0..N_SHARDS).collect());
+
+    // Ranges of all shards cover the whole ring without overlapping
+    let mut ranges = info
+        .shard_key_ranges
+        .values()
+        .flatten()
+        .copied()
+        .collect_vec();
+    ranges.sort_unstable();
+    assert_eq!(ranges.first().unwrap().0, 0);
+    assert_eq!(ranges.last().unwrap().1, u64::MAX);
+    for (previous, next) in ranges.iter().tuple_windows() {
+        assert_eq!(previous.1 + 1, next.0);
+    }
+}
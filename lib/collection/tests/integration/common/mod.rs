@@ -4,7 +4,7 @@ use std::num::{NonZeroU32, NonZeroU64};
 use std::path::Path;
 use std::sync::Arc;
 
-use collection::collection::{Collection, RequestShardTransfer};
+use collection::collection::{Collection, RequestShardTransfer, SearchHooks, UpdateHooks};
 use collection::config::{CollectionConfig, CollectionParams, WalConfig};
 use collection::operations::types::{CollectionError, VectorParams};
 use collection::optimizers_builder::OptimizersConfig;
@@ -34,6 +34,23 @@ pub const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
 #[cfg(test)]
 #[allow(dead_code)]
 pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32) -> Collection {
+    let collection_config = simple_collection_config(shard_number);
+
+    let snapshot_path = collection_path.join("snapshots");
+
+    // Default to a collection with all the shards local
+    new_local_collection(
+        "test".to_string(),
+        collection_path,
+        &snapshot_path,
+        &collection_config,
+    )
+    .await
+    .unwrap()
+}
+
+/// Config of the collection with a single 4-dimensional vector and `Dot` distance
+pub fn simple_collection_config(shard_number: u32) -> CollectionConfig {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
@@ -52,27 +69,18 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
-    let collection_config = CollectionConfig {
+    CollectionConfig {
         params: collection_params,
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
-    };
-
-    let snapshot_path = collection_path.join("snapshots");
-
-    // Default to a collection with all the shards local
-    new_local_collection(
-        "test".to_string(),
-        collection_path,
-        &snapshot_path,
-        &collection_config,
-    )
-    .await
-    .unwrap()
+        use_cross_device_snapshot_copy: None,
+    }
 }
 
 pub fn dummy_on_replica_failure() -> ChangePeerState {
@@ -90,6 +98,40 @@ pub async fn new_local_collection(
     path: &Path,
     snapshots_path: &Path,
     config: &CollectionConfig,
+) -> Result<Collection, CollectionError> {
+    new_local_collection_with_search_hooks(id, path, snapshots_path, config, Default::default())
+        .await
+}
+
+/// Same as [`new_local_collection`], but with hooks called on each search
+#[cfg(test)]
+pub async fn new_local_collection_with_search_hooks(
+    id: CollectionId,
+    path: &Path,
+    snapshots_path: &Path,
+    config: &CollectionConfig,
+    search_hooks: SearchHooks,
+) -> Result<Collection, CollectionError> {
+    new_local_collection_with_hooks(
+        id,
+        path,
+        snapshots_path,
+        config,
+        search_hooks,
+        Default::default(),
+    )
+    .await
+}
+
+/// Same as [`new_local_collection`], but with hooks called on each search and update
+#[cfg(test)]
+pub async fn new_local_collection_with_hooks(
+    id: CollectionId,
+    path: &Path,
+    snapshots_path: &Path,
+    config: &CollectionConfig,
+    search_hooks: SearchHooks,
+    update_hooks: UpdateHooks,
 ) -> Result<Collection, CollectionError> {
     let collection = Collection::new(
         id,
@@ -103,6 +145,9 @@ pub async fn new_local_collection(
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        search_hooks,
+        update_hooks,
+        None,
         None,
     )
     .await;
@@ -135,7 +180,11 @@ pub async fn load_local_collection(
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        Default::default(),
+        Default::default(),
+        None,
         None,
     )
     .await
+    .unwrap()
 }
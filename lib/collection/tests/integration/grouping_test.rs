@@ -42,6 +42,7 @@ mod group_by {
             with_payload: None,
             with_vector: None,
             score_threshold: None,
+            ..Default::default()
         });
 
         let request = GroupRequest::with_limit_from_request(source, "docId".to_string(), 3);
@@ -205,6 +206,7 @@ mod group_by {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             3,
@@ -240,6 +242,7 @@ mod group_by {
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: Some(WithVector::Bool(true)),
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             3,
@@ -286,6 +289,7 @@ mod group_by {
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: Some(WithVector::Bool(true)),
                 score_threshold: None,
+                ..Default::default()
             }),
             "other_stuff".to_string(),
             3,
@@ -330,6 +334,7 @@ mod group_by {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             0,
@@ -370,6 +375,7 @@ mod group_by {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             3,
@@ -410,6 +416,7 @@ mod group_by {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             3,
@@ -454,6 +461,7 @@ mod group_by {
                 with_payload: None,
                 with_vector: None,
                 score_threshold: None,
+                ..Default::default()
             }),
             "docId".to_string(),
             400,
@@ -511,6 +519,7 @@ mod group_by_builder {
             with_payload: None,
             with_vector: None,
             score_threshold: None,
+            ..Default::default()
         });
 
         let request = GroupRequest::with_limit_from_request(source_request, "docId".to_string(), 3);
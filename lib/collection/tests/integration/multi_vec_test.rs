@@ -8,7 +8,9 @@ use collection::operations::point_ops::{
     PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
 };
 use collection::operations::types::{
-    CollectionError, PointRequest, RecommendRequest, SearchRequest, VectorParams, VectorsConfig,
+    CollectionError, CoreSearchRequest, Fusion, MultiVectorSearchRequest, PointRequest,
+    RecommendRequest, SearchRequest, VectorParams, VectorParamsDiff, VectorQuery, VectorsConfig,
+    VectorsConfigDiff, WeightedVector,
 };
 use collection::operations::CollectionUpdateOperations;
 use collection::recommendations::recommend_by;
@@ -61,6 +63,8 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
     let collection_config = CollectionConfig {
@@ -69,6 +73,7 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        use_cross_device_snapshot_copy: None,
     };
 
     let snapshot_path = collection_path.join("snapshots");
@@ -128,6 +133,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let result = collection
@@ -156,6 +162,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let result = collection.search(failed_search_request, None, None).await;
@@ -178,6 +185,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         with_vector: Some(true.into()),
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let result = collection
@@ -201,6 +209,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 ids: vec![6.into()],
                 with_payload: Some(WithPayloadInterface::Bool(false)),
                 with_vector: WithVector::Selector(vec![VEC_NAME1.to_string()]),
+                strict: false,
             },
             None,
             None,
@@ -267,3 +276,317 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         }
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_multi_vec_ensemble_search() {
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vec_ensemble")
+        .tempdir()
+        .unwrap();
+
+    let collection = multi_vec_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let points = (0..100)
+        .map(|i| {
+            let mut vectors = NamedVectors::default();
+            vectors.insert(VEC_NAME1.to_string(), vec![i as f32, 0.0, 0.0, 0.0]);
+            vectors.insert(VEC_NAME2.to_string(), vec![0.0, (100 - i) as f32, 0.0, 0.0]);
+            PointStruct {
+                id: i.into(),
+                vector: vectors.into(),
+                payload: None,
+            }
+        })
+        .collect();
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsList(points),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let ensemble_request = |weight1: f32, weight2: f32| SearchRequest {
+        vector: VectorQuery::Ensemble(vec![
+            WeightedVector {
+                name: VEC_NAME1.to_string(),
+                vector: vec![1.0, 0.0, 0.0, 0.0],
+                weight: weight1,
+            },
+            WeightedVector {
+                name: VEC_NAME2.to_string(),
+                vector: vec![0.0, 1.0, 0.0, 0.0],
+                weight: weight2,
+            },
+        ]),
+        filter: None,
+        limit: 3,
+        offset: 0,
+        with_payload: None,
+        with_vector: None,
+        params: None,
+        score_threshold: None,
+        ..Default::default()
+    };
+
+    // Vector 1 dominates: best point is the one closest by vector 1
+    let result = collection
+        .search(ensemble_request(3.0, 1.0), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0].id, 99.into());
+    // Point 99 is not among the top points of vector 2, so it gets no score from it
+    assert_eq!(result[0].score, 3.0 * 99.0);
+
+    // Vector 2 dominates: best point is the one closest by vector 2
+    let result = collection
+        .search(ensemble_request(1.0, 3.0), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0].id, 0.into());
+    assert_eq!(result[0].score, 3.0 * 100.0);
+
+    // Reciprocal Rank Fusion ignores weights and scores, only ranks matter.
+    // Top points of both vectors are ranked first, equal scores are ordered by ID
+    let rrf_request = |rrf_k: Option<u32>| SearchRequest {
+        fusion: Some(Fusion::Rrf),
+        rrf_k,
+        ..ensemble_request(3.0, 1.0)
+    };
+    let result = collection
+        .search(rrf_request(None), None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.iter().map(|point| point.id).collect::<Vec<_>>(),
+        vec![0.into(), 99.into(), 1.into()],
+    );
+    assert_eq!(result[0].score, 1.0 / 61.0);
+    assert_eq!(result[1].score, 1.0 / 61.0);
+    assert_eq!(result[2].score, 1.0 / 62.0);
+
+    let result = collection
+        .search(rrf_request(Some(0)), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result[0].score, 1.0);
+
+    // Ensembles which were not split are rejected by the conversion for shards
+    let result = CoreSearchRequest::try_from(ensemble_request(1.0, 1.0));
+    assert!(matches!(result, Err(CollectionError::BadRequest { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_multi_vector_search_batch() {
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vector_search_batch")
+        .tempdir()
+        .unwrap();
+
+    let collection = multi_vec_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let points = (0..100)
+        .map(|i| {
+            let mut vectors = NamedVectors::default();
+            vectors.insert(VEC_NAME1.to_string(), vec![i as f32, 0.0, 0.0, 0.0]);
+            vectors.insert(VEC_NAME2.to_string(), vec![(100 - i) as f32, 0.0, 0.0, 0.0]);
+            PointStruct {
+                id: i.into(),
+                vector: vectors.into(),
+                payload: None,
+            }
+        })
+        .collect();
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsList(points),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let search = || -> SearchRequest {
+        serde_json::from_value(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0, 0.0],
+            "limit": 3,
+        }))
+        .unwrap()
+    };
+
+    // The same query vector finds different points in each of the vectors
+    let request = MultiVectorSearchRequest {
+        searches: vec![
+            (VEC_NAME1.to_string(), search()),
+            (VEC_NAME2.to_string(), search()),
+        ],
+    };
+    let results = collection
+        .multi_vector_search_batch(request, None, None)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[VEC_NAME1][0].id, 99.into());
+    assert_eq!(results[VEC_NAME2][0].id, 0.into());
+
+    let request = MultiVectorSearchRequest {
+        searches: vec![
+            (VEC_NAME1.to_string(), search()),
+            (VEC_NAME1.to_string(), search()),
+        ],
+    };
+    let result = collection
+        .multi_vector_search_batch(request, None, None)
+        .await;
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "{result:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_multi_vec_update_unknown_vector() {
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vec_update_unknown_vector")
+        .tempdir()
+        .unwrap();
+
+    let collection = multi_vec_collection_fixture(collection_dir.path(), 1).await;
+
+    let diff = VectorsConfigDiff::Update(BTreeMap::from([(
+        "new_vector".to_string(),
+        VectorParamsDiff {
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: Some(true),
+        },
+    )]));
+    let result = collection.update_vectors_from_diff(&diff).await;
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "{result:?}"
+    );
+
+    // Configuration is left untouched
+    let info = collection.info(None).await.unwrap();
+    match info.config.params.vectors {
+        VectorsConfig::Multi(vectors) => {
+            assert_eq!(
+                vectors.keys().collect::<Vec<_>>(),
+                vec![VEC_NAME1, VEC_NAME2],
+            );
+        }
+        VectorsConfig::Single(_) => panic!("expected multi vector config"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_multi_vec_add_vector() {
+    const VEC_NAME3: &str = "vec3";
+
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vec_add_vector")
+        .tempdir()
+        .unwrap();
+
+    let collection = multi_vec_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let point = |id: u64, vec3: Option<Vec<f32>>| {
+        let mut vectors = NamedVectors::default();
+        vectors.insert(VEC_NAME1.to_string(), vec![id as f32, 0.0, 0.0, 0.0]);
+        vectors.insert(VEC_NAME2.to_string(), vec![0.0, id as f32, 0.0, 0.0]);
+        if let Some(vec3) = vec3 {
+            vectors.insert(VEC_NAME3.to_string(), vec3);
+        }
+        PointStruct {
+            id: id.into(),
+            vector: vectors.into(),
+            payload: None,
+        }
+    };
+    let upsert = |points| {
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsList(points),
+        ))
+    };
+    collection
+        .update_from_client(
+            upsert((0..100).map(|id| point(id, None)).collect()),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+
+    let vector_params = VectorParams {
+        size: NonZeroU64::new(4).unwrap(),
+        distance: Distance::Dot,
+        hnsw_config: None,
+        quantization_config: None,
+        on_disk: None,
+    };
+    let diff = VectorsConfigDiff::Add(BTreeMap::from([(
+        VEC_NAME3.to_string(),
+        vector_params.clone(),
+    )]));
+    collection.update_vectors_from_diff(&diff).await.unwrap();
+    collection.recreate_optimizers_blocking(None).await.unwrap();
+
+    // Existing vectors can't be added again
+    let diff = VectorsConfigDiff::Add(BTreeMap::from([(VEC_NAME1.to_string(), vector_params)]));
+    let result = collection.update_vectors_from_diff(&diff).await;
+    assert!(
+        matches!(result, Err(CollectionError::BadInput { .. })),
+        "{result:?}"
+    );
+
+    // Existing points get zero vectors
+    let retrieve = collection
+        .retrieve(
+            PointRequest {
+                ids: vec![6.into()],
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_vector: WithVector::Selector(vec![VEC_NAME3.to_string()]),
+                strict: false,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    match retrieve[0].vector.as_ref().unwrap() {
+        VectorStruct::Single(_) => panic!("expected multi vector"),
+        VectorStruct::Multi(vectors) => assert_eq!(vectors[VEC_NAME3], vec![0.0; 4]),
+    }
+
+    // New points are searchable by the added vector
+    collection
+        .update_from_client(
+            upsert(vec![point(100, Some(vec![1.0, 1.0, 1.0, 1.0]))]),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+    let result = collection
+        .search(
+            SearchRequest {
+                vector: NamedVector {
+                    name: VEC_NAME3.to_string(),
+                    vector: vec![1.0, 1.0, 1.0, 1.0],
+                }
+                .into(),
+                limit: 3,
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 3);
+    assert_eq!(result[0].id, 100.into());
+    assert_eq!(result[0].score, 4.0);
+    assert_eq!(result[1].score, 0.0);
+}
@@ -1,9 +1,11 @@
+use std::collections::HashSet;
+
 use collection::operations::point_ops::{
     PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
 };
-use collection::operations::types::SearchRequest;
+use collection::operations::types::{ScrollKey, ScrollRequest, SearchRequest};
 use collection::operations::CollectionUpdateOperations;
-use segment::types::WithPayloadInterface;
+use segment::types::{Condition, Filter, HasIdCondition, WithPayloadInterface};
 use tempfile::Builder;
 
 use crate::common::{simple_collection_fixture, N_SHARDS};
@@ -50,6 +52,7 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let reference_result = collection
@@ -71,6 +74,7 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let page_1_result = collection.search(page_1_request, None, None).await.unwrap();
@@ -90,6 +94,7 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         with_vector: None,
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let page_9_result = collection.search(page_9_request, None, None).await.unwrap();
@@ -100,3 +105,86 @@ async fn test_collection_paginated_search_with_shards(shard_number: u32) {
         assert_eq!(page_9_result[i], reference_result[page_size * 9 + i]);
     }
 }
+
+/// Filter matches points of a single shard only, other shards return empty pages.
+/// Paging must still visit every matching point exactly once.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collection_scroll_with_empty_filtered_shards() {
+    let collection_dir = Builder::new()
+        .prefix("test_collection_scroll_with_empty_filtered_shards")
+        .tempdir()
+        .unwrap();
+
+    let collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let points = (0..100u64)
+        .map(|i| PointStruct {
+            id: i.into(),
+            vector: vec![i as f32, 0.0, 0.0, 0.0].into(),
+            payload: None,
+        })
+        .collect();
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsList(points),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Find points of a single shard
+    let all_points = collection
+        .scroll_by(
+            ScrollRequest {
+                limit: Some(100),
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_shard_key: true,
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap()
+        .points;
+    let shard_id = all_points[0].shard_id.unwrap();
+    let expected_ids: Vec<_> = all_points
+        .iter()
+        .filter(|point| point.shard_id == Some(shard_id))
+        .map(|point| point.id)
+        .collect();
+
+    let filter = Filter {
+        should: None,
+        must: Some(vec![Condition::HasId(HasIdCondition::from(
+            expected_ids.iter().copied().collect::<HashSet<_>>(),
+        ))]),
+        must_not: None,
+    };
+
+    let mut scrolled_ids = vec![];
+    let mut offset = None;
+    loop {
+        let page = collection
+            .scroll_by(
+                ScrollRequest {
+                    offset: offset.map(ScrollKey::from),
+                    limit: Some(3),
+                    filter: Some(filter.clone()),
+                    with_payload: Some(WithPayloadInterface::Bool(false)),
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        scrolled_ids.extend(page.points.iter().map(|point| point.id));
+        match page.next_page_offset {
+            Some(next_offset) => offset = Some(next_offset),
+            None => break,
+        }
+    }
+
+    assert_eq!(scrolled_ids, expected_ids);
+}
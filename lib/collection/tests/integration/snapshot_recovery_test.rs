@@ -7,7 +7,10 @@ use collection::operations::point_ops::{
     PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
 };
 use collection::operations::shared_storage_config::SharedStorageConfig;
-use collection::operations::types::{NodeType, SearchRequest, VectorParams, VectorsConfig};
+use collection::operations::snapshot_ops::SnapshotProgressCallback;
+use collection::operations::types::{
+    CollectionError, NodeType, SearchRequest, VectorParams, VectorsConfig,
+};
 use collection::operations::CollectionUpdateOperations;
 use collection::shards::channel_service::ChannelService;
 use collection::shards::collection_shard_distribution::CollectionShardDistribution;
@@ -16,7 +19,8 @@ use segment::types::{Distance, WithPayloadInterface, WithVector};
 use tempfile::Builder;
 
 use crate::common::{
-    dummy_on_replica_failure, dummy_request_shard_transfer, TEST_OPTIMIZERS_CONFIG,
+    dummy_on_replica_failure, dummy_request_shard_transfer, simple_collection_config,
+    TEST_OPTIMIZERS_CONFIG,
 };
 
 async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
@@ -37,6 +41,8 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        max_payload_size_bytes: None,
+        read_only: false,
     };
 
     let config = CollectionConfig {
@@ -45,6 +51,7 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         wal_config,
         hnsw_config: Default::default(),
         quantization_config: Default::default(),
+        use_cross_device_snapshot_copy: None,
     };
 
     let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
@@ -79,6 +86,9 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        Default::default(),
+        Default::default(),
+        None,
         None,
     )
     .await
@@ -112,7 +122,7 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
     // Take a snapshot
     let snapshots_temp_dir = Builder::new().prefix("temp_dir").tempdir().unwrap();
     let snapshot_description = collection
-        .create_snapshot(snapshots_temp_dir.path(), 0)
+        .create_snapshot(snapshots_temp_dir.path(), 0, None)
         .await
         .unwrap();
 
@@ -121,6 +131,7 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         recover_dir.path(),
         0,
         false,
+        None,
     ) {
         panic!("Failed to restore snapshot: {err}")
     }
@@ -135,9 +146,13 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
         None,
+        Default::default(),
+        Default::default(),
+        None,
         None,
     )
-    .await;
+    .await
+    .unwrap();
 
     let query_vector = vec![1.0, 0.0, 0.0, 0.0];
 
@@ -150,6 +165,7 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
         with_vector: Some(WithVector::Bool(true)),
         params: None,
         score_threshold: None,
+        ..Default::default()
     };
 
     let reference_result = collection
@@ -180,3 +196,84 @@ async fn test_snapshot_and_recover_collection_normal() {
 async fn test_snapshot_and_recover_collection_listener() {
     _test_snapshot_and_recover_collection(NodeType::Listener).await;
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_snapshot_concurrency_limit() {
+    let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
+    let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+    let snapshots_temp_dir = Builder::new().prefix("temp_dir").tempdir().unwrap();
+
+    let storage_config = SharedStorageConfig {
+        max_concurrent_snapshots: 1,
+        ..Default::default()
+    };
+
+    let collection = Collection::new(
+        "test".to_string(),
+        0,
+        collection_dir.path(),
+        snapshots_path.path(),
+        &simple_collection_config(1),
+        Arc::new(storage_config),
+        CollectionShardDistribution::all_local(Some(1), 0),
+        ChannelService::default(),
+        dummy_on_replica_failure(),
+        dummy_request_shard_transfer(),
+        None,
+        Default::default(),
+        Default::default(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    collection
+        .set_shard_replica_state(0, 0, ReplicaState::Active, None)
+        .await
+        .unwrap();
+    let collection = Arc::new(collection);
+
+    // Hold the first snapshot on its first progress report, until released
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let release_rx = std::sync::Mutex::new(release_rx);
+    let progress_cb: SnapshotProgressCallback = Box::new(move |_progress| {
+        let _ = started_tx.send(());
+        let _ = release_rx.lock().unwrap().recv();
+    });
+
+    // Blocked callback occupies its thread, so run the snapshot on a dedicated one
+    let first_snapshot = {
+        let collection = collection.clone();
+        let temp_dir = snapshots_temp_dir.path().to_path_buf();
+        let runtime = tokio::runtime::Handle::current();
+        std::thread::spawn(move || {
+            runtime.block_on(collection.create_snapshot(&temp_dir, 0, Some(progress_cb)))
+        })
+    };
+    tokio::task::spawn_blocking(move || started_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // No permit is available while the first snapshot is in progress
+    let result = collection
+        .create_snapshot(snapshots_temp_dir.path(), 0, None)
+        .await;
+    assert!(
+        matches!(result, Err(CollectionError::Unavailable { .. })),
+        "unexpected result: {result:?}",
+    );
+
+    // Permit is released once the snapshot is created
+    drop(release_tx);
+    tokio::task::spawn_blocking(move || first_snapshot.join())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    collection
+        .create_snapshot(snapshots_temp_dir.path(), 0, None)
+        .await
+        .unwrap();
+}
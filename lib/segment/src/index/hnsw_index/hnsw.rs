@@ -22,8 +22,8 @@ use crate::index::hnsw_index::build_condition_checker::BuildConditionChecker;
 use crate::index::hnsw_index::config::HnswGraphConfig;
 use crate::index::hnsw_index::graph_layers::GraphLayers;
 use crate::index::hnsw_index::graph_layers_builder::GraphLayersBuilder;
-use crate::index::hnsw_index::max_rayon_threads;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
+use crate::index::hnsw_index::{graph_search_ef, graph_search_top, max_rayon_threads};
 use crate::index::query_estimator::adjust_to_available_vectors;
 use crate::index::sample_estimation::sample_check_cardinality;
 use crate::index::struct_payload_index::StructPayloadIndex;
@@ -219,10 +219,6 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
         params: Option<&SearchParams>,
         is_stopped: &AtomicBool,
     ) -> Vec<ScoredPointOffset> {
-        let ef = params
-            .and_then(|params| params.hnsw_ef)
-            .unwrap_or(self.config.ef);
-
         let id_tracker = self.id_tracker.borrow();
         let vector_storage = self.vector_storage.borrow();
         let quantized_storage = vector_storage.quantized_storage();
@@ -266,17 +262,12 @@ impl<TGraphLinks: GraphLinks> HNSWIndex<TGraphLinks> {
             return Vec::new();
         };
 
-        if quantized && quantization_params.rescore {
-            let oversampling = quantization_params.oversampling.unwrap_or(1.0);
-
-            let oversampled_top = if oversampling > 1.0 {
-                (oversampling * top as f64) as usize
-            } else {
-                // Very unlikely this is reached because validation enforces oversampling >= 1.0
-                top
-            };
+        let rescore = quantized && quantization_params.rescore;
+        let graph_top = graph_search_top(top, params, rescore);
+        let ef = graph_search_ef(self.config.ef, graph_top, params);
 
-            let search_result = graph.search(oversampled_top, ef, points_scorer);
+        if rescore {
+            let search_result = graph.search(graph_top, ef, points_scorer);
 
             let raw_scorer = new_stoppable_raw_scorer(
                 vector.to_owned(),
@@ -12,6 +12,8 @@ mod search_context;
 #[cfg(test)]
 mod tests;
 
+use crate::types::SearchParams;
+
 pub fn max_rayon_threads(max_indexing_threads: usize) -> usize {
     if max_indexing_threads == 0 {
         let num_cpu = crate::common::cpu::get_num_cpus();
@@ -20,3 +22,36 @@ pub fn max_rayon_threads(max_indexing_threads: usize) -> usize {
         max_indexing_threads
     }
 }
+
+/// Number of points the graph search looks for.
+///
+/// If quantized vectors are rescored afterwards, more points are searched, according to the
+/// oversampling of the search params.
+pub fn graph_search_top(top: usize, params: Option<&SearchParams>, rescore: bool) -> usize {
+    let oversampling = params
+        .and_then(|params| params.quantization)
+        .and_then(|quantization| quantization.oversampling)
+        .unwrap_or(1.0);
+
+    if rescore && oversampling > 1.0 {
+        (oversampling * top as f64) as usize
+    } else {
+        // Very unlikely this is reached with rescoring, because validation enforces oversampling >= 1.0
+        top
+    }
+}
+
+/// Size of the beam of the graph search.
+///
+/// `hnsw_ef` of the search params, or `default_ef` of the index, but at least the number of points
+/// the graph search looks for, see [`graph_search_top`].
+pub fn graph_search_ef(
+    default_ef: usize,
+    graph_top: usize,
+    params: Option<&SearchParams>,
+) -> usize {
+    params
+        .and_then(|params| params.hnsw_ef)
+        .unwrap_or(default_ef)
+        .max(graph_top)
+}
@@ -25,14 +25,17 @@ use crate::entry::entry_point::{
 };
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::CardinalityEstimation;
+use crate::index::plain_payload_index::PlainIndex;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::{PayloadIndex, VectorIndex, VectorIndexEnum};
+use crate::segment_constructor::{get_vector_storage_path, open_vector_storage};
 use crate::spaces::tools::peek_top_smallest_iterable;
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    Filter, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadKeyType, PayloadKeyTypeRef,
-    PayloadSchemaType, PointIdType, PointOffsetType, ScoredPoint, SearchParams, SegmentConfig,
-    SegmentInfo, SegmentState, SegmentType, SeqNumberType, VectorDataInfo, WithPayload, WithVector,
+    Filter, Indexes, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadKeyType,
+    PayloadKeyTypeRef, PayloadSchemaType, PointIdType, PointOffsetType, ScoredPoint, SearchParams,
+    SegmentConfig, SegmentInfo, SegmentState, SegmentType, SeqNumberType, VectorDataConfig,
+    VectorDataInfo, WithPayload, WithVector,
 };
 use crate::utils;
 use crate::utils::fs::find_symlink;
@@ -592,6 +595,8 @@ impl Segment {
                     score: scored_point_offset.score,
                     payload,
                     vector,
+                    score_breakdown: None,
+                    search_params_echo: None,
                 })
             })
             .collect()
@@ -698,6 +703,73 @@ impl Segment {
         self.id_tracker.borrow().total_point_count()
     }
 
+    /// Add storage of a new named vector, with a zero vector for each point of the segment.
+    ///
+    /// The vector gets a plain index without quantization, regardless of the given config,
+    /// optimizers are expected to index it. Only appendable storage types are supported.
+    /// Segment state is saved, so the vector is kept on reload.
+    pub fn add_vector(
+        &mut self,
+        vector_name: &str,
+        vector_config: VectorDataConfig,
+    ) -> OperationResult<()> {
+        if self.vector_data.contains_key(vector_name) {
+            return Err(OperationError::service_error(format!(
+                "Vector {vector_name} already exists in segment {}",
+                self.current_path.display(),
+            )));
+        }
+        let vector_config = VectorDataConfig {
+            index: Indexes::Plain {},
+            quantization_config: None,
+            ..vector_config
+        };
+        if !vector_config.is_appendable() {
+            return Err(OperationError::service_error(format!(
+                "Vector {vector_name} can't be added with {:?} storage",
+                vector_config.storage_type,
+            )));
+        }
+
+        let vector_storage_path = get_vector_storage_path(&self.current_path, vector_name);
+        let vector_storage = open_vector_storage(
+            &self.database,
+            &vector_config,
+            &vector_storage_path,
+            vector_name,
+        )?;
+        {
+            let id_tracker = self.id_tracker.borrow();
+            let mut vector_storage = vector_storage.borrow_mut();
+            let zero_vector = vec![0.0; vector_config.size];
+            for internal_id in 0..id_tracker.total_point_count() as PointOffsetType {
+                vector_storage.insert_vector(internal_id, &zero_vector)?;
+                // Keep removed points removed
+                if id_tracker.external_id(internal_id).is_none() {
+                    vector_storage.delete_vector(internal_id)?;
+                }
+            }
+            vector_storage.flusher()()?;
+        }
+
+        let vector_index = Arc::new(AtomicRefCell::new(VectorIndexEnum::Plain(PlainIndex::new(
+            self.id_tracker.clone(),
+            vector_storage.clone(),
+            self.payload_index.clone(),
+        ))));
+        self.vector_data.insert(
+            vector_name.to_owned(),
+            VectorData {
+                vector_storage,
+                vector_index,
+            },
+        );
+        self.segment_config
+            .vector_data
+            .insert(vector_name.to_owned(), vector_config);
+        self.save_current_state()
+    }
+
     pub fn prefault_mmap_pages(&self) {
         let tasks: Vec<_> = self
             .vector_data
@@ -6,12 +6,13 @@ use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
 use log::info;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use rocksdb::DB;
 use semver::Version;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+use crate::common::rocksdb_wrapper::{create_db_cf_if_not_exists, open_db, DB_VECTOR_CF};
 use crate::common::version::StorageVersion;
 use crate::data_types::vectors::DEFAULT_VECTOR_NAME;
 use crate::entry::entry_point::{OperationError, OperationResult};
@@ -27,12 +28,12 @@ use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
 use crate::segment::{Segment, SegmentVersion, VectorData, SEGMENT_STATE_FILE};
 use crate::types::{
     Distance, Indexes, PayloadStorageType, SegmentConfig, SegmentState, SegmentType, SeqNumberType,
-    VectorStorageType,
+    VectorDataConfig, VectorStorageType,
 };
 use crate::vector_storage::appendable_mmap_vector_storage::open_appendable_memmap_vector_storage;
 use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
 use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
-use crate::vector_storage::VectorStorage;
+use crate::vector_storage::{VectorStorage, VectorStorageEnum};
 
 pub const PAYLOAD_INDEX_PATH: &str = "payload_index";
 pub const VECTOR_STORAGE_PATH: &str = "vector_storage";
@@ -61,6 +62,43 @@ pub fn get_vector_index_path(segment_path: &Path, vector_name: &str) -> PathBuf
     segment_path.join(get_vector_name_with_prefix(VECTOR_INDEX_PATH, vector_name))
 }
 
+/// Open storage of the named vector, of the type selected in the config
+pub fn open_vector_storage(
+    database: &Arc<RwLock<DB>>,
+    vector_config: &VectorDataConfig,
+    vector_storage_path: &Path,
+    vector_name: &str,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageEnum>>> {
+    match vector_config.storage_type {
+        // In memory
+        VectorStorageType::Memory => {
+            let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
+            // Column is missing if the vector is added to an existing segment
+            create_db_cf_if_not_exists(database.clone(), &db_column_name).map_err(|err| {
+                OperationError::service_error(format!("RocksDB create column error: {err}"))
+            })?;
+            open_simple_vector_storage(
+                database.clone(),
+                &db_column_name,
+                vector_config.size,
+                vector_config.distance,
+            )
+        }
+        // Mmap on disk, not appendable
+        VectorStorageType::Mmap => open_memmap_vector_storage(
+            vector_storage_path,
+            vector_config.size,
+            vector_config.distance,
+        ),
+        // Chunked mmap on disk, appendable
+        VectorStorageType::ChunkedMmap => open_appendable_memmap_vector_storage(
+            vector_storage_path,
+            vector_config.size,
+            vector_config.distance,
+        ),
+    }
+}
+
 fn create_segment(
     version: Option<SeqNumberType>,
     segment_path: &Path,
@@ -99,31 +137,8 @@ fn create_segment(
         let vector_storage_path = get_vector_storage_path(segment_path, vector_name);
         let vector_index_path = get_vector_index_path(segment_path, vector_name);
 
-        // Select suitable vector storage type based on configuration
-        let vector_storage = match vector_config.storage_type {
-            // In memory
-            VectorStorageType::Memory => {
-                let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
-                open_simple_vector_storage(
-                    database.clone(),
-                    &db_column_name,
-                    vector_config.size,
-                    vector_config.distance,
-                )?
-            }
-            // Mmap on disk, not appendable
-            VectorStorageType::Mmap => open_memmap_vector_storage(
-                &vector_storage_path,
-                vector_config.size,
-                vector_config.distance,
-            )?,
-            // Chunked mmap on disk, appendable
-            VectorStorageType::ChunkedMmap => open_appendable_memmap_vector_storage(
-                &vector_storage_path,
-                vector_config.size,
-                vector_config.distance,
-            )?,
-        };
+        let vector_storage =
+            open_vector_storage(&database, vector_config, &vector_storage_path, vector_name)?;
 
         // Warn when number of points between ID tracker and storage differs
         let point_count = id_tracker.borrow().total_point_count();
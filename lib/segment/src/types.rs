@@ -167,6 +167,7 @@ impl Distance {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Order {
     LargeBetter,
     SmallBetter,
@@ -185,6 +186,13 @@ pub struct ScoredPoint {
     pub payload: Option<Payload>,
     /// Vector of the point
     pub vector: Option<VectorStruct>,
+    /// Components of the score, only returned if explanation of the score is requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<HashMap<String, ScoreType>>,
+    /// Effective search params, which produced the point, only returned if requested.
+    /// Only reported for points found in shards local to the peer, which received the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_params_echo: Option<SearchParamsEcho>,
 }
 
 impl Eq for ScoredPoint {}
@@ -334,6 +342,34 @@ pub struct SearchParams {
     /// guarantee that all uploaded vectors will be included in search results
     #[serde(default)]
     pub indexed_only: bool,
+
+    /// If true, candidates found by the approximate search are re-ranked by the exact distance
+    /// between the query and their full-precision vectors.
+    /// Default is false.
+    #[serde(default)]
+    pub rescore_with_exact: bool,
+
+    /// How many candidates to pre-select for `rescore_with_exact`, relative to the limit.
+    /// Default is 1.0.
+    ///
+    /// For example, if `rescore_oversampling` is 3.0 and `limit` is 10, then 30 candidates will be
+    /// found by the approximate search, and the top-10 of them returned after exact re-scoring.
+    #[serde(default)]
+    #[validate(range(min = 1.0))]
+    pub rescore_oversampling: Option<f64>,
+}
+
+/// Effective search params, as resolved from the request and the collection configuration
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SearchParamsEcho {
+    /// Size of the beam of the HNSW search.
+    /// Not set if no HNSW index is used, e.g. for exact search or if no index is built yet
+    pub ef_used: Option<usize>,
+    /// Oversampling factor of the quantized search, 1.0 if no rescoring is done
+    pub oversampling_used: f64,
+    /// Whether quantized vectors are used for the search
+    pub quantization_applied: bool,
 }
 
 /// Vector index configuration
@@ -8,7 +8,10 @@ use segment::entry::entry_point::{OperationError, SegmentEntry};
 use segment::fixtures::index_fixtures::random_vector;
 use segment::segment_constructor::load_segment;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
-use segment::types::{Condition, Distance, Filter, SearchParams, WithPayload};
+use segment::types::{
+    Condition, Distance, Filter, Indexes, SearchParams, VectorDataConfig, VectorStorageType,
+    WithPayload,
+};
 use tempfile::Builder;
 
 use crate::fixtures::segment::{build_segment_1, build_segment_3};
@@ -263,6 +266,8 @@ fn test_update_named_vector() {
         exact: true,
         quantization: None,
         indexed_only: false,
+        rescore_with_exact: false,
+        rescore_oversampling: None,
     };
     let nearest_upsert = segment
         .search(
@@ -328,3 +333,69 @@ fn test_update_named_vector() {
     // check that nearests are the same
     assert_eq!(nearest_upsert.id, nearest_update.id);
 }
+
+#[test]
+fn test_add_vector() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+
+    let path = {
+        let mut segment = build_segment_1(dir.path());
+        segment.delete_point(6, 5.into()).unwrap();
+        segment
+            .add_vector(
+                "added",
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    storage_type: VectorStorageType::Memory,
+                    index: Indexes::Plain {},
+                    quantization_config: None,
+                },
+            )
+            .unwrap();
+
+        // Existing points get zero vectors
+        assert_eq!(
+            segment.vector("added", 1.into()).unwrap(),
+            Some(vec![0.0; 2])
+        );
+
+        // Vector can't be added twice
+        let vector_config = segment.config().vector_data["added"].clone();
+        assert!(segment.add_vector("added", vector_config).is_err());
+
+        let mut vectors = NamedVectors::default();
+        vectors.insert(DEFAULT_VECTOR_NAME.to_string(), vec![1.0, 1.0, 1.0, 1.0]);
+        vectors.insert("added".to_string(), vec![1.0, 2.0]);
+        segment.upsert_point(7, 6.into(), vectors).unwrap();
+        segment.flush(true).unwrap();
+        segment.current_path.clone()
+    };
+
+    // Added vector is kept on reload
+    let segment = load_segment(&path).unwrap().unwrap();
+    assert!(segment.config().vector_data.contains_key("added"));
+    assert_eq!(
+        segment.vector("added", 1.into()).unwrap(),
+        Some(vec![0.0; 2])
+    );
+    assert_eq!(
+        segment.vector("added", 6.into()).unwrap(),
+        Some(vec![1.0, 2.0]),
+    );
+    assert!(!segment.has_point(5.into()));
+
+    let res = segment
+        .search(
+            "added",
+            &[1.0, 1.0].into(),
+            &WithPayload::default(),
+            &false.into(),
+            None,
+            1,
+            None,
+            &false.into(),
+        )
+        .unwrap();
+    assert_eq!(res[0].id, 6.into());
+}
@@ -143,6 +143,10 @@ pub struct CreateCollection {
     #[serde(default, alias = "quantization")]
     #[validate]
     pub quantization_config: Option<QuantizationConfig>,
+    /// Max size of the payload of a single point, serialized as JSON.
+    /// Updates with larger payloads are rejected. Default: no limit
+    #[serde(default)]
+    pub max_payload_size_bytes: Option<usize>,
 }
 
 /// Operation for creating new collection and (optionally) specify index params
@@ -180,7 +184,7 @@ impl CreateCollectionOperation {
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct UpdateCollection {
-    /// Vector data parameters to update.
+    /// Vector data parameters to update, or new named vectors to add.
     /// It is possible to provide one config for single vector mode and list of configs for multiple vectors mode.
     #[validate]
     pub vectors: Option<VectorsConfigDiff>,
@@ -321,6 +325,7 @@ impl From<CollectionConfig> for CreateCollection {
             optimizers_config: Some(value.optimizer_config.into()),
             init_from: None,
             quantization_config: value.quantization_config,
+            max_payload_size_bytes: value.params.max_payload_size_bytes,
         }
     }
 }
@@ -722,7 +722,7 @@ impl<C: CollectionContainer> Storage for ConsensusManager<C> {
     }
 
     fn snapshot(&self, request_index: u64, _to: u64) -> raft::Result<raft::eraftpb::Snapshot> {
-        let collections_data = self.toc.collections_snapshot();
+        let collections_data = self.toc.collections_snapshot().map_err(raft_error_other)?;
         let persistent = self.persistent.read();
         let raft_state = persistent.state().clone();
         if raft_state.hard_state.commit >= request_index {
@@ -922,8 +922,11 @@ mod tests {
             Ok(true)
         }
 
-        fn collections_snapshot(&self) -> super::CollectionsSnapshot {
-            super::CollectionsSnapshot::default()
+        fn collections_snapshot(
+            &self,
+        ) -> Result<super::CollectionsSnapshot, crate::content_manager::errors::StorageError>
+        {
+            Ok(super::CollectionsSnapshot::default())
         }
 
         fn apply_collections_snapshot(
@@ -16,6 +16,7 @@ pub fn error_to_status(error: StorageError) -> tonic::Status {
         StorageError::BadRequest { .. } => tonic::Code::InvalidArgument,
         StorageError::Locked { .. } => tonic::Code::FailedPrecondition,
         StorageError::Timeout { .. } => tonic::Code::DeadlineExceeded,
+        StorageError::Forbidden { .. } => tonic::Code::PermissionDenied,
     };
     tonic::Status::new(error_code, format!("{error}"))
 }
@@ -45,6 +46,7 @@ impl TryFrom<api::grpc::qdrant::CreateCollection> for CollectionMetaOperations {
                     .quantization_config
                     .map(TryInto::try_into)
                     .transpose()?,
+                max_payload_size_bytes: None,
             },
         )))
     }
@@ -5,7 +5,7 @@ use collection::collection::Collection;
 use collection::operations::point_ops::{
     PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
 };
-use collection::operations::types::{CollectionError, CollectionResult, ScrollRequest};
+use collection::operations::types::{CollectionError, CollectionResult, ScrollKey, ScrollRequest};
 use collection::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
 use collection::shards::replica_set::ReplicaState;
 use collection::shards::shard::{PeerId, ShardId};
@@ -28,7 +28,7 @@ async fn get_local_source_shards(
     source: &Collection,
     this_peer_id: PeerId,
 ) -> CollectionResult<Vec<ShardId>> {
-    let collection_state = source.state().await;
+    let collection_state = source.state().await?;
 
     let mut local_responsible_shards = Vec::new();
 
@@ -78,11 +78,14 @@ async fn replicate_shard_data(
 
     loop {
         let request = ScrollRequest {
-            offset,
+            offset: offset.map(ScrollKey::from),
             limit: Some(limit),
             filter: None,
             with_payload: Some(WithPayloadInterface::Bool(true)),
             with_vector: WithVector::Bool(true),
+            with_shard_key: false,
+            shard_key: None,
+            with_total_count: false,
         };
 
         let collections_read = collections.read().await;
@@ -134,7 +137,9 @@ async fn wait_all_shards_active(
 ) -> CollectionResult<()> {
     let collections_read = collections.read().await;
     let collection = handle_get_collection(collections_read.get(collection_name))?;
-    let is_initialized = collection.wait_collection_initiated(COLLECTION_INITIATION_TIMEOUT);
+    let is_initialized = collection
+        .wait_collection_ready(COLLECTION_INITIATION_TIMEOUT)
+        .await;
     if !is_initialized {
         return Err(CollectionError::service_error(format!(
             "Collection {} was not initialized within {} sec timeout",
@@ -189,7 +194,7 @@ pub async fn transfer_indexes(
     // Do this action on the "main" peer only
     let collections_read = collections.read().await;
     let collection = handle_get_collection(collections_read.get(source_collection))?;
-    let state = collection.state().await;
+    let state = collection.state().await?;
     let max_peer = state
         .shards
         .iter()
@@ -24,6 +24,8 @@ pub enum StorageError {
     Locked { description: String },
     #[error("Timeout: {description}")]
     Timeout { description: String },
+    #[error("Forbidden: {description}")]
+    Forbidden { description: String },
 }
 
 impl StorageError {
@@ -91,6 +93,17 @@ impl StorageError {
             CollectionError::Timeout { .. } => StorageError::Timeout {
                 description: overriding_description,
             },
+            CollectionError::Unavailable { .. } => StorageError::ServiceError {
+                description: overriding_description,
+                backtrace: None,
+            },
+            CollectionError::Forbidden { .. } => StorageError::Forbidden {
+                description: overriding_description,
+            },
+            CollectionError::TransferFinalizationFailed { .. } => StorageError::ServiceError {
+                description: overriding_description,
+                backtrace: None,
+            },
         }
     }
 }
@@ -132,6 +145,15 @@ impl From<CollectionError> for StorageError {
             CollectionError::Timeout { .. } => StorageError::Timeout {
                 description: format!("{err}"),
             },
+            CollectionError::Unavailable { .. } => StorageError::ServiceError {
+                description: format!("{err}"),
+                backtrace: None,
+            },
+            CollectionError::Forbidden { description } => StorageError::Forbidden { description },
+            CollectionError::TransferFinalizationFailed { .. } => StorageError::ServiceError {
+                description: format!("{err}"),
+                backtrace: None,
+            },
         }
     }
 }
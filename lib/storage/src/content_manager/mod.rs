@@ -194,7 +194,7 @@ pub trait CollectionContainer {
         operation: CollectionMetaOperations,
     ) -> Result<bool, StorageError>;
 
-    fn collections_snapshot(&self) -> CollectionsSnapshot;
+    fn collections_snapshot(&self) -> Result<CollectionsSnapshot, StorageError>;
 
     fn apply_collections_snapshot(&self, data: CollectionsSnapshot) -> Result<(), StorageError>;
 
@@ -1,13 +1,15 @@
+use std::collections::HashMap;
+
 use collection::collection::Collection;
 use collection::config::CollectionConfig;
-use collection::operations::snapshot_ops::{SnapshotPriority, SnapshotRecover};
+use collection::operations::snapshot_ops::{RecoveryMode, SnapshotPriority, SnapshotRecover};
 use collection::shards::replica_set::ReplicaState;
 use collection::shards::shard::{PeerId, ShardId};
 use collection::shards::shard_config::ShardType;
 use collection::shards::shard_versioning::latest_shard_paths;
 
 use crate::content_manager::collection_meta_ops::{
-    CollectionMetaOperations, CreateCollectionOperation,
+    CollectionMetaOperations, CreateCollectionOperation, SetShardReplicaState,
 };
 use crate::content_manager::snapshots::download::download_snapshot;
 use crate::dispatcher::Dispatcher;
@@ -45,6 +47,57 @@ pub async fn activate_shard(
     Ok(())
 }
 
+/// Change state of the replica through consensus and wait for it to be applied
+async fn set_shard_replica_state(
+    dispatcher: &Dispatcher,
+    collection_name: &str,
+    peer_id: PeerId,
+    shard_id: ShardId,
+    state: ReplicaState,
+    from_state: Option<ReplicaState>,
+) -> Result<(), StorageError> {
+    let operation = CollectionMetaOperations::SetShardReplicaState(SetShardReplicaState {
+        collection_name: collection_name.to_string(),
+        shard_id,
+        peer_id,
+        state,
+        from_state,
+    });
+    dispatcher
+        .submit_collection_meta_op(operation, None)
+        .await?;
+    Ok(())
+}
+
+/// Bring local replicas, which were not recovered, back to the state they had before recovery
+async fn restore_replica_states(
+    dispatcher: &Dispatcher,
+    collection_name: &str,
+    peer_id: PeerId,
+    recovery_state: ReplicaState,
+    previous_states: &HashMap<ShardId, ReplicaState>,
+) {
+    for (shard_id, previous_state) in previous_states {
+        if *previous_state == recovery_state {
+            continue;
+        }
+        let result = set_shard_replica_state(
+            dispatcher,
+            collection_name,
+            peer_id,
+            *shard_id,
+            *previous_state,
+            Some(recovery_state),
+        )
+        .await;
+        if let Err(err) = result {
+            log::error!(
+                "Can't restore state {previous_state:?} of shard {shard_id} of collection {collection_name} after failed recovery: {err}",
+            );
+        }
+    }
+}
+
 pub async fn do_recover_from_snapshot(
     dispatcher: &Dispatcher,
     collection_name: &str,
@@ -69,7 +122,12 @@ async fn _do_recover_from_snapshot(
     collection_name: &str,
     source: SnapshotRecover,
 ) -> Result<bool, StorageError> {
-    let SnapshotRecover { location, priority } = source;
+    let SnapshotRecover {
+        location,
+        priority,
+        force,
+        recovery_mode,
+    } = source;
     let toc = dispatcher.toc();
 
     let this_peer_id = toc.this_peer_id;
@@ -106,6 +164,7 @@ async fn _do_recover_from_snapshot(
     );
 
     let tmp_collection_dir_clone = tmp_collection_dir.path().to_path_buf();
+    let expected_collection_id = (!force).then(|| collection_name.to_string());
     let restoring = tokio::task::spawn_blocking(move || {
         // Unpack snapshot collection to the target folder
         Collection::restore_snapshot(
@@ -113,6 +172,7 @@ async fn _do_recover_from_snapshot(
             &tmp_collection_dir_clone,
             this_peer_id,
             is_distributed,
+            expected_collection_id.as_deref(),
         )
     });
     restoring.await??;
@@ -136,7 +196,7 @@ async fn _do_recover_from_snapshot(
         }
     };
 
-    let state = collection.state().await;
+    let state = collection.state().await?;
 
     // Check config compatibility
     // Check vectors config
@@ -154,27 +214,72 @@ async fn _do_recover_from_snapshot(
         )));
     }
 
-    // Deactivate collection local shards during recovery
+    let priority = priority.unwrap_or_default();
+    let recovery_mode = recovery_mode.unwrap_or_default();
+
+    // Updates missed during online recovery are synchronized from other replicas
+    if recovery_mode == RecoveryMode::Online {
+        if !matches!(priority, SnapshotPriority::Replica) {
+            return Err(StorageError::bad_input(
+                "Online recovery is only supported with `replica` snapshot priority",
+            ));
+        }
+        for (shard_id, shard_info) in &state.shards {
+            if !shard_info.replicas.contains_key(&this_peer_id) {
+                continue;
+            }
+            let has_other_active_replicas = shard_info
+                .replicas
+                .iter()
+                .any(|(peer_id, state)| *state == ReplicaState::Active && *peer_id != this_peer_id);
+            if !has_other_active_replicas {
+                return Err(StorageError::bad_input(format!(
+                    "Online recovery requires another active replica of shard {shard_id}",
+                )));
+            }
+        }
+    }
+
+    let recovery_state = match recovery_mode {
+        RecoveryMode::Blocking => ReplicaState::Partial,
+        RecoveryMode::Online => ReplicaState::Recovering,
+    };
+
+    // Deactivate collection local shards during recovery,
+    // remember their states to bring them back if recovery fails
+    let mut previous_states = HashMap::new();
     for (shard_id, shard_info) in &state.shards {
         let local_shard_state = shard_info.replicas.get(&this_peer_id);
         match local_shard_state {
             None => {} // Shard is not on this node, skip
             Some(state) => {
-                if state != &ReplicaState::Partial {
-                    toc.send_set_replica_state_proposal(
-                        collection_name.to_string(),
+                previous_states.insert(*shard_id, *state);
+                if state != &recovery_state {
+                    let result = set_shard_replica_state(
+                        &dispatcher,
+                        collection_name,
                         this_peer_id,
                         *shard_id,
-                        ReplicaState::Partial,
+                        recovery_state,
                         None,
-                    )?;
+                    )
+                    .await;
+                    if let Err(err) = result {
+                        restore_replica_states(
+                            &dispatcher,
+                            collection_name,
+                            this_peer_id,
+                            recovery_state,
+                            &previous_states,
+                        )
+                        .await;
+                        return Err(err);
+                    }
                 }
             }
         }
     }
 
-    let priority = priority.unwrap_or_default();
-
     // Recover shards from the snapshot
     for (shard_id, shard_info) in &state.shards {
         let shards = latest_shard_paths(tmp_collection_dir.path(), *shard_id).await?;
@@ -198,15 +303,32 @@ async fn _do_recover_from_snapshot(
                 snapshot_shard_path.display()
             );
 
-            let recovered = collection
+            let recovered = match collection
                 .recover_local_shard_from(&snapshot_shard_path, *shard_id)
-                .await?;
+                .await
+            {
+                Ok(recovered) => recovered,
+                Err(err) => {
+                    restore_replica_states(
+                        &dispatcher,
+                        collection_name,
+                        this_peer_id,
+                        recovery_state,
+                        &previous_states,
+                    )
+                    .await;
+                    return Err(err.into());
+                }
+            };
 
             if !recovered {
                 log::debug!("Shard {} if not in snapshot", shard_id);
                 continue;
             }
 
+            // Recovered shard is activated or synchronized below
+            previous_states.remove(shard_id);
+
             // If this is the only replica, we can activate it
             // If not - de-sync is possible, so we need to run synchronization
             let other_active_replicas: Vec<_> = shard_info
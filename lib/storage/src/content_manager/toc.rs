@@ -4,9 +4,9 @@ use std::fs::{create_dir_all, read_dir};
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
-use collection::collection::{Collection, RequestShardTransfer};
+use collection::collection::{Collection, RequestShardTransfer, VectorLookup};
 use collection::collection_state;
 use collection::config::{
     default_replication_factor, default_write_consistency_factor, CollectionConfig,
@@ -19,9 +19,9 @@ use collection::operations::consistency_params::ReadConsistency;
 use collection::operations::point_ops::WriteOrdering;
 use collection::operations::snapshot_ops::SnapshotDescription;
 use collection::operations::types::{
-    AliasDescription, CollectionResult, CountRequest, CountResult, GroupsResult, PointRequest,
-    RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest,
-    SearchRequestBatch, UpdateResult, VectorsConfig,
+    AliasDescription, CollectionError, CollectionResult, CountRequest, CountResult, GroupsResult,
+    PointRequest, RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult,
+    SearchBatchResult, SearchRequest, SearchRequestBatch, UpdateResult, VectorsConfig,
 };
 use collection::operations::CollectionUpdateOperations;
 use collection::recommendations::{recommend_batch_by, recommend_by};
@@ -34,8 +34,9 @@ use collection::shards::transfer::shard_transfer::{
 };
 use collection::shards::{replica_set, CollectionId};
 use collection::telemetry::CollectionTelemetry;
+use futures::FutureExt as _;
 use segment::common::cpu::get_num_cpus;
-use segment::types::ScoredPoint;
+use segment::types::{ScoredPoint, WithPayloadInterface, WithVector};
 use tokio::runtime::Runtime;
 use tokio::sync::{Mutex, RwLock, RwLockReadGuard, Semaphore};
 use uuid::Uuid;
@@ -73,7 +74,7 @@ pub struct TableOfContent {
     search_runtime: Runtime,
     update_runtime: Runtime,
     general_runtime: Runtime,
-    alias_persistence: RwLock<AliasPersistence>,
+    alias_persistence: Arc<RwLock<AliasPersistence>>,
     pub this_peer_id: PeerId,
     channel_service: ChannelService,
     /// Backlink to the consensus, if none - single node mode
@@ -112,7 +113,12 @@ impl TableOfContent {
         }
         let collection_paths =
             read_dir(&collections_path).expect("Can't read Collections directory");
-        let mut collections: HashMap<String, Collection> = Default::default();
+        let alias_path = Path::new(&storage_config.storage_path).join(ALIASES_PATH);
+        let alias_persistence = Arc::new(RwLock::new(
+            AliasPersistence::open(alias_path).expect("Can't open database by the provided config"),
+        ));
+        // Shared with the loaded collections for vector lookups, filled as collections are loaded
+        let collections: Arc<RwLock<Collections>> = Default::default();
         for entry in collection_paths {
             let collection_path = entry
                 .expect("Can't access of one of the collection files")
@@ -138,33 +144,39 @@ impl TableOfContent {
                 panic!("Can't create a directory for snapshot of {collection_name}: {e}")
             });
             log::info!("Loading collection: {}", collection_name);
-            let collection = general_runtime.block_on(Collection::load(
-                collection_name.clone(),
-                this_peer_id,
-                &collection_path,
-                &collection_snapshots_path,
-                storage_config.to_shared_storage_config().into(),
-                channel_service.clone(),
-                Self::change_peer_state_callback(
-                    consensus_proposal_sender.clone(),
+            let collection = general_runtime
+                .block_on(Collection::load(
                     collection_name.clone(),
-                    ReplicaState::Dead,
-                    None,
-                ),
-                Self::request_shard_transfer_callback(
-                    consensus_proposal_sender.clone(),
-                    collection_name.clone(),
-                ),
-                Some(search_runtime.handle().clone()),
-                Some(update_runtime.handle().clone()),
-            ));
+                    this_peer_id,
+                    &collection_path,
+                    &collection_snapshots_path,
+                    storage_config.to_shared_storage_config().into(),
+                    channel_service.clone(),
+                    Self::change_peer_state_callback(
+                        consensus_proposal_sender.clone(),
+                        collection_name.clone(),
+                        ReplicaState::Dead,
+                        None,
+                    ),
+                    Self::request_shard_transfer_callback(
+                        consensus_proposal_sender.clone(),
+                        collection_name.clone(),
+                    ),
+                    Some(Self::vector_lookup_callback(
+                        Arc::downgrade(&collections),
+                        Arc::downgrade(&alias_persistence),
+                    )),
+                    Default::default(),
+                    Default::default(),
+                    Some(search_runtime.handle().clone()),
+                    Some(update_runtime.handle().clone()),
+                ))
+                .unwrap_or_else(|err| panic!("Can't load collection {collection_name}: {err}"));
 
-            collections.insert(collection_name, collection);
+            general_runtime
+                .block_on(collections.write())
+                .insert(collection_name, collection);
         }
-        let alias_path = Path::new(&storage_config.storage_path).join(ALIASES_PATH);
-        let alias_persistence =
-            AliasPersistence::open(alias_path).expect("Can't open database by the provided config");
-
         let rate_limiter = match storage_config.performance.update_rate_limit {
             Some(limit) => Some(Semaphore::new(limit)),
             None => {
@@ -184,12 +196,12 @@ impl TableOfContent {
         };
 
         TableOfContent {
-            collections: Arc::new(RwLock::new(collections)),
+            collections,
             storage_config: Arc::new(storage_config.clone()),
             search_runtime,
             update_runtime,
             general_runtime,
-            alias_persistence: RwLock::new(alias_persistence),
+            alias_persistence,
             this_peer_id,
             channel_service,
             consensus_proposal_sender,
@@ -331,6 +343,7 @@ impl TableOfContent {
             write_consistency_factor,
             init_from,
             quantization_config,
+            max_payload_size_bytes,
         } = operation;
 
         self.collections
@@ -388,6 +401,8 @@ impl TableOfContent {
                     description: "`write_consistency_factor` cannot be 0".to_string(),
                 },
             )?,
+            max_payload_size_bytes,
+            read_only: false,
         };
         let wal_config = match wal_config_diff {
             None => self.storage_config.wal.clone(),
@@ -415,6 +430,7 @@ impl TableOfContent {
             optimizer_config: optimizers_config,
             hnsw_config,
             quantization_config,
+            use_cross_device_snapshot_copy: None,
         };
         let collection = Collection::new(
             collection_name.to_string(),
@@ -435,6 +451,12 @@ impl TableOfContent {
                 self.consensus_proposal_sender.clone(),
                 collection_name.to_string(),
             ),
+            Some(Self::vector_lookup_callback(
+                Arc::downgrade(&self.collections),
+                Arc::downgrade(&self.alias_persistence),
+            )),
+            Default::default(),
+            Default::default(),
             Some(self.search_runtime.handle().clone()),
             Some(self.update_runtime.handle().clone()),
         )
@@ -472,7 +494,7 @@ impl TableOfContent {
         source_collection: &CollectionId,
     ) -> Result<(), StorageError> {
         let collection = self.get_collection(source_collection).await?;
-        let collection_vectors_schema = collection.state().await.config.params.vectors;
+        let collection_vectors_schema = collection.state().await?.config.params.vectors;
         collection_vectors_schema.check_compatible(vectors)?;
         Ok(())
     }
@@ -541,7 +563,7 @@ impl TableOfContent {
                         shard_id,
                         peer_id,
                         ReplicaState::Active,
-                        Some(ReplicaState::Initializing),
+                        Some(HashSet::from([ReplicaState::Initializing])),
                     )
                     .await?;
             }
@@ -708,6 +730,50 @@ impl TableOfContent {
         })
     }
 
+    fn vector_lookup_callback(
+        collections: Weak<RwLock<Collections>>,
+        aliases: Weak<RwLock<AliasPersistence>>,
+    ) -> VectorLookup {
+        Arc::new(move |collection_name, point_id| {
+            let collections = collections.clone();
+            let aliases = aliases.clone();
+            async move {
+                let (Some(collections), Some(aliases)) = (collections.upgrade(), aliases.upgrade())
+                else {
+                    return Err(CollectionError::service_error(
+                        "Collections are not available".to_string(),
+                    ));
+                };
+                let collections = collections.read().await;
+                let real_collection_name = aliases
+                    .read()
+                    .await
+                    .get(&collection_name)
+                    .unwrap_or(collection_name);
+                let collection = collections.get(&real_collection_name).ok_or_else(|| {
+                    CollectionError::NotFound {
+                        what: format!("Collection {real_collection_name}"),
+                    }
+                })?;
+                let request = PointRequest {
+                    ids: vec![point_id],
+                    with_payload: Some(WithPayloadInterface::Bool(false)),
+                    with_vector: WithVector::Bool(true),
+                    strict: false,
+                };
+                collection
+                    .retrieve(request, None, None)
+                    .await?
+                    .into_iter()
+                    .next()
+                    .ok_or(CollectionError::PointNotFound {
+                        missed_point_id: point_id,
+                    })
+            }
+            .boxed()
+        })
+    }
+
     pub fn request_shard_transfer(
         &self,
         collection_name: String,
@@ -760,28 +826,44 @@ impl TableOfContent {
         } = operation.update_collection;
         let collection = self.get_collection(&operation.collection_name).await?;
         let mut recreate_optimizers = false;
+        // Optimizer kinds to recreate, `None` means all of them
+        let mut restart_mask = Some(HashSet::new());
 
         if let Some(diff) = optimizers_config {
-            collection.update_optimizer_params_from_diff(diff).await?;
-            recreate_optimizers = true;
+            let restart_reasons = collection.update_optimizer_params_from_diff(diff).await?;
+            if let Some(restart_mask) = &mut restart_mask {
+                restart_mask.extend(
+                    restart_reasons
+                        .iter()
+                        .flat_map(|reason| reason.affected_optimizers().iter().copied()),
+                );
+            }
+            recreate_optimizers |= !restart_reasons.is_empty();
         }
         if let Some(diff) = params {
             collection.update_params_from_diff(diff).await?;
             recreate_optimizers = true;
+            restart_mask = None;
         }
         if let Some(diff) = hnsw_config {
             collection.update_hnsw_config_from_diff(diff).await?;
             recreate_optimizers = true;
+            restart_mask = None;
         }
         if let Some(diff) = vectors {
             collection.update_vectors_from_diff(&diff).await?;
             recreate_optimizers = true;
+            restart_mask = None;
         }
         if let Some(diff) = quantization_config {
-            collection
+            // Warning of the update is not reported, optimizers are recreated right below
+            let update_result = collection
                 .update_quantization_config_from_diff(diff)
                 .await?;
-            recreate_optimizers = true;
+            if update_result.requires_optimizer_restart {
+                recreate_optimizers = true;
+                restart_mask = None;
+            }
         }
         if let Some(changes) = replica_changes {
             collection.handle_replica_changes(changes).await?;
@@ -789,7 +871,9 @@ impl TableOfContent {
 
         // Recreate optimizers
         if recreate_optimizers {
-            collection.recreate_optimizers_blocking().await?;
+            collection
+                .recreate_optimizers_blocking(restart_mask)
+                .await?;
         }
         Ok(true)
     }
@@ -944,7 +1028,7 @@ impl TableOfContent {
                 operation.shard_id,
                 operation.peer_id,
                 operation.state,
-                operation.from_state,
+                operation.from_state.map(|state| HashSet::from([state])),
             )
             .await?;
         Ok(())
@@ -988,7 +1072,8 @@ impl TableOfContent {
                     config: _,
                     shards,
                     transfers,
-                } = collection.state().await;
+                    state_checksum: _,
+                } = collection.state().await?;
                 let all_peers: HashSet<_> = self
                     .channel_service
                     .id_to_address
@@ -1042,14 +1127,16 @@ impl TableOfContent {
             }
             ShardTransferOperations::Finish(transfer) => {
                 // Validate transfer exists to prevent double handling
-                validate_transfer_exists(&transfer.key(), &collection.state().await.transfers)?;
+                validate_transfer_exists(&transfer.key(), &collection.state().await?.transfers)?;
                 collection.finish_shard_transfer(transfer).await?;
             }
             ShardTransferOperations::Abort { transfer, reason } => {
                 // Validate transfer exists to prevent double handling
-                validate_transfer_exists(&transfer, &collection.state().await.transfers)?;
+                validate_transfer_exists(&transfer, &collection.state().await?.transfers)?;
                 log::warn!("Aborting shard transfer: {reason}");
-                collection.abort_shard_transfer(transfer).await?;
+                collection
+                    .abort_shard_transfer(transfer, Some(&reason))
+                    .await?;
             }
         };
         Ok(())
@@ -1099,6 +1186,68 @@ impl TableOfContent {
         Ok(())
     }
 
+    /// Available disk space for the storage of the given collection
+    pub async fn collection_available_disk_space(
+        &self,
+        collection_name: &str,
+    ) -> Result<u64, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.available_disk_space().await?)
+    }
+
+    /// Estimated size of the local replica of the shard, if present on this peer
+    pub async fn collection_local_shard_size(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+    ) -> Result<Option<u64>, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.estimate_local_shard_size(shard_id).await)
+    }
+
+    /// Check if a snapshot of the shard pushed by another peer can be accepted
+    pub async fn check_pushed_shard_snapshot(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from_peer_id: PeerId,
+        snapshot_size: u64,
+    ) -> Result<(), StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .check_pushed_shard_snapshot(shard_id, from_peer_id, snapshot_size)
+            .await?;
+        Ok(())
+    }
+
+    /// Restore the local shard from a snapshot pushed by another peer
+    ///
+    /// The push is checked again, as the transfer could be aborted while the snapshot was received.
+    /// Replica state is changed by finishing the transfer.
+    pub async fn restore_pushed_shard_snapshot(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from_peer_id: PeerId,
+        snapshot_path: &Path,
+    ) -> Result<(), StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        // Snapshot is already stored, disk space was checked before
+        collection
+            .check_pushed_shard_snapshot(shard_id, from_peer_id, 0)
+            .await?;
+        collection
+            .restore_shard_snapshot(
+                shard_id,
+                snapshot_path,
+                self.this_peer_id,
+                self.is_distributed(),
+                &self.optional_temp_or_snapshot_temp_path()?,
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Recommend points using positive and negative example from the request
     ///
     /// # Arguments
@@ -1188,17 +1337,19 @@ impl TableOfContent {
     /// * `shard_selection` - which local shard to use
     /// # Result
     ///
-    /// Points with search score
+    /// Points with search score along with the number of shards, which responded successfully.
+    /// If the timeout is hit while retrieving payloads, points are returned without payloads,
+    /// see [`Collection::search_batch_with_timeout`]
     pub async fn search_batch(
         &self,
         collection_name: &str,
         request: SearchRequestBatch,
         read_consistency: Option<ReadConsistency>,
         shard_selection: Option<ShardId>,
-    ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+    ) -> Result<SearchBatchResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .search_batch(request, read_consistency, shard_selection)
+            .search_batch_with_timeout(request, read_consistency, shard_selection)
             .await
             .map_err(|err| err.into())
     }
@@ -1223,7 +1374,7 @@ impl TableOfContent {
     ) -> Result<CountResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         collection
-            .count(request, shard_selection)
+            .count(request, shard_selection, None)
             .await
             .map_err(|err| err.into())
     }
@@ -1356,6 +1507,7 @@ impl TableOfContent {
         shard_selection: Option<ShardId>,
         wait: bool,
         ordering: WriteOrdering,
+        idempotency_key: Option<Uuid>,
     ) -> Result<UpdateResult, StorageError> {
         let collection = self.get_collection(collection_name).await?;
         let result = match shard_selection {
@@ -1373,7 +1525,7 @@ impl TableOfContent {
                     self.check_write_lock()?;
                 }
                 collection
-                    .update_from_client(operation, wait, ordering)
+                    .update_from_client_idempotent(operation, wait, ordering, idempotency_key)
                     .await
             }
         };
@@ -1388,19 +1540,23 @@ impl TableOfContent {
         self.channel_service.id_to_address.read().clone()
     }
 
-    pub fn collections_snapshot_sync(&self) -> consensus_manager::CollectionsSnapshot {
+    pub fn collections_snapshot_sync(
+        &self,
+    ) -> Result<consensus_manager::CollectionsSnapshot, StorageError> {
         self.general_runtime.block_on(self.collections_snapshot())
     }
 
-    pub async fn collections_snapshot(&self) -> consensus_manager::CollectionsSnapshot {
+    pub async fn collections_snapshot(
+        &self,
+    ) -> Result<consensus_manager::CollectionsSnapshot, StorageError> {
         let mut collections: HashMap<CollectionId, collection_state::State> = HashMap::new();
         for (id, collection) in self.collections.read().await.iter() {
-            collections.insert(id.clone(), collection.state().await);
+            collections.insert(id.clone(), collection.state().await?);
         }
-        consensus_manager::CollectionsSnapshot {
+        Ok(consensus_manager::CollectionsSnapshot {
             collections,
             aliases: self.alias_persistence.read().await.state().clone(),
-        }
+        })
     }
 
     pub fn apply_collections_snapshot(
@@ -1438,6 +1594,12 @@ impl TableOfContent {
                             self.consensus_proposal_sender.clone(),
                             id.to_string(),
                         ),
+                        Some(Self::vector_lookup_callback(
+                            Arc::downgrade(&self.collections),
+                            Arc::downgrade(&self.alias_persistence),
+                        )),
+                        Default::default(),
+                        Default::default(),
                         Some(self.search_runtime.handle().clone()),
                         Some(self.update_runtime.handle().clone()),
                     )
@@ -1452,7 +1614,7 @@ impl TableOfContent {
                 };
 
                 // Update collection state
-                if &collection.state().await != state {
+                if &collection.state().await? != state {
                     if let Some(proposal_sender) = self.consensus_proposal_sender.clone() {
                         // In some cases on state application it might be needed to abort the transfer
                         let abort_transfer = |transfer| {
@@ -1524,7 +1686,7 @@ impl TableOfContent {
         // snapshot directory is mounted as network share and multiple writes to it could be slow
         let temp_dir = self.optional_temp_or_storage_temp_path()?;
         Ok(collection
-            .create_snapshot(&temp_dir, self.this_peer_id)
+            .create_snapshot(&temp_dir, self.this_peer_id, None)
             .await?)
     }
 
@@ -1577,19 +1739,19 @@ impl TableOfContent {
         result
     }
 
-    pub async fn peer_has_shards(&self, peer_id: PeerId) -> bool {
+    pub async fn peer_has_shards(&self, peer_id: PeerId) -> Result<bool, StorageError> {
         for collection in self.collections.read().await.values() {
-            let state = collection.state().await;
+            let state = collection.state().await?;
             if state
                 .shards
                 .into_values()
                 .flat_map(|shard_info| shard_info.replicas.into_keys())
                 .any(|x| x == peer_id)
             {
-                return true;
+                return Ok(true);
             }
         }
-        false
+        Ok(false)
     }
 
     pub fn set_locks(&self, is_write_locked: bool, error_message: Option<String>) {
@@ -1642,7 +1804,7 @@ impl CollectionContainer for TableOfContent {
         self.perform_collection_meta_op_sync(operation)
     }
 
-    fn collections_snapshot(&self) -> consensus_manager::CollectionsSnapshot {
+    fn collections_snapshot(&self) -> Result<consensus_manager::CollectionsSnapshot, StorageError> {
         self.collections_snapshot_sync()
     }
 
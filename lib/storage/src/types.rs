@@ -3,7 +3,13 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use collection::config::WalConfig;
-use collection::operations::shared_storage_config::SharedStorageConfig;
+use collection::operations::shared_storage_config::{
+    SharedStorageConfig, DEFAULT_IDEMPOTENCY_TTL, DEFAULT_MAX_CONCURRENT_SNAPSHOTS,
+    DEFAULT_MIN_INDEXING_THRESHOLD_KB, DEFAULT_PARALLEL_MERGE_THRESHOLD,
+    DEFAULT_POINTS_COUNT_CACHE_TTL, DEFAULT_SHARD_INFO_TIMEOUT,
+    DEFAULT_SHARD_TRANSFER_INIT_TIMEOUT_SECS, DEFAULT_SNAPSHOT_MEMORY_WARNING_THRESHOLD_BYTES,
+    DEFAULT_STALE_REPLICA_TIMEOUT,
+};
 use collection::operations::types::NodeType;
 use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::shard::PeerId;
@@ -69,6 +75,48 @@ pub struct StorageConfig {
     /// Provided value will be used error message for unavailable requests.
     #[serde(default)]
     pub recovery_mode: Option<String>,
+    /// If true - concurrent updates of the same point are applied one after another,
+    /// in the order they were received.
+    #[serde(default)]
+    pub serialized_point_updates: bool,
+    /// How long to remember results of updates with idempotency keys, in seconds.
+    /// Retried updates with a known key return the original result. `0` - disabled.
+    #[serde(default = "default_idempotency_ttl_secs")]
+    pub idempotency_ttl_secs: u64,
+    /// Max number of snapshots of a single collection, which can be created at the same time.
+    #[serde(default = "default_max_concurrent_snapshots")]
+    #[validate(range(min = 1))]
+    pub max_concurrent_snapshots: usize,
+    /// Timeout for collecting the information of a single shard, in milliseconds.
+    /// Shards which don't respond in time are reported as unavailable.
+    #[serde(default = "default_shard_info_timeout_ms")]
+    #[validate(range(min = 1))]
+    pub shard_info_timeout_ms: u64,
+    /// Active local replica, which has pending updates but didn't apply any of them
+    /// for this many seconds, is marked as dead and recovered from other replicas. `0` - disabled.
+    #[serde(default = "default_stale_replica_timeout_secs")]
+    pub stale_replica_timeout_secs: u64,
+    /// Log a warning if memory usage grows by more than this many megabytes
+    /// while archiving a snapshot. `0` - disabled.
+    #[serde(default = "default_snapshot_memory_warning_threshold_mb")]
+    pub snapshot_memory_warning_threshold_mb: u64,
+    /// Max time to wait for consensus to register a shard transfer to this peer, in seconds.
+    /// Increase for large collections on slow networks.
+    #[serde(default = "default_shard_transfer_init_timeout_secs")]
+    #[validate(range(min = 1))]
+    pub shard_transfer_init_timeout_secs: u64,
+    /// Lowest non-zero indexing threshold of a collection, which can be set with an update
+    /// of the collection optimizers, in kilobytes.
+    #[serde(default = "default_min_indexing_threshold_kb")]
+    pub min_indexing_threshold_kb: usize,
+    /// Search batches with more searches than this merge the results of shards
+    /// on multiple threads.
+    #[serde(default = "default_parallel_merge_threshold")]
+    pub parallel_merge_threshold: usize,
+    /// How long approximate count of all points in a collection is served from cache,
+    /// in milliseconds. `0` - disabled.
+    #[serde(default = "default_points_count_cache_ttl_ms")]
+    pub points_count_cache_ttl_ms: u64,
 }
 
 impl StorageConfig {
@@ -81,6 +129,16 @@ impl StorageConfig {
             self.performance
                 .search_timeout_sec
                 .map(|x| Duration::from_secs(x as u64)),
+            self.serialized_point_updates,
+            Duration::from_secs(self.idempotency_ttl_secs),
+            self.max_concurrent_snapshots,
+            Duration::from_millis(self.shard_info_timeout_ms),
+            Duration::from_secs(self.stale_replica_timeout_secs),
+            self.snapshot_memory_warning_threshold_mb * 1024 * 1024,
+            self.shard_transfer_init_timeout_secs,
+            self.min_indexing_threshold_kb,
+            self.parallel_merge_threshold,
+            Duration::from_millis(self.points_count_cache_ttl_ms),
         )
     }
 }
@@ -89,6 +147,42 @@ fn default_snapshots_path() -> String {
     "./snapshots".to_string()
 }
 
+const fn default_idempotency_ttl_secs() -> u64 {
+    DEFAULT_IDEMPOTENCY_TTL.as_secs()
+}
+
+const fn default_max_concurrent_snapshots() -> usize {
+    DEFAULT_MAX_CONCURRENT_SNAPSHOTS
+}
+
+const fn default_shard_info_timeout_ms() -> u64 {
+    DEFAULT_SHARD_INFO_TIMEOUT.as_millis() as u64
+}
+
+const fn default_stale_replica_timeout_secs() -> u64 {
+    DEFAULT_STALE_REPLICA_TIMEOUT.as_secs()
+}
+
+const fn default_snapshot_memory_warning_threshold_mb() -> u64 {
+    DEFAULT_SNAPSHOT_MEMORY_WARNING_THRESHOLD_BYTES / (1024 * 1024)
+}
+
+const fn default_shard_transfer_init_timeout_secs() -> u64 {
+    DEFAULT_SHARD_TRANSFER_INIT_TIMEOUT_SECS
+}
+
+const fn default_min_indexing_threshold_kb() -> usize {
+    DEFAULT_MIN_INDEXING_THRESHOLD_KB
+}
+
+const fn default_parallel_merge_threshold() -> usize {
+    DEFAULT_PARALLEL_MERGE_THRESHOLD
+}
+
+const fn default_points_count_cache_ttl_ms() -> u64 {
+    DEFAULT_POINTS_COUNT_CACHE_TTL.as_millis() as u64
+}
+
 const fn default_on_disk_payload() -> bool {
     false
 }
@@ -55,6 +55,16 @@ fn test_alias_operation() {
         handle_collection_load_errors: false,
         recovery_mode: None,
         async_scorer: false,
+        serialized_point_updates: false,
+        idempotency_ttl_secs: 0,
+        max_concurrent_snapshots: 1,
+        shard_info_timeout_ms: 1000,
+        stale_replica_timeout_secs: 0,
+        snapshot_memory_warning_threshold_mb: 0,
+        shard_transfer_init_timeout_secs: 60,
+        min_indexing_threshold_kb: 1000,
+        parallel_merge_threshold: 32,
+        points_count_cache_ttl_ms: 1000,
     };
 
     let search_runtime = Runtime::new().unwrap();
@@ -101,6 +111,7 @@ fn test_alias_operation() {
                         write_consistency_factor: None,
                         init_from: None,
                         quantization_config: None,
+                        max_payload_size_bytes: None,
                     },
                 )),
                 None,
@@ -42,7 +42,10 @@ async fn remove_peer(
     let dispatcher = dispatcher.into_inner();
     let peer_id = peer_id.into_inner();
 
-    let has_shards = dispatcher.peer_has_shards(peer_id).await;
+    let has_shards = match dispatcher.peer_has_shards(peer_id).await {
+        Ok(has_shards) => has_shards,
+        Err(err) => return process_response::<()>(Err(err), timing),
+    };
     if !params.force && has_shards {
         return process_response::<()>(
             Err(StorageError::BadRequest {
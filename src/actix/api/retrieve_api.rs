@@ -31,6 +31,7 @@ async fn do_get_point(
         ids: vec![point_id],
         with_payload: Some(WithPayloadInterface::Bool(true)),
         with_vector: true.into(),
+        strict: false,
     };
 
     toc.retrieve(collection_name, request, read_consistency, None)
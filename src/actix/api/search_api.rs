@@ -1,7 +1,12 @@
 use actix_web::rt::time::Instant;
-use actix_web::{post, web, Responder};
+use actix_web::{post, web, HttpResponse, Responder};
 use actix_web_validator::{Json, Path, Query};
-use collection::operations::types::{SearchGroupsRequest, SearchRequest, SearchRequestBatch};
+use api::grpc::models::ApiStatus;
+use collection::operations::types::{
+    SearchDryRunResult, SearchGroupsRequest, SearchRequest, SearchRequestBatch,
+};
+use segment::types::ScoredPoint;
+use serde::Serialize;
 use storage::content_manager::toc::TableOfContent;
 
 use super::read_params::ReadParams;
@@ -30,6 +35,24 @@ async fn search_points(
     process_response(response, timing)
 }
 
+/// Response of the batch search, extends the regular response with information about shards
+#[derive(Debug, Serialize)]
+struct SearchBatchResponse {
+    result: Vec<Vec<ScoredPoint>>,
+    status: ApiStatus,
+    time: f64,
+    /// Number of shards, which responded successfully.
+    /// May be less than the number of queried shards if `min_shards` or `wait_for_shards` is set
+    shards_successful: usize,
+    /// Set if the search timed out while retrieving payloads,
+    /// results are then returned without payloads and vectors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+    /// Estimated cost of the batch, set instead of the results if `dry_run` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dry_run: Option<SearchDryRunResult>,
+}
+
 #[post("/collections/{name}/points/search/batch")]
 async fn batch_search_points(
     toc: web::Data<TableOfContent>,
@@ -48,7 +71,17 @@ async fn batch_search_points(
     )
     .await;
 
-    process_response(response, timing)
+    match response {
+        Ok(batch_result) => HttpResponse::Ok().json(SearchBatchResponse {
+            result: batch_result.results,
+            status: ApiStatus::Ok,
+            time: timing.elapsed().as_secs_f64(),
+            shards_successful: batch_result.shards_successful,
+            timeout: batch_result.timeout.map(|err| err.to_string()),
+            dry_run: batch_result.dry_run,
+        }),
+        Err(err) => process_response::<()>(Err(err), timing),
+    }
 }
 
 #[post("/collections/{name}/points/search/groups")]
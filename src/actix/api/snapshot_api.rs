@@ -10,7 +10,8 @@ use actix_web_validator as valid;
 use collection::collection::Collection;
 use collection::common::file_utils::move_file;
 use collection::operations::snapshot_ops::{
-    ShardSnapshotLocation, ShardSnapshotRecover, SnapshotPriority, SnapshotRecover,
+    RecoveryMode, ShardSnapshotLocation, ShardSnapshotManifest, ShardSnapshotRecover,
+    SnapshotPriority, SnapshotRecover,
 };
 use collection::shards::replica_set::ReplicaState;
 use collection::shards::shard::ShardId;
@@ -47,6 +48,11 @@ struct SnapshotPath {
 pub struct SnapshotUploadingParam {
     pub wait: Option<bool>,
     pub priority: Option<SnapshotPriority>,
+    /// Allow recovering a snapshot created from another collection
+    pub force: Option<bool>,
+    /// How local shards are recovered, `blocking` by default.
+    /// Only applies to the recovery of collection snapshots
+    pub recovery_mode: Option<RecoveryMode>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema, Validate)]
@@ -167,6 +173,8 @@ async fn upload_snapshot(
     let snapshot_recover = SnapshotRecover {
         location: snapshot_location,
         priority: params.priority,
+        force: params.force.unwrap_or_default(),
+        recovery_mode: params.recovery_mode,
     };
 
     let response = do_recover_from_snapshot(
@@ -308,7 +316,12 @@ async fn create_shard_snapshot(
         let (collection, shard) = path.into_inner();
         let collection = toc.get_collection(&collection).await?;
         let snapshot = collection
-            .create_shard_snapshot(shard, &toc.optional_temp_or_snapshot_temp_path()?)
+            .create_shard_snapshot(
+                shard,
+                &toc.optional_temp_or_snapshot_temp_path()?,
+                None,
+                false,
+            )
             .await?;
 
         Ok(snapshot)
@@ -375,13 +388,36 @@ async fn upload_shard_snapshot(
     query: web::Query<SnapshotUploadingParam>,
     MultipartForm(form): MultipartForm<SnapshottingForm>,
 ) -> impl Responder {
-    let SnapshotUploadingParam { wait, priority } = query.into_inner();
+    let SnapshotUploadingParam {
+        wait,
+        priority,
+        force,
+        recovery_mode: _,
+    } = query.into_inner();
 
     let future = async move {
         let (collection, shard) = path.into_inner();
         let collection = toc.get_collection(&collection).await?;
         collection.assert_shard_exists(shard).await?;
 
+        if !force.unwrap_or_default() {
+            let snapshot_path = form.snapshot.file.path().to_path_buf();
+            let manifest = tokio::task::spawn_blocking(move || {
+                ShardSnapshotManifest::read_from_archive(&snapshot_path)
+            })
+            .await??;
+
+            if let Some(manifest) = manifest {
+                if manifest.collection_id != collection.name() {
+                    return Err(StorageError::bad_input(format!(
+                        "Snapshot was created from collection {}, but is restored into collection {}",
+                        manifest.collection_id,
+                        collection.name(),
+                    )));
+                }
+            }
+        }
+
         recover_shard_snapshot_impl(
             &toc,
             &collection,
@@ -468,7 +504,7 @@ async fn recover_shard_snapshot_impl(
         )
         .await?;
 
-    let state = collection.state().await;
+    let state = collection.state().await?;
     let shard_info = state.shards.get(&shard).unwrap(); // TODO: Handle `unwrap`?..
 
     // TODO: Unify (and de-duplicate) "recovered shard state notification" logic in `_do_recover_from_snapshot` with this one!
@@ -7,6 +7,7 @@ use collection::operations::vector_ops::{DeleteVectors, UpdateVectors};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use storage::content_manager::toc::TableOfContent;
+use uuid::Uuid;
 use validator::Validate;
 
 use super::CollectionPath;
@@ -30,12 +31,20 @@ pub struct UpdateParam {
     pub ordering: Option<WriteOrdering>,
 }
 
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct PointsUpdateParam {
+    pub wait: Option<bool>,
+    pub ordering: Option<WriteOrdering>,
+    /// Retries of the operation with the same key are applied only once
+    pub idempotency_key: Option<Uuid>,
+}
+
 #[put("/collections/{name}/points")]
 async fn upsert_points(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<PointInsertOperations>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -49,6 +58,7 @@ async fn upsert_points(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -59,7 +69,7 @@ async fn delete_points(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<PointsSelector>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -73,6 +83,7 @@ async fn delete_points(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -83,7 +94,7 @@ async fn update_vectors(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<UpdateVectors>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -97,6 +108,7 @@ async fn update_vectors(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -107,7 +119,7 @@ async fn delete_vectors(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<DeleteVectors>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -121,6 +133,7 @@ async fn delete_vectors(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -131,7 +144,7 @@ async fn set_payload(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<SetPayload>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -145,6 +158,7 @@ async fn set_payload(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -155,7 +169,7 @@ async fn overwrite_payload(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<SetPayload>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -169,6 +183,7 @@ async fn overwrite_payload(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -179,7 +194,7 @@ async fn delete_payload(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<DeletePayload>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -193,6 +208,7 @@ async fn delete_payload(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
@@ -203,7 +219,7 @@ async fn clear_payload(
     toc: web::Data<TableOfContent>,
     collection: Path<CollectionPath>,
     operation: Json<PointsSelector>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let timing = Instant::now();
     let operation = operation.into_inner();
@@ -217,6 +233,7 @@ async fn clear_payload(
         None,
         wait,
         ordering,
+        params.idempotency_key,
     )
     .await;
     process_response(response, timing)
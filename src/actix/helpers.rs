@@ -22,6 +22,7 @@ pub fn storage_into_actix_error(err: StorageError) -> Error {
         StorageError::BadRequest { .. } => error::ErrorBadRequest(format!("{err}")),
         StorageError::Locked { .. } => error::ErrorForbidden(format!("{err}")),
         StorageError::Timeout { .. } => error::ErrorRequestTimeout(format!("{err}")),
+        StorageError::Forbidden { .. } => error::ErrorForbidden(format!("{err}")),
     }
 }
 
@@ -62,6 +63,7 @@ where
                 StorageError::BadRequest { .. } => HttpResponse::BadRequest(),
                 StorageError::Locked { .. } => HttpResponse::Forbidden(),
                 StorageError::Timeout { .. } => HttpResponse::RequestTimeout(),
+                StorageError::Forbidden { .. } => HttpResponse::Forbidden(),
             };
 
             resp.json(ApiResponse::<()> {
@@ -182,6 +184,7 @@ impl From<StorageError> for HttpError {
             StorageError::Timeout { description } => {
                 (http::StatusCode::REQUEST_TIMEOUT, description)
             }
+            StorageError::Forbidden { description } => (http::StatusCode::FORBIDDEN, description),
         };
 
         Self {
@@ -88,6 +88,9 @@ pub async fn do_create_snapshot(
             name: "".to_string(),
             creation_time: None,
             size: 0,
+            content_hash: None,
+            peak_memory_bytes_during_archiving: 0,
+            qdrant_version: None,
         })
     }
 }
@@ -149,18 +152,20 @@ pub async fn do_update_collection_cluster(
             // validate source peer exists
             validate_peer_exists(move_shard.from_peer_id)?;
 
+            let transfer = ShardTransfer {
+                shard_id: move_shard.shard_id,
+                to: move_shard.to_peer_id,
+                from: move_shard.from_peer_id,
+                sync: false,
+            };
+
+            // validate target peer has enough disk space
+            collection.check_transfer_disk_space(&transfer).await?;
+
             // submit operation to consensus
             dispatcher
                 .submit_collection_meta_op(
-                    CollectionMetaOperations::TransferShard(
-                        collection_name,
-                        Start(ShardTransfer {
-                            shard_id: move_shard.shard_id,
-                            to: move_shard.to_peer_id,
-                            from: move_shard.from_peer_id,
-                            sync: false,
-                        }),
-                    ),
+                    CollectionMetaOperations::TransferShard(collection_name, Start(transfer)),
                     wait_timeout,
                 )
                 .await
@@ -182,18 +187,20 @@ pub async fn do_update_collection_cluster(
             // validate source peer exists
             validate_peer_exists(replicate_shard.from_peer_id)?;
 
+            let transfer = ShardTransfer {
+                shard_id: replicate_shard.shard_id,
+                to: replicate_shard.to_peer_id,
+                from: replicate_shard.from_peer_id,
+                sync: true,
+            };
+
+            // validate target peer has enough disk space
+            collection.check_transfer_disk_space(&transfer).await?;
+
             // submit operation to consensus
             dispatcher
                 .submit_collection_meta_op(
-                    CollectionMetaOperations::TransferShard(
-                        collection_name,
-                        Start(ShardTransfer {
-                            shard_id: replicate_shard.shard_id,
-                            to: replicate_shard.to_peer_id,
-                            from: replicate_shard.from_peer_id,
-                            sync: true,
-                        }),
-                    ),
+                    CollectionMetaOperations::TransferShard(collection_name, Start(transfer)),
                     wait_timeout,
                 )
                 .await
@@ -5,8 +5,8 @@ use collection::operations::point_ops::{
 };
 use collection::operations::types::{
     CountRequest, CountResult, GroupsResult, PointRequest, RecommendGroupsRequest, Record,
-    ScrollRequest, ScrollResult, SearchGroupsRequest, SearchRequest, SearchRequestBatch,
-    UpdateResult,
+    ScrollRequest, ScrollResult, SearchBatchResult, SearchGroupsRequest, SearchRequest,
+    SearchRequestBatch, UpdateResult,
 };
 use collection::operations::vector_ops::{DeleteVectors, UpdateVectors, VectorOperations};
 use collection::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
@@ -16,6 +16,7 @@ use segment::types::{PayloadFieldSchema, ScoredPoint};
 use serde::{Deserialize, Serialize};
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
+use uuid::Uuid;
 use validator::Validate;
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate)]
@@ -114,6 +115,7 @@ pub async fn do_upsert_points(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(operation));
@@ -123,6 +125,7 @@ pub async fn do_upsert_points(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -134,6 +137,7 @@ pub async fn do_delete_points(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let point_operation = match points {
         PointsSelector::PointIdsSelector(points) => {
@@ -150,6 +154,7 @@ pub async fn do_delete_points(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -161,6 +166,7 @@ pub async fn do_update_vectors(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::VectorOperation(VectorOperations::UpdateVectors(operation));
@@ -170,6 +176,7 @@ pub async fn do_update_vectors(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -181,7 +188,14 @@ pub async fn do_delete_vectors(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
+    if idempotency_key.is_some() && operation.filter.is_some() && operation.points.is_some() {
+        return Err(StorageError::bad_request(
+            "Idempotency key can't be used to delete vectors by both filter and points",
+        ));
+    }
+
     let vector_names: Vec<_> = operation.vector.into_iter().collect();
 
     let mut result = None;
@@ -197,6 +211,7 @@ pub async fn do_delete_vectors(
                 shard_selection,
                 wait,
                 ordering,
+                idempotency_key,
             )
             .await?,
         );
@@ -212,6 +227,7 @@ pub async fn do_delete_vectors(
                 shard_selection,
                 wait,
                 ordering,
+                idempotency_key,
             )
             .await?,
         );
@@ -227,6 +243,7 @@ pub async fn do_set_payload(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(operation));
@@ -236,6 +253,7 @@ pub async fn do_set_payload(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -247,6 +265,7 @@ pub async fn do_overwrite_payload(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PayloadOperation(PayloadOps::OverwritePayload(operation));
@@ -256,6 +275,7 @@ pub async fn do_overwrite_payload(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -267,6 +287,7 @@ pub async fn do_delete_payload(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PayloadOperation(PayloadOps::DeletePayload(operation));
@@ -276,6 +297,7 @@ pub async fn do_delete_payload(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -287,6 +309,7 @@ pub async fn do_clear_payload(
     shard_selection: Option<ShardId>,
     wait: bool,
     ordering: WriteOrdering,
+    idempotency_key: Option<Uuid>,
 ) -> Result<UpdateResult, StorageError> {
     let points_operation = match points {
         PointsSelector::PointIdsSelector(points) => PayloadOps::ClearPayload {
@@ -304,6 +327,7 @@ pub async fn do_clear_payload(
         shard_selection,
         wait,
         ordering,
+        idempotency_key,
     )
     .await
 }
@@ -327,6 +351,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -338,6 +363,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -349,6 +375,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -360,6 +387,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -371,6 +399,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -382,6 +411,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -393,6 +423,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -404,6 +435,7 @@ pub async fn do_batch_update_points(
                     shard_selection,
                     wait,
                     ordering,
+                    None,
                 )
                 .await
             }
@@ -433,6 +465,7 @@ pub async fn do_create_index(
         shard_selection,
         wait,
         ordering,
+        None,
     )
     .await
 }
@@ -454,6 +487,7 @@ pub async fn do_delete_index(
         shard_selection,
         wait,
         ordering,
+        None,
     )
     .await
 }
@@ -475,7 +509,7 @@ pub async fn do_search_batch_points(
     request: SearchRequestBatch,
     read_consistency: Option<ReadConsistency>,
     shard_selection: Option<ShardId>,
-) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+) -> Result<SearchBatchResult, StorageError> {
     toc.search_batch(collection_name, request, read_consistency, shard_selection)
         .await
 }
@@ -27,7 +27,13 @@ pub async fn handle_existing_collections(
             Err(_) => break,
         };
 
-        let collection_state = collection_obj.state().await;
+        let collection_state = match collection_obj.state().await {
+            Ok(collection_state) => collection_state,
+            Err(err) => {
+                log::error!("Can't get state of collection {collection}: {err}");
+                continue;
+            }
+        };
         let shards_number = collection_state.config.params.shard_number.get();
 
         let mut collection_create_operation = CreateCollectionOperation::new(
@@ -49,6 +55,7 @@ pub async fn handle_existing_collections(
                 optimizers_config: Some(collection_state.config.optimizer_config.into()),
                 init_from: None,
                 quantization_config: collection_state.config.quantization_config,
+                max_payload_size_bytes: collection_state.config.params.max_payload_size_bytes,
             },
         );
 
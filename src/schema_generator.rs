@@ -10,7 +10,7 @@ use collection::operations::types::{
     AliasDescription, CollectionClusterInfo, CollectionInfo, CollectionsAliasesResponse,
     CountRequest, CountResult, GroupsResult, PointGroup, PointRequest, RecommendGroupsRequest,
     RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult,
-    SearchGroupsRequest, SearchRequest, SearchRequestBatch, UpdateResult,
+    SearchDryRunResult, SearchGroupsRequest, SearchRequest, SearchRequestBatch, UpdateResult,
 };
 use collection::operations::vector_ops::{DeleteVectors, UpdateVectors};
 use schemars::gen::SchemaSettings;
@@ -75,6 +75,7 @@ struct AllDefinitions {
     b7: GroupsResult,
     b8: UpdateOperations,
     b9: ShardSnapshotRecover,
+    ba: SearchDryRunResult,
 }
 
 fn save_schema<T: JsonSchema>() {
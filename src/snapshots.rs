@@ -66,6 +66,7 @@ pub fn recover_snapshots(
             &collection_temp_path,
             this_peer_id,
             is_distributed,
+            None,
         ) {
             panic!("Failed to recover snapshot {collection_name}: {err}");
         }
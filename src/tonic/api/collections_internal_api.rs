@@ -3,12 +3,15 @@ use std::time::Instant;
 
 use api::grpc::qdrant::collections_internal_server::CollectionsInternal;
 use api::grpc::qdrant::{
-    CollectionOperationResponse, GetCollectionInfoRequestInternal, GetCollectionInfoResponse,
-    InitiateShardTransferRequest,
+    CheckDiskSpaceRequest, CheckDiskSpaceResponse, CollectionOperationResponse,
+    GetCollectionInfoRequestInternal, GetCollectionInfoResponse, InitiateShardTransferRequest,
+    UploadShardSnapshotRequest,
 };
 use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
-use tonic::{Request, Response, Status};
+use tokio::io::AsyncWriteExt as _;
+use tonic::{Request, Response, Status, Streaming};
+use uuid::Uuid;
 
 use super::validate_and_log;
 use crate::tonic::api::collections_common::get;
@@ -68,4 +71,135 @@ impl CollectionsInternal for CollectionsInternalService {
         };
         Ok(Response::new(response))
     }
+
+    async fn check_disk_space(
+        &self,
+        request: Request<CheckDiskSpaceRequest>,
+    ) -> Result<Response<CheckDiskSpaceResponse>, Status> {
+        validate_and_log(request.get_ref());
+        let timing = Instant::now();
+        let CheckDiskSpaceRequest {
+            collection_name,
+            shard_id,
+        } = request.into_inner();
+
+        let available_bytes = self
+            .toc
+            .collection_available_disk_space(&collection_name)
+            .await
+            .map_err(error_to_status)?;
+
+        let shard_size_bytes = match shard_id {
+            Some(shard_id) => self
+                .toc
+                .collection_local_shard_size(&collection_name, shard_id)
+                .await
+                .map_err(error_to_status)?,
+            None => None,
+        };
+
+        let response = CheckDiskSpaceResponse {
+            available_bytes,
+            time: timing.elapsed().as_secs_f64(),
+            shard_size_bytes,
+        };
+        Ok(Response::new(response))
+    }
+    async fn upload_shard_snapshot(
+        &self,
+        request: Request<Streaming<UploadShardSnapshotRequest>>,
+    ) -> Result<Response<CollectionOperationResponse>, Status> {
+        let timing = Instant::now();
+        let mut chunks = request.into_inner();
+
+        let Some(first_chunk) = chunks.message().await? else {
+            return Err(Status::invalid_argument("Shard snapshot upload is empty"));
+        };
+        validate_and_log(&first_chunk);
+        let UploadShardSnapshotRequest {
+            collection_name,
+            shard_id,
+            data,
+            from_peer_id,
+            snapshot_size,
+        } = first_chunk;
+
+        // Reject unexpected snapshots before receiving them
+        self.toc
+            .check_pushed_shard_snapshot(&collection_name, shard_id, from_peer_id, snapshot_size)
+            .await
+            .map_err(error_to_status)?;
+
+        let temp_dir = self
+            .toc
+            .optional_temp_or_snapshot_temp_path()
+            .map_err(|err| error_to_status(err.into()))?;
+        let snapshot_path = temp_dir.join(format!(
+            "{collection_name}-shard-{shard_id}-pushed-{}.snapshot",
+            Uuid::new_v4(),
+        ));
+
+        let result = async {
+            let mut writer = tokio::fs::File::create(&snapshot_path).await?;
+            let mut data = data;
+            let mut received_size = 0;
+            loop {
+                received_size += data.len() as u64;
+                if received_size > snapshot_size {
+                    return Err(Status::invalid_argument(format!(
+                        "Shard snapshot is larger than announced {snapshot_size} bytes",
+                    )));
+                }
+                writer.write_all(&data).await?;
+
+                let Some(chunk) = chunks.message().await? else {
+                    break;
+                };
+                if chunk.collection_name != collection_name
+                    || chunk.shard_id != shard_id
+                    || chunk.from_peer_id != from_peer_id
+                    || chunk.snapshot_size != snapshot_size
+                {
+                    return Err(Status::invalid_argument(
+                        "All chunks of a shard snapshot must belong to the same snapshot",
+                    ));
+                }
+                data = chunk.data;
+            }
+            writer.flush().await?;
+            drop(writer);
+
+            if received_size != snapshot_size {
+                return Err(Status::invalid_argument(format!(
+                    "Shard snapshot is incomplete: \
+                     received {received_size} of {snapshot_size} bytes",
+                )));
+            }
+
+            self.toc
+                .restore_pushed_shard_snapshot(
+                    &collection_name,
+                    shard_id,
+                    from_peer_id,
+                    &snapshot_path,
+                )
+                .await
+                .map_err(error_to_status)
+        }
+        .await;
+
+        if let Err(err) = tokio::fs::remove_file(&snapshot_path).await {
+            log::warn!(
+                "Failed to remove pushed shard snapshot {}: {err}",
+                snapshot_path.display(),
+            );
+        }
+        result?;
+
+        let response = CollectionOperationResponse {
+            result: true,
+            time: timing.elapsed().as_secs_f64(),
+        };
+        Ok(Response::new(response))
+    }
 }
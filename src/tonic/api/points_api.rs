@@ -145,6 +145,9 @@ impl Points for PointsService {
             collection_name,
             search_points,
             read_consistency,
+            explain_score,
+            timeout_ms,
+            dry_run,
         } = request.into_inner();
         search_batch(
             self.toc.as_ref(),
@@ -152,6 +155,9 @@ impl Points for PointsService {
             search_points,
             read_consistency,
             None,
+            explain_score.unwrap_or_default(),
+            timeout_ms,
+            dry_run.unwrap_or_default(),
         )
         .await
     }
@@ -13,14 +13,14 @@ use api::grpc::qdrant::{
     UpdateBatchPoints, UpdateBatchResponse, UpdatePointVectors, UpsertPoints,
 };
 use collection::operations::consistency_params::ReadConsistency;
-use collection::operations::conversions::write_ordering_from_proto;
+use collection::operations::conversions::{tiebreaker_from_proto, write_ordering_from_proto};
 use collection::operations::payload_ops::DeletePayload;
 use collection::operations::point_ops::{
     self, PointInsertOperations, PointOperations, PointSyncOperation,
 };
 use collection::operations::types::{
-    default_exact_count, PointRequest, RecommendRequestBatch, ScrollRequest, SearchRequest,
-    SearchRequestBatch,
+    default_exact_count, PointRequest, RecommendRequestBatch, ScrollKey, ScrollRequest,
+    SearchRequest, SearchRequestBatch,
 };
 use collection::operations::vector_ops::{DeleteVectors, PointVectors, UpdateVectors};
 use collection::operations::CollectionUpdateOperations;
@@ -32,6 +32,7 @@ use segment::types::{
 use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
 use tonic::{Response, Status};
+use uuid::Uuid;
 
 use crate::common::points::{
     do_clear_payload, do_count_points, do_create_index, do_delete_index, do_delete_payload,
@@ -55,6 +56,16 @@ fn extract_points_selector(
     Ok((points, filter))
 }
 
+fn idempotency_key_from_proto(idempotency_key: Option<String>) -> Result<Option<Uuid>, Status> {
+    idempotency_key
+        .map(|key| {
+            Uuid::parse_str(&key).map_err(|err| {
+                Status::invalid_argument(format!("Unable to parse idempotency key {key}: {err}"))
+            })
+        })
+        .transpose()
+}
+
 pub fn points_operation_response(
     timing: Instant,
     update_result: collection::operations::types::UpdateResult,
@@ -75,6 +86,7 @@ pub async fn upsert(
         wait,
         points,
         ordering,
+        idempotency_key,
     } = upsert_points;
     let points = points
         .into_iter()
@@ -89,6 +101,7 @@ pub async fn upsert(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -132,6 +145,7 @@ pub async fn sync(
             shard_selection,
             wait.unwrap_or(false),
             write_ordering_from_proto(ordering)?,
+            None,
         )
         .await
         .map_err(error_to_status)?;
@@ -150,6 +164,7 @@ pub async fn delete(
         wait,
         points,
         ordering,
+        idempotency_key,
     } = delete_points;
 
     let points_selector = match points {
@@ -165,6 +180,7 @@ pub async fn delete(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -183,6 +199,7 @@ pub async fn update_vectors(
         wait,
         points,
         ordering,
+        idempotency_key,
     } = update_point_vectors;
 
     // Build list of operation points
@@ -209,6 +226,7 @@ pub async fn update_vectors(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -228,6 +246,7 @@ pub async fn delete_vectors(
         points_selector,
         vectors,
         ordering,
+        idempotency_key,
     } = delete_point_vectors;
 
     let (points, filter) = extract_points_selector(points_selector)?;
@@ -250,6 +269,7 @@ pub async fn delete_vectors(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -269,6 +289,7 @@ pub async fn set_payload(
         payload,
         points_selector,
         ordering,
+        idempotency_key,
     } = set_payload_points;
 
     let (points, filter) = extract_points_selector(points_selector)?;
@@ -286,6 +307,7 @@ pub async fn set_payload(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -305,6 +327,7 @@ pub async fn overwrite_payload(
         payload,
         points_selector,
         ordering,
+        idempotency_key,
     } = set_payload_points;
 
     let (points, filter) = extract_points_selector(points_selector)?;
@@ -322,6 +345,7 @@ pub async fn overwrite_payload(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -341,6 +365,7 @@ pub async fn delete_payload(
         keys,
         points_selector,
         ordering,
+        idempotency_key,
     } = delete_payload_points;
 
     let (points, filter) = extract_points_selector(points_selector)?;
@@ -358,6 +383,7 @@ pub async fn delete_payload(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -376,6 +402,7 @@ pub async fn clear_payload(
         wait,
         points,
         ordering,
+        idempotency_key,
     } = clear_payload_points;
 
     let points_selector = match points {
@@ -391,6 +418,7 @@ pub async fn clear_payload(
         shard_selection,
         wait.unwrap_or(false),
         write_ordering_from_proto(ordering)?,
+        idempotency_key_from_proto(idempotency_key)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -428,6 +456,7 @@ pub async fn update_batch(
                         points: points.points,
                         wait,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -441,6 +470,7 @@ pub async fn update_batch(
                         wait,
                         points: Some(points),
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -460,6 +490,7 @@ pub async fn update_batch(
                         payload,
                         points_selector,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -479,6 +510,7 @@ pub async fn update_batch(
                         payload,
                         points_selector,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -498,6 +530,7 @@ pub async fn update_batch(
                         keys,
                         points_selector,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -511,6 +544,7 @@ pub async fn update_batch(
                         wait,
                         points: Some(points),
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -526,6 +560,7 @@ pub async fn update_batch(
                         wait,
                         points,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -545,6 +580,7 @@ pub async fn update_batch(
                         points_selector,
                         vectors,
                         ordering,
+                        idempotency_key: None,
                     },
                     shard_selection,
                 )
@@ -674,6 +710,7 @@ pub async fn search(
         vector_name,
         with_vectors,
         read_consistency,
+        tiebreaker,
     } = search_points;
 
     let search_request = SearchRequest {
@@ -692,6 +729,8 @@ pub async fn search(
                 .unwrap_or_default(),
         ),
         score_threshold,
+        tiebreaker: tiebreaker_from_proto(tiebreaker)?,
+        ..Default::default()
     };
 
     let read_consistency = ReadConsistency::try_from_optional(read_consistency)?;
@@ -724,6 +763,9 @@ pub async fn search_batch(
     search_points: Vec<SearchPoints>,
     read_consistency: Option<ReadConsistencyGrpc>,
     shard_selection: Option<ShardId>,
+    explain_score: bool,
+    timeout_ms: Option<u64>,
+    dry_run: bool,
 ) -> Result<Response<SearchBatchResponse>, Status> {
     let searches: Result<Vec<_>, Status> = search_points
         .into_iter()
@@ -732,12 +774,19 @@ pub async fn search_batch(
 
     let search_requests = SearchRequestBatch {
         searches: searches?,
+        debug: false,
+        dry_run,
+        explain_score,
+        min_shards: None,
+        wait_for_shards: None,
+        timeout_ms,
+        score_normalizer: None,
     };
 
     let read_consistency = ReadConsistency::try_from_optional(read_consistency)?;
 
     let timing = Instant::now();
-    let scored_points = do_search_batch_points(
+    let batch_result = do_search_batch_points(
         toc,
         &collection_name,
         search_requests,
@@ -747,14 +796,24 @@ pub async fn search_batch(
     .await
     .map_err(error_to_status)?;
 
+    let hnsw_ef_used = batch_result.hnsw_ef_used;
     let response = SearchBatchResponse {
-        result: scored_points
+        result: batch_result
+            .results
             .into_iter()
-            .map(|points| BatchResult {
+            .enumerate()
+            .map(|(index, points)| BatchResult {
                 result: points.into_iter().map(|p| p.into()).collect(),
+                hnsw_ef_used: hnsw_ef_used
+                    .get(index)
+                    .copied()
+                    .flatten()
+                    .map(|ef| ef as u64),
             })
             .collect(),
         time: timing.elapsed().as_secs_f64(),
+        timeout: batch_result.timeout.map(|err| err.to_string()),
+        dry_run: batch_result.dry_run.map(|dry_run| dry_run.into()),
     };
 
     Ok(Response::new(response))
@@ -884,6 +943,7 @@ pub async fn recommend_batch(
             .into_iter()
             .map(|points| BatchResult {
                 result: points.into_iter().map(|p| p.into()).collect(),
+                hnsw_ef_used: None,
             })
             .collect(),
         time: timing.elapsed().as_secs_f64(),
@@ -939,13 +999,19 @@ pub async fn scroll(
     } = scroll_points;
 
     let scroll_request = ScrollRequest {
-        offset: offset.map(|o| o.try_into()).transpose()?,
+        offset: offset
+            .map(|o| o.try_into())
+            .transpose()?
+            .map(ScrollKey::PointId),
         limit: limit.map(|l| l as usize),
         filter: filter.map(|f| f.try_into()).transpose()?,
         with_payload: with_payload.map(|wp| wp.try_into()).transpose()?,
         with_vector: with_vectors
             .map(|selector| selector.into())
             .unwrap_or_default(),
+        with_shard_key: false,
+        shard_key: None,
+        with_total_count: false,
     };
 
     let read_consistency = ReadConsistency::try_from_optional(read_consistency)?;
@@ -988,6 +1054,7 @@ pub async fn count(
     let count_request = collection::operations::types::CountRequest {
         filter: filter.map(|f| f.try_into()).transpose()?,
         exact: exact.unwrap_or_else(default_exact_count),
+        shard_key: None,
     };
 
     let timing = Instant::now();
@@ -1025,6 +1092,7 @@ pub async fn get(
         with_vector: with_vectors
             .map(|selector| selector.into())
             .unwrap_or_default(),
+        strict: false,
     };
 
     let read_consistency = ReadConsistency::try_from_optional(read_consistency)?;
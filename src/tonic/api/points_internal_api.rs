@@ -202,6 +202,7 @@ impl PointsInternal for PointsInternalService {
             collection_name,
             search_points,
             shard_id,
+            explain_score,
         } = request.into_inner();
 
         // Individual `read_consistency` values are ignored by `search_batch`...
@@ -216,6 +217,9 @@ impl PointsInternal for PointsInternalService {
             search_points,
             None, // *Have* to be `None`!
             shard_id,
+            explain_score.unwrap_or_default(),
+            None,
+            false,
         )
         .await
     }
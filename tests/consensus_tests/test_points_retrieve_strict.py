@@ -0,0 +1,81 @@
+import pathlib
+
+from .utils import *
+from .assertions import assert_http_ok
+
+N_PEERS = 3
+N_SHARDS = 3
+N_REPLICA = 1
+
+
+def test_points_retrieve_strict(tmp_path: pathlib.Path):
+    assert_project_root()
+    peer_dirs = make_peer_folders(tmp_path, N_PEERS)
+
+    # Gathers REST API uris
+    peer_api_uris = []
+
+    # Start bootstrap
+    (bootstrap_api_uri, bootstrap_uri) = start_first_peer(
+        peer_dirs[0], "peer_0_0.log")
+    peer_api_uris.append(bootstrap_api_uri)
+
+    # Wait for leader
+    leader = wait_peer_added(bootstrap_api_uri)
+
+    # Start other peers
+    for i in range(1, len(peer_dirs)):
+        peer_api_uris.append(start_peer(
+            peer_dirs[i], f"peer_0_{i}.log", bootstrap_uri))
+
+    # Wait for cluster
+    wait_for_uniform_cluster_status(peer_api_uris, leader)
+
+    # Single replica of each shard, so every peer has to read some points from remote shards
+    r = requests.put(
+        f"{peer_api_uris[0]}/collections/test_collection", json={
+            "vectors": {
+                "size": 4,
+                "distance": "Dot"
+            },
+            "shard_number": N_SHARDS,
+            "replication_factor": N_REPLICA,
+        })
+    assert_http_ok(r)
+
+    wait_collection_exists_and_active_on_all_peers(collection_name="test_collection", peer_api_uris=peer_api_uris)
+
+    r = requests.put(
+        f"{peer_api_uris[0]}/collections/test_collection/points?wait=true", json={
+            "points": [
+                {"id": i, "vector": [0.1 * i, 0.2, 0.3, 0.4]} for i in range(1, 11)
+            ]
+        })
+    assert_http_ok(r)
+
+    for uri in peer_api_uris:
+        # All points exist, found on local and remote shards
+        r = requests.post(
+            f"{uri}/collections/test_collection/points", json={
+                "ids": list(range(1, 11)),
+                "strict": True,
+            })
+        assert_http_ok(r)
+        assert sorted(point["id"] for point in r.json()["result"]) == list(range(1, 11))
+
+        # Missing points are reported, the existing ones on remote shards are not
+        r = requests.post(
+            f"{uri}/collections/test_collection/points", json={
+                "ids": list(range(1, 11)) + [100],
+                "strict": True,
+            })
+        assert r.status_code == 404
+        assert "point ids [NumId(100)]" in r.json()["status"]["error"]
+
+        # Without strict mode, missing points are silently omitted
+        r = requests.post(
+            f"{uri}/collections/test_collection/points", json={
+                "ids": list(range(1, 11)) + [100],
+            })
+        assert_http_ok(r)
+        assert len(r.json()["result"]) == 10
@@ -118,6 +118,8 @@ def test_points_search(tmp_path: pathlib.Path):
 
         # only one search in the batch
         assert len(r_batch.json()["result"]) == 1
+        # all shards responded
+        assert r_batch.json()["shards_successful"] == N_SHARDS
         # assert same number of results
         assert len(r_search.json()["result"]) == len(r_batch.json()["result"][0])
         # assert stable across peers
@@ -88,10 +88,10 @@ curl -X GET ${SNAPSHOT_URL} -H 'Content-Type: application/json' --fail -s -o tes
 # Upload snapshot via URL
 curl -X PUT "http://${QDRANT_HOST}/collections/test_collection_recovered_1/snapshots/recover" \
      -H 'Content-Type: application/json' \
-     --fail -s -d "{\"location\": \"${SNAPSHOT_URL}\"}" | jq
+     --fail -s -d "{\"location\": \"${SNAPSHOT_URL}\", \"force\": true}" | jq
 
 # Upload snapshot as file
-curl -X POST "http://${QDRANT_HOST}/collections/test_collection_recovered_2/snapshots/upload" \
+curl -X POST "http://${QDRANT_HOST}/collections/test_collection_recovered_2/snapshots/upload?force=true" \
      -H 'Content-Type:multipart/form-data' \
      -F 'snapshot=@test_collection.snapshot' | jq
 